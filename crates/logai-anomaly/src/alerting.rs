@@ -2,8 +2,10 @@
 
 use crate::config::Severity;
 use crate::detection::Anomaly;
+use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
+use thiserror::Error;
 use uuid::Uuid;
 
 // unique key to identify an alert (rule + service)
@@ -29,11 +31,36 @@ pub struct ActiveAlert {
     pub state: AlertState,
     pub severity: Severity,
     pub message: String,
+    pub current_value: f64,
+    pub expected_value: f64,
     pub firing_at: DateTime<Utc>,
     pub last_notified_at: DateTime<Utc>,
     pub acknowledged_at: Option<DateTime<Utc>>,
 }
 
+// Why a channel couldn't deliver an alert
+#[derive(Error, Debug)]
+pub enum AlertError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    // the service answered, but not with a success
+    #[error("{service} API error: {message}")]
+    Rejected { service: &'static str, message: String },
+
+    #[error("Email error: {0}")]
+    Email(String),
+}
+
+// A place alerts get delivered to (Slack, Discord, email, PagerDuty, or a test double)
+#[async_trait]
+pub trait AlertChannel: Send + Sync {
+    // short name used in logs, e.g. "slack"
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, alert: &ActiveAlert) -> Result<(), AlertError>;
+}
+
 // Main alert engine - manages all active alerts
 pub struct AlertEngine {
     // Currently active alerts (key -> alert)
@@ -41,6 +68,12 @@ pub struct AlertEngine {
 
     // cooldown periods per rule
     cooldowns: HashMap<String, u64>,
+
+    // where alerts are sent, by the name rules route to
+    channels: Vec<(String, Box<dyn AlertChannel>)>,
+
+    // channel names per rule, for rules that don't alert everywhere
+    routes: HashMap<String, Vec<String>>,
 }
 
 impl AlertEngine {
@@ -49,7 +82,50 @@ impl AlertEngine {
         Self {
             active_alerts: HashMap::new(),
             cooldowns: HashMap::new(),
+            channels: Vec::new(),
+            routes: HashMap::new(),
+        }
+    }
+
+    // add a delivery channel under its own name ("slack"), alerts go to all of them
+    // unless their rule is routed
+    pub fn add_channel(&mut self, channel: Box<dyn AlertChannel>) {
+        let name = channel.name().to_string();
+        self.add_named_channel(name, channel);
+    }
+
+    // add a channel under a configured name ([channels.oncall] is "oncall"), so several
+    // channels of one kind can be routed to separately
+    pub fn add_named_channel(&mut self, name: impl Into<String>, channel: Box<dyn AlertChannel>) {
+        self.channels.push((name.into(), channel));
+    }
+
+    // names of the channels added so far
+    pub fn channel_names(&self) -> Vec<&str> {
+        self.channels.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    // send a rule's alerts only to the channels with these names (an empty list silences it)
+    pub fn set_channels(&mut self, rule_name: &str, channels: Vec<String>) {
+        self.routes.insert(rule_name.to_string(), channels);
+    }
+
+    // send an alert to every channel of its rule; one failing doesn't stop the others.
+    // Returns the failures as "channel: error"
+    pub async fn notify(&self, alert: &ActiveAlert) -> Vec<String> {
+        let route = self.routes.get(&alert.key.rule_name);
+        let channels = self
+            .channels
+            .iter()
+            .filter(|(name, _)| route.is_none_or(|names| names.contains(name)));
+
+        let mut failures = Vec::new();
+        for (name, channel) in channels {
+            if let Err(e) = channel.send(alert).await {
+                failures.push(format!("{}: {}", name, e));
+            }
         }
+        failures
     }
 
     // set cooldown for a rule called when loading config
@@ -68,9 +144,11 @@ impl AlertEngine {
                 service: anomaly.service.clone(),
             };
             
-            // Check if alert exists and if we should send
+            // Check if alert exists and if we should send. An escalated severity
+            // is news, so it skips the cooldown (but not an acknowledgement)
             let should_send = if let Some(existing) = self.active_alerts.get(&key) {
-                self.should_alert(existing, &anomaly.rule_name, now)
+                let escalated = anomaly.severity > existing.severity && existing.state != AlertState::Acknowledged;
+                escalated || self.should_alert(existing, &anomaly.rule_name, now)
             } else {
                 true // New alert, always send
             };
@@ -79,7 +157,10 @@ impl AlertEngine {
                 if let Some(existing) = self.active_alerts.get_mut(&key) {
                     // Update existing alert
                     existing.last_notified_at = now;
+                    existing.severity = anomaly.severity;
                     existing.message = anomaly.message.clone();
+                    existing.current_value = anomaly.current_value;
+                    existing.expected_value = anomaly.expected_value;
                     alerts_to_send.push(existing.clone());
                 } else {
                     // New alert - create and track it
@@ -89,6 +170,8 @@ impl AlertEngine {
                         state: AlertState::Firing,
                         severity: anomaly.severity,
                         message: anomaly.message.clone(),
+                        current_value: anomaly.current_value,
+                        expected_value: anomaly.expected_value,
                         firing_at: now,
                         last_notified_at: now,
                         acknowledged_at: None,
@@ -137,3 +220,87 @@ impl AlertEngine {
         self.active_alerts.values().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::RecordingChannel;
+
+    fn anomaly() -> Anomaly {
+        Anomaly {
+            id: Uuid::new_v4(),
+            rule_name: "Error Spike".to_string(),
+            service: "payment-api".to_string(),
+            severity: Severity::Critical,
+            message: "Error count spike: 50 errors in 5 minutes".to_string(),
+            current_value: 50.0,
+            expected_value: 4.0,
+            detected_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fired_anomaly_reaches_every_channel() {
+        let slack = RecordingChannel::new("slack");
+        let discord = RecordingChannel::new("discord");
+        let mut engine = AlertEngine::new();
+        engine.add_channel(Box::new(slack.clone()));
+        engine.add_channel(Box::new(discord.clone()));
+
+        let alerts = engine.process_anomalies(vec![anomaly()]);
+        assert_eq!(alerts.len(), 1);
+        assert!(engine.notify(&alerts[0]).await.is_empty());
+
+        for channel in [&slack, &discord] {
+            let sent = channel.sent();
+            assert_eq!(sent.len(), 1);
+            assert_eq!(sent[0].key.service, "payment-api");
+            assert_eq!(sent[0].severity, Severity::Critical);
+        }
+
+        // still within the cooldown, so nothing new goes out
+        assert!(engine.process_anomalies(vec![anomaly()]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_routed_rule_skips_other_channels() {
+        let slack = RecordingChannel::new("slack");
+        let pagerduty = RecordingChannel::new("pagerduty");
+        let mut engine = AlertEngine::new();
+        engine.add_channel(Box::new(slack.clone()));
+        engine.add_channel(Box::new(pagerduty.clone()));
+        engine.set_channels("Volume Drop", vec!["slack".to_string()]);
+
+        let volume_drop = Anomaly {
+            rule_name: "Volume Drop".to_string(),
+            ..anomaly()
+        };
+        for alert in engine.process_anomalies(vec![volume_drop, anomaly()]) {
+            assert!(engine.notify(&alert).await.is_empty());
+        }
+
+        let rules = |channel: &RecordingChannel| -> Vec<String> {
+            channel.sent().into_iter().map(|alert| alert.key.rule_name).collect()
+        };
+        assert_eq!(rules(&slack), ["Volume Drop", "Error Spike"]);
+        // the unrouted rule still goes everywhere
+        assert_eq!(rules(&pagerduty), ["Error Spike"]);
+    }
+
+    #[tokio::test]
+    async fn test_named_channels_of_one_kind_routed_apart() {
+        let team = RecordingChannel::new("slack");
+        let oncall = RecordingChannel::new("slack");
+        let mut engine = AlertEngine::new();
+        engine.add_named_channel("team-slack", Box::new(team.clone()));
+        engine.add_named_channel("oncall-slack", Box::new(oncall.clone()));
+        engine.set_channels("Error Spike", vec!["oncall-slack".to_string()]);
+        assert_eq!(engine.channel_names(), ["team-slack", "oncall-slack"]);
+
+        for alert in engine.process_anomalies(vec![anomaly()]) {
+            assert!(engine.notify(&alert).await.is_empty());
+        }
+        assert!(team.sent().is_empty());
+        assert_eq!(oncall.sent().len(), 1);
+    }
+}
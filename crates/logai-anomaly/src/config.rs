@@ -1,8 +1,16 @@
 //! Configuration parsing for anomaly detection rules
 
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+// longest window a rule may look back over (7 days)
+const MAX_WINDOW_MINUTES: u64 = 7 * 24 * 60;
+
+// names a rule's alert.channels may always use: the [slack] and [discord] sections, and
+// email (configured from SMTP_* variables). [channels.<name>] sections add more
+pub const ALERT_CHANNELS: &[&str] = &["slack", "discord", "email"];
 
 
 // Main config structure
@@ -14,6 +22,19 @@ pub struct AnomalyConfig {
     // Slack config
     pub slack: SlackConfig,
 
+    // Discord config (off when the section is missing)
+    #[serde(default)]
+    pub discord: DiscordConfig,
+
+    // named channels, [channels.<name>]; rules route to them by that name
+    #[serde(default)]
+    pub channels: BTreeMap<String, ChannelConfig>,
+
+    // bump severity one level once a rule fires this many checks in a row for a service
+    // (off when unset)
+    #[serde(default)]
+    pub escalate_after: Option<u32>,
+
     // list of anomaly detection rules
     #[serde(default)]
     pub rules: Vec<Rule>,
@@ -21,7 +42,7 @@ pub struct AnomalyConfig {
 
 // Slcak webhook config
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct SlackConfig {
     // check slack notifications are enabled
     pub enabled: bool,
@@ -30,6 +51,28 @@ pub struct SlackConfig {
     pub webhook_url: String,
 }
 
+// Discord webhook config
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DiscordConfig {
+    // check discord notifications are enabled
+    pub enabled: bool,
+
+    // webhook url (Server Settings > Integrations > Webhooks)
+    pub webhook_url: String,
+}
+
+// A named alert channel. Defining one turns it on; a kind can be used more than once,
+// e.g. two Slack webhooks for two teams
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ChannelConfig {
+    Slack { webhook_url: String },
+    Discord { webhook_url: String },
+    // the Events API v2 integration key of a PagerDuty service
+    Pagerduty { routing_key: String },
+}
+
 // A single anomaly detection rule
 #[derive(Debug, Deserialize)]
 pub struct Rule {
@@ -65,6 +108,20 @@ pub enum Detection {
         operator: Operator,  // comparison operator
         value: f64,          // threshold value
         window_minutes: u64, // time window in minutes
+
+        // compare the % change from the recent baseline instead of the value itself,
+        // e.g. operator = ">", value = 200.0 is "up more than 200%"
+        #[serde(default)]
+        compare_to_baseline: bool,
+        // baseline period for compare_to_baseline
+        #[serde(default = "default_baseline_minutes")]
+        baseline_window_minutes: u64,
+    },
+    // error messages (normalized to templates) logged in the last 5 minutes that
+    // never appeared in the `lookback_hours` before
+    #[serde(rename = "new_error")]
+    NewError {
+        lookback_hours: u64,
     },
 }
 
@@ -76,6 +133,20 @@ pub enum Metric {
     ErrorCount, // count of errror-level logs
     ErrorRate,  // % of logs that are errors
     LogVolume,  // total log volume
+    LatencyP95, // 95th percentile of fields.latency_ms
+    LatencyAvg, // mean of fields.latency_ms
+}
+
+impl Metric {
+    // latency metrics come from the JSON fields column and are in milliseconds
+    pub fn is_latency(&self) -> bool {
+        matches!(self, Metric::LatencyP95 | Metric::LatencyAvg)
+    }
+
+    // counts grow with the window, the rest (rate, latency) don't
+    pub fn is_count(&self) -> bool {
+        matches!(self, Metric::ErrorCount | Metric::LogVolume)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
@@ -126,7 +197,7 @@ impl Operator {
 }
 
 // alerrt severity levels
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Info,
@@ -134,6 +205,16 @@ pub enum Severity {
     Critical,
 }
 
+impl Severity {
+    // one level up, critical stays critical
+    pub fn escalated(self) -> Self {
+        match self {
+            Severity::Info => Severity::Warning,
+            Severity::Warning | Severity::Critical => Severity::Critical,
+        }
+    }
+}
+
 // alert config for a rule
 #[derive(Debug, Deserialize)] 
 pub struct AlertSettings {
@@ -142,6 +223,10 @@ pub struct AlertSettings {
 
     // cooldown period in minutes
     pub cooldown_minutes: u64,
+
+    // channels this rule's alerts go to, e.g. ["slack"]; every configured channel when unset
+    #[serde(default)]
+    pub channels: Option<Vec<String>>,
 }
 
 // defualt value helper for serde
@@ -149,9 +234,365 @@ fn default_true() -> bool {
     true
 }
 
-// Load configuration from a TOML file
+fn default_baseline_minutes() -> u64 {
+    60
+}
+
+// One file of a rules directory: its [[rules]], and optionally the top-level settings
+// (check_interval_seconds, escalate_after, [slack], [discord]) - those may only appear in one file.
+// Named [channels.*] may be spread over files, each name defined once
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    check_interval_seconds: Option<u64>,
+    escalate_after: Option<u32>,
+    slack: Option<SlackConfig>,
+    discord: Option<DiscordConfig>,
+    #[serde(default)]
+    channels: BTreeMap<String, ChannelConfig>,
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+impl AnomalyConfig {
+    // Merge every *.toml file in a directory (not recursive, in file name order).
+    // Settings default to a 60s interval and Slack off when no file sets them.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        files.sort();
+        if files.is_empty() {
+            return Err(format!("no *.toml rule files in {}", dir.as_ref().display()).into());
+        }
+
+        let mut interval: Option<(u64, PathBuf)> = None;
+        let mut escalate_after: Option<(u32, PathBuf)> = None;
+        let mut slack: Option<(SlackConfig, PathBuf)> = None;
+        let mut discord: Option<(DiscordConfig, PathBuf)> = None;
+        let mut channels = BTreeMap::new();
+        let mut channel_defined_in: HashMap<String, PathBuf> = HashMap::new();
+        let mut rules = Vec::new();
+        let mut defined_in: HashMap<String, PathBuf> = HashMap::new();
+
+        for path in files {
+            let content = fs::read_to_string(&path)?;
+            let file: RulesFile =
+                toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+            if let Some(seconds) = file.check_interval_seconds {
+                if let Some((_, first)) = &interval {
+                    return Err(setting_twice("check_interval_seconds", first, &path).into());
+                }
+                interval = Some((seconds, path.clone()));
+            }
+            if let Some(checks) = file.escalate_after {
+                if let Some((_, first)) = &escalate_after {
+                    return Err(setting_twice("escalate_after", first, &path).into());
+                }
+                escalate_after = Some((checks, path.clone()));
+            }
+            if let Some(config) = file.slack {
+                if let Some((_, first)) = &slack {
+                    return Err(setting_twice("[slack]", first, &path).into());
+                }
+                slack = Some((config, path.clone()));
+            }
+            if let Some(config) = file.discord {
+                if let Some((_, first)) = &discord {
+                    return Err(setting_twice("[discord]", first, &path).into());
+                }
+                discord = Some((config, path.clone()));
+            }
+            for (name, config) in file.channels {
+                if let Some(first) = channel_defined_in.get(&name) {
+                    return Err(setting_twice(&format!("[channels.{}]", name), first, &path).into());
+                }
+                channel_defined_in.insert(name.clone(), path.clone());
+                channels.insert(name, config);
+            }
+
+            // alert cooldowns are keyed by rule name, so names must be unique across files
+            for rule in file.rules {
+                if let Some(first) = defined_in.get(&rule.name) {
+                    return Err(format!(
+                        "duplicate rule name '{}' in {} (already defined in {})",
+                        rule.name,
+                        path.display(),
+                        first.display()
+                    )
+                    .into());
+                }
+                defined_in.insert(rule.name.clone(), path.clone());
+                rules.push(rule);
+            }
+        }
+
+        Ok(Self {
+            check_interval_seconds: interval.map_or(60, |(seconds, _)| seconds),
+            slack: slack.map(|(config, _)| config).unwrap_or_default(),
+            discord: discord.map(|(config, _)| config).unwrap_or_default(),
+            channels,
+            escalate_after: escalate_after.map(|(checks, _)| checks),
+            rules,
+        })
+    }
+
+    // check what deserializing can't catch: zero intervals, empty service lists,
+    // out-of-range thresholds, duplicate rule names, routes to channels that don't exist
+    pub fn validate(&self) -> ValidationReport {
+        let channels = self.channel_names();
+        let mut report = ValidationReport {
+            errors: self.validate_settings(),
+            rules: self.rules.iter().map(|rule| RuleReport::for_rule(rule, &channels)).collect(),
+        };
+        report.flag_duplicate_names();
+        report
+    }
+
+    // every name a rule may route to: the built-in channels, then the named ones
+    pub fn channel_names(&self) -> Vec<String> {
+        ALERT_CHANNELS
+            .iter()
+            .map(|name| name.to_string())
+            .chain(self.channels.keys().cloned())
+            .collect()
+    }
+
+    // everything outside the rules
+    fn validate_settings(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.check_interval_seconds == 0 {
+            errors.push("check_interval_seconds must be at least 1".to_string());
+        }
+        if self.escalate_after == Some(0) {
+            errors.push("escalate_after must be at least 1".to_string());
+        }
+        if self.slack.enabled && self.slack.webhook_url.trim().is_empty() {
+            errors.push("slack is enabled but slack.webhook_url is empty".to_string());
+        }
+        if !self.slack.webhook_url.is_empty() && !self.slack.webhook_url.starts_with("https://") {
+            errors.push("slack.webhook_url must be an https:// URL".to_string());
+        }
+        if self.discord.enabled && self.discord.webhook_url.trim().is_empty() {
+            errors.push("discord is enabled but discord.webhook_url is empty".to_string());
+        }
+        if !self.discord.webhook_url.is_empty() && !self.discord.webhook_url.starts_with("https://") {
+            errors.push("discord.webhook_url must be an https:// URL".to_string());
+        }
+        for (name, channel) in &self.channels {
+            if ALERT_CHANNELS.contains(&name.as_str()) {
+                errors.push(format!("channels.{}: the name is taken by the built-in channel", name));
+            }
+            match channel {
+                ChannelConfig::Slack { webhook_url } | ChannelConfig::Discord { webhook_url }
+                    if !webhook_url.starts_with("https://") =>
+                {
+                    errors.push(format!("channels.{}.webhook_url must be an https:// URL", name));
+                }
+                ChannelConfig::Pagerduty { routing_key } if routing_key.trim().is_empty() => {
+                    errors.push(format!("channels.{}.routing_key is empty", name));
+                }
+                _ => {}
+            }
+        }
+        errors
+    }
+}
+
+impl Rule {
+    // problems with this rule, empty when it's fine
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("name is empty".to_string());
+        }
+        if self.services.is_empty() {
+            errors.push("services is empty (use [\"*\"] for all services)".to_string());
+        } else if self.services.iter().any(|s| s.trim().is_empty()) {
+            errors.push("services contains an empty name".to_string());
+        }
+
+        match &self.detection {
+            Detection::Statistical { baseline_window_minutes, .. } => {
+                errors.extend(check_window("baseline_window_minutes", *baseline_window_minutes));
+            }
+            Detection::Threshold {
+                value,
+                window_minutes,
+                compare_to_baseline: true,
+                baseline_window_minutes,
+                ..
+            } => {
+                errors.extend(check_window("window_minutes", *window_minutes));
+                errors.extend(check_window("baseline_window_minutes", *baseline_window_minutes));
+                // a metric can't drop by more than 100%
+                if !value.is_finite() || *value < -100.0 {
+                    errors.push(format!("value is a % change, it must be a number >= -100, got {}", value));
+                }
+            }
+            Detection::Threshold { metric, value, window_minutes, .. } => {
+                errors.extend(check_window("window_minutes", *window_minutes));
+                if !value.is_finite() || *value < 0.0 {
+                    errors.push(format!("value must be a number >= 0, got {}", value));
+                } else if matches!(metric, Metric::ErrorRate) && *value > 100.0 {
+                    errors.push(format!("error_rate is a percentage, value must be 0-100, got {}", value));
+                }
+            }
+            Detection::NewError { lookback_hours } => {
+                let max_hours = MAX_WINDOW_MINUTES / 60;
+                if *lookback_hours == 0 || *lookback_hours > max_hours {
+                    errors.push(format!("lookback_hours must be 1-{} (7 days), got {}", max_hours, lookback_hours));
+                }
+            }
+        }
+        errors
+    }
+
+    // alert.channels entries that aren't among `channels` (AnomalyConfig::channel_names)
+    pub fn unknown_channels(&self, channels: &[String]) -> Vec<String> {
+        self.alert
+            .channels
+            .iter()
+            .flatten()
+            .filter(|channel| !channels.contains(channel))
+            .map(|channel| format!("unknown alert channel '{}' (channels: {})", channel, channels.join(", ")))
+            .collect()
+    }
+}
+
+fn check_window(field: &str, minutes: u64) -> Option<String> {
+    match minutes {
+        0 => Some(format!("{} must be at least 1", field)),
+        m if m > MAX_WINDOW_MINUTES => Some(format!("{} must be at most {} (7 days), got {}", field, MAX_WINDOW_MINUTES, m)),
+        _ => None,
+    }
+}
+
+// Result of validating a config, per rule
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    // problems outside the rules (interval, slack, unreadable file)
+    pub errors: Vec<String>,
+    pub rules: Vec<RuleReport>,
+}
+
+#[derive(Debug)]
+pub struct RuleReport {
+    pub name: String,
+    pub errors: Vec<String>,
+}
+
+impl RuleReport {
+    fn for_rule(rule: &Rule, channels: &[String]) -> Self {
+        let mut errors = rule.validate();
+        errors.extend(rule.unknown_channels(channels));
+        Self {
+            name: rule.name.clone(),
+            errors,
+        }
+    }
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty() && self.rules.iter().all(|r| r.errors.is_empty())
+    }
+
+    // alert cooldowns are tracked by rule name, so two rules can't share one
+    fn flag_duplicate_names(&mut self) {
+        let mut seen = HashSet::new();
+        for rule in &mut self.rules {
+            if !rule.name.is_empty() && !seen.insert(rule.name.clone()) {
+                rule.errors.push("duplicate rule name".to_string());
+            }
+        }
+    }
+}
+
+// Validate a TOML config rule by rule, so an unknown metric in one rule is reported
+// against that rule instead of failing the whole file
+pub fn validate_config_str(content: &str) -> ValidationReport {
+    let mut table: toml::Table = match toml::from_str(content) {
+        Ok(table) => table,
+        Err(e) => {
+            return ValidationReport {
+                errors: vec![format!("invalid TOML: {}", e.message())],
+                rules: Vec::new(),
+            };
+        }
+    };
+
+    let raw_rules = match table.remove("rules") {
+        Some(toml::Value::Array(rules)) => rules,
+        Some(_) => {
+            return ValidationReport {
+                errors: vec!["rules must be a list of [[rules]] tables".to_string()],
+                rules: Vec::new(),
+            };
+        }
+        None => Vec::new(),
+    };
+
+    // the rest of the file, checked with no rules attached
+    let (errors, channels) = match toml::Value::Table(table).try_into::<AnomalyConfig>() {
+        Ok(settings) => (settings.validate_settings(), settings.channel_names()),
+        Err(e) => (
+            vec![e.message().to_string()],
+            ALERT_CHANNELS.iter().map(|name| name.to_string()).collect(),
+        ),
+    };
+
+    let rules = raw_rules
+        .into_iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            let name = raw
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("rule #{}", i + 1));
+            match raw.try_into::<Rule>() {
+                Ok(rule) => RuleReport::for_rule(&rule, &channels),
+                Err(e) => RuleReport {
+                    name,
+                    errors: vec![e.message().to_string()],
+                },
+            }
+        })
+        .collect();
+
+    let mut report = ValidationReport { errors, rules };
+    report.flag_duplicate_names();
+    report
+}
+
+// Validate a config file or rules directory; only failing to read a file is an Err.
+// In a directory, a broken file or a name used twice stops the merge and is the one error reported.
+pub fn validate_config<P: AsRef<Path>>(path: P) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+    if path.as_ref().is_dir() {
+        return Ok(match AnomalyConfig::from_dir(path) {
+            Ok(config) => config.validate(),
+            Err(e) => ValidationReport {
+                errors: vec![e.to_string()],
+                rules: Vec::new(),
+            },
+        });
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(validate_config_str(&content))
+}
+
+fn setting_twice(setting: &str, first: &Path, second: &Path) -> String {
+    format!("{} is set in both {} and {}", setting, first.display(), second.display())
+}
+
+// Load configuration from a TOML file, or from a directory of them (see AnomalyConfig::from_dir)
 
 pub fn load_config<P: AsRef<Path>>(path: P) -> Result<AnomalyConfig, Box<dyn std::error::Error>> {
+    if path.as_ref().is_dir() {
+        return AnomalyConfig::from_dir(path);
+    }
     let content = fs::read_to_string(path)?;
     let config: AnomalyConfig = toml::from_str(&content)?;
     Ok(config)
@@ -190,4 +631,245 @@ cooldown_minutes = 10
         assert_eq!(config.rules.len(), 1);
         assert_eq!(config.rules[0].name, "Error Spike");
     }
+
+    const VALID: &str = r#"
+check_interval_seconds = 60
+
+[slack]
+enabled = false
+webhook_url = ""
+
+[[rules]]
+name = "Error Spike"
+services = ["*"]
+
+[rules.detection]
+type = "statistical"
+metric = "error_count"
+sensitivity = "high"
+baseline_window_minutes = 60
+
+[rules.alert]
+severity = "warning"
+cooldown_minutes = 10
+
+[[rules]]
+name = "Error Rate"
+services = ["payment-*"]
+
+[rules.detection]
+type = "threshold"
+metric = "error_rate"
+operator = ">="
+value = 5.0
+window_minutes = 5
+
+[rules.alert]
+severity = "critical"
+cooldown_minutes = 5
+"#;
+
+    #[test]
+    fn test_valid_config() {
+        let report = validate_config_str(VALID);
+        assert!(report.is_valid(), "{:?}", report);
+        let names: Vec<&str> = report.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["Error Spike", "Error Rate"]);
+
+        let shipped = validate_config(concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/anomaly-rules.toml")).unwrap();
+        assert!(shipped.is_valid(), "{:?}", shipped);
+    }
+
+    #[test]
+    fn test_malformed_rules_reported_per_rule() {
+        let config = VALID
+            .replace(r#"services = ["payment-*"]"#, "services = []")
+            .replace("value = 5.0", "value = 250.0")
+            .replace(r#"metric = "error_count""#, r#"metric = "error_counts""#);
+        let report = validate_config_str(&config);
+        assert!(!report.is_valid());
+        assert!(report.errors.is_empty());
+
+        // unknown metric: a deserialize error, attributed to its rule
+        assert_eq!(report.rules[0].name, "Error Spike");
+        assert_eq!(report.rules[0].errors.len(), 1);
+        assert!(report.rules[0].errors[0].contains("error_counts"));
+
+        let errors = &report.rules[1].errors;
+        assert!(errors.iter().any(|e| e.starts_with("services is empty")));
+        assert!(errors.iter().any(|e| e.contains("percentage")));
+
+        let routed = VALID.replace("cooldown_minutes = 5", "cooldown_minutes = 5\nchannels = [\"slack\", \"pager\"]");
+        let report = validate_config_str(&routed);
+        assert_eq!(
+            report.rules[1].errors,
+            ["unknown alert channel 'pager' (channels: slack, discord, email)"]
+        );
+    }
+
+    #[test]
+    fn test_named_channels() {
+        let config = VALID.replace(
+            "[[rules]]\nname = \"Error Rate\"",
+            r#"[channels.oncall]
+type = "pagerduty"
+routing_key = "R0UT1NGKEY"
+
+[channels.team-slack]
+type = "slack"
+webhook_url = "https://hooks.slack.com/services/T0/B0/x"
+
+[[rules]]
+name = "Error Rate""#,
+        );
+        let routed = config.replace("cooldown_minutes = 5", "cooldown_minutes = 5\nchannels = [\"oncall\", \"team-slack\"]");
+        let report = validate_config_str(&routed);
+        assert!(report.is_valid(), "{:?}", report);
+
+        let parsed: AnomalyConfig = toml::from_str(&routed).unwrap();
+        assert!(matches!(&parsed.channels["oncall"], ChannelConfig::Pagerduty { routing_key } if routing_key == "R0UT1NGKEY"));
+        assert_eq!(parsed.channel_names(), ["slack", "discord", "email", "oncall", "team-slack"]);
+
+        let report = validate_config_str(&config.replace("cooldown_minutes = 5", "cooldown_minutes = 5\nchannels = [\"pager\"]"));
+        assert_eq!(
+            report.rules[1].errors,
+            ["unknown alert channel 'pager' (channels: slack, discord, email, oncall, team-slack)"]
+        );
+
+        // a built-in name, and a webhook that isn't https
+        let clashing = config
+            .replace("[channels.oncall]", "[channels.slack]")
+            .replace("https://hooks.slack.com", "http://hooks.slack.com");
+        assert_eq!(
+            validate_config_str(&clashing).errors,
+            [
+                "channels.slack: the name is taken by the built-in channel",
+                "channels.team-slack.webhook_url must be an https:// URL"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_settings_and_windows() {
+        let config = VALID
+            .replace("check_interval_seconds = 60", "check_interval_seconds = 0\nescalate_after = 0")
+            .replace("enabled = false", "enabled = true")
+            .replace("baseline_window_minutes = 60", "baseline_window_minutes = 0")
+            .replace(r#"operator = ">=""#, r#"operator = "=>""#)
+            .replace("Error Rate", "Error Spike");
+        let report = validate_config_str(&config);
+
+        assert_eq!(
+            report.errors,
+            [
+                "check_interval_seconds must be at least 1",
+                "escalate_after must be at least 1",
+                "slack is enabled but slack.webhook_url is empty"
+            ]
+        );
+        assert_eq!(report.rules[0].errors, ["baseline_window_minutes must be at least 1"]);
+        // bad operator, and the name is taken
+        assert_eq!(report.rules[1].errors.len(), 2);
+        assert!(report.rules[1].errors[0].contains("=>"));
+        assert_eq!(report.rules[1].errors[1], "duplicate rule name");
+
+        let report = validate_config_str("check_interval_seconds = ");
+        assert!(report.errors[0].starts_with("invalid TOML"));
+        assert!(report.rules.is_empty());
+    }
+
+    // a fresh directory under the system temp dir holding the given files
+    fn rules_dir(files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("logai-rules-{}", uuid::Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+        dir
+    }
+
+    fn rule(name: &str, service: &str) -> String {
+        format!(
+            r#"
+[[rules]]
+name = "{}"
+services = ["{}"]
+
+[rules.detection]
+type = "threshold"
+metric = "log_volume"
+operator = "<"
+value = 5.0
+window_minutes = 5
+
+[rules.alert]
+severity = "critical"
+cooldown_minutes = 5
+"#,
+            name, service
+        )
+    }
+
+    #[test]
+    fn test_rules_dir_merges_files() {
+        let settings = "check_interval_seconds = 30\n[slack]\nenabled = false\nwebhook_url = \"\"\n";
+        let payments = format!("{}{}", rule("Payments Down", "payment-service"), rule("Refunds Down", "refunds"));
+        let dir = rules_dir(&[
+            ("00-settings.toml", settings),
+            ("payments.toml", &payments),
+            ("auth.toml", &rule("Auth Down", "auth-service")),
+            ("README.md", "not a rules file"),
+        ]);
+
+        let config = load_config(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(config.check_interval_seconds, 30);
+        let names: Vec<&str> = config.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["Auth Down", "Payments Down", "Refunds Down"]);
+        assert!(config.validate().is_valid());
+    }
+
+    #[test]
+    fn test_rules_dir_rejects_duplicate_names() {
+        let dir = rules_dir(&[
+            ("auth.toml", &rule("Service Down", "auth-service")),
+            ("payments.toml", &rule("Service Down", "payment-service")),
+        ]);
+
+        let err = AnomalyConfig::from_dir(&dir).unwrap_err().to_string();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.starts_with("duplicate rule name 'Service Down' in"), "{}", err);
+        assert!(err.contains("payments.toml") && err.contains("auth.toml"));
+    }
+
+    #[test]
+    fn test_rules_dir_rejects_channel_defined_twice() {
+        let oncall = "[channels.oncall]\ntype = \"pagerduty\"\nrouting_key = \"R0UT1NGKEY\"\n";
+        let dir = rules_dir(&[("auth.toml", oncall), ("payments.toml", oncall)]);
+
+        let err = AnomalyConfig::from_dir(&dir).unwrap_err().to_string();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.starts_with("[channels.oncall] is set in both"), "{}", err);
+    }
+
+    #[test]
+    fn test_shipped_config_parses() {
+        let config = load_config(concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/anomaly-rules.toml")).unwrap();
+        let latency = config
+            .rules
+            .iter()
+            .find(|r| r.name == "Latency Regression")
+            .expect("latency example rule");
+        assert!(matches!(
+            latency.detection,
+            Detection::Statistical { metric: Metric::LatencyP95, .. }
+        ));
+
+        let new_error = config.rules.iter().find(|r| r.name == "New Error").expect("new error example rule");
+        assert!(matches!(new_error.detection, Detection::NewError { lookback_hours: 24 }));
+        assert!(new_error.validate().is_empty());
+    }
 }
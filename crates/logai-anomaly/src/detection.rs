@@ -3,8 +3,22 @@
 use crate::config::{Detection, Metric, Rule, Severity};
 use chrono::{DateTime, Utc};
 use clickhouse::Client;
+use logai_core::{LogEntry, LogLevel, RawLogEntry};
+use logai_rag::MessageNormalizer;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+// the "current" window new errors are looked for in, like statistical detection's
+const NEW_ERROR_WINDOW_MINUTES: u64 = 5;
+
+/// Field naming the rule on logs the runner publishes for anomalies; its presence is how
+/// detection tells them apart from the service's own logs
+pub const ANOMALY_RULE_FIELD: &str = "anomaly_rule";
+/// SQL predicate every detection query (and the API's anomaly views) filters with, or an
+/// error spike would feed on its own reports
+pub const SKIP_ANOMALY_LOGS: &str = "NOT JSONHas(fields, 'anomaly_rule')";
+
 // represnts a detected anomaly
 #[derive(Debug, Clone)]
 pub struct Anomaly {
@@ -18,14 +32,55 @@ pub struct Anomaly {
     pub detected_at: DateTime<Utc>, // when detected
 }
 
+impl Anomaly {
+    /// The anomaly as a log of the affected service (Fatal when critical, Error otherwise),
+    /// sharing its id, so it gets indexed and shows up in searches like any other
+    pub fn to_log_entry(&self) -> LogEntry {
+        let level = match self.severity {
+            Severity::Critical => LogLevel::Fatal,
+            Severity::Warning | Severity::Info => LogLevel::Error,
+        };
+        let fields = [
+            (ANOMALY_RULE_FIELD, serde_json::json!(self.rule_name)),
+            ("anomaly_id", serde_json::json!(self.id)),
+            ("current_value", serde_json::json!(self.current_value)),
+            ("expected_value", serde_json::json!(self.expected_value)),
+        ];
+        let mut entry = LogEntry::from_raw(RawLogEntry {
+            message: self.message.clone(),
+            timestamp: Some(self.detected_at),
+            service: Some(self.service.clone()),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields: fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+        });
+        entry.id = self.id;
+        entry
+    }
+}
+
+// one error message with its count in the current window and in the lookback before it
+#[derive(Debug, Deserialize, clickhouse::Row)]
+struct ErrorMessageRow {
+    message: String,
+    recent: u64,
+    earlier: u64,
+}
+
 // mian anomaly detector
 pub struct AnomalyDetector {
     clickhouse: Client,
+    normalizer: MessageNormalizer,
 }
 
 impl AnomalyDetector {
     pub fn new(clickhouse: Client) -> Self {
-        Self { clickhouse }
+        Self {
+            clickhouse,
+            normalizer: MessageNormalizer::new(),
+        }
     }
 
     //check a single rule and return any detected anomalies
@@ -66,7 +121,10 @@ impl AnomalyDetector {
                     operator,
                     value,
                     window_minutes,
+                    compare_to_baseline,
+                    baseline_window_minutes,
                 } => {
+                    let baseline_window = compare_to_baseline.then_some(*baseline_window_minutes);
                     self.check_threshold(
                         rule,
                         &service,
@@ -74,9 +132,13 @@ impl AnomalyDetector {
                         *operator,
                         *value,
                         *window_minutes,
+                        baseline_window,
                     )
                     .await?
                 }
+                Detection::NewError { lookback_hours } => {
+                    self.check_new_error(rule, &service, *lookback_hours).await?
+                }
             };
             if let Some(a) = anomaly {
                 anomalies.push(a);
@@ -128,11 +190,7 @@ impl AnomalyDetector {
         let threshold = avg + (sigma * stddev);
 
         // check if anomaly
-        let is_anomaly = if stddev > 0.0 {
-            current > threshold
-        } else {
-            current > 15.0
-        };
+        let is_anomaly = exceeds_baseline(metric, current, avg, stddev, sigma);
 
         if is_anomaly {
             let message = format!(
@@ -158,8 +216,10 @@ impl AnomalyDetector {
         }
     }
 
-    // Threshold detection check if current value matches operators condition
+    // Threshold detection check if current value matches operators condition.
+    // With a baseline window the condition is on the % change from the baseline average.
 
+    #[allow(clippy::too_many_arguments)]
     async fn check_threshold(
         &self,
         rule: &Rule,
@@ -168,10 +228,43 @@ impl AnomalyDetector {
         operator: crate::config::Operator,
         value: f64,
         window_minutes: u64,
+        baseline_window_minutes: Option<u64>,
     ) -> Result<Option<Anomaly>, Box<dyn std::error::Error>> {
         // get current value
         let current = self.get_metric(service, metric, window_minutes).await?;
 
+        if let Some(baseline_minutes) = baseline_window_minutes {
+            let (baseline, _) = self.get_baseline(service, metric, baseline_minutes).await?;
+            let now = per_minute(metric, current, window_minutes);
+            let Some(change) = percent_change(now, baseline) else {
+                return Ok(None);
+            };
+            if !operator.evaluate(change, value) {
+                return Ok(None);
+            }
+
+            let message = format!(
+                "{} {:+.0}% vs the last {} min: current={:.1}, baseline={:.1} ({} {:+.0}%)",
+                metric_name(metric),
+                change,
+                baseline_minutes,
+                now,
+                baseline,
+                operator_symbol(&operator),
+                value,
+            );
+            return Ok(Some(Anomaly {
+                id: Uuid::new_v4(),
+                rule_name: rule.name.clone(),
+                service: service.to_string(),
+                severity: rule.alert.severity,
+                message,
+                current_value: now,
+                expected_value: baseline,
+                detected_at: Utc::now(),
+            }));
+        }
+
         // check using operator
         if operator.evaluate(current, value) {
             let message = format!(
@@ -196,6 +289,52 @@ impl AnomalyDetector {
         }
     }
 
+    // New error detection: error templates in the last few minutes that the lookback
+    // window before them never had. Fires once per service with the most frequent one.
+    async fn check_new_error(
+        &self,
+        rule: &Rule,
+        service: &str,
+        lookback_hours: u64,
+    ) -> Result<Option<Anomaly>, Box<dyn std::error::Error>> {
+        let query = format!(
+            "SELECT message,
+                countIf(timestamp > now() - INTERVAL {window} MINUTE) AS recent,
+                countIf(timestamp <= now() - INTERVAL {window} MINUTE) AS earlier
+            FROM logs
+            WHERE service = '{service}'
+            AND level IN ('Error', 'Fatal')
+            AND timestamp > now() - INTERVAL {window} MINUTE - INTERVAL {hours} HOUR
+            AND {skip}
+            GROUP BY message",
+            window = NEW_ERROR_WINDOW_MINUTES,
+            skip = SKIP_ANOMALY_LOGS,
+            service = service,
+            hours = lookback_hours
+        );
+        let rows: Vec<ErrorMessageRow> = self.clickhouse.query(&query).fetch_all().await?;
+
+        let new = new_templates(&rows, &self.normalizer);
+        let Some((template, count)) = new.first() else {
+            return Ok(None);
+        };
+
+        let mut message = format!("New error: {}", template);
+        if new.len() > 1 {
+            message.push_str(&format!(" (+{} more new in the last {} min)", new.len() - 1, NEW_ERROR_WINDOW_MINUTES));
+        }
+        Ok(Some(Anomaly {
+            id: Uuid::new_v4(),
+            rule_name: rule.name.clone(),
+            service: service.to_string(),
+            severity: rule.alert.severity,
+            message,
+            current_value: *count as f64,
+            expected_value: 0.0,
+            detected_at: Utc::now(),
+        }))
+    }
+
     // get metric value from clickhouese
     async fn get_metric(
         &self,
@@ -203,27 +342,14 @@ impl AnomalyDetector {
         metric: Metric,
         minutes: u64,
     ) -> Result<f64, Box<dyn std::error::Error>> {
-        let query = match metric {
-            Metric::ErrorCount => {
-                format!(
-                    "SELECT toFloat64(count(*)) FROM logs WHERE service = '{}' AND level = 'Error' AND timestamp > now() - INTERVAL {} MINUTE",
-                    service, minutes
-                )
-            }
-            Metric::ErrorRate => {
-                format!(
-                    "SELECT countIf(level = 'Error') * 100.0 / count(*) FROM logs WHERE service = '{}' AND timestamp > now() - INTERVAL {} MINUTE",
-                    service, minutes
-                )
-            }
-            Metric::LogVolume => {
-                format!(
-                    "SELECT toFloat64(count(*)) FROM logs WHERE service = '{}' AND timestamp > now() - INTERVAL {} MINUTE",
-                    service, minutes
-                )
-            }
-        };
-        
+        let query = format!(
+            "SELECT {} FROM logs WHERE service = '{}' AND timestamp > now() - INTERVAL {} MINUTE AND {}",
+            metric_select(metric),
+            service,
+            minutes,
+            SKIP_ANOMALY_LOGS
+        );
+
         let result: f64 = self
             .clickhouse
             .query(&query)
@@ -234,42 +360,123 @@ impl AnomalyDetector {
         Ok(result)
     }
 
-    // Get baseline avg and std deviation from clikchouse
+    // Get baseline avg and std deviation from the per-minute values in clikchouse
     async fn get_baseline(
         &self,
         service: &str,
         metric: Metric,
         minutes: u64,
     ) -> Result<(f64, f64), Box<dyn std::error::Error>> {
-        let inner_select = match metric {
-            Metric::ErrorCount => "countIf(level = 'Error') as val",
-            Metric::ErrorRate => "countIf(level = 'Error') * 100.0 / count(*) as val",
-            Metric::LogVolume => "count(*) as val",
-        };
         let query = format!(
-            "SELECT avg(val) as avg_val, stddevPop(val) as stddev_val FROM (
-                SELECT 
-                    toStartOfMinute(timestamp) as minute,
-                    {}
-                FROM logs
-                WHERE service = '{}'
-                AND timestamp > now() - INTERVAL {} MINUTE
-                GROUP BY minute
-            )",
-            inner_select, service, minutes
+            "SELECT {} as val
+            FROM logs
+            WHERE service = '{}'
+            AND timestamp > now() - INTERVAL {} MINUTE
+            AND {}
+            GROUP BY toStartOfMinute(timestamp)",
+            metric_select(metric),
+            service,
+            minutes,
+            SKIP_ANOMALY_LOGS
         );
-        // this return two values: avg and stddev
-        let result: (f64, f64) = self
+        let buckets: Vec<f64> = self
             .clickhouse
             .query(&query)
-            .fetch_one()
+            .fetch_all::<f64>()
             .await
-            .unwrap_or((0.0, 0.0));
+            .unwrap_or_default();
 
-        Ok(result)
+        Ok(baseline_stats(&buckets))
     }
 }
 
+// ClickHouse expression computing a metric over the selected rows.
+// Latency reads fields.latency_ms; rows without it are NULL and skipped, no rows gives nan.
+fn metric_select(metric: Metric) -> &'static str {
+    match metric {
+        Metric::ErrorCount => "toFloat64(countIf(level = 'Error'))",
+        Metric::ErrorRate => "countIf(level = 'Error') * 100.0 / count(*)",
+        Metric::LogVolume => "toFloat64(count(*))",
+        Metric::LatencyP95 => {
+            "ifNull(toFloat64(quantile(0.95)(JSONExtract(fields, 'latency_ms', 'Nullable(Float64)'))), nan)"
+        }
+        Metric::LatencyAvg => {
+            "ifNull(toFloat64(avg(JSONExtract(fields, 'latency_ms', 'Nullable(Float64)'))), nan)"
+        }
+    }
+}
+
+// mean and population stddev of per-minute values (minutes without data are nan and skipped)
+fn baseline_stats(buckets: &[f64]) -> (f64, f64) {
+    let values: Vec<f64> = buckets.iter().copied().filter(|v| v.is_finite()).collect();
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let avg = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - avg).powi(2)).sum::<f64>() / n;
+    (avg, variance.sqrt())
+}
+
+// Is the current value a spike against the baseline?
+// With a flat baseline (stddev 0) counts need more than 15, latency needs +50% over average.
+fn exceeds_baseline(metric: Metric, current: f64, avg: f64, stddev: f64, sigma: f64) -> bool {
+    if !current.is_finite() {
+        return false;
+    }
+    if stddev > 0.0 {
+        current > avg + (sigma * stddev)
+    } else if metric.is_latency() {
+        avg > 0.0 && current > avg * 1.5
+    } else {
+        current > 15.0
+    }
+}
+
+// The baseline is an average of per-minute values, so counts over a longer window
+// are scaled down to one minute before comparing
+fn per_minute(metric: Metric, value: f64, window_minutes: u64) -> f64 {
+    if metric.is_count() && window_minutes > 0 {
+        value / window_minutes as f64
+    } else {
+        value
+    }
+}
+
+// % change from the baseline; growth from a zero baseline is infinite.
+// None when there's no current data (latency in a window without latency_ms).
+fn percent_change(current: f64, baseline: f64) -> Option<f64> {
+    if !current.is_finite() {
+        return None;
+    }
+    if baseline == 0.0 {
+        return Some(if current > 0.0 { f64::INFINITY } else { 0.0 });
+    }
+    Some((current - baseline) / baseline * 100.0)
+}
+
+// Templates with occurrences in the current window and none in the lookback, most
+// frequent first. Messages differing only in ids, numbers or IPs share a template.
+fn new_templates(rows: &[ErrorMessageRow], normalizer: &MessageNormalizer) -> Vec<(String, u64)> {
+    let seen: HashSet<String> = rows
+        .iter()
+        .filter(|row| row.earlier > 0)
+        .map(|row| normalizer.normalize(&row.message))
+        .collect();
+
+    let mut new: HashMap<String, u64> = HashMap::new();
+    for row in rows.iter().filter(|row| row.recent > 0) {
+        let template = normalizer.normalize(&row.message);
+        if !seen.contains(&template) {
+            *new.entry(template).or_default() += row.recent;
+        }
+    }
+
+    let mut new: Vec<(String, u64)> = new.into_iter().collect();
+    new.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    new
+}
+
 // Helper get human readable metric name
 
 fn metric_name(metric: Metric) -> &'static str {
@@ -277,6 +484,8 @@ fn metric_name(metric: Metric) -> &'static str {
         Metric::ErrorCount => "Error count",
         Metric::ErrorRate => "Error rate",
         Metric::LogVolume => "Log volume",
+        Metric::LatencyP95 => "Latency p95 (ms)",
+        Metric::LatencyAvg => "Average latency (ms)",
     }
 }
 
@@ -291,3 +500,130 @@ fn operator_symbol(op: &crate::config::Operator) -> &'static str {
         Operator::Equal => "==",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_regression_against_baseline() {
+        // an hour of p95 buckets hovering around 120ms
+        let buckets: Vec<f64> = (0..60).map(|i| 110.0 + (i % 5) as f64 * 5.0).collect();
+        let (avg, stddev) = baseline_stats(&buckets);
+        assert!((avg - 120.0).abs() < 1e-9);
+        assert!((stddev - 50.0_f64.sqrt()).abs() < 1e-9);
+
+        let sigma = 2.0;
+        assert!(!exceeds_baseline(Metric::LatencyP95, 130.0, avg, stddev, sigma));
+        assert!(exceeds_baseline(Metric::LatencyP95, 450.0, avg, stddev, sigma));
+    }
+
+    #[test]
+    fn test_missing_latency_minutes_are_ignored() {
+        // minutes with no latency_ms come back as nan
+        let buckets = [100.0, f64::NAN, 100.0, f64::NAN];
+        assert_eq!(baseline_stats(&buckets), (100.0, 0.0));
+
+        // flat baseline: latency needs +50%, not the count floor of 15
+        assert!(!exceeds_baseline(Metric::LatencyAvg, 140.0, 100.0, 0.0, 2.0));
+        assert!(exceeds_baseline(Metric::LatencyAvg, 160.0, 100.0, 0.0, 2.0));
+        assert!(exceeds_baseline(Metric::ErrorCount, 16.0, 0.0, 0.0, 2.0));
+
+        // no data in the current window is never an anomaly
+        assert!(!exceeds_baseline(Metric::LatencyP95, f64::NAN, 100.0, 10.0, 2.0));
+        assert_eq!(baseline_stats(&[]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_threshold_absolute_and_relative() {
+        use crate::config::Operator;
+
+        // absolute (default): the window value against the configured value
+        assert!(Operator::GreaterThan.evaluate(12.0, 10.0));
+        assert!(!Operator::GreaterThan.evaluate(8.0, 10.0));
+
+        // relative: error rate from a 2% baseline to 7% is +250%, over a 200% threshold
+        let (baseline, _) = baseline_stats(&[1.0, 2.0, 3.0]);
+        let change = percent_change(per_minute(Metric::ErrorRate, 7.0, 5), baseline).unwrap();
+        assert!((change - 250.0).abs() < 1e-9);
+        assert!(Operator::GreaterThan.evaluate(change, 200.0));
+
+        // counts: 150 errors in 5 minutes is 30/min, +50% on a 20/min baseline
+        let change = percent_change(per_minute(Metric::ErrorCount, 150.0, 5), 20.0).unwrap();
+        assert!((change - 50.0).abs() < 1e-9);
+        assert!(!Operator::GreaterThan.evaluate(change, 200.0));
+
+        // log volume down 80%: "<" with a negative change
+        let change = percent_change(per_minute(Metric::LogVolume, 100.0, 5), 100.0).unwrap();
+        assert!(Operator::LessThan.evaluate(change, -50.0));
+
+        // errors out of nowhere count as unbounded growth; no latency data is no verdict
+        assert_eq!(percent_change(3.0, 0.0), Some(f64::INFINITY));
+        assert_eq!(percent_change(0.0, 0.0), Some(0.0));
+        assert_eq!(percent_change(f64::NAN, 120.0), None);
+    }
+
+    #[test]
+    fn test_new_error_templates() {
+        let row = |message: &str, recent: u64, earlier: u64| ErrorMessageRow {
+            message: message.to_string(),
+            recent,
+            earlier,
+        };
+        let rows = [
+            // yesterday's timeouts, and a different order id today: not new
+            row("upstream timed out after 5000ms (order 1842)", 0, 40),
+            row("upstream timed out after 3000ms (order 1907)", 6, 0),
+            // a brand new failure, logged with three different user ids
+            row("cannot decrypt token for user_12", 2, 0),
+            row("cannot decrypt token for user_77", 1, 0),
+            row("cannot decrypt token for user_93", 1, 0),
+            // and a rarer one
+            row("disk quota exceeded on /var/lib/payments", 1, 0),
+            // only in the lookback: gone, not new
+            row("connection reset by peer 10.0.0.7:5432", 0, 3),
+        ];
+
+        let new = new_templates(&rows, &MessageNormalizer::new());
+        assert_eq!(
+            new,
+            [
+                ("cannot decrypt token for user_<NUM>".to_string(), 4),
+                ("disk quota exceeded on /var/lib/payments".to_string(), 1),
+            ]
+        );
+
+        // nothing in the current window, or nothing the lookback hadn't seen
+        assert!(new_templates(&rows[..1], &MessageNormalizer::new()).is_empty());
+        assert!(new_templates(&rows[..2], &MessageNormalizer::new()).is_empty());
+    }
+
+    #[test]
+    fn test_anomaly_as_log_entry() {
+        let anomaly = Anomaly {
+            id: Uuid::new_v4(),
+            rule_name: "Error Spike".to_string(),
+            service: "payments".to_string(),
+            severity: Severity::Critical,
+            message: "Error count is 42, expected ~5".to_string(),
+            current_value: 42.0,
+            expected_value: 5.0,
+            detected_at: Utc::now(),
+        };
+
+        let entry = anomaly.to_log_entry();
+        assert_eq!(entry.id, anomaly.id);
+        assert_eq!(entry.timestamp, anomaly.detected_at);
+        assert_eq!(entry.service, "payments");
+        assert_eq!(entry.level, LogLevel::Fatal);
+        assert_eq!(entry.message, "Error count is 42, expected ~5");
+        assert_eq!(entry.fields[ANOMALY_RULE_FIELD], serde_json::json!("Error Spike"));
+        assert_eq!(entry.fields["anomaly_id"], serde_json::json!(anomaly.id.to_string()));
+        assert_eq!(entry.fields["current_value"], serde_json::json!(42.0));
+        // the field detection queries leave out
+        assert!(SKIP_ANOMALY_LOGS.contains(ANOMALY_RULE_FIELD));
+
+        let warning = Anomaly { severity: Severity::Warning, ..anomaly };
+        assert_eq!(warning.to_log_entry().level, LogLevel::Error);
+    }
+}
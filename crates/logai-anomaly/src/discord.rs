@@ -0,0 +1,193 @@
+//! Discord webhook integration
+
+use crate::alerting::{ActiveAlert, AlertChannel, AlertError};
+use crate::config::Severity;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// retries after a 429, and the longest we wait for one
+const MAX_RETRIES: u32 = 2;
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(10);
+
+// Discord client for sending alerts
+pub struct DiscordClient {
+    client: Client,
+    webhook_url: String,
+    enabled: bool,
+}
+
+// discord webhook payload
+#[derive(Serialize)]
+struct DiscordMessage {
+    username: String,
+    embeds: Vec<DiscordEmbed>,
+}
+
+// discord embed (colored card with details)
+#[derive(Serialize)]
+struct DiscordEmbed {
+    title: String,
+    description: String,
+    color: u32,
+    fields: Vec<DiscordField>,
+    footer: DiscordFooter,
+    timestamp: String,
+}
+
+// discord field (name value in embed)
+#[derive(Serialize)]
+struct DiscordField {
+    name: String,
+    value: String,
+    inline: bool,
+}
+
+#[derive(Serialize)]
+struct DiscordFooter {
+    text: String,
+}
+
+// body of a 429 response
+#[derive(Deserialize)]
+struct RateLimited {
+    retry_after: f64, // seconds
+}
+
+impl DiscordClient {
+    // create a new Discord client
+    pub fn new(webhook_url: String, enabled: bool) -> Self {
+        Self {
+            client: Client::new(),
+            webhook_url,
+            enabled,
+        }
+    }
+
+    // Build Discord message from alert
+    fn build_message(&self, alert: &ActiveAlert) -> DiscordMessage {
+        let field = |name: &str, value: String| DiscordField {
+            name: name.to_string(),
+            value,
+            inline: true,
+        };
+
+        DiscordMessage {
+            username: "LogAI".to_string(),
+            embeds: vec![DiscordEmbed {
+                title: alert.key.rule_name.clone(),
+                description: alert.message.clone(),
+                color: severity_to_color(&alert.severity),
+                fields: vec![
+                    field("Service", alert.key.service.clone()),
+                    field("Current", format!("{:.1}", alert.current_value)),
+                    field("Expected", format!("{:.1}", alert.expected_value)),
+                ],
+                footer: DiscordFooter {
+                    text: format!("LogAI Anomaly Detection · {:?}", alert.severity),
+                },
+                timestamp: alert.firing_at.to_rfc3339(),
+            }],
+        }
+    }
+}
+
+#[async_trait]
+impl AlertChannel for DiscordClient {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    // send an alert to discord, waiting out rate limits a couple of times
+    async fn send(&self, alert: &ActiveAlert) -> Result<(), AlertError> {
+        //skip if disabled
+        if !self.enabled {
+            return Ok(());
+        }
+        let message = self.build_message(alert);
+
+        let mut retries = 0;
+        loop {
+            let response = self.client.post(&self.webhook_url).json(&message).send().await?;
+
+            if response.status().is_success() {
+                return Ok(());
+            }
+            if response.status() == StatusCode::TOO_MANY_REQUESTS && retries < MAX_RETRIES {
+                retries += 1;
+                tokio::time::sleep(retry_after(response).await).await;
+                continue;
+            }
+
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AlertError::Rejected { service: "Discord", message: error_text });
+        }
+    }
+}
+
+// Discord sends how long to wait in the body (and the Retry-After header); 1s if neither parses
+async fn retry_after(response: reqwest::Response) -> Duration {
+    let header = response
+        .headers()
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<f64>().ok());
+    let body = response.json::<RateLimited>().await.ok().map(|r| r.retry_after);
+
+    body.or(header)
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+        .unwrap_or(Duration::from_secs(1))
+        .min(MAX_RETRY_WAIT)
+}
+
+// Convert severity to an embed color (0xRRGGBB)
+fn severity_to_color(severity: &Severity) -> u32 {
+    match severity {
+        Severity::Critical => 0xED4245, // red
+        Severity::Warning => 0xFEE75C,  // yellow
+        Severity::Info => 0x57F287,     // green
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::{AlertKey, AlertState};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn alert(severity: Severity) -> ActiveAlert {
+        ActiveAlert {
+            id: Uuid::new_v4(),
+            key: AlertKey {
+                rule_name: "Error Spike".to_string(),
+                service: "payment-api".to_string(),
+            },
+            state: AlertState::Firing,
+            severity,
+            message: "Error count spike detected: current=50.0, expected=4.2 (threshold=12.0)".to_string(),
+            current_value: 50.0,
+            expected_value: 4.2,
+            firing_at: Utc::now(),
+            last_notified_at: Utc::now(),
+            acknowledged_at: None,
+        }
+    }
+
+    #[test]
+    fn test_embed_color_and_fields() {
+        let client = DiscordClient::new(String::new(), true);
+
+        let critical = client.build_message(&alert(Severity::Critical));
+        assert_eq!(critical.embeds[0].color, 0xED4245);
+        let warning = client.build_message(&alert(Severity::Warning));
+        assert_eq!(warning.embeds[0].color, 0xFEE75C);
+
+        let embed = &critical.embeds[0];
+        assert_eq!(embed.title, "Error Spike");
+        let fields: Vec<(&str, &str)> = embed.fields.iter().map(|f| (f.name.as_str(), f.value.as_str())).collect();
+        assert_eq!(fields, [("Service", "payment-api"), ("Current", "50.0"), ("Expected", "4.2")]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Email alerts over SMTP
+
+use crate::alerting::{ActiveAlert, AlertChannel, AlertError};
+use crate::config::Severity;
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+// how the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmtpSecurity {
+    Tls,      // implicit TLS from the first byte (port 465)
+    StartTls, // plain connection upgraded with STARTTLS (port 587)
+    None,     // no encryption, only for local relays like MailHog
+}
+
+impl SmtpSecurity {
+    fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::None => 25,
+        }
+    }
+}
+
+// Email client for sending alerts
+pub struct EmailClient {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailClient {
+    // Environment variables:
+    // - SMTP_HOST: SMTP server; email alerts are off when unset
+    // - SMTP_PORT: default 465 for tls, 587 for starttls, 25 for none
+    // - SMTP_TLS: tls, starttls or none (default starttls)
+    // - SMTP_USER / SMTP_PASS: login, skipped when SMTP_USER is unset
+    // - ALERT_EMAIL_FROM: sender address, e.g. "LogAI <alerts@example.com>"
+    // - ALERT_EMAIL_TO: comma-separated recipients
+    pub fn from_env() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let host = match std::env::var("SMTP_HOST") {
+            Ok(host) if !host.trim().is_empty() => host,
+            _ => return Ok(None),
+        };
+
+        let security = match std::env::var("SMTP_TLS") {
+            Ok(mode) => parse_security(&mode)
+                .ok_or_else(|| format!("Unknown SMTP_TLS '{}' (supported: tls, starttls, none)", mode))?,
+            Err(_) => SmtpSecurity::StartTls,
+        };
+        let port = match std::env::var("SMTP_PORT") {
+            Ok(port) => port.parse().map_err(|_| format!("Invalid SMTP_PORT '{}'", port))?,
+            Err(_) => security.default_port(),
+        };
+
+        let from = std::env::var("ALERT_EMAIL_FROM").map_err(|_| "ALERT_EMAIL_FROM must be set when SMTP_HOST is")?;
+        let to = std::env::var("ALERT_EMAIL_TO").map_err(|_| "ALERT_EMAIL_TO must be set when SMTP_HOST is")?;
+        let to = to
+            .split(',')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .map(|addr| addr.parse().map_err(|e| format!("Invalid ALERT_EMAIL_TO address '{}': {}", addr, e)))
+            .collect::<Result<Vec<Mailbox>, String>>()?;
+        if to.is_empty() {
+            return Err("ALERT_EMAIL_TO has no addresses".into());
+        }
+
+        let credentials = std::env::var("SMTP_USER")
+            .ok()
+            .map(|user| Credentials::new(user, std::env::var("SMTP_PASS").unwrap_or_default()));
+
+        let mut builder = match security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)?,
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?,
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(port);
+        if let Some(credentials) = credentials {
+            builder = builder.credentials(credentials);
+        }
+
+        Ok(Some(Self {
+            mailer: builder.build(),
+            from: from.parse().map_err(|e| format!("Invalid ALERT_EMAIL_FROM '{}': {}", from, e))?,
+            to,
+        }))
+    }
+}
+
+#[async_trait]
+impl AlertChannel for EmailClient {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    // send an alert to every recipient in one email
+    async fn send(&self, alert: &ActiveAlert) -> Result<(), AlertError> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(render_subject(alert))
+            .header(ContentType::TEXT_HTML);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+
+        let message = message.body(render_body(alert)).map_err(|e| AlertError::Email(e.to_string()))?;
+        self.mailer.send(message).await.map_err(|e| AlertError::Email(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn parse_security(mode: &str) -> Option<SmtpSecurity> {
+    match mode.trim().to_lowercase().as_str() {
+        "tls" | "ssl" => Some(SmtpSecurity::Tls),
+        "starttls" => Some(SmtpSecurity::StartTls),
+        "none" => Some(SmtpSecurity::None),
+        _ => None,
+    }
+}
+
+// [LogAI][Warning] payment-service: Error Spike
+pub fn render_subject(alert: &ActiveAlert) -> String {
+    format!("[LogAI][{:?}] {}: {}", alert.severity, alert.key.service, alert.key.rule_name)
+}
+
+// Same details as the Slack message, as a small HTML table
+pub fn render_body(alert: &ActiveAlert) -> String {
+    let color = match alert.severity {
+        Severity::Critical => "#d32f2f",
+        Severity::Warning => "#f9a825",
+        Severity::Info => "#388e3c",
+    };
+    let row = |label: &str, value: &str| {
+        format!(
+            "<tr><td style=\"padding:4px 12px 4px 0;color:#666\">{}</td><td style=\"padding:4px 0\">{}</td></tr>",
+            label,
+            escape_html(value)
+        )
+    };
+
+    format!(
+        "<div style=\"font-family:sans-serif;border-left:4px solid {};padding-left:12px\">\
+         <h2 style=\"margin:0 0 8px\">{}</h2>\
+         <p>{}</p>\
+         <table>{}{}{}</table>\
+         <p style=\"color:#999;font-size:12px\">LogAI Anomaly Detection</p>\
+         </div>",
+        color,
+        escape_html(&alert.key.rule_name),
+        escape_html(&alert.message),
+        row("Service", &alert.key.service),
+        row("Severity", &format!("{:?}", alert.severity)),
+        row("Detected at", &alert.firing_at.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+    )
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::{AlertKey, AlertState};
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn alert() -> ActiveAlert {
+        let at = Utc.with_ymd_and_hms(2026, 2, 10, 3, 0, 0).unwrap();
+        ActiveAlert {
+            id: Uuid::new_v4(),
+            key: AlertKey {
+                rule_name: "Error Spike".to_string(),
+                service: "payment-service".to_string(),
+            },
+            state: AlertState::Firing,
+            severity: Severity::Warning,
+            message: "Error count spike detected: current=42.0, expected=3.1 (threshold=9.5) <db>".to_string(),
+            current_value: 42.0,
+            expected_value: 3.1,
+            firing_at: at,
+            last_notified_at: at,
+            acknowledged_at: None,
+        }
+    }
+
+    #[test]
+    fn test_rendered_email() {
+        let alert = alert();
+        assert_eq!(render_subject(&alert), "[LogAI][Warning] payment-service: Error Spike");
+
+        let body = render_body(&alert);
+        assert!(body.contains("<h2 style=\"margin:0 0 8px\">Error Spike</h2>"));
+        assert!(body.contains("current=42.0, expected=3.1 (threshold=9.5) &lt;db&gt;"));
+        assert!(body.contains(">payment-service</td>"));
+        assert!(body.contains(">Warning</td>"));
+        assert!(body.contains(">2026-02-10 03:00:00 UTC</td>"));
+    }
+
+    #[test]
+    fn test_security_modes() {
+        assert_eq!(parse_security("STARTTLS"), Some(SmtpSecurity::StartTls));
+        assert_eq!(parse_security("tls"), Some(SmtpSecurity::Tls));
+        assert_eq!(parse_security("none"), Some(SmtpSecurity::None));
+        assert_eq!(parse_security("plain"), None);
+        assert_eq!(SmtpSecurity::Tls.default_port(), 465);
+    }
+}
@@ -3,11 +3,19 @@
 pub mod config;
 pub mod detection;
 pub mod alerting;
+pub mod discord;
+pub mod email;
+pub mod pagerduty;
 pub mod slack;
+pub mod recording;
 pub mod runner;
 
 pub use config::AnomalyConfig;
 pub use detection::AnomalyDetector;
-pub use alerting::AlertEngine;
+pub use alerting::{AlertChannel, AlertEngine, AlertError};
 pub use slack::SlackClient;
+pub use discord::DiscordClient;
+pub use email::EmailClient;
+pub use pagerduty::PagerDutyClient;
+pub use recording::RecordingChannel;
 pub use runner::AnomalyRunner;
\ No newline at end of file
@@ -0,0 +1,130 @@
+//! PagerDuty Events API v2 integration
+
+use crate::alerting::{ActiveAlert, AlertChannel, AlertError};
+use crate::config::Severity;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+
+const EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+// PagerDuty client for triggering incidents
+pub struct PagerDutyClient {
+    client: Client,
+    routing_key: String,
+}
+
+// Events API v2 trigger event
+#[derive(Serialize)]
+struct PagerDutyEvent {
+    routing_key: String,
+    event_action: &'static str,
+    // repeats of one rule on one service update the same incident
+    dedup_key: String,
+    payload: PagerDutyPayload,
+}
+
+#[derive(Serialize)]
+struct PagerDutyPayload {
+    summary: String,
+    source: String,
+    severity: &'static str,
+    timestamp: String,
+    custom_details: serde_json::Value,
+}
+
+impl PagerDutyClient {
+    // create a client for one PagerDuty service (its Events API v2 integration key)
+    pub fn new(routing_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            routing_key,
+        }
+    }
+
+    // Build the trigger event from alert
+    fn build_event(&self, alert: &ActiveAlert) -> PagerDutyEvent {
+        PagerDutyEvent {
+            routing_key: self.routing_key.clone(),
+            event_action: "trigger",
+            dedup_key: format!("{}/{}", alert.key.rule_name, alert.key.service),
+            payload: PagerDutyPayload {
+                summary: format!("{}: {}", alert.key.rule_name, alert.message),
+                source: alert.key.service.clone(),
+                severity: severity_name(&alert.severity),
+                timestamp: alert.firing_at.to_rfc3339(),
+                custom_details: serde_json::json!({
+                    "current": alert.current_value,
+                    "expected": alert.expected_value,
+                }),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl AlertChannel for PagerDutyClient {
+    fn name(&self) -> &'static str {
+        "pagerduty"
+    }
+
+    // trigger (or update) the incident for this alert
+    async fn send(&self, alert: &ActiveAlert) -> Result<(), AlertError> {
+        let event = self.build_event(alert);
+        let response = self.client.post(EVENTS_URL).json(&event).send().await?;
+
+        // 202 Accepted on success
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(AlertError::Rejected { service: "PagerDuty", message: error_text })
+        }
+    }
+}
+
+// PagerDuty's own severity names
+fn severity_name(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "critical",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::{AlertKey, AlertState};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_trigger_event() {
+        let client = PagerDutyClient::new("R0UT1NGKEY".to_string());
+        let alert = ActiveAlert {
+            id: Uuid::new_v4(),
+            key: AlertKey {
+                rule_name: "OOM".to_string(),
+                service: "payment-api".to_string(),
+            },
+            state: AlertState::Firing,
+            severity: Severity::Critical,
+            message: "3 out-of-memory kills in 5 minutes".to_string(),
+            current_value: 3.0,
+            expected_value: 0.0,
+            firing_at: Utc::now(),
+            last_notified_at: Utc::now(),
+            acknowledged_at: None,
+        };
+
+        let event = serde_json::to_value(client.build_event(&alert)).unwrap();
+        assert_eq!(event["routing_key"], "R0UT1NGKEY");
+        assert_eq!(event["event_action"], "trigger");
+        assert_eq!(event["dedup_key"], "OOM/payment-api");
+        assert_eq!(event["payload"]["source"], "payment-api");
+        assert_eq!(event["payload"]["severity"], "critical");
+        assert_eq!(event["payload"]["summary"], "OOM: 3 out-of-memory kills in 5 minutes");
+        assert_eq!(event["payload"]["custom_details"]["current"], 3.0);
+    }
+}
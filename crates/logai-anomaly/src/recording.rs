@@ -0,0 +1,39 @@
+//! Recording alert channel - captures alerts instead of sending them (no network needed)
+
+use crate::alerting::{ActiveAlert, AlertChannel, AlertError};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+// Channel that keeps every alert it is sent. Clones share the recording, so keep one
+// and hand a clone to the `AlertEngine`
+#[derive(Clone)]
+pub struct RecordingChannel {
+    name: &'static str,
+    sent: Arc<Mutex<Vec<ActiveAlert>>>,
+}
+
+impl RecordingChannel {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            sent: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    // alerts received so far, oldest first
+    pub fn sent(&self) -> Vec<ActiveAlert> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl AlertChannel for RecordingChannel {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn send(&self, alert: &ActiveAlert) -> Result<(), AlertError> {
+        self.sent.lock().unwrap().push(alert.clone());
+        Ok(())
+    }
+}
@@ -1,8 +1,13 @@
-use crate::alerting::AlertEngine;
-use crate::config::{AnomalyConfig, load_config};
-use crate::detection::AnomalyDetector;
+use crate::alerting::{AlertChannel, AlertEngine, AlertKey};
+use crate::config::{AnomalyConfig, ChannelConfig, load_config};
+use crate::detection::{Anomaly, AnomalyDetector};
+use crate::discord::DiscordClient;
+use crate::email::EmailClient;
+use crate::pagerduty::PagerDutyClient;
 use crate::slack::SlackClient;
 use clickhouse::Client;
+use logai_core::LogEntry;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::time::Duration;
 use tokio::time::interval;
@@ -13,11 +18,61 @@ pub struct AnomalyRunner {
     config: AnomalyConfig,
     detector: AnomalyDetector,
     alert_engine: AlertEngine,
-    slack_client: SlackClient,
+    escalation: Escalation,
+    index: Option<AnomalyIndex>,
+}
+
+// where alerted anomalies are published as logs, for the workers to store and embed
+struct AnomalyIndex {
+    nats: async_nats::Client,
+    subject: String,
+}
+
+// consecutive firings per (rule, service), kept between ticks so a rule that
+// keeps firing gets its severity bumped
+struct Escalation {
+    escalate_after: Option<u32>,
+    firings: HashMap<AlertKey, u32>,
+}
+
+impl Escalation {
+    fn new(escalate_after: Option<u32>) -> Self {
+        Self {
+            escalate_after,
+            firings: HashMap::new(),
+        }
+    }
+
+    // count this check of a rule and escalate services that reached the limit;
+    // services the rule passed cleanly for start over
+    fn apply(&mut self, rule_name: &str, anomalies: &mut [Anomaly]) {
+        let fired: HashSet<&str> = anomalies.iter().map(|a| a.service.as_str()).collect();
+        self.firings
+            .retain(|key, _| key.rule_name != rule_name || fired.contains(key.service.as_str()));
+
+        for service in fired {
+            let key = AlertKey {
+                rule_name: rule_name.to_string(),
+                service: service.to_string(),
+            };
+            *self.firings.entry(key).or_insert(0) += 1;
+        }
+
+        let Some(limit) = self.escalate_after else { return };
+        for anomaly in anomalies {
+            let key = AlertKey {
+                rule_name: rule_name.to_string(),
+                service: anomaly.service.clone(),
+            };
+            if self.firings.get(&key).is_some_and(|count| *count >= limit) {
+                anomaly.severity = anomaly.severity.escalated();
+            }
+        }
+    }
 }
 
 impl AnomalyRunner {
-    // create a new runner from config file
+    // create a new runner from a config file or a directory of rule files
     pub fn new<P: AsRef<Path>>(
         config_path: P,
         clickhouse_url: &str,
@@ -32,22 +87,58 @@ impl AnomalyRunner {
         let detector = AnomalyDetector::new(clickhouse);
         let mut alert_engine = AlertEngine::new();
 
-        // Set coooldowns from config
+        // Set coooldowns and channel routes from config
         for rule in &config.rules {
             alert_engine.set_cooldown(&rule.name, rule.alert.cooldown_minutes);
+            if let Some(channels) = &rule.alert.channels {
+                alert_engine.set_channels(&rule.name, channels.clone());
+            }
         }
 
-        // create Slack Client
-        let slack_client = SlackClient::new(config.slack.webhook_url.clone(), config.slack.enabled);
+        // create Slack and Discord Clients (only the enabled ones, so routes can tell)
+        if config.slack.enabled {
+            let slack_client = SlackClient::new(config.slack.webhook_url.clone(), true);
+            alert_engine.add_channel(Box::new(slack_client));
+        }
+        if config.discord.enabled {
+            let discord_client = DiscordClient::new(config.discord.webhook_url.clone(), true);
+            alert_engine.add_channel(Box::new(discord_client));
+        }
+
+        // email is configured from the environment (SMTP_HOST and friends)
+        if let Some(email_client) = EmailClient::from_env()? {
+            alert_engine.add_channel(Box::new(email_client));
+        }
+
+        // [channels.<name>] sections
+        for (name, channel) in &config.channels {
+            alert_engine.add_named_channel(name.clone(), named_channel(channel));
+        }
+
+        for warning in check_routes(&config, &alert_engine.channel_names())? {
+            eprintln!("Warning: {}", warning);
+        }
 
         Ok(Self {
+            escalation: Escalation::new(config.escalate_after),
             config,
             detector,
             alert_engine,
-            slack_client,
+            index: None,
         })
     }
 
+    /// Also publish each alerted anomaly as a log on the ingest subject (`logs.ingest` unless
+    /// NATS_INGEST_SUBJECT says otherwise), so it can be searched and asked about later.
+    /// Only anomalies that got through the cooldown are published, not every repeat.
+    pub fn with_index(mut self, nats: async_nats::Client, subject: impl Into<String>) -> Self {
+        self.index = Some(AnomalyIndex {
+            nats,
+            subject: subject.into(),
+        });
+        self
+    }
+
     pub async fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let check_interval = Duration::from_secs(self.config.check_interval_seconds);
         let mut ticker = interval(check_interval);
@@ -61,17 +152,28 @@ impl AnomalyRunner {
             // check each rule
             for rule in &self.config.rules {
                 match self.detector.check_rule(rule).await {
-                    Ok(anomalies) => {
+                    Ok(mut anomalies) => {
+                        self.escalation.apply(&rule.name, &mut anomalies);
                         if !anomalies.is_empty() {
                             println!("Detected {} anomalies for rule '{}", anomalies.len(), rule.name);
+                            let entries: Vec<LogEntry> = anomalies.iter().map(Anomaly::to_log_entry).collect();
 
                             // process through alerts engine deduplication
                             let alerts = self.alert_engine.process_anomalies(anomalies);
 
-                            // send to Slack
+                            if let Some(index) = &self.index {
+                                // one rule per pass, so the service tells which were alerted
+                                for entry in entries.iter().filter(|e| alerts.iter().any(|a| a.key.service == e.service)) {
+                                    if let Err(e) = index.publish(entry).await {
+                                        eprintln!("Failed to publish anomaly log for '{}': {}", entry.service, e);
+                                    }
+                                }
+                            }
+
+                            // send to Slack, Discord, email
                             for alert in alerts {
-                                if let Err(e) = self.slack_client.send_alert(&alert).await {
-                                    eprintln!("Failed to send Slack alert: {}", e);
+                                for failure in self.alert_engine.notify(&alert).await {
+                                    eprintln!("Failed to send alert via {}", failure);
                                 }
                             }
                         }
@@ -84,3 +186,148 @@ impl AnomalyRunner {
         }
     }
 }
+
+fn named_channel(config: &ChannelConfig) -> Box<dyn AlertChannel> {
+    match config {
+        ChannelConfig::Slack { webhook_url } => Box::new(SlackClient::new(webhook_url.clone(), true)),
+        ChannelConfig::Discord { webhook_url } => Box::new(DiscordClient::new(webhook_url.clone(), true)),
+        ChannelConfig::Pagerduty { routing_key } => Box::new(PagerDutyClient::new(routing_key.clone())),
+    }
+}
+
+// A route naming a channel that doesn't exist (a typo like "slak") is an error. Known
+// channels that aren't set up (Slack disabled, no SMTP_HOST) are warnings, and so is a
+// rule whose alerts would go nowhere because none of its channels is
+fn check_routes(config: &AnomalyConfig, active: &[&str]) -> Result<Vec<String>, String> {
+    let known = config.channel_names();
+    let mut warnings = Vec::new();
+    for rule in &config.rules {
+        let Some(route) = &rule.alert.channels else { continue };
+        if let Some(error) = rule.unknown_channels(&known).into_iter().next() {
+            return Err(format!("rule '{}': {}", rule.name, error));
+        }
+        let inactive: Vec<&str> = route
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !active.contains(name))
+            .collect();
+        if !route.is_empty() && inactive.len() == route.len() {
+            warnings.push(format!(
+                "rule '{}' alerts nowhere: none of its channels ({}) is configured",
+                rule.name,
+                inactive.join(", ")
+            ));
+        } else if !inactive.is_empty() {
+            warnings.push(format!("rule '{}': channels not configured: {}", rule.name, inactive.join(", ")));
+        }
+    }
+    Ok(warnings)
+}
+
+impl AnomalyIndex {
+    async fn publish(&self, entry: &LogEntry) -> Result<(), Box<dyn std::error::Error>> {
+        let payload = serde_json::to_vec(entry)?;
+        self.nats.publish(self.subject.clone(), payload.into()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Severity;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn warning(service: &str) -> Anomaly {
+        Anomaly {
+            id: Uuid::new_v4(),
+            rule_name: "Error Spike".to_string(),
+            service: service.to_string(),
+            severity: Severity::Warning,
+            message: "error_count is 42".to_string(),
+            current_value: 42.0,
+            expected_value: 5.0,
+            detected_at: Utc::now(),
+        }
+    }
+
+    // one check: escalate, then run through the alert engine like the loop does
+    fn tick(escalation: &mut Escalation, engine: &mut AlertEngine, services: &[&str]) -> Vec<Severity> {
+        let mut anomalies: Vec<Anomaly> = services.iter().map(|s| warning(s)).collect();
+        escalation.apply("Error Spike", &mut anomalies);
+        engine.process_anomalies(anomalies).iter().map(|a| a.severity).collect()
+    }
+
+    #[test]
+    fn test_persistent_warning_escalates_to_critical() {
+        let mut escalation = Escalation::new(Some(3));
+        let mut engine = AlertEngine::new();
+        engine.set_cooldown("Error Spike", 0);
+
+        assert_eq!(tick(&mut escalation, &mut engine, &["payments"]), [Severity::Warning]);
+        assert_eq!(tick(&mut escalation, &mut engine, &["payments"]), [Severity::Warning]);
+        assert_eq!(tick(&mut escalation, &mut engine, &["payments"]), [Severity::Critical]);
+
+        // a clean check resets the count
+        tick(&mut escalation, &mut engine, &[]);
+        assert_eq!(tick(&mut escalation, &mut engine, &["payments"]), [Severity::Warning]);
+    }
+
+    #[test]
+    fn test_routes_checked_at_start() {
+        let config = |channels: &str| -> AnomalyConfig {
+            toml::from_str(&format!(
+                r#"
+check_interval_seconds = 60
+
+[slack]
+enabled = true
+webhook_url = "https://hooks.slack.com/services/T0/B0/x"
+
+[channels.oncall]
+type = "pagerduty"
+routing_key = "R0UT1NGKEY"
+
+[[rules]]
+name = "OOM"
+services = ["*"]
+
+[rules.detection]
+type = "new_error"
+lookback_hours = 24
+
+[rules.alert]
+severity = "critical"
+cooldown_minutes = 5
+channels = {}
+"#,
+                channels
+            ))
+            .unwrap()
+        };
+        let active = ["slack", "oncall"];
+
+        assert!(check_routes(&config(r#"["oncall", "slack"]"#), &active).unwrap().is_empty());
+        let err = check_routes(&config(r#"["slak"]"#), &active).unwrap_err();
+        assert!(err.starts_with("rule 'OOM': unknown alert channel 'slak'"), "{}", err);
+
+        // email without SMTP_HOST: the rule would alert nowhere
+        let warnings = check_routes(&config(r#"["email"]"#), &active).unwrap();
+        assert_eq!(warnings, ["rule 'OOM' alerts nowhere: none of its channels (email) is configured"]);
+        let warnings = check_routes(&config(r#"["email", "oncall"]"#), &active).unwrap();
+        assert_eq!(warnings, ["rule 'OOM': channels not configured: email"]);
+    }
+
+    #[test]
+    fn test_escalation_skips_cooldown() {
+        let mut escalation = Escalation::new(Some(2));
+        let mut engine = AlertEngine::new();
+        engine.set_cooldown("Error Spike", 60);
+
+        assert_eq!(tick(&mut escalation, &mut engine, &["payments"]), [Severity::Warning]);
+        assert_eq!(tick(&mut escalation, &mut engine, &["payments"]), [Severity::Critical]);
+        // still critical, still in cooldown
+        assert!(tick(&mut escalation, &mut engine, &["payments"]).is_empty());
+    }
+}
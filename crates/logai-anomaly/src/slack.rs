@@ -1,7 +1,8 @@
 //! Slack webhook integration
 
-use crate::alerting::ActiveAlert;
+use crate::alerting::{ActiveAlert, AlertChannel, AlertError};
 use crate::config::Severity;
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::Serialize;
 
@@ -48,32 +49,6 @@ impl SlackClient {
         }
     }
 
-    // send an alert to stack
-    pub async fn send_alert(&self, alert: &ActiveAlert) -> Result<(), Box<dyn std::error::Error>> {
-        //skip if disabled
-        if !self.enabled {
-            return Ok(());
-        }
-        // build the message
-        let message = self.build_message(alert);
-
-        // send to slack
-        let response = self
-            .client
-            .post(&self.webhook_url)
-            .json(&message)
-            .send()
-            .await?;
-
-        // Check response
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            let error_text = response.text().await.unwrap_or_default();
-            Err(format!("Slack API error: {}", error_text).into())
-        }
-    }
-
     // Build Slack message form alert
     fn build_message(&self, alert: &ActiveAlert) -> SlackMessage {
         let emoji = match alert.severity {
@@ -116,3 +91,36 @@ impl SlackClient {
         }
     }
 }
+
+#[async_trait]
+impl AlertChannel for SlackClient {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    // send an alert to stack
+    async fn send(&self, alert: &ActiveAlert) -> Result<(), AlertError> {
+        //skip if disabled
+        if !self.enabled {
+            return Ok(());
+        }
+        // build the message
+        let message = self.build_message(alert);
+
+        // send to slack
+        let response = self
+            .client
+            .post(&self.webhook_url)
+            .json(&message)
+            .send()
+            .await?;
+
+        // Check response
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(AlertError::Rejected { service: "Slack", message: error_text })
+        }
+    }
+}
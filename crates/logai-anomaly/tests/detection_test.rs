@@ -1,7 +1,7 @@
 use clickhouse::Client;
 use logai_anomaly::config::{load_config, Severity};
 use logai_anomaly::detection::{Anomaly,AnomalyDetector};
-use logai_anomaly::alerting::{AlertEngine, AlertKey};
+use logai_anomaly::alerting::{AlertChannel, AlertEngine, AlertKey};
 use chrono::Utc;
 use uuid::Uuid;
 use logai_anomaly::slack::SlackClient;
@@ -100,11 +100,13 @@ async fn test_slack_client() {
         state: logai_anomaly::alerting::AlertState::Firing,
         severity: Severity::Critical,
         message: "Error count spike: 50 errors in 5 minutes".to_string(),
+        current_value: 50.0,
+        expected_value: 4.0,
         firing_at: Utc::now(),
         last_notified_at: Utc::now(),
         acknowledged_at: None,
     };
-    match client.send_alert(&alert).await {
+    match client.send(&alert).await {
         Ok(_) => println!("✅ Alert sent to Slack!"),
         Err(e) => println!("❌ Failed: {}", e),
     }
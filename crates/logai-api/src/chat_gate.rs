@@ -0,0 +1,180 @@
+// Chat gating: greetings get a canned hello and gibberish or off-topic messages a canned
+// redirect, without touching Qdrant. Keyword lists first; only a message with nothing
+// log-like in it goes to the LLM for a yes/no.
+
+/// What the chat handler should do with a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateDecision {
+    /// Run retrieval as usual
+    Pass,
+    Greeting,
+    OffTopic,
+    /// No keyword either way: ask the LLM (`ChatGate::off_topic_prompt`)
+    AskLlm,
+}
+
+const GREETINGS: &[&str] = &[
+    "hi", "hello", "hey", "good morning", "good afternoon", "good evening", "howdy", "sup", "what's up", "yo",
+];
+const GIBBERISH: &[&str] = &["asdf", "qwer", "zxcv", "hjkl", "jkl;"];
+const LOG_KEYWORDS: &[&str] = &[
+    "error", "log", "warn", "debug", "info", "service", "api", "database", "db", "timeout", "slow", "failed",
+    "failure", "crash", "down", "outage", "issue", "problem", "anomal", "incident", "alert", "critical", "auth",
+    "payment", "nginx", "redis", "kafka", "query", "connection", "latency", "performance", "traffic", "request",
+    "response", "yesterday", "today", "last hour", "last minute", "recent", "happened", "show me", "find",
+];
+// a number right after one of these ("exit code 137", "errno 111") is a status too
+const STATUS_WORDS: &[&str] = &["status", "code", "exit", "errno", "http", "signal"];
+// messages this short aren't worth an LLM call, they pass
+const MIN_CLASSIFIED_LEN: usize = 6;
+
+#[derive(Debug, Clone)]
+pub struct ChatGate {
+    pub enabled: bool,
+    pub greetings: Vec<String>,
+    pub gibberish: Vec<String>,
+    pub log_keywords: Vec<String>,
+}
+
+impl Default for ChatGate {
+    fn default() -> Self {
+        let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        Self {
+            enabled: true,
+            greetings: owned(GREETINGS),
+            gibberish: owned(GIBBERISH),
+            log_keywords: owned(LOG_KEYWORDS),
+        }
+    }
+}
+
+impl ChatGate {
+    /// Environment variables:
+    /// - LOGAI_DISABLE_CHAT_GATE: Skip all gating, every message goes to retrieval (default: false)
+    /// - LOGAI_CHAT_GREETINGS: Comma-separated greetings answered with a hello (replaces the defaults)
+    /// - LOGAI_CHAT_GIBBERISH: Comma-separated keyboard-mash fragments answered with a redirect
+    /// - LOGAI_CHAT_LOG_KEYWORDS: Comma-separated words that mark a message as about logs
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let list = |key: &str, default: Vec<String>| {
+            var(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|word| word.trim().to_lowercase())
+                        .filter(|word| !word.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|words| !words.is_empty())
+                .unwrap_or(default)
+        };
+        let disabled = var("LOGAI_DISABLE_CHAT_GATE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or(false);
+
+        Self {
+            enabled: !disabled,
+            greetings: list("LOGAI_CHAT_GREETINGS", defaults.greetings),
+            gibberish: list("LOGAI_CHAT_GIBBERISH", defaults.gibberish),
+            log_keywords: list("LOGAI_CHAT_LOG_KEYWORDS", defaults.log_keywords),
+        }
+    }
+
+    pub fn check(&self, message: &str) -> GateDecision {
+        if !self.enabled {
+            return GateDecision::Pass;
+        }
+        let msg = message.trim().to_lowercase();
+        // "502?", "hey, any 500s on checkout": a status is about logs whatever else it says
+        let about_logs = has_status(&msg) || self.log_keywords.iter().any(|k| msg.contains(k.as_str()));
+
+        let greeting = self
+            .greetings
+            .iter()
+            .any(|g| msg == *g || msg.strip_prefix(g.as_str()).is_some_and(|rest| rest.starts_with([' ', ',', '!'])));
+        if greeting && !about_logs {
+            return GateDecision::Greeting;
+        }
+        if self.gibberish.iter().any(|p| msg.contains(p.as_str())) {
+            return GateDecision::OffTopic;
+        }
+        if about_logs || msg.len() < MIN_CLASSIFIED_LEN {
+            return GateDecision::Pass;
+        }
+        GateDecision::AskLlm
+    }
+
+    /// Yes/no question for messages `check` couldn't place; anything but YES is off-topic
+    pub fn off_topic_prompt(message: &str) -> String {
+        format!(
+            r#"Is this question about analyzing logs, debugging, system errors, or infrastructure monitoring?
+Question: "{}"
+Answer YES or NO only."#,
+            message
+        )
+    }
+}
+
+// An HTTP status (100-599, "502s" too) or a number after a status word
+fn has_status(msg: &str) -> bool {
+    let tokens: Vec<&str> = msg
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let http_status = |token: &str| {
+        let digits = token.strip_suffix('s').unwrap_or(token);
+        digits.len() == 3 && digits.parse::<u16>().is_ok_and(|code| (100..600).contains(&code))
+    };
+    tokens.iter().any(|t| http_status(t))
+        || tokens
+            .windows(2)
+            .any(|pair| STATUS_WORDS.contains(&pair[0]) && pair[1].chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logai_core::testing::vars;
+
+    #[test]
+    fn test_gate_decisions() {
+        let gate = ChatGate::default();
+        assert_eq!(gate.check("502?"), GateDecision::Pass);
+        assert_eq!(gate.check("hi"), GateDecision::Greeting);
+        assert_eq!(gate.check("Hello!"), GateDecision::Greeting);
+        assert_eq!(gate.check("asdf"), GateDecision::OffTopic);
+        assert_eq!(gate.check("Why is the payment service slow since this morning?"), GateDecision::Pass);
+
+        // a status skips the LLM, and outweighs a greeting
+        assert_eq!(gate.check("any 504s from checkout"), GateDecision::Pass);
+        assert_eq!(gate.check("worker exited with exit code 137"), GateDecision::Pass);
+        assert_eq!(gate.check("hey, why all the 500s"), GateDecision::Pass);
+        // not greetings: words that merely start like one
+        assert_eq!(gate.check("highest memory pod"), GateDecision::AskLlm);
+        assert_eq!(gate.check("what's the weather in Paris"), GateDecision::AskLlm);
+        assert_eq!(gate.check("year 2025 was good"), GateDecision::AskLlm);
+    }
+
+    #[test]
+    fn test_gate_from_env() {
+        let off = ChatGate::from_vars(vars(&[("LOGAI_DISABLE_CHAT_GATE", "true")]));
+        for message in ["hi", "asdf", "what's the weather in Paris"] {
+            assert_eq!(off.check(message), GateDecision::Pass);
+        }
+
+        let custom = ChatGate::from_vars(vars(&[
+            ("LOGAI_CHAT_GREETINGS", "hallo, moin"),
+            ("LOGAI_CHAT_LOG_KEYWORDS", "Billing, ledger"),
+            ("LOGAI_CHAT_GIBBERISH", " , "),
+        ]));
+        assert_eq!(custom.check("moin"), GateDecision::Greeting);
+        assert_eq!(custom.check("hi"), GateDecision::Pass);
+        assert_eq!(custom.check("ledger mismatch overnight"), GateDecision::Pass);
+        // an empty list keeps the defaults
+        assert_eq!(custom.gibberish, ChatGate::default().gibberish);
+    }
+}
@@ -3,39 +3,44 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use logai_anomaly::detection::SKIP_ANOMALY_LOGS;
+use serde::Deserialize;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::models::{AlertItem, AlertsQuery, AlertsResponse, AnomaliesQuery, AnomaliesResponse, AnomalyItem};
+use crate::handlers::sql_string;
+use crate::models::{
+    AlertItem, AlertsQuery, AlertsResponse, AnomaliesQuery, AnomaliesResponse, AnomalyItem, LevelCounts,
+};
 use crate::state::AppState;
 
+// a level shift needs at least this many warn+error logs in the current window
+const LEVEL_SHIFT_MIN_LOGS: u64 = 10;
+// ...and the error share of warn+error has to grow by this much over the baseline
+const LEVEL_SHIFT_MIN_INCREASE: f64 = 0.3;
+
+/// One service's level counts: the current window, plus the rest of the hour as its baseline
+#[derive(Deserialize, clickhouse::Row)]
+pub(crate) struct LevelRow {
+    pub service: String,
+    pub total: u64,
+    pub info: u64,
+    pub warn: u64,
+    pub error: u64,
+    pub fatal: u64,
+    pub baseline_total: u64,
+    pub baseline_warn: u64,
+    pub baseline_error: u64,
+}
+
 pub async fn get_alerts(
     State(state): State<Arc<AppState>>,
     Query(params): Query<AlertsQuery>,
 ) -> Result<Json<AlertsResponse>, (StatusCode, String)> {
-    info!(status = ?params.status, "Alerts request");
-
-    let query = match &params.status {
-        Some(status) if status == "firing" => {
-            "SELECT service, level, message, timestamp 
-             FROM logs 
-             WHERE level = 'Error' 
-             AND timestamp > now() - INTERVAL 1 HOUR
-             ORDER BY timestamp DESC
-             LIMIT 20"
-        }
-        _ => {
-            "SELECT service, level, message, timestamp 
-             FROM logs 
-             WHERE level = 'Error' 
-             AND timestamp > now() - INTERVAL 24 HOUR
-             ORDER BY timestamp DESC
-             LIMIT 50"
-        }
-    };
+    info!(status = ?params.status, from = ?params.from, to = ?params.to, "Alerts request");
 
     let rows: Vec<(String, String, String, i64)> = state.clickhouse
-        .query(query)
+        .query(&alerts_query(&params))
         .fetch_all()
         .await
         .unwrap_or_default();
@@ -70,6 +75,35 @@ pub async fn get_alerts(
     Ok(Json(AlertsResponse { alerts }))
 }
 
+/// Recent errors: the last hour for `status=firing`, else the last day; `from`/`to` replace that
+/// window, and `to` alone moves its end
+fn alerts_query(params: &AlertsQuery) -> String {
+    let (hours, limit) = match params.status.as_deref() {
+        Some("firing") => (1, 20),
+        _ => (24, 50),
+    };
+    let mut conditions = vec!["level = 'Error'".to_string()];
+    match (params.from, params.to) {
+        (Some(from), _) => conditions.push(format!("timestamp >= toDateTime64({}, 3)", from)),
+        // the default window ends at `to` when only that is given
+        (None, Some(to)) => conditions.push(format!("timestamp > toDateTime64({}, 3) - INTERVAL {} HOUR", to, hours)),
+        (None, None) => conditions.push(format!("timestamp > now() - INTERVAL {} HOUR", hours)),
+    }
+    if let Some(to) = params.to {
+        conditions.push(format!("timestamp <= toDateTime64({}, 3)", to));
+    }
+
+    format!(
+        "SELECT service, level, message, timestamp
+         FROM logs
+         WHERE {}
+         ORDER BY timestamp DESC
+         LIMIT {}",
+        conditions.join(" AND "),
+        limit
+    )
+}
+
 pub async fn get_anomalies(
     State(state): State<Arc<AppState>>,
     Query(params): Query<AnomaliesQuery>,
@@ -93,8 +127,8 @@ pub async fn get_anomalies(
     for service in services {
         let current_errors: u64 = state.clickhouse
             .query(&format!(
-                "SELECT count(*) FROM logs WHERE service = '{}' AND level = 'Error' AND timestamp > now() - INTERVAL 5 MINUTE",
-                service
+                "SELECT count(*) FROM logs WHERE service = '{}' AND level = 'Error' AND timestamp > now() - INTERVAL 5 MINUTE AND {}",
+                service, SKIP_ANOMALY_LOGS
             ))
             .fetch_one()
             .await
@@ -106,10 +140,10 @@ pub async fn get_anomalies(
                     SELECT count(*) as error_count 
                     FROM logs 
                     WHERE service = '{}' AND level = 'Error' 
-                    AND timestamp > now() - INTERVAL 1 HOUR
+                    AND timestamp > now() - INTERVAL 1 HOUR AND {}
                     GROUP BY toStartOfFiveMinutes(timestamp)
                 )",
-                service
+                service, SKIP_ANOMALY_LOGS
             ))
             .fetch_one()
             .await
@@ -137,8 +171,8 @@ pub async fn get_anomalies(
 
         let current_volume: u64 = state.clickhouse
             .query(&format!(
-                "SELECT count(*) FROM logs WHERE service = '{}' AND timestamp > now() - INTERVAL 5 MINUTE",
-                service
+                "SELECT count(*) FROM logs WHERE service = '{}' AND timestamp > now() - INTERVAL 5 MINUTE AND {}",
+                service, SKIP_ANOMALY_LOGS
             ))
             .fetch_one()
             .await
@@ -150,10 +184,10 @@ pub async fn get_anomalies(
                     SELECT count(*) as log_count 
                     FROM logs 
                     WHERE service = '{}' 
-                    AND timestamp > now() - INTERVAL 1 HOUR
+                    AND timestamp > now() - INTERVAL 1 HOUR AND {}
                     GROUP BY toStartOfFiveMinutes(timestamp)
                 )",
-                service
+                service, SKIP_ANOMALY_LOGS
             ))
             .fetch_one()
             .await
@@ -174,10 +208,179 @@ pub async fn get_anomalies(
         }
     }
 
+    // all services in one grouped query rather than one per service
+    let level_rows: Vec<LevelRow> = match state
+        .clickhouse
+        .query(&level_counts_query(params.service.as_deref(), 5, Some(20)))
+        .fetch_all()
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(error = %e, "Level distribution query failed");
+            Vec::new()
+        }
+    };
+    anomalies.extend(level_rows.iter().filter_map(level_shift));
+
+    let level_distribution = level_rows
+        .into_iter()
+        .map(|row| LevelCounts {
+            service: row.service,
+            info: row.info,
+            warn: row.warn,
+            error: row.error,
+            fatal: row.fatal,
+        })
+        .collect();
+
     info!(count = anomalies.len(), "Anomalies detected");
 
     Ok(Json(AnomaliesResponse {
         anomalies,
+        level_distribution,
         checked_at: now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
     }))
 }
+
+/// Level counts per service: the last `window_minutes` by level, and the rest of the hour
+/// before as the baseline (for `level_shift` and the health scores). Levels are stored as
+/// "Info", "Warn", ...; Notice counts as info. Logs the anomaly runner published are left
+/// out, like in detection. Without a limit every service in the hour is returned
+pub(crate) fn level_counts_query(service: Option<&str>, window_minutes: u64, limit: Option<u64>) -> String {
+    let service_filter = service
+        .map(|s| format!(" AND service = {}", sql_string(s)))
+        .unwrap_or_default();
+    let limit = limit.map(|n| format!("\n         LIMIT {}", n)).unwrap_or_default();
+    let current = format!("timestamp > now() - INTERVAL {} MINUTE", window_minutes);
+    format!(
+        "SELECT service,
+                countIf({current}) AS total,
+                countIf(level IN ('Info', 'Notice') AND {current}) AS info,
+                countIf(level = 'Warn' AND {current}) AS warn,
+                countIf(level = 'Error' AND {current}) AS error,
+                countIf(level = 'Fatal' AND {current}) AS fatal,
+                countIf(NOT ({current})) AS baseline_total,
+                countIf(level = 'Warn' AND NOT ({current})) AS baseline_warn,
+                countIf(level = 'Error' AND NOT ({current})) AS baseline_error
+         FROM logs
+         WHERE {skip} AND timestamp > now() - INTERVAL 1 HOUR{service_filter}
+         GROUP BY service
+         ORDER BY service{limit}",
+        current = current,
+        skip = SKIP_ANOMALY_LOGS,
+        service_filter = service_filter,
+        limit = limit
+    )
+}
+
+/// Warnings turning into errors: the error share of warn+error jumped, even if the error
+/// count alone isn't a spike yet
+fn level_shift(row: &LevelRow) -> Option<AnomalyItem> {
+    let share = |warn: u64, error: u64| {
+        let total = warn + error;
+        (total > 0).then(|| error as f64 / total as f64)
+    };
+    if row.warn + row.error < LEVEL_SHIFT_MIN_LOGS {
+        return None;
+    }
+    let current = share(row.warn, row.error)?;
+    // a service that was erroring all along didn't shift
+    let baseline = share(row.baseline_warn, row.baseline_error)?;
+    if current - baseline < LEVEL_SHIFT_MIN_INCREASE {
+        return None;
+    }
+
+    Some(AnomalyItem {
+        service: row.service.clone(),
+        rule: "Level Shift".to_string(),
+        severity: "warning".to_string(),
+        message: format!(
+            "Warn→Error shift: {:.0}% of warnings/errors are errors in last 5 min (baseline: {:.0}%)",
+            current * 100.0,
+            baseline * 100.0
+        ),
+        current_value: current,
+        expected_value: baseline,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(warn: u64, error: u64, baseline_warn: u64, baseline_error: u64) -> LevelRow {
+        LevelRow {
+            service: "checkout".to_string(),
+            total: 120 + warn + error,
+            info: 120,
+            warn,
+            error,
+            fatal: 0,
+            baseline_total: 1200 + baseline_warn + baseline_error,
+            baseline_warn,
+            baseline_error,
+        }
+    }
+
+    #[test]
+    fn test_level_counts_query() {
+        let query = level_counts_query(None, 5, Some(20));
+        // one grouped pass, a column per level
+        assert!(query.contains("GROUP BY service"));
+        assert!(query.contains("countIf(timestamp > now() - INTERVAL 5 MINUTE) AS total"));
+        assert!(query.contains("countIf(NOT (timestamp > now() - INTERVAL 5 MINUTE)) AS baseline_total"));
+        assert!(query.contains("LIMIT 20"));
+        assert!(query.contains("countIf(level IN ('Info', 'Notice') AND timestamp > now() - INTERVAL 5 MINUTE) AS info"));
+        for level in ["Warn", "Error", "Fatal"] {
+            assert!(query.contains(&format!("countIf(level = '{}' AND timestamp > now() - INTERVAL 5 MINUTE)", level)));
+        }
+        assert!(query.contains("countIf(level = 'Error' AND NOT (timestamp > now() - INTERVAL 5 MINUTE)) AS baseline_error"));
+        assert!(query.contains("WHERE NOT JSONHas(fields, 'anomaly_rule') AND timestamp > now() - INTERVAL 1 HOUR\n"));
+
+        let query = level_counts_query(Some("pay'ments"), 15, None);
+        assert!(query.contains("countIf(level = 'Fatal' AND timestamp > now() - INTERVAL 15 MINUTE) AS fatal"));
+        assert!(query.contains("AND timestamp > now() - INTERVAL 1 HOUR AND service = 'pay\\'ments'"));
+        assert!(query.trim_end().ends_with("ORDER BY service"));
+    }
+
+    #[test]
+    fn test_alerts_query_window() {
+        let params = |status: Option<&str>, from: Option<i64>, to: Option<i64>| AlertsQuery {
+            status: status.map(str::to_string),
+            from,
+            to,
+        };
+
+        let firing = alerts_query(&params(Some("firing"), None, None));
+        assert!(firing.contains("WHERE level = 'Error' AND timestamp > now() - INTERVAL 1 HOUR\n"));
+        assert!(firing.ends_with("LIMIT 20"));
+        assert!(alerts_query(&params(None, None, None)).contains("INTERVAL 24 HOUR"));
+
+        let ranged = alerts_query(&params(None, Some(1770685200), Some(1770692400)));
+        assert!(ranged.contains(
+            "WHERE level = 'Error' AND timestamp >= toDateTime64(1770685200, 3) AND timestamp <= toDateTime64(1770692400, 3)"
+        ));
+        assert!(!ranged.contains("INTERVAL"));
+
+        let until = alerts_query(&params(Some("firing"), None, Some(1770692400)));
+        assert!(until.contains(
+            "timestamp > toDateTime64(1770692400, 3) - INTERVAL 1 HOUR AND timestamp <= toDateTime64(1770692400, 3)"
+        ));
+        assert!(!until.contains("now()"));
+    }
+
+    #[test]
+    fn test_level_shift() {
+        // 18 warnings / 2 errors an hour ago, now 4 warnings / 8 errors
+        let shift = level_shift(&row(4, 8, 18, 2)).expect("warnings became errors");
+        assert_eq!(shift.rule, "Level Shift");
+        assert!((shift.current_value - 8.0 / 12.0).abs() < 1e-9);
+        assert!((shift.expected_value - 0.1).abs() < 1e-9);
+
+        // same mix as before, too few logs, or no baseline to compare against
+        assert!(level_shift(&row(4, 8, 40, 80)).is_none());
+        assert!(level_shift(&row(1, 5, 18, 2)).is_none());
+        assert!(level_shift(&row(4, 8, 0, 0)).is_none());
+    }
+}
@@ -0,0 +1,417 @@
+// Explicit root-cause analysis: POST /api/causal always runs the causal chain analyzer,
+// whatever the wording of the question (ask/chat only do when it reads like a "why").
+// Also home of the time-window retrieval that chat uses for causal questions.
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use logai_rag::{CausalError, RagEngine};
+use qdrant_client::qdrant::{
+    Condition, Direction, Filter, OrderByBuilder, Range, ScrollPointsBuilder, SearchPointsBuilder, Value,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+
+use crate::handlers::{embed_query, get_string, log_collections, scroll_collections, search_collections};
+use crate::models::{ApiError, CausalChainResponse, CausalRequest, SourceLog};
+use crate::state::AppState;
+
+// most recent logs kept from one window
+const WINDOW_LIMIT: u32 = 200;
+// window logs weren't ranked by similarity, give them a middling score
+const WINDOW_LOG_SCORE: f32 = 0.5;
+
+pub async fn causal_analysis(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CausalRequest>,
+) -> Result<Json<CausalChainResponse>, (StatusCode, Json<ApiError>)> {
+    let start = Instant::now();
+    info!(query = %req.query, service = ?req.service, from = ?req.from, to = ?req.to, "Causal request");
+    let service = req.service.as_deref();
+
+    // no window given: find the failure semantically, then look at what led up to it.
+    // The chain names its logs itself, there's no sources list to fill
+    let logs = match (req.from, req.to) {
+        (None, None) => {
+            let semantic = semantic_logs(&state, &req.query, service, None).await?;
+            let limit = state.retrieval.causal_rerank_k;
+            gather_causal_context(&state, &req.query, semantic, service, limit, None).await?
+        }
+        (from, to) => {
+            let end = to.unwrap_or_else(|| Utc::now().timestamp());
+            let begin = from.unwrap_or(end - state.rag_engine.causal_config().window_secs);
+            window_logs(&state, begin, end, service, None).await?
+        }
+    };
+
+    let llm_start = Instant::now();
+    let chain = investigate(&state.rag_engine, &req.query, logs, service).await?;
+    state.metrics.observe_llm(llm_start.elapsed());
+
+    info!(
+        chain_len = chain.chain.len(),
+        time_ms = start.elapsed().as_millis() as u64,
+        "Causal analysis complete"
+    );
+    Ok(Json(chain))
+}
+
+/// Logs for causal analysis: the semantic hits plus everything logged in the window
+/// (`LOGAI_CAUSAL_WINDOW_SECS`) before the worst error among them, deduplicated and reranked.
+/// Without an error to anchor the window, just the top `fallback_limit` semantic hits.
+pub async fn gather_causal_context(
+    state: &AppState,
+    query: &str,
+    semantic: Vec<(String, f32)>,
+    service: Option<&str>,
+    fallback_limit: usize,
+    sources: Option<&mut SourceIndex>,
+) -> Result<Vec<String>, (StatusCode, Json<ApiError>)> {
+    let Some(effect_time) = find_effect_timestamp(&semantic) else {
+        info!("No ERROR timestamp found, using semantic results only");
+        let reranked = state.reranker.rerank(query, merge_unique(semantic, Vec::new()), fallback_limit);
+        return Ok(reranked.iter().map(|r| r.context_line()).take(fallback_limit).collect());
+    };
+
+    let window_secs = state.rag_engine.causal_config().window_secs;
+    info!(effect_time = %effect_time, window_secs, "Found effect timestamp, fetching window");
+    let end = effect_time.timestamp();
+    let window: Vec<(String, f32)> = window_logs(state, end - window_secs, end, service, sources)
+        .await?
+        .into_iter()
+        .map(|log| (log, WINDOW_LOG_SCORE))
+        .collect();
+
+    let merged = merge_unique(semantic, window);
+    info!(merged_count = merged.len(), "Merged logs for causal analysis");
+
+    // more than a plain answer gets: the analyzer needs the lead-up, not just the top hits
+    let reranked = state.reranker.rerank(query, merged, state.retrieval.causal_rerank_k);
+    Ok(reranked.iter().map(|r| r.context_line()).collect())
+}
+
+// Semantic hits first (they keep their score), then window logs not already present
+fn merge_unique(semantic: Vec<(String, f32)>, window: Vec<(String, f32)>) -> Vec<(String, f32)> {
+    let mut seen = HashSet::new();
+    semantic
+        .into_iter()
+        .chain(window)
+        .filter(|(log, _)| seen.insert(log.clone()))
+        .collect()
+}
+
+/// Run the analyzer; "nothing to investigate" is a 404 rather than a failure
+async fn investigate(
+    engine: &RagEngine,
+    query: &str,
+    logs: Vec<String>,
+    service: Option<&str>,
+) -> Result<CausalChainResponse, (StatusCode, Json<ApiError>)> {
+    match engine.analyze_causal(query, logs, service).await {
+        Ok(chain) => Ok(chain.into()),
+        Err(e @ (CausalError::NoLogsFound | CausalError::NoErrorFound)) => Err(ApiError::not_found(e.to_string())),
+        Err(e) => Err(ApiError::internal(e.to_string())),
+    }
+}
+
+async fn semantic_logs(
+    state: &AppState,
+    query: &str,
+    service: Option<&str>,
+    mut sources: Option<&mut SourceIndex>,
+) -> Result<Vec<(String, f32)>, (StatusCode, Json<ApiError>)> {
+    let query_vector = embed_query(state, query)?;
+
+    let width = state.retrieval.chat_search_width;
+    let search = |collection: &str| {
+        let builder = SearchPointsBuilder::new(collection, query_vector.clone(), width).with_payload(true);
+        match service {
+            Some(service) => builder.filter(Filter::must([Condition::matches("service", service.to_string())])),
+            None => builder,
+        }
+    };
+
+    let collections = log_collections(state, service).await?;
+    let qdrant_start = Instant::now();
+    let results = search_collections(state, &collections, width, search)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.metrics.qdrant_search_latency.observe(qdrant_start.elapsed());
+
+    Ok(results
+        .iter()
+        .map(|point| (point_line(&mut sources, &point.payload), point.score))
+        .collect())
+}
+
+/// Every log between `from` and `to` (unix seconds), not just the semantically similar ones,
+/// newest first so the moments right before the failure are kept
+pub async fn window_logs(
+    state: &AppState,
+    from: i64,
+    to: i64,
+    service: Option<&str>,
+    mut sources: Option<&mut SourceIndex>,
+) -> Result<Vec<String>, (StatusCode, Json<ApiError>)> {
+    let mut conditions = vec![Condition::range(
+        "timestamp_unix",
+        Range {
+            gte: Some(from as f64),
+            lte: Some(to as f64),
+            ..Default::default()
+        },
+    )];
+    if let Some(service) = service {
+        conditions.push(Condition::matches("service", service.to_string()));
+    }
+
+    let scroll = |collection: &str| {
+        ScrollPointsBuilder::new(collection)
+            .filter(Filter::must(conditions.clone()))
+            .order_by(OrderByBuilder::new("timestamp_unix").direction(Direction::Desc as i32))
+            .limit(WINDOW_LIMIT)
+            .with_payload(true)
+    };
+
+    let collections = log_collections(state, service).await?;
+    let result = scroll_collections(state, &collections, WINDOW_LIMIT, scroll)
+        .await
+        .map_err(|e| ApiError::internal(format!("Scroll failed: {}", e)))?;
+
+    let logs: Vec<String> = result.iter().map(|point| point_line(&mut sources, &point.payload)).collect();
+    info!(from, to, window_logs_count = logs.len(), "Time-window logs retrieved");
+    Ok(logs)
+}
+
+/// A point as the JSON line the causal analyzer parses
+pub fn log_line(payload: &HashMap<String, Value>) -> String {
+    serde_json::json!({
+        "timestamp": get_string(payload, "timestamp"),
+        "level": get_string(payload, "level"),
+        "service": get_string(payload, "service"),
+        "message": get_string(payload, "message"),
+    })
+    .to_string()
+}
+
+/// `log_line` of the point, through the source index when there is one
+fn point_line(sources: &mut Option<&mut SourceIndex>, payload: &HashMap<String, Value>) -> String {
+    match sources {
+        Some(index) => index.line(payload),
+        None => log_line(payload),
+    }
+}
+
+/// The stored records behind context lines, so an answer can list its sources by id:
+/// filled while points become lines, read back once reranking has picked the lines.
+/// Identical lines (the same message at the same second) keep every record, by log_id.
+#[derive(Default)]
+pub struct SourceIndex(HashMap<String, Vec<SourceLog>>);
+
+impl SourceIndex {
+    /// `log_line` of the point, remembering the record it came from
+    pub fn line(&mut self, payload: &HashMap<String, Value>) -> String {
+        let line = log_line(payload);
+        let log_id = get_string(payload, "log_id");
+        let records = self.0.entry(line.clone()).or_default();
+        // the same point can come back twice, from the search and from the window
+        if !records.iter().any(|record| record.log_id == log_id) {
+            records.push(SourceLog {
+                log_id,
+                timestamp: get_string(payload, "timestamp"),
+                service: get_string(payload, "service"),
+                level: get_string(payload, "level"),
+                message: get_string(payload, "message"),
+            });
+        }
+        line
+    }
+
+    /// Records of context lines, in their order; a line that appears again gets the next
+    /// record behind it. A line the reranker gave an `occurrences` count stands for its own
+    /// record; lines not from this index are skipped.
+    pub fn sources(&self, lines: &[String]) -> Vec<SourceLog> {
+        let mut taken: HashMap<String, usize> = HashMap::new();
+        lines
+            .iter()
+            .filter_map(|line| {
+                let line = if self.0.contains_key(line) { line.clone() } else { without_occurrences(line)? };
+                let records = self.0.get(&line)?;
+                let nth = taken.entry(line).or_default();
+                let record = records.get(*nth)?;
+                *nth += 1;
+                Some(record)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn without_occurrences(line: &str) -> Option<String> {
+    let serde_json::Value::Object(mut log) = serde_json::from_str(line).ok()? else {
+        return None;
+    };
+    log.remove("occurrences")?;
+    Some(serde_json::Value::Object(log).to_string())
+}
+
+/// Find the timestamp of the most severe ERROR from search results
+/// This will be used as the "effect" for causal chain analysis
+pub fn find_effect_timestamp(logs_with_scores: &[(String, f32)]) -> Option<DateTime<Utc>> {
+    // Parse logs and find the most recent ERROR/FATAL
+    let mut best_timestamp: Option<DateTime<Utc>> = None;
+    let mut best_severity: u8 = 0;
+
+    for (log_json, _score) in logs_with_scores {
+        let Ok(parsed) = serde_json::from_str::<serde_json::Value>(log_json) else { continue };
+        let level = parsed.get("level")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_uppercase();
+
+        let severity = match level.as_str() {
+            "FATAL" | "CRITICAL" => 5,
+            "ERROR" | "ERR" => 4,
+            "WARN" | "WARNING" => 3,
+            _ => 0,
+        };
+
+        // Only consider ERROR or higher
+        if severity < 4 {
+            continue;
+        }
+        let timestamp = parsed
+            .get("timestamp")
+            .and_then(|v| v.as_str())
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok());
+        if let Some(ts) = timestamp {
+            let ts_utc = ts.with_timezone(&Utc);
+            // Pick the most severe, or if same severity, the most recent
+            if severity > best_severity
+                || (severity == best_severity && best_timestamp.is_none_or(|best| ts_utc > best))
+            {
+                best_severity = severity;
+                best_timestamp = Some(ts_utc);
+            }
+        }
+    }
+
+    best_timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use logai_rag::{MockLlmClient, RagConfig, RerankConfig, Reranker};
+
+    fn line(timestamp: &str, level: &str, message: &str) -> String {
+        serde_json::json!({
+            "timestamp": timestamp,
+            "level": level,
+            "service": "checkout",
+            "message": message,
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_window_without_errors_is_clean_404() {
+        let mock = Arc::new(MockLlmClient::new());
+        let engine = RagEngine::with_client(RagConfig::default(), mock.clone()).unwrap();
+        let logs = vec![
+            line("2026-02-10T03:00:00Z", "Info", "Order 1842 created"),
+            line("2026-02-10T03:00:05Z", "Warn", "Payment provider slow (1.8s)"),
+        ];
+
+        let result = investigate(&engine, "why did checkout fail?", logs, None).await;
+        let response = result.err().expect("no error to investigate").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "No error logs found to investigate");
+        // the analyzer bailed out before spending an LLM call
+        assert_eq!(mock.call_count(), 0);
+    }
+
+    #[test]
+    fn test_merge_keeps_semantic_first_and_dedups() {
+        let db = line("2026-02-10T03:00:00Z", "Error", "db timeout");
+        let pool = line("2026-02-10T02:59:00Z", "Warn", "pool exhausted");
+        let deploy = line("2026-02-10T02:58:00Z", "Info", "deploy v42");
+
+        let semantic = vec![(db.clone(), 0.9), (db.clone(), 0.9), (pool.clone(), 0.7)];
+        let window = vec![(deploy.clone(), 0.5), (pool.clone(), 0.5), (db.clone(), 0.5)];
+        let merged = merge_unique(semantic, window);
+
+        assert_eq!(merged, vec![(db, 0.9), (pool, 0.7), (deploy, 0.5)]);
+    }
+
+    #[test]
+    fn test_effect_is_most_severe_then_latest() {
+        let logs = vec![
+            (line("2026-02-10T03:00:00Z", "Error", "db timeout"), 0.9),
+            (line("2026-02-10T03:02:00Z", "Error", "payment failed"), 0.8),
+            (line("2026-02-10T03:05:00Z", "Warn", "retrying"), 0.7),
+        ];
+        let effect = find_effect_timestamp(&logs).unwrap();
+        assert_eq!(effect.to_rfc3339(), "2026-02-10T03:02:00+00:00");
+        assert!(find_effect_timestamp(&logs[2..]).is_none());
+
+        // FATAL wins over a later ERROR; unparseable timestamps are skipped
+        let logs = vec![
+            (line("2026-02-10T03:00:00Z", "Fatal", "out of memory"), 0.5),
+            (line("2026-02-10T03:09:00Z", "Error", "request failed"), 0.9),
+            (line("yesterday", "Fatal", "disk full"), 0.9),
+        ];
+        let effect = find_effect_timestamp(&logs).unwrap();
+        assert_eq!(effect.to_rfc3339(), "2026-02-10T03:00:00+00:00");
+    }
+
+    #[test]
+    fn test_sources_include_ids_and_timestamps() {
+        let point = |id: &str, timestamp: &str, level: &str, message: &str| -> HashMap<String, Value> {
+            [("log_id", id), ("timestamp", timestamp), ("level", level), ("service", "checkout"), ("message", message)]
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), Value::from(value)))
+                .collect()
+        };
+        let mut sources = SourceIndex::default();
+        let hits = vec![
+            (sources.line(&point("11111111-0000-0000-0000-000000000001", "2026-02-10T03:00:00Z", "Info", "cart loaded")), 0.6),
+            (sources.line(&point("22222222-0000-0000-0000-000000000002", "2026-02-10T03:00:05Z", "Error", "payment 4411 failed")), 0.9),
+            (sources.line(&point("33333333-0000-0000-0000-000000000003", "2026-02-10T03:00:07Z", "Error", "payment 4412 failed")), 0.8),
+        ];
+
+        // look-alikes collapse into the best one, which gets an occurrences count
+        let reranker = Reranker::with_config(RerankConfig {
+            dedup_similar: true,
+            ..RerankConfig::default()
+        });
+        let lines: Vec<String> = reranker.rerank("payment failed", hits, 10).iter().map(|r| r.context_line()).collect();
+        assert!(lines[0].contains("\"occurrences\":2"));
+
+        let found = sources.sources(&lines);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].log_id, "22222222-0000-0000-0000-000000000002");
+        assert_eq!(found[0].timestamp, "2026-02-10T03:00:05Z");
+        assert_eq!(found[0].message, "payment 4411 failed");
+        assert_eq!((found[1].log_id.as_str(), found[1].level.as_str()), ("11111111-0000-0000-0000-000000000001", "Info"));
+
+        // identical lines from different logs each list their own record
+        let mut sources = SourceIndex::default();
+        let timeout = point("44444444-0000-0000-0000-000000000004", "2026-02-10T03:01:00Z", "Error", "upstream timeout");
+        let again = point("55555555-0000-0000-0000-000000000005", "2026-02-10T03:01:00Z", "Error", "upstream timeout");
+        let lines = vec![sources.line(&timeout), sources.line(&again), sources.line(&timeout)];
+        assert_eq!(lines[0], lines[1]);
+        let ids: Vec<String> = sources.sources(&lines[..2]).into_iter().map(|s| s.log_id).collect();
+        assert_eq!(ids, vec!["44444444-0000-0000-0000-000000000004", "55555555-0000-0000-0000-000000000005"]);
+        // a point seen twice is still one record
+        assert_eq!(sources.sources(&lines).len(), 2);
+
+        // lines that didn't come from a point have no record to list
+        assert!(sources.sources(&[line("2026-02-10T03:00:09Z", "Info", "order shipped")]).is_empty());
+    }
+}
@@ -3,16 +3,19 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use chrono::{DateTime, Utc};
-use qdrant_client::qdrant::{Condition, Filter, Range, ScrollPointsBuilder, SearchPointsBuilder};
+use qdrant_client::qdrant::{Condition, Filter, Range, SearchPointsBuilder};
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::info;
 
-use crate::handlers::get_string;
-use crate::models::{ApiError, ChatApiResponse, ChatMessage, ChatRequest, CausalChainResponse, SessionInfo, SessionQuery};
-use crate::state::{AppState, ChatSession, QueryIntent, COLLECTION_NAME};
+use crate::chat_gate::{ChatGate, GateDecision};
+use crate::handlers::{
+    above_min_score, embed_query, gather_causal_context, log_collections, min_score, search_collections,
+    strict_conditions, SourceIndex,
+};
+use crate::models::{ApiError, ChatApiResponse, ChatMessage, ChatRequest, CausalChainResponse, SessionInfo, SessionQuery, SessionsResponse};
+use crate::state::{evict_sessions, list_sessions, remove_session, AppState, ChatSession, QueryIntent};
 
 // Import RAG's QueryIntent (different from our local one)
 use logai_rag::QueryIntent as RagQueryIntent;
@@ -23,42 +26,21 @@ pub async fn chat_logs(
 ) -> Result<Json<ChatApiResponse>, (StatusCode, Json<ApiError>)> {
     let start = Instant::now();
     info!(session = %req.session_id, message = %req.message, "CHAT request");
-    
-    // Configurable max logs (default: 20)
-    let max_context_logs: usize = std::env::var("LOGAI_MAX_CONTEXT_LOGS")
-        .ok().and_then(|s| s.parse().ok()).unwrap_or(20);
-
-    let msg_lower = req.message.to_lowercase().trim().to_string();
-    let greetings = ["hi", "hello", "hey", "good morning", "good afternoon", "good evening", "howdy", "sup", "what's up", "yo"];
-    let is_greeting = greetings.iter().any(|g| msg_lower == *g || msg_lower.starts_with(&format!("{} ", g)));
-
-    let gibberish_patterns = ["asdf", "qwer", "zxcv", "hjkl", "jkl;"];
-    let is_gibberish = gibberish_patterns.iter().any(|p| msg_lower.contains(p));
-
-    let log_keywords = ["error", "log", "warn", "debug", "info", "service", "api", "database", "db",
-        "timeout", "slow", "failed", "failure", "crash", "down", "outage", "issue", "problem",
-        "anomal", "incident", "alert", "critical", "auth", "payment", "nginx", "redis", "kafka",
-        "query", "connection", "latency", "performance", "traffic", "request", "response",
-        "yesterday", "today", "last hour", "last minute", "recent", "happened", "show me", "find"];
-    let has_log_context = log_keywords.iter().any(|k| msg_lower.contains(k));
-
-    let is_offtopic = if !has_log_context && msg_lower.len() > 5 {
-        let classification = state.rag_engine.classify(&format!(
-            r#"Is this question about analyzing logs, debugging, system errors, or infrastructure monitoring?
-Question: "{}"
-Answer YES or NO only."#,
-            req.message
-        )).await;
-
-        match classification {
-            Ok(response) => !response.to_uppercase().contains("YES"),
-            Err(_) => false,
+    let min_score = min_score(req.min_score, state.min_score)?;
+    let max_context_logs = state.retrieval.chat_rerank_k;
+
+    let decision = match state.chat_gate.check(&req.message) {
+        GateDecision::AskLlm => {
+            // an unreachable LLM shouldn't turn away a real question
+            match state.rag_engine.classify(&ChatGate::off_topic_prompt(&req.message)).await {
+                Ok(response) if !response.to_uppercase().contains("YES") => GateDecision::OffTopic,
+                _ => GateDecision::Pass,
+            }
         }
-    } else {
-        false
+        decision => decision,
     };
 
-    if is_greeting {
+    if decision == GateDecision::Greeting {
         let elapsed = start.elapsed().as_millis();
         return Ok(Json(ChatApiResponse {
             answer: "Hello! I'm LogAI, your log analysis assistant. Ask me about errors, performance issues, or anomalies in your logs. For example:\n\n• \"Show me errors in the last hour\"\n• \"What happened yesterday?\"\n• \"Why is the payment service slow?\"\n• \"Summarize auth failures\"".to_string(),
@@ -68,11 +50,12 @@ Answer YES or NO only."#,
             context_logs: 0,
             conversation_turn: 1,
             source_logs: vec![],
+            sources: vec![],
             causal_chain: None,
         }));
     }
 
-    if is_gibberish || is_offtopic {
+    if decision == GateDecision::OffTopic {
         let elapsed = start.elapsed().as_millis();
         return Ok(Json(ChatApiResponse {
             answer: "I'm LogAI - I specialize in analyzing your system logs. I can help with:\n\n• Finding errors and warnings\n• Investigating performance issues\n• Summarizing anomalies and incidents\n• Debugging service failures\n\nTry: \"Show me errors in the last hour\" or \"Why is the database slow?\"".to_string(),
@@ -82,16 +65,28 @@ Answer YES or NO only."#,
             context_logs: 0,
             conversation_turn: 1,
             source_logs: vec![],
+            sources: vec![],
             causal_chain: None,
         }));
     }
 
-    let (history, last_logs, last_query, turn) = {
+    let (history, last_logs, last_sources, last_query, turn) = {
         let mut sessions = state.sessions.write().unwrap();
+        // Make room before creating a new session so the cap holds between sweeps
+        if !sessions.contains_key(&req.session_id)
+            && sessions.len() >= state.session_config.max_sessions
+        {
+            evict_sessions(
+                &mut sessions,
+                state.session_config.ttl,
+                state.session_config.max_sessions.saturating_sub(1),
+            );
+        }
         let session = sessions.entry(req.session_id.clone()).or_insert_with(|| {
             ChatSession {
                 history: Vec::new(),
                 last_logs: Vec::new(),
+                last_sources: Vec::new(),
                 last_query: String::new(),
                 created_at: std::time::Instant::now(),
             }
@@ -99,12 +94,15 @@ Answer YES or NO only."#,
         if !req.history.is_empty() && session.history.is_empty() {
             session.history = req.history.clone();
         }
-        (
+        let snapshot = (
             session.history.clone(),
             session.last_logs.clone(),
+            session.last_sources.clone(),
             session.last_query.clone(),
             session.history.len() / 2 + 1,
-        )
+        );
+        state.metrics.set_sessions(sessions.len());
+        snapshot
     };
 
     let intent = classify_query_intent(&state.rag_engine, &last_query, &req.message).await;
@@ -122,9 +120,9 @@ Answer YES or NO only."#,
 
     // For causal queries, always fetch fresh logs with temporal context
     // For non-causal follow-ups, use cached logs
-    let logs = if intent == QueryIntent::FollowUp && !last_logs.is_empty() && !is_causal_query {
+    let (logs, sources) = if intent == QueryIntent::FollowUp && !last_logs.is_empty() && !is_causal_query {
         info!("Using cached logs from previous turn (non-causal follow-up)");
-        last_logs
+        (last_logs, last_sources)
     } else {
         info!(
             is_follow_up = (intent == QueryIntent::FollowUp),
@@ -133,13 +131,7 @@ Answer YES or NO only."#,
             "Fetching fresh logs for causal query or new search"
         );
 
-        let query_vector = {
-            let mut model = state.model.lock().unwrap();
-            let embeddings = model
-                .embed(vec![analyzed.search_query.clone()], None)
-                .map_err(|e| ApiError::internal(e.to_string()))?;
-            embeddings.into_iter().next().ok_or_else(|| ApiError::internal("No embedding"))?
-        };
+        let query_vector = embed_query(&state, &analyzed.search_query)?;
 
         let mut conditions = vec![];
         if let Some(from) = analyzed.from {
@@ -162,7 +154,10 @@ Answer YES or NO only."#,
                 },
             ));
         }
-        // Note: service/level filters removed - semantic search handles relevance
+        // service/level are left to semantic search unless the caller wants them enforced
+        if req.strict_filters {
+            conditions.extend(strict_conditions(&analyzed));
+        }
 
         let filter = if conditions.is_empty() {
             None
@@ -170,35 +165,32 @@ Answer YES or NO only."#,
             Some(Filter::must(conditions))
         };
 
-        let mut search_builder =
-            SearchPointsBuilder::new(COLLECTION_NAME, query_vector, 100).with_payload(true);
-        if let Some(f) = filter.clone() {
-            search_builder = search_builder.filter(f);
-        }
+        let width = state.retrieval.chat_search_width;
+        let search = |collection: &str| {
+            let builder = SearchPointsBuilder::new(collection, query_vector.clone(), width).with_payload(true);
+            match filter.clone() {
+                Some(f) => builder.filter(f),
+                None => builder,
+            }
+        };
 
-        let results = state
-            .qdrant
-            .search_points(search_builder)
+        let collections = log_collections(&state, analyzed.service.as_deref()).await?;
+        let qdrant_start = Instant::now();
+        let results = search_collections(&state, &collections, width, search)
             .await
             .map_err(|e| ApiError::internal(e.to_string()))?;
+        state.metrics.qdrant_search_latency.observe(qdrant_start.elapsed());
 
         // Build JSON log strings with full metadata for causal analysis
+        let mut sources = SourceIndex::default();
         let logs_with_scores: Vec<(String, f32)> = results
-            .result
             .iter()
-            .map(|point| {
-                let payload = &point.payload;
-                let log_json = serde_json::json!({
-                    "timestamp": get_string(payload, "timestamp"),
-                    "level": get_string(payload, "level"),
-                    "service": get_string(payload, "service"),
-                    "message": get_string(payload, "message"),
-                });
-                (log_json.to_string(), point.score)
-            })
+            .map(|point| (sources.line(&point.payload), point.score))
             .collect();
 
-        info!(logs_found = logs_with_scores.len(), "Logs retrieved via semantic search");
+        let found = logs_with_scores.len();
+        let logs_with_scores = above_min_score(logs_with_scores, min_score, state.scores_are_distances);
+        info!(logs_found = found, relevant = logs_with_scores.len(), min_score, "Logs retrieved via semantic search");
 
         if logs_with_scores.is_empty() {
             return Err(ApiError::not_found("No relevant logs found for your query. Try broadening your search."));
@@ -207,93 +199,7 @@ Answer YES or NO only."#,
         // For CAUSAL queries: augment with time-window retrieval
         let final_logs = if is_causal_query {
             info!("Causal query detected - fetching temporal context");
-            
-            // Find the most recent ERROR from semantic search results
-            let effect_timestamp = find_effect_timestamp(&logs_with_scores);
-            
-            if let Some(effect_time) = effect_timestamp {
-                info!(effect_time = %effect_time, "Found effect timestamp, fetching 5-min window");
-                
-                // Fetch all logs from (effect_time - 5 minutes) to effect_time
-                let window_start = effect_time.timestamp() - 300; // 5 minutes before
-                let window_end = effect_time.timestamp();
-                
-                let time_filter = Filter::must(vec![
-                    Condition::range(
-                        "timestamp_unix",
-                        Range {
-                            gte: Some(window_start as f64),
-                            lte: Some(window_end as f64),
-                            ..Default::default()
-                        },
-                    ),
-                ]);
-                
-                // Scroll to get ALL logs in the time window (not just semantically similar)
-                let scroll_request = ScrollPointsBuilder::new(COLLECTION_NAME)
-                    .filter(time_filter)
-                    .limit(200)
-                    .with_payload(true);
-                
-                let scroll_result = state
-                    .qdrant
-                    .scroll(scroll_request)
-                    .await
-                    .map_err(|e| ApiError::internal(format!("Scroll failed: {}", e)))?;
-                
-                let window_logs: Vec<(String, f32)> = scroll_result
-                    .result
-                    .iter()
-                    .map(|point| {
-                        let payload = &point.payload;
-                        let log_json = serde_json::json!({
-                            "timestamp": get_string(payload, "timestamp"),
-                            "level": get_string(payload, "level"),
-                            "service": get_string(payload, "service"),
-                            "message": get_string(payload, "message"),
-                        });
-                        // Give time-window logs a base score of 0.5
-                        (log_json.to_string(), 0.5_f32)
-                    })
-                    .collect();
-                
-                info!(window_logs_count = window_logs.len(), "Time-window logs retrieved");
-                
-                // Merge semantic results + time-window results, deduplicate
-                let mut seen = HashSet::new();
-                let mut merged: Vec<(String, f32)> = Vec::new();
-                
-                // Add semantic results first (higher priority)
-                for (log, score) in logs_with_scores {
-                    if seen.insert(log.clone()) {
-                        merged.push((log, score));
-                    }
-                }
-                
-                // Add time-window results
-                for (log, score) in window_logs {
-                    if seen.insert(log.clone()) {
-                        merged.push((log, score));
-                    }
-                }
-                
-                info!(merged_count = merged.len(), "Merged logs for causal analysis");
-                
-                // For causal analysis, we want more logs (not just top 10)
-                // Take up to 50 unique logs for richer causal context
-                let reranked = state.reranker.rerank(&req.message, merged, 50);
-                reranked.into_iter().map(|r| r.message).collect()
-            } else {
-                // No effect found, fall back to normal behavior
-                info!("No ERROR timestamp found, using semantic results only");
-                let mut seen = HashSet::new();
-                let unique_logs: Vec<(String, f32)> = logs_with_scores
-                    .into_iter()
-                    .filter(|(msg, _)| seen.insert(msg.clone()))
-                    .collect();
-                let reranked = state.reranker.rerank(&req.message, unique_logs, max_context_logs);
-                reranked.into_iter().map(|r| r.message).take(max_context_logs).collect()
-            }
+            gather_causal_context(&state, &req.message, logs_with_scores, None, max_context_logs, Some(&mut sources)).await?
         } else {
             // Normal (non-causal) query - existing behavior
             let mut seen = HashSet::new();
@@ -303,10 +209,11 @@ Answer YES or NO only."#,
                 .collect();
 
             let reranked = state.reranker.rerank(&req.message, unique_logs, max_context_logs);
-            reranked.into_iter().map(|r| r.message).take(max_context_logs).collect()
+            reranked.iter().map(|r| r.context_line()).take(max_context_logs).collect()
         };
-        
-        final_logs
+
+        let final_sources = sources.sources(&final_logs);
+        (final_logs, final_sources)
     };
 
     let context_logs = logs.len();
@@ -334,11 +241,13 @@ Answer YES or NO only."#,
         "Calling RAG engine query_with_intent"
     );
 
+    let llm_start = Instant::now();
     let rag_response = state
         .rag_engine
         .query_with_intent(&full_query, logs.clone(), intent_override)
         .await
         .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.metrics.observe_llm(llm_start.elapsed());
     
     info!(
         has_causal_chain = rag_response.causal_chain.is_some(),
@@ -360,6 +269,7 @@ Answer YES or NO only."#,
                 content: rag_response.answer.clone(),
             });
             session.last_logs = logs;
+            session.last_sources = sources.clone();
             session.last_query = req.message.clone();
             if session.history.len() > 20 {
                 session.history.drain(0..2);
@@ -384,6 +294,7 @@ Answer YES or NO only."#,
         context_logs,
         conversation_turn: turn,
         source_logs: response_logs,
+        sources,
         causal_chain: rag_response.causal_chain.map(CausalChainResponse::from),
     }))
 }
@@ -457,53 +368,29 @@ pub async fn get_session(
     let sessions = state.sessions.read().unwrap();
 
     match sessions.get(&params.session_id) {
-        Some(session) => Ok(Json(SessionInfo {
-            session_id: params.session_id,
-            turns: session.history.len() / 2,
-            last_logs_count: session.last_logs.len(),
-            age_seconds: session.created_at.elapsed().as_secs(),
-        })),
+        Some(session) => Ok(Json(session.info(&params.session_id))),
         None => Err(ApiError::not_found("Session not found")),
     }
 }
 
-/// Find the timestamp of the most severe ERROR from search results
-/// This will be used as the "effect" for causal chain analysis
-fn find_effect_timestamp(logs_with_scores: &[(String, f32)]) -> Option<DateTime<Utc>> {
-    // Parse logs and find the most recent ERROR/FATAL
-    let mut best_timestamp: Option<DateTime<Utc>> = None;
-    let mut best_severity: u8 = 0;
-    
-    for (log_json, _score) in logs_with_scores {
-        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(log_json) {
-            let level = parsed.get("level")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_uppercase();
-            
-            let severity = match level.as_str() {
-                "FATAL" | "CRITICAL" => 5,
-                "ERROR" | "ERR" => 4,
-                "WARN" | "WARNING" => 3,
-                _ => 0,
-            };
-            
-            // Only consider ERROR or higher
-            if severity >= 4 {
-                if let Some(ts_str) = parsed.get("timestamp").and_then(|v| v.as_str()) {
-                    if let Ok(ts) = DateTime::parse_from_rfc3339(ts_str) {
-                        let ts_utc = ts.with_timezone(&Utc);
-                        // Pick the most severe, or if same severity, the most recent
-                        if severity > best_severity || 
-                           (severity == best_severity && best_timestamp.map_or(true, |best| ts_utc > best)) {
-                            best_severity = severity;
-                            best_timestamp = Some(ts_utc);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    best_timestamp
+/// GET /api/sessions: every live session, for debugging multi-user chat (admin only)
+pub async fn get_sessions(State(state): State<Arc<AppState>>) -> Json<SessionsResponse> {
+    let sessions = list_sessions(&state.sessions);
+    Json(SessionsResponse {
+        count: sessions.len(),
+        max_sessions: state.session_config.max_sessions,
+        sessions,
+    })
+}
+
+/// DELETE /api/session: drop a session now instead of waiting for its TTL (admin only)
+pub async fn delete_session(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SessionQuery>,
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
+    let remaining = remove_session(&state.sessions, &params.session_id)
+        .ok_or_else(|| ApiError::not_found("Session not found"))?;
+    state.metrics.set_sessions(remaining);
+    info!(session = %params.session_id, "Session deleted");
+    Ok(StatusCode::NO_CONTENT)
 }
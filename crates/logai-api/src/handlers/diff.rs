@@ -0,0 +1,233 @@
+// GET /api/diff: what changed in the errors between a "good" range A and a "bad" range B.
+// Messages are grouped into templates with the reranker's normalizer, so
+// "timeout after 3012ms" and "timeout after 2987ms" count as the same error.
+
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    http::StatusCode,
+    Json,
+};
+use logai_rag::MessageNormalizer;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::handlers::{bad_query, sql_string};
+use crate::models::{ApiError, DiffQuery, DiffResponse, ErrorDelta};
+use crate::state::AppState;
+
+// entries per list in the response
+const DIFF_LIMIT: usize = 20;
+// distinct (category, message) pairs read per range; templates fold most of them together
+const MAX_MESSAGES: usize = 5000;
+// error_category is NULL for logs the worker couldn't classify
+const UNCATEGORIZED: &str = "Uncategorized";
+
+#[derive(Deserialize, clickhouse::Row)]
+struct ErrorCountRow {
+    category: String,
+    message: String,
+    count: u64,
+}
+
+/// Error counts for one range, keyed by category and by message template
+#[derive(Default)]
+struct ErrorProfile {
+    total: u64,
+    categories: HashMap<String, u64>,
+    templates: HashMap<String, u64>,
+}
+
+impl ErrorProfile {
+    fn from_rows(rows: Vec<ErrorCountRow>, normalizer: &MessageNormalizer) -> Self {
+        let mut profile = Self::default();
+        for row in rows {
+            profile.total += row.count;
+            let category = if row.category.is_empty() { UNCATEGORIZED.to_string() } else { row.category };
+            *profile.categories.entry(category).or_default() += row.count;
+            *profile.templates.entry(normalizer.normalize(&row.message)).or_default() += row.count;
+        }
+        profile
+    }
+}
+
+pub async fn get_diff(
+    State(state): State<Arc<AppState>>,
+    query: Result<Query<DiffQuery>, QueryRejection>,
+) -> Result<Json<DiffResponse>, (StatusCode, Json<ApiError>)> {
+    let Query(params) = query.map_err(bad_query)?;
+    info!(
+        from_a = ?params.from_a,
+        to_a = ?params.to_a,
+        from_b = ?params.from_b,
+        to_b = ?params.to_b,
+        service = ?params.service,
+        "Diff request"
+    );
+
+    let (Some(from_a), Some(to_a), Some(from_b), Some(to_b)) = (params.from_a, params.to_a, params.from_b, params.to_b)
+    else {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "from_a, to_a, from_b and to_b are all required"));
+    };
+    if from_a > to_a || from_b > to_b {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Each range needs from <= to"));
+    }
+
+    let normalizer = MessageNormalizer::new();
+    let mut profiles = Vec::with_capacity(2);
+    for (from, to) in [(from_a, to_a), (from_b, to_b)] {
+        let rows: Vec<ErrorCountRow> = state
+            .clickhouse
+            .query(&error_counts_query(from, to, params.service.as_deref()))
+            .fetch_all()
+            .await
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        profiles.push(ErrorProfile::from_rows(rows, &normalizer));
+    }
+    let (a, b) = (&profiles[0], &profiles[1]);
+
+    let (new_errors, disappeared_errors, biggest_increases) = split_deltas(count_deltas(&a.templates, &b.templates));
+    let mut categories = count_deltas(&a.categories, &b.categories);
+    categories.truncate(DIFF_LIMIT);
+
+    info!(
+        total_a = a.total,
+        total_b = b.total,
+        new = new_errors.len(),
+        disappeared = disappeared_errors.len(),
+        "Diff computed"
+    );
+
+    Ok(Json(DiffResponse {
+        from_a,
+        to_a,
+        from_b,
+        to_b,
+        service: params.service,
+        total_a: a.total,
+        total_b: b.total,
+        categories,
+        new_errors,
+        disappeared_errors,
+        biggest_increases,
+    }))
+}
+
+/// Error and fatal counts per (category, message) in [from, to]
+fn error_counts_query(from: i64, to: i64, service: Option<&str>) -> String {
+    let service = service
+        .map(|s| format!(" AND service = {}", sql_string(s)))
+        .unwrap_or_default();
+    format!(
+        "SELECT ifNull(error_category, '') AS category, message, count() AS count
+         FROM logs
+         WHERE level IN ('Error', 'Fatal')
+         AND timestamp >= toDateTime64({}, 3) AND timestamp <= toDateTime64({}, 3){}
+         GROUP BY category, message
+         ORDER BY count DESC
+         LIMIT {}",
+        from, to, service, MAX_MESSAGES
+    )
+}
+
+/// Every key in either map with both counts, biggest increase first (ties by key)
+fn count_deltas(a: &HashMap<String, u64>, b: &HashMap<String, u64>) -> Vec<ErrorDelta> {
+    let mut deltas: Vec<ErrorDelta> = a
+        .keys()
+        .chain(b.keys().filter(|key| !a.contains_key(*key)))
+        .map(|key| {
+            let count_a = a.get(key).copied().unwrap_or(0);
+            let count_b = b.get(key).copied().unwrap_or(0);
+            ErrorDelta {
+                key: key.clone(),
+                count_a,
+                count_b,
+                change: count_b as i64 - count_a as i64,
+            }
+        })
+        .collect();
+    deltas.sort_by(|x, y| y.change.cmp(&x.change).then_with(|| x.key.cmp(&y.key)));
+    deltas
+}
+
+/// (new in B, gone from B, grown in both), each capped at DIFF_LIMIT.
+/// Disappeared errors come out with the biggest A count first.
+fn split_deltas(deltas: Vec<ErrorDelta>) -> (Vec<ErrorDelta>, Vec<ErrorDelta>, Vec<ErrorDelta>) {
+    let mut new_errors = Vec::new();
+    let mut disappeared = Vec::new();
+    let mut increases = Vec::new();
+    for delta in deltas {
+        if delta.count_a == 0 {
+            new_errors.push(delta);
+        } else if delta.count_b == 0 {
+            disappeared.push(delta);
+        } else if delta.change > 0 {
+            increases.push(delta);
+        }
+    }
+    disappeared.reverse();
+    for list in [&mut new_errors, &mut disappeared, &mut increases] {
+        list.truncate(DIFF_LIMIT);
+    }
+    (new_errors, disappeared, increases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(pairs: &[(&str, u64)]) -> HashMap<String, u64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    fn delta(key: &str, count_a: u64, count_b: u64) -> ErrorDelta {
+        ErrorDelta { key: key.to_string(), count_a, count_b, change: count_b as i64 - count_a as i64 }
+    }
+
+    #[test]
+    fn test_count_deltas() {
+        let a = counts(&[("db timeout", 5), ("cache miss", 10), ("auth failed", 3), ("disk full", 1)]);
+        let b = counts(&[("db timeout", 40), ("cache miss", 4), ("payment declined", 12), ("auth failed", 3)]);
+
+        let deltas = count_deltas(&a, &b);
+        assert_eq!(
+            deltas,
+            vec![
+                delta("db timeout", 5, 40),
+                delta("payment declined", 0, 12),
+                delta("auth failed", 3, 3),
+                delta("disk full", 1, 0),
+                delta("cache miss", 10, 4),
+            ]
+        );
+
+        let (new_errors, disappeared, increases) = split_deltas(deltas);
+        assert_eq!(new_errors, vec![delta("payment declined", 0, 12)]);
+        assert_eq!(disappeared, vec![delta("disk full", 1, 0)]);
+        // unchanged and shrinking templates aren't increases
+        assert_eq!(increases, vec![delta("db timeout", 5, 40)]);
+    }
+
+    #[test]
+    fn test_profile_groups_templates() {
+        let row = |category: &str, message: &str, count| ErrorCountRow {
+            category: category.to_string(),
+            message: message.to_string(),
+            count,
+        };
+        let profile = ErrorProfile::from_rows(
+            vec![
+                row("Timeout", "upstream timed out after 3012ms", 4),
+                row("Timeout", "upstream timed out after 2987ms", 2),
+                row("", "something odd", 1),
+            ],
+            &MessageNormalizer::new(),
+        );
+
+        assert_eq!(profile.total, 7);
+        assert_eq!(profile.templates["upstream timed out after <NUM>ms"], 6);
+        assert_eq!(profile.categories["Timeout"], 6);
+        assert_eq!(profile.categories[UNCATEGORIZED], 1);
+    }
+}
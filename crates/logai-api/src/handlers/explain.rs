@@ -0,0 +1,90 @@
+// POST /api/explain: what one log line means and what to check next, straight from the LLM
+// with no search behind it. Takes the line itself (tail view) or a stored log's id (search results).
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::models::{ApiError, ExplainRequest, ExplainResponse};
+use crate::state::AppState;
+
+#[derive(Deserialize, clickhouse::Row)]
+struct ExplainRow {
+    level: String,
+    service: String,
+    message: String,
+}
+
+pub async fn explain_log(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExplainRequest>,
+) -> Result<Json<ExplainResponse>, (StatusCode, Json<ApiError>)> {
+    let start = Instant::now();
+    info!(log_id = ?req.log_id, context = req.context.len(), "Explain request");
+
+    let log = match (req.log.as_deref().map(str::trim).filter(|l| !l.is_empty()), req.log_id.as_deref()) {
+        (Some(log), _) => log.to_string(),
+        (None, Some(id)) => stored_log_line(&state, id).await?,
+        (None, None) => return Err(ApiError::new(StatusCode::BAD_REQUEST, "Either log or log_id is required")),
+    };
+
+    let llm_start = Instant::now();
+    let explained = state
+        .rag_engine
+        .explain(&log, &req.context)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    if !explained.cached {
+        state.metrics.observe_llm(llm_start.elapsed());
+    }
+
+    let (provider, model) = state.rag_engine.provider_info();
+    let elapsed = start.elapsed().as_millis();
+    info!(cached = explained.cached, time_ms = elapsed, "Explain complete");
+    Ok(Json(ExplainResponse {
+        log,
+        explanation: explained.explanation,
+        next_steps: explained.next_steps,
+        cached: explained.cached,
+        response_time_ms: elapsed,
+        provider: format!("{} • {}", provider, model),
+    }))
+}
+
+async fn stored_log_line(state: &AppState, id: &str) -> Result<String, (StatusCode, Json<ApiError>)> {
+    let id = Uuid::parse_str(id.trim())
+        .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid log id '{}'", id)))?;
+    let row: Option<ExplainRow> = state
+        .clickhouse
+        .query("SELECT level, service, message FROM logs WHERE id = ? LIMIT 1")
+        .bind(id)
+        .fetch_optional()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    row.map(|row| log_line(&row))
+        .ok_or_else(|| ApiError::not_found(format!("Log {} not found", id)))
+}
+
+// No timestamp: explanations are cached by this line, and the same message logged
+// a minute later means the same thing
+fn log_line(row: &ExplainRow) -> String {
+    format!("{} {}: {}", row.level.to_uppercase(), row.service, row.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_log_line() {
+        let row = ExplainRow {
+            level: "Error".to_string(),
+            service: "payment".to_string(),
+            message: "connection pool exhausted (max=20)".to_string(),
+        };
+        assert_eq!(log_line(&row), "ERROR payment: connection pool exhausted (max=20)");
+    }
+}
@@ -0,0 +1,241 @@
+// Bulk export: GET /api/logs/export streams every matching log as NDJSON or CSV.
+// Rows are read off a ClickHouse cursor and sent in chunks, so memory stays flat
+// however large the range is.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::Response,
+    Json,
+};
+use futures::{stream, Stream, StreamExt};
+use logai_core::LogLevel;
+use std::fmt::Display;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::models::{ApiError, ExportFormat, ExportQuery, RecentLogRow};
+use crate::state::AppState;
+
+// a month per request unless the caller passes unbounded=true
+const MAX_EXPORT_RANGE_SECS: i64 = 31 * 24 * 3600;
+// rows per body chunk (fewer when ClickHouse hasn't sent that many yet)
+const CHUNK_ROWS: usize = 1000;
+const CSV_HEADER: &str = "log_id,timestamp,service,level,message\n";
+
+// (from, to) in unix seconds, None = open-ended
+type ExportRange = (Option<i64>, Option<i64>);
+
+pub async fn export_logs(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ExportQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    let (from, to) = export_range(&params, chrono::Utc::now().timestamp())?;
+    info!(?from, ?to, service = ?params.service, level = ?params.level, format = ?params.format, "Export request");
+
+    let mut conditions = vec!["1=1".to_string()];
+    let mut binds: Vec<String> = vec![];
+    if let Some(from) = from {
+        conditions.push(format!("timestamp >= toDateTime({})", from));
+    }
+    if let Some(to) = to {
+        conditions.push(format!("timestamp < toDateTime({})", to));
+    }
+    if let Some(ref service) = params.service {
+        conditions.push("service = ?".to_string());
+        binds.push(service.clone());
+    }
+    if let Some(ref level) = params.level {
+        let level = LogLevel::from_str(level)
+            .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Unknown log level"))?;
+        conditions.push("level = ?".to_string());
+        binds.push(format!("{:?}", level));
+    }
+
+    let sql = format!(
+        "SELECT toString(id) as log_id, service, level, message, toString(timestamp) as timestamp
+         FROM logs
+         WHERE {}
+         ORDER BY timestamp, log_id",
+        conditions.join(" AND ")
+    );
+    let mut query = state.clickhouse.query(&sql);
+    for value in binds {
+        query = query.bind(value);
+    }
+    let cursor = query
+        .fetch::<RecentLogRow>()
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    let (content_type, filename) = match params.format {
+        ExportFormat::Ndjson => ("application/x-ndjson", "logs.ndjson"),
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "logs.csv"),
+    };
+    let body = Body::from_stream(export_chunks(rows(cursor), params.format, CHUNK_ROWS));
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))
+        .body(body)
+        .map_err(|e| ApiError::internal(e.to_string()))
+}
+
+/// Resolve and check the requested window; `to` defaults to now
+fn export_range(
+    params: &ExportQuery,
+    now: i64,
+) -> Result<ExportRange, (StatusCode, Json<ApiError>)> {
+    if params.unbounded {
+        return Ok((params.from, params.to));
+    }
+
+    let from = params.from.ok_or_else(|| {
+        ApiError::new(StatusCode::BAD_REQUEST, "from is required (or pass unbounded=true to export everything)")
+    })?;
+    let to = params.to.unwrap_or(now);
+    if to <= from {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "to must be after from"));
+    }
+    if to - from > MAX_EXPORT_RANGE_SECS {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "Range exceeds 31 days; narrow it or pass unbounded=true",
+        ));
+    }
+    Ok((Some(from), Some(to)))
+}
+
+// ClickHouse cursor as a stream; stops after the first error
+fn rows(
+    cursor: clickhouse::query::RowCursor<RecentLogRow>,
+) -> impl Stream<Item = clickhouse::error::Result<RecentLogRow>> {
+    stream::unfold(Some(cursor), |cursor| async move {
+        let mut cursor = cursor?;
+        match cursor.next().await {
+            Ok(Some(row)) => Some((Ok(row), Some(cursor))),
+            Ok(None) => None,
+            Err(e) => Some((Err(e), None)),
+        }
+    })
+}
+
+/// Serialize rows into body chunks of at most `chunk_rows` rows (CSV gets its header up front).
+/// An error ends the body early, so the client sees a truncated transfer rather than a clean EOF.
+fn export_chunks<S, E>(
+    rows: S,
+    format: ExportFormat,
+    chunk_rows: usize,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>>
+where
+    S: Stream<Item = Result<RecentLogRow, E>>,
+    E: Display,
+{
+    let header = match format {
+        ExportFormat::Csv => Some(Ok(Bytes::from_static(CSV_HEADER.as_bytes()))),
+        ExportFormat::Ndjson => None,
+    };
+
+    let body = rows.ready_chunks(chunk_rows).map(move |chunk| {
+        let mut buf = Vec::new();
+        for row in chunk {
+            let row = row.map_err(|e| {
+                warn!("Export aborted: {}", e);
+                std::io::Error::other(e.to_string())
+            })?;
+            match format {
+                ExportFormat::Ndjson => {
+                    serde_json::to_writer(&mut buf, &row)?;
+                    buf.push(b'\n');
+                }
+                ExportFormat::Csv => {
+                    let fields = [&row.log_id, &row.timestamp, &row.service, &row.level, &row.message];
+                    let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                    buf.extend_from_slice(line.join(",").as_bytes());
+                    buf.push(b'\n');
+                }
+            }
+        }
+        Ok(Bytes::from(buf))
+    });
+
+    stream::iter(header).chain(body)
+}
+
+// RFC 4180 quoting
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(i: usize) -> Result<RecentLogRow, String> {
+        Ok(RecentLogRow {
+            log_id: format!("id-{}", i),
+            service: "checkout".to_string(),
+            level: "Error".to_string(),
+            message: format!("timeout, attempt {}", i),
+            timestamp: format!("2026-02-10 03:00:0{}.000", i),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_export_streams_in_chunks() {
+        let rows = stream::iter((0..5).map(row));
+        let chunks: Vec<Bytes> = export_chunks(rows, ExportFormat::Ndjson, 2)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        // 5 rows at 2 per chunk, each chunk holding whole lines
+        let lines_per_chunk: Vec<usize> = chunks.iter().map(|c| c.iter().filter(|b| **b == b'\n').count()).collect();
+        assert_eq!(lines_per_chunk, vec![2, 2, 1]);
+
+        let body: Vec<u8> = chunks.concat();
+        let parsed: Vec<serde_json::Value> = body
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 5);
+        assert_eq!(parsed[4]["log_id"], "id-4");
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_and_midstream_error() {
+        let rows = stream::iter(vec![row(0), Err("connection reset".to_string()), row(2)]);
+        let chunks: Vec<Result<Bytes, std::io::Error>> = export_chunks(rows, ExportFormat::Csv, 10).collect().await;
+
+        assert_eq!(chunks[0].as_ref().unwrap().as_ref(), CSV_HEADER.as_bytes());
+        assert!(chunks[1].is_err());
+        assert_eq!(csv_field("timeout, attempt 1"), "\"timeout, attempt 1\"");
+    }
+
+    #[test]
+    fn test_export_range_limits() {
+        let query = |from, to, unbounded| ExportQuery {
+            from,
+            to,
+            service: None,
+            level: None,
+            format: ExportFormat::Ndjson,
+            unbounded,
+        };
+        let now = 1_770_000_000;
+
+        assert_eq!(
+            export_range(&query(Some(now - 3600), None, false), now).ok(),
+            Some((Some(now - 3600), Some(now)))
+        );
+        assert!(export_range(&query(None, None, false), now).is_err());
+        assert!(export_range(&query(Some(now - 40 * 86400), None, false), now).is_err());
+        assert!(export_range(&query(Some(now), Some(now - 1), false), now).is_err());
+        assert_eq!(export_range(&query(None, None, true), now).ok(), Some((None, None)));
+    }
+}
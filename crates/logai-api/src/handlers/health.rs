@@ -0,0 +1,114 @@
+// Health checks: `/health` is a cheap liveness probe, `/health?deep=true` and `/readyz`
+// ping NATS, ClickHouse and Qdrant and answer 503 if any of them is unreachable.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::models::{DependencyStatus, HealthQuery, HealthReport};
+use crate::state::AppState;
+
+// a dependency that takes longer than this counts as down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub async fn health(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HealthQuery>,
+) -> Response {
+    if params.deep {
+        readyz(State(state)).await.into_response()
+    } else {
+        "ok".into_response()
+    }
+}
+
+pub async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthReport>) {
+    let (nats, clickhouse, qdrant) = tokio::join!(
+        check("nats", state.nats.flush()),
+        check("clickhouse", state.clickhouse.query("SELECT 1").fetch_one::<u8>()),
+        check("qdrant", state.qdrant.list_collections()),
+    );
+    report(vec![nats, clickhouse, qdrant])
+}
+
+/// Run one ping with a timeout, timing it
+async fn check<T, E, F>(name: &str, ping: F) -> DependencyStatus
+where
+    F: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let start = Instant::now();
+    let error = match tokio::time::timeout(CHECK_TIMEOUT, ping).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("no response within {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    if let Some(ref e) = error {
+        warn!(dependency = name, "Health check failed: {}", e);
+    }
+
+    DependencyStatus {
+        name: name.to_string(),
+        ok: error.is_none(),
+        latency_ms: start.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+fn report(dependencies: Vec<DependencyStatus>) -> (StatusCode, Json<HealthReport>) {
+    let failed: Vec<String> = dependencies
+        .iter()
+        .filter(|d| !d.ok)
+        .map(|d| d.name.clone())
+        .collect();
+    let (status, label) = if failed.is_empty() {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+
+    (status, Json(HealthReport {
+        status: label.to_string(),
+        failed,
+        dependencies,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failing_dependency_is_503() {
+        let dependencies = vec![
+            check("nats", async { Ok::<_, String>(()) }).await,
+            check("clickhouse", async { Err::<(), _>("Connection refused".to_string()) }).await,
+            check("qdrant", async { Ok::<_, String>(()) }).await,
+        ];
+
+        let response = report(dependencies).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["status"], "unavailable");
+        assert_eq!(json["failed"], serde_json::json!(["clickhouse"]));
+        assert_eq!(json["dependencies"][1]["error"], "Connection refused");
+    }
+
+    #[tokio::test]
+    async fn test_all_healthy_is_200() {
+        let ok = check("nats", async { Ok::<_, String>(()) }).await;
+        let (status, Json(report)) = report(vec![ok]);
+        assert_eq!(status, StatusCode::OK);
+        assert!(report.failed.is_empty());
+    }
+}
@@ -1,29 +1,72 @@
 use axum::{
+    body::Body,
     extract::State,
     http::StatusCode,
+    Extension,
     Json,
 };
-use logai_core::{LogEntry, RawLogEntry};
+use futures::StreamExt;
+use logai_core::otlp::ExportLogsRequest;
+use logai_core::{LogEntry, RawLogEntry, REQUEST_ID_HEADER};
+use std::future::Future;
 use std::sync::Arc;
 use tracing::info;
 
-use crate::models::{IngestResponse, RawIngestResponse, RawLogRequest};
+use crate::middleware::RequestId;
+use crate::models::{ApiError, FormatsResponse, IngestResponse, OtlpExportResponse, RawIngestResponse, RawLogRequest};
 use crate::state::AppState;
 
-pub async fn ingest_log(
-    State(state): State<Arc<AppState>>,
-    Json(raw): Json<RawLogEntry>,
-) -> Result<Json<IngestResponse>, (StatusCode, String)> {
-    let entry = LogEntry::from_raw(raw);
+/// NATS headers for published logs: the request id, so the worker's logs about them can be matched up
+fn ingest_headers(request_id: Option<Extension<RequestId>>) -> async_nats::HeaderMap {
+    let mut headers = async_nats::HeaderMap::new();
+    if let Some(Extension(RequestId(id))) = request_id {
+        headers.insert(REQUEST_ID_HEADER, id.as_str());
+    }
+    headers
+}
 
-    let payload = serde_json::to_vec(&entry)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+/// Whether ingest sampling drops `entry` (LOGAI_SAMPLE_*_RATE); counted in the metrics if so
+fn sampled_out(state: &AppState, entry: &LogEntry) -> bool {
+    let dropped = !state.sampling.keeps(entry);
+    if dropped {
+        state.metrics.sampled_out_logs.inc();
+    }
+    dropped
+}
 
+/// Publish one log on the ingest subject unless sampling drops it: NATS, then the service
+/// registry and the ingest counter, the same for every ingest endpoint. False if sampled out
+async fn publish_entry(
+    state: &AppState,
+    headers: &async_nats::HeaderMap,
+    entry: &LogEntry,
+) -> Result<bool, (StatusCode, Json<ApiError>)> {
+    if sampled_out(state, entry) {
+        return Ok(false);
+    }
+    let payload = serde_json::to_vec(entry).map_err(|e| ApiError::internal(e.to_string()))?;
     state
         .nats
-        .publish("logs.ingest", payload.into())
+        .publish_with_headers(state.ingest_subject.clone(), headers.clone(), payload.into())
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.services.record(&entry.service);
+    state.metrics.ingested_logs.inc();
+    Ok(true)
+}
+
+pub async fn ingest_log(
+    State(state): State<Arc<AppState>>,
+    request_id: Option<Extension<RequestId>>,
+    Json(raw): Json<RawLogEntry>,
+) -> Result<Json<IngestResponse>, (StatusCode, Json<ApiError>)> {
+    let entry = LogEntry::from_raw(raw);
+    if !publish_entry(&state, &ingest_headers(request_id), &entry).await? {
+        return Ok(Json(IngestResponse {
+            id: entry.id.to_string(),
+            status: "sampled_out".to_string(),
+        }));
+    }
 
     info!(
         id = %entry.id,
@@ -40,27 +83,29 @@ pub async fn ingest_log(
 
 pub async fn ingest_raw_log(
     State(state): State<Arc<AppState>>,
+    request_id: Option<Extension<RequestId>>,
     Json(req): Json<RawLogRequest>,
-) -> Result<Json<RawIngestResponse>, (StatusCode, String)> {
-    let total = req.lines.len();
+) -> Result<Json<RawIngestResponse>, (StatusCode, Json<ApiError>)> {
+    let headers = ingest_headers(request_id);
+    let results = if req.multiline {
+        state.parser_registry.parse_multiline(&req.format, &req.lines)
+    } else {
+        state.parser_registry.parse_lines(&req.format, &req.lines)
+    };
+    // with multiline on (or a multi-line format), a stack trace or block counts as one log
+    let total = results.len();
     let mut parsed = 0;
     let mut failed = 0;
+    let mut sampled = 0;
 
-    for line in req.lines {
-        match state.parser_registry.parse(&req.format, &line) {
+    for result in results {
+        match result {
             Ok(mut raw) => {
                 raw.service = Some(req.service.clone());
-                let entry = LogEntry::from_raw(raw);
-                let payload = serde_json::to_vec(&entry)
-                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-                state
-                    .nats
-                    .publish("logs.ingest", payload.into())
-                    .await
-                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
                 parsed += 1;
+                if !publish_entry(&state, &headers, &LogEntry::from_raw(raw)).await? {
+                    sampled += 1;
+                }
             }
             Err(_) => {
                 failed += 1;
@@ -68,7 +113,7 @@ pub async fn ingest_raw_log(
         }
     }
 
-    info!(total, parsed, failed, format = %req.format, "Raw logs ingested");
+    info!(total, parsed, failed, sampled, format = %req.format, multiline = req.multiline, "Raw logs ingested");
 
     Ok(Json(RawIngestResponse {
         total,
@@ -76,3 +121,193 @@ pub async fn ingest_raw_log(
         failed,
     }))
 }
+
+/// POST /api/otlp/logs: an OTLP/HTTP JSON export, for collectors and SDKs that ship logs
+/// over OpenTelemetry
+pub async fn ingest_otlp_logs(
+    State(state): State<Arc<AppState>>,
+    request_id: Option<Extension<RequestId>>,
+    Json(req): Json<ExportLogsRequest>,
+) -> Result<Json<OtlpExportResponse>, (StatusCode, Json<ApiError>)> {
+    let headers = ingest_headers(request_id);
+    let entries = req.into_raw_entries();
+    let total = entries.len();
+    let mut sampled = 0;
+
+    for raw in entries {
+        if !publish_entry(&state, &headers, &LogEntry::from_raw(raw)).await? {
+            sampled += 1;
+        }
+    }
+
+    info!(total, sampled, "OTLP logs ingested");
+
+    Ok(Json(OtlpExportResponse {}))
+}
+
+// longest NDJSON line kept; anything longer is counted as failed, not buffered
+const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// POST /api/logs/stream: an `application/x-ndjson` body, one `RawLogEntry` per line, each
+/// published as soon as its line is in, so a file of any size goes up in one request.
+/// Lines that aren't a log count as failed; if the upload breaks off, the logs before the
+/// break have already been published.
+pub async fn ingest_log_stream(
+    State(state): State<Arc<AppState>>,
+    request_id: Option<Extension<RequestId>>,
+    body: Body,
+) -> Result<Json<RawIngestResponse>, (StatusCode, Json<ApiError>)> {
+    let headers = ingest_headers(request_id);
+    let counts = for_each_ndjson_entry(body, |raw| {
+        let state = state.clone();
+        let headers = headers.clone();
+        async move {
+            publish_entry(&state, &headers, &LogEntry::from_raw(raw)).await?;
+            Ok(())
+        }
+    })
+    .await?;
+
+    info!(total = counts.total, parsed = counts.parsed, failed = counts.failed, "NDJSON logs ingested");
+    Ok(Json(counts))
+}
+
+/// Hands each log in an NDJSON body to `handle` as the body arrives. Blank lines are
+/// skipped, a last line without a newline still counts.
+async fn for_each_ndjson_entry<F, Fut>(
+    body: Body,
+    mut handle: F,
+) -> Result<RawIngestResponse, (StatusCode, Json<ApiError>)>
+where
+    F: FnMut(RawLogEntry) -> Fut,
+    Fut: Future<Output = Result<(), (StatusCode, Json<ApiError>)>>,
+{
+    let mut counts = RawIngestResponse { total: 0, parsed: 0, failed: 0 };
+    let mut lines = NdjsonLines::default();
+    let mut stream = body.into_data_stream();
+    let mut finished = false;
+
+    while !finished {
+        let complete = match stream.next().await {
+            Some(chunk) => {
+                let chunk = chunk.map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
+                lines.push(&chunk)
+            }
+            None => {
+                finished = true;
+                lines.finish()
+            }
+        };
+        for line in complete {
+            let raw = match line {
+                Some(line) if line.trim_ascii().is_empty() => continue,
+                Some(line) => serde_json::from_slice::<RawLogEntry>(&line).ok(),
+                None => None,
+            };
+            counts.total += 1;
+            match raw {
+                Some(raw) => {
+                    handle(raw).await?;
+                    counts.parsed += 1;
+                }
+                None => counts.failed += 1,
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Splits body chunks into lines, carrying a partial line over to the next chunk
+#[derive(Default)]
+struct NdjsonLines {
+    partial: Vec<u8>,
+    // the current line went over MAX_LINE_BYTES, drop it up to its newline
+    oversized: bool,
+}
+
+impl NdjsonLines {
+    /// Lines completed by `chunk`; None for one over MAX_LINE_BYTES
+    fn push(&mut self, chunk: &[u8]) -> Vec<Option<Vec<u8>>> {
+        let mut lines = Vec::new();
+        let mut rest = chunk;
+        while let Some(end) = rest.iter().position(|b| *b == b'\n') {
+            self.extend(&rest[..end]);
+            lines.push(self.take());
+            rest = &rest[end + 1..];
+        }
+        self.extend(rest);
+        lines
+    }
+
+    /// What's left once the body has ended
+    fn finish(&mut self) -> Vec<Option<Vec<u8>>> {
+        if self.partial.is_empty() && !self.oversized {
+            return Vec::new();
+        }
+        vec![self.take()]
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        if self.oversized {
+            return;
+        }
+        if self.partial.len() + bytes.len() > MAX_LINE_BYTES {
+            self.partial = Vec::new();
+            self.oversized = true;
+        } else {
+            self.partial.extend_from_slice(bytes);
+        }
+    }
+
+    fn take(&mut self) -> Option<Vec<u8>> {
+        let line = std::mem::take(&mut self.partial);
+        (!std::mem::replace(&mut self.oversized, false)).then_some(line)
+    }
+}
+
+pub async fn get_formats(State(state): State<Arc<AppState>>) -> Json<FormatsResponse> {
+    Json(FormatsResponse {
+        formats: state.parser_registry.list(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+
+    #[tokio::test]
+    async fn test_ndjson_stream() {
+        // lines split across chunks the way a slow upload delivers them
+        let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+            Ok(Bytes::from(r#"{"message":"payment failed","service":"checkout","level":"error"}"#.to_string() + "\n{\"mess")),
+            Ok(Bytes::from("age\":\"cache warmed\"}\n\n")),
+            Ok(Bytes::from("not json\n")),
+            Ok(Bytes::from(r#"{"message":"retrying","level":"warn"}"#)),
+        ];
+        let body = Body::from_stream(futures::stream::iter(chunks));
+
+        let mut received = Vec::new();
+        let Ok(counts) = for_each_ndjson_entry(body, |raw| {
+            received.push(raw);
+            async { Ok(()) }
+        })
+        .await
+        else {
+            panic!("the body reads fine");
+        };
+
+        assert_eq!((counts.total, counts.parsed, counts.failed), (4, 3, 1));
+        let messages: Vec<&str> = received.iter().map(|raw| raw.message.as_str()).collect();
+        assert_eq!(messages, ["payment failed", "cache warmed", "retrying"]);
+        assert_eq!(received[0].service.as_deref(), Some("checkout"));
+    }
+
+    #[test]
+    fn test_oversized_line_dropped() {
+        let mut lines = NdjsonLines::default();
+        assert!(lines.push(&vec![b'x'; MAX_LINE_BYTES]).is_empty());
+        assert_eq!(lines.push(b"xx\n{}\n"), vec![None, Some(b"{}".to_vec())]);
+        assert!(lines.finish().is_empty());
+    }
+}
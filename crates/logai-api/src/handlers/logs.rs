@@ -0,0 +1,219 @@
+// GET /api/logs/{id}: the full stored record of one log. Search and the recent-logs list
+// only return a projection; this is where raw, fields and trace_id come back.
+// DELETE /api/logs removes every log matching one field, e.g. a user's data on request.
+
+use axum::{
+    extract::{rejection::QueryRejection, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::DateTime;
+use logai_core::{ErrorCategory, LogEntry, LogLevel};
+use qdrant_client::qdrant::{Condition, CountPointsBuilder, DeletePointsBuilder, Filter};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{bad_query, field_condition, is_field_name, log_collections, sql_string};
+use crate::models::{ApiError, DeleteLogsQuery, DeleteLogsResponse};
+use crate::state::AppState;
+
+// log ids per Qdrant delete, keeps each filter a reasonable size
+const DELETE_BATCH: usize = 1000;
+
+#[derive(Deserialize, clickhouse::Row)]
+struct LogRow {
+    timestamp_ms: i64,
+    level: String,
+    service: String,
+    message: String,
+    raw: String,
+    trace_id: Option<String>,
+    span_id: Option<String>,
+    error_category: Option<String>,
+    fields: String,
+    ingested_at_ms: i64,
+}
+
+pub async fn get_log(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<LogEntry>, (StatusCode, Json<ApiError>)> {
+    info!(id = %id, "Log lookup");
+    let id = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid log id '{}'", id)))?;
+
+    let row: Option<LogRow> = state
+        .clickhouse
+        .query(
+            "SELECT toUnixTimestamp64Milli(timestamp) AS timestamp_ms, level, service, message, raw,
+                    trace_id, span_id, error_category, fields,
+                    toUnixTimestamp64Milli(ingested_at) AS ingested_at_ms
+             FROM logs
+             WHERE id = ?
+             LIMIT 1",
+        )
+        .bind(id)
+        .fetch_optional()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    into_log_entry(id, row).map(Json)
+}
+
+/// Rebuild the `LogEntry` the worker stored; 404 when there's no row
+fn into_log_entry(id: Uuid, row: Option<LogRow>) -> Result<LogEntry, (StatusCode, Json<ApiError>)> {
+    let row = row.ok_or_else(|| ApiError::not_found(format!("Log {} not found", id)))?;
+    let timestamp = |ms: i64| {
+        DateTime::from_timestamp_millis(ms).ok_or_else(|| ApiError::internal(format!("Invalid timestamp {}", ms)))
+    };
+
+    Ok(LogEntry {
+        id,
+        timestamp: timestamp(row.timestamp_ms)?,
+        level: LogLevel::from_str(&row.level).unwrap_or(LogLevel::Info),
+        service: row.service,
+        message: row.message,
+        raw: row.raw,
+        trace_id: row.trace_id,
+        span_id: row.span_id,
+        error_category: row.error_category.as_deref().and_then(ErrorCategory::from_name),
+        // written by the worker with serde_json, so only a hand-edited row fails here
+        fields: serde_json::from_str(&row.fields).unwrap_or_default(),
+        ingested_at: timestamp(row.ingested_at_ms)?,
+    })
+}
+
+pub async fn delete_logs(
+    State(state): State<Arc<AppState>>,
+    query: Result<Query<DeleteLogsQuery>, QueryRejection>,
+) -> Result<Json<DeleteLogsResponse>, (StatusCode, Json<ApiError>)> {
+    let Query(query) = query.map_err(bad_query)?;
+    let condition = delete_condition(&query).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    warn!(field = %query.field.trim(), "Deleting logs by field");
+
+    let ids: Vec<String> = state
+        .clickhouse
+        .query(&format!("SELECT toString(id) FROM logs WHERE {}", condition))
+        .fetch_all()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    // Qdrant first: the ids come from ClickHouse, so a failure here can simply be retried
+    let collections = log_collections(&state, None).await?;
+    let mut qdrant_deleted = 0;
+    for batch in ids.chunks(DELETE_BATCH) {
+        // single-log points by log_id, and whole chunks that contain any of these logs
+        let filter = Filter::should([
+            Condition::matches("log_id", batch.to_vec()),
+            Condition::matches("log_ids", batch.to_vec()),
+        ]);
+        for collection in &collections {
+            let counted = state
+                .qdrant
+                .count(CountPointsBuilder::new(collection).filter(filter.clone()).exact(true))
+                .await
+                .map_err(|e| ApiError::internal(e.to_string()))?;
+            state
+                .qdrant
+                .delete_points(DeletePointsBuilder::new(collection).points(filter.clone()).wait(true))
+                .await
+                .map_err(|e| ApiError::internal(e.to_string()))?;
+            qdrant_deleted += counted.result.map_or(0, |r| r.count);
+        }
+    }
+
+    if !ids.is_empty() {
+        state
+            .clickhouse
+            .query(&format!("ALTER TABLE logs DELETE WHERE {} SETTINGS mutations_sync = 1", condition))
+            .execute()
+            .await
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+    }
+
+    info!(clickhouse = ids.len(), qdrant = qdrant_deleted, "Deleted logs");
+    Ok(Json(DeleteLogsResponse {
+        clickhouse_deleted: ids.len() as u64,
+        qdrant_deleted,
+    }))
+}
+
+/// WHERE clause for a delete; refuses an empty field or value, which would otherwise
+/// turn into a condition matching far more than intended
+fn delete_condition(query: &DeleteLogsQuery) -> Result<String, String> {
+    let (field, value) = (query.field.trim(), query.value.trim());
+    if field.is_empty() || value.is_empty() {
+        return Err("Refusing to delete without a filter: field and value are both required".to_string());
+    }
+    if !is_field_name(field) {
+        return Err(format!("Invalid field '{}'", field));
+    }
+    Ok(match field {
+        "trace_id" => format!("trace_id = {}", sql_string(value)),
+        _ => field_condition(&(field.to_string(), value.to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+
+    fn row() -> LogRow {
+        LogRow {
+            timestamp_ms: 1770692400412,
+            level: "Error".to_string(),
+            service: "checkout".to_string(),
+            message: "Payment failed".to_string(),
+            raw: r#"{"message":"Payment failed"}"#.to_string(),
+            trace_id: Some("abc123".to_string()),
+            span_id: None,
+            error_category: Some("HttpError".to_string()),
+            fields: r#"{"status_code":502}"#.to_string(),
+            ingested_at_ms: 1770692401000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_log_is_404() {
+        let id = Uuid::new_v4();
+        let Err(err) = into_log_entry(id, None) else { panic!("no row should be a 404") };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], format!("Log {} not found", id));
+        assert_eq!(json["code"], 404);
+    }
+
+    #[test]
+    fn test_row_to_entry() {
+        let entry = into_log_entry(Uuid::nil(), Some(row())).ok().expect("row converts");
+        assert_eq!(entry.level, LogLevel::Error);
+        assert_eq!(entry.timestamp.to_rfc3339(), "2026-02-10T03:00:00.412+00:00");
+        assert_eq!(entry.error_category, Some(ErrorCategory::HttpError));
+        assert_eq!(entry.fields["status_code"], 502);
+        assert_eq!(entry.trace_id.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_delete_requires_a_filter() {
+        let query = |field: &str, value: &str| DeleteLogsQuery {
+            field: field.to_string(),
+            value: value.to_string(),
+        };
+
+        for (field, value) in [("user_id", ""), ("user_id", "   "), ("", "u123"), ("", "")] {
+            let err = delete_condition(&query(field, value)).unwrap_err();
+            assert!(err.starts_with("Refusing to delete without a filter"), "{:?}", (field, value));
+        }
+        assert!(delete_condition(&query("user_id') OR 1=1 --", "u123")).is_err());
+
+        let condition = delete_condition(&query("user_id", "u123")).unwrap();
+        assert!(condition.contains("JSONExtractString(fields, 'user_id') = 'u123'"));
+        assert_eq!(delete_condition(&query("trace_id", "abc")).unwrap(), "trace_id = 'abc'");
+    }
+}
@@ -0,0 +1,47 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+use std::sync::Arc;
+
+use crate::metrics::Metrics;
+
+/// Prometheus scrape endpoint
+pub async fn get_metrics(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::track_requests;
+    use axum::{body::Body, http::Request, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn get_body(app: &Router, uri: &str) -> String {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_shows_up_in_metrics() {
+        let metrics = Arc::new(Metrics::new());
+        let app = Router::new()
+            .route("/api/search", get(|| async { "[]" }))
+            .route("/metrics", get(get_metrics))
+            .layer(middleware::from_fn_with_state(metrics.clone(), track_requests))
+            .with_state(metrics);
+
+        get_body(&app, "/api/search?q=timeout").await;
+        let output = get_body(&app, "/metrics").await;
+
+        assert!(output.contains("logai_search_requests_total 1"));
+        assert!(output.contains("logai_ask_requests_total 0"));
+        assert!(output.contains("logai_chat_duration_seconds_count 0"));
+    }
+}
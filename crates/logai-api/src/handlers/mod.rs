@@ -3,15 +3,199 @@ mod search;
 mod chat;
 mod stats;
 mod alerts;
+mod metrics;
+mod stream;
+mod trace;
+mod export;
+mod health;
+mod causal;
+mod logs;
+mod diff;
+mod explain;
+mod service_health;
+mod patterns;
 
 pub use ingest::*;
 pub use search::*;
 pub use chat::*;
 pub use stats::*;
 pub use alerts::*;
+pub use metrics::*;
+pub use stream::*;
+pub use trace::*;
+pub use export::*;
+pub use health::*;
+pub use causal::*;
+pub use logs::*;
+pub use diff::*;
+pub use explain::*;
+pub use service_health::*;
+pub use patterns::*;
 
+use axum::{extract::rejection::QueryRejection, http::StatusCode, Json};
+use futures::future::try_join_all;
+use logai_core::embedding::{is_log_collection, CollectionLayout, COLLECTION_NAME};
+use logai_rag::AnalyzedQuery;
+use qdrant_client::qdrant::{Condition, RetrievedPoint, ScoredPoint, ScrollPointsBuilder, SearchPointsBuilder};
+use qdrant_client::QdrantError;
 use std::collections::HashMap;
 
+use crate::models::ApiError;
+use crate::state::AppState;
+
+// (field, value) from `filters=field:value,...`
+pub type FieldFilter = (String, String);
+
+/// `status_code:500,endpoint:/api/checkout` -> pairs (field names are
+/// restricted to [A-Za-z0-9_-]); the value is everything after the first ':'
+pub fn parse_filters(raw: &str) -> Result<Vec<FieldFilter>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (field, value) = pair
+                .split_once(':')
+                .map(|(f, v)| (f.trim(), v.trim()))
+                .filter(|(f, v)| !f.is_empty() && !v.is_empty())
+                .ok_or_else(|| format!("Invalid filter '{}', expected field:value", pair))?;
+            if !is_field_name(field) {
+                return Err(format!("Invalid filter field '{}'", field));
+            }
+            Ok((field.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Whether `field` is safe to splice into a JSON path: letters, digits, `_` and `-`
+pub fn is_field_name(field: &str) -> bool {
+    field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Quoted ClickHouse string literal; backslashes are escapes there, so they go first
+pub fn sql_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// WHERE clause matching one `fields` key. The worker stores `fields` as a JSON string, so this goes
+/// through ClickHouse's JSON functions: the string form covers `"status_code":"500"`, the raw
+/// form numbers and booleans (`"status_code":500`). `parse_filters` already restricted the key.
+pub fn field_condition((field, value): &FieldFilter) -> String {
+    let value = sql_string(value);
+    format!(
+        "(JSONExtractString(fields, '{field}') = {value} OR JSONExtractRaw(fields, '{field}') = {value})",
+        field = field,
+        value = value
+    )
+}
+
+/// Malformed query parameters as a JSON 400 like every other API error, instead of axum's plain text
+pub fn bad_query(rejection: QueryRejection) -> (StatusCode, Json<ApiError>) {
+    ApiError::new(StatusCode::BAD_REQUEST, rejection.body_text())
+}
+
+/// The score floor for this request: its own `min_score` if given, else the server default
+pub fn min_score(requested: Option<f32>, default: f32) -> Result<f32, (StatusCode, Json<ApiError>)> {
+    match requested {
+        Some(score) if !(0.0..=1.0).contains(&score) => Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("min_score must be between 0 and 1, got {}", score),
+        )),
+        Some(score) => Ok(score),
+        None => Ok(default),
+    }
+}
+
+/// Keep hits scoring at least `min_score`; an empty result means nothing relevant was found.
+/// Euclid scores are distances (lower is closer, no upper bound), so the 0-1 floor doesn't
+/// apply to them and every hit is kept
+pub fn above_min_score(hits: Vec<(String, f32)>, min_score: f32, distances: bool) -> Vec<(String, f32)> {
+    if distances {
+        return hits;
+    }
+    hits.into_iter().filter(|(_, score)| *score >= min_score).collect()
+}
+
+/// Qdrant matches for the service and level read from a question ("errors in payment"),
+/// applied only when a request asks for `strict_filters`
+pub fn strict_conditions(analyzed: &AnalyzedQuery) -> Vec<Condition> {
+    let mut conditions = vec![];
+    if let Some(service) = &analyzed.service {
+        conditions.push(Condition::matches("service", service.clone()));
+    }
+    if let Some(level) = &analyzed.level {
+        // same "Error"/"Warn" spelling the worker stores
+        conditions.push(Condition::matches("level", level.clone()));
+    }
+    conditions
+}
+
+/// Embed a search query, reusing the vector from a recent identical query
+pub fn embed_query(state: &AppState, query: &str) -> Result<Vec<f32>, (StatusCode, Json<ApiError>)> {
+    state.embedding_cache.get_or_embed(query, |query| {
+        let embeddings = state
+            .model
+            .with(|model| model.embed(vec![query.to_string()], None))
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        embeddings.into_iter().next().ok_or_else(|| ApiError::internal("No embedding"))
+    })
+}
+
+/// Collections a query reads. With one collection per service: the service's own when the
+/// query is scoped to one (none if nothing was stored for it yet), otherwise all of them.
+pub async fn log_collections(
+    state: &AppState,
+    service: Option<&str>,
+) -> Result<Vec<String>, (StatusCode, Json<ApiError>)> {
+    if state.collections == CollectionLayout::Single {
+        return Ok(vec![COLLECTION_NAME.to_string()]);
+    }
+    let scoped = service.map(|service| state.collections.collection_for(service));
+    let existing = state
+        .qdrant
+        .list_collections()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    Ok(existing
+        .collections
+        .into_iter()
+        .map(|c| c.name)
+        .filter(|name| match &scoped {
+            Some(scoped) => name == scoped,
+            None => is_log_collection(name),
+        })
+        .collect())
+}
+
+/// The same search on every collection, merged best score first and cut to `limit`
+pub async fn search_collections(
+    state: &AppState,
+    collections: &[String],
+    limit: u64,
+    search: impl Fn(&str) -> SearchPointsBuilder,
+) -> Result<Vec<ScoredPoint>, QdrantError> {
+    let responses = try_join_all(collections.iter().map(|name| state.qdrant.search_points(search(name)))).await?;
+    let mut points: Vec<ScoredPoint> = responses.into_iter().flat_map(|r| r.result).collect();
+    points.sort_by(|a, b| b.score.total_cmp(&a.score));
+    points.truncate(limit as usize);
+    Ok(points)
+}
+
+/// The same scroll on every collection (each ordered by `timestamp_unix`, newest first),
+/// merged newest first and cut to `limit`
+pub async fn scroll_collections(
+    state: &AppState,
+    collections: &[String],
+    limit: u32,
+    scroll: impl Fn(&str) -> ScrollPointsBuilder,
+) -> Result<Vec<RetrievedPoint>, QdrantError> {
+    let responses = try_join_all(collections.iter().map(|name| state.qdrant.scroll(scroll(name)))).await?;
+    let mut points: Vec<RetrievedPoint> = responses.into_iter().flat_map(|r| r.result).collect();
+    let timestamp = |point: &RetrievedPoint| point.payload.get("timestamp_unix").and_then(|v| v.as_integer());
+    points.sort_by_key(|point| std::cmp::Reverse(timestamp(point)));
+    points.truncate(limit as usize);
+    Ok(points)
+}
+
 pub fn get_string(
     payload: &HashMap<String, qdrant_client::qdrant::Value>,
     key: &str,
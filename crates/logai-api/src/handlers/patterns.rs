@@ -0,0 +1,214 @@
+// GET /api/patterns: the message templates behind a range of logs, most frequent first.
+// A Drain-lite: ClickHouse masks each message into a template with the reranker's normalizer
+// patterns (numbers, UUIDs, IPs and paths) and counts identical templates together.
+
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    http::StatusCode,
+    Json,
+};
+use logai_rag::MessageNormalizer;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::handlers::{bad_query, sql_string};
+use crate::models::{ApiError, LogPattern, PatternsQuery, PatternsResponse};
+use crate::state::AppState;
+
+// templates in the response
+const PATTERN_LIMIT: usize = 50;
+// (template, level) pairs read, most frequent first; past this the rarer templates are left
+// out (the response says so)
+const MAX_ROWS: usize = 10_000;
+// range when the caller gives no `from`
+const DEFAULT_RANGE_SECS: i64 = 3600;
+
+#[derive(Deserialize, clickhouse::Row)]
+struct TemplateCountRow {
+    template: String,
+    level: String,
+    count: u64,
+    // the template's most frequent message at this level
+    example: String,
+}
+
+pub async fn get_patterns(
+    State(state): State<Arc<AppState>>,
+    query: Result<Query<PatternsQuery>, QueryRejection>,
+) -> Result<Json<PatternsResponse>, (StatusCode, Json<ApiError>)> {
+    let Query(params) = query.map_err(bad_query)?;
+    let to = params.to.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let from = params.from.unwrap_or(to - DEFAULT_RANGE_SECS);
+    info!(from, to, service = ?params.service, "Patterns request");
+    if from > to {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "from must not be after to"));
+    }
+
+    let service = params.service.as_deref();
+    let mut rows: Vec<TemplateCountRow> = state
+        .clickhouse
+        .query(&template_counts_query(from, to, service))
+        .fetch_all()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let truncated = rows.len() > MAX_ROWS;
+    rows.truncate(MAX_ROWS);
+    // counted apart so it stays the whole range when the rows above were cut
+    let total: u64 = state
+        .clickhouse
+        .query(&format!("SELECT count() FROM logs WHERE {}", range_filter(from, to, service)))
+        .fetch_one()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    let mut patterns = cluster(rows);
+    info!(total, templates = patterns.len(), truncated, "Patterns computed");
+    patterns.truncate(PATTERN_LIMIT);
+
+    Ok(Json(PatternsResponse {
+        from,
+        to,
+        service: params.service,
+        total,
+        truncated,
+        patterns,
+    }))
+}
+
+/// Logs in [from, to], of one service if given
+fn range_filter(from: i64, to: i64, service: Option<&str>) -> String {
+    let service = service
+        .map(|s| format!(" AND service = {}", sql_string(s)))
+        .unwrap_or_default();
+    format!(
+        "timestamp >= toDateTime64({}, 3) AND timestamp <= toDateTime64({}, 3){}",
+        from, to, service
+    )
+}
+
+/// `MessageNormalizer::template` of `message` in SQL: the same patterns in the same order
+fn template_expr() -> String {
+    let mask = |expr: String, pattern: &str, replacement: &str| {
+        format!("replaceRegexpAll({}, {}, {})", expr, sql_string(pattern), sql_string(replacement))
+    };
+    let expr = mask("message".to_string(), MessageNormalizer::UUID_PATTERN, "<UUID>");
+    let expr = mask(expr, MessageNormalizer::IP_PATTERN, "<IP>");
+    let expr = mask(expr, MessageNormalizer::NUMBER_PATTERN, "<NUM>");
+    // ClickHouse writes the kept group as \1 where the regex crate has ${1}
+    mask(expr, MessageNormalizer::PATH_PATTERN, "\\1<PATH>")
+}
+
+/// Log counts per (template, level) in [from, to]; one row past `MAX_ROWS` tells whether
+/// any were cut
+fn template_counts_query(from: i64, to: i64, service: Option<&str>) -> String {
+    format!(
+        "SELECT {} AS template, toString(level) AS level, count() AS count,
+                topK(1)(message)[1] AS example
+         FROM logs
+         WHERE {}
+         GROUP BY template, level
+         ORDER BY count DESC
+         LIMIT {}",
+        template_expr(),
+        range_filter(from, to, service),
+        MAX_ROWS + 1
+    )
+}
+
+/// One pattern per template, levels folded together, the biggest count first, ties by
+/// template. The example comes from the template's most frequent level.
+fn cluster(rows: Vec<TemplateCountRow>) -> Vec<LogPattern> {
+    let mut by_template: HashMap<String, (LogPattern, u64)> = HashMap::new();
+    for row in rows {
+        let template = row.template;
+        let (pattern, example_count) = by_template.entry(template.clone()).or_insert_with(|| {
+            let pattern = LogPattern {
+                template,
+                count: 0,
+                example: String::new(),
+                level_distribution: Default::default(),
+            };
+            (pattern, 0)
+        });
+        pattern.count += row.count;
+        *pattern.level_distribution.entry(row.level).or_default() += row.count;
+        if row.count > *example_count {
+            *example_count = row.count;
+            pattern.example = row.example;
+        }
+    }
+
+    let mut patterns: Vec<LogPattern> = by_template.into_values().map(|(pattern, _)| pattern).collect();
+    patterns.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.template.cmp(&b.template)));
+    patterns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(template: &str, level: &str, count: u64, example: &str) -> TemplateCountRow {
+        TemplateCountRow {
+            template: template.to_string(),
+            level: level.to_string(),
+            count,
+            example: example.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cluster_three_templates() {
+        let rows = vec![
+            row("GET <PATH> took <NUM>ms", "Info", 50, "GET /api/orders/0 took 10ms"),
+            row("connection to <IP> refused", "Error", 20, "connection to 10.0.3.7:5432 refused"),
+            row("session <UUID> expired", "Warn", 20, "session 6f1c2b9e-0d4a-4e51-9a0e-2c8d1e5b7a00 expired"),
+            row("connection to <IP> refused", "Fatal", 10, "connection to 10.0.0.7:5432 refused"),
+        ];
+
+        let patterns = cluster(rows);
+        assert_eq!(patterns.len(), 3);
+
+        let counts: Vec<(&str, u64)> = patterns.iter().map(|p| (p.template.as_str(), p.count)).collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("GET <PATH> took <NUM>ms", 50),
+                ("connection to <IP> refused", 30),
+                ("session <UUID> expired", 20),
+            ]
+        );
+        assert_eq!(patterns[0].example, "GET /api/orders/0 took 10ms");
+        assert_eq!(patterns[1].example, "connection to 10.0.3.7:5432 refused");
+        assert_eq!(patterns[1].level_distribution.get("Error"), Some(&20));
+        assert_eq!(patterns[1].level_distribution.get("Fatal"), Some(&10));
+        assert_eq!(patterns[2].level_distribution.len(), 1);
+    }
+
+    #[test]
+    fn test_template_expr_masks_like_the_normalizer() {
+        let expr = template_expr();
+        // UUIDs innermost, paths outermost, as in MessageNormalizer::template
+        assert!(expr.starts_with(&format!(
+            "replaceRegexpAll(replaceRegexpAll(replaceRegexpAll(replaceRegexpAll(message, {}, '<UUID>'), {}, '<IP>'), {}, '<NUM>')",
+            sql_string(MessageNormalizer::UUID_PATTERN),
+            sql_string(MessageNormalizer::IP_PATTERN),
+            sql_string(MessageNormalizer::NUMBER_PATTERN),
+        )));
+        assert!(expr.ends_with(&format!("{}, '\\\\1<PATH>')", sql_string(MessageNormalizer::PATH_PATTERN))));
+        // the path pattern's quotes and backslashes survive the SQL string
+        assert!(expr.contains(r#"'(^|[\\s"\'=:(\\[])"#));
+    }
+
+    #[test]
+    fn test_template_counts_query() {
+        let sql = template_counts_query(100, 200, Some("check'out"));
+        assert!(sql.contains("toDateTime64(100, 3) AND timestamp <= toDateTime64(200, 3)"));
+        assert!(sql.contains("AND service = 'check\\'out'"));
+        assert!(sql.contains(&format!("SELECT {} AS template", template_expr())));
+        assert!(sql.contains("GROUP BY template, level"));
+        assert!(sql.trim_end().ends_with(&format!("LIMIT {}", MAX_ROWS + 1)));
+        assert!(!range_filter(100, 200, None).contains("service ="));
+    }
+}
@@ -1,35 +1,416 @@
 use axum::{
-    extract::{Query, State},
+    extract::{rejection::QueryRejection, Query, State},
     http::StatusCode,
     Json,
 };
-use qdrant_client::qdrant::{Condition, Filter, Range, SearchPointsBuilder};
+use logai_core::LogLevel;
+use logai_rag::{highlight_ranges, QueryIntent};
+use qdrant_client::qdrant::{
+    Condition, Direction, Filter, OrderByBuilder, Range, RetrievedPoint, ScrollPointsBuilder,
+    SearchPointsBuilder,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::info;
 
-use crate::handlers::get_string;
-use crate::models::{AskQuery, AskResponse, CausalChainResponse, QueryAnalysisResponse, SearchQuery, SearchResult};
-use crate::state::{AppState, COLLECTION_NAME};
+use crate::handlers::{
+    above_min_score, bad_query, embed_query, field_condition, get_string, handle_trace_query, log_collections,
+    min_score, parse_filters, scroll_collections, search_collections, sql_string, strict_conditions, FieldFilter,
+    SourceIndex,
+};
+use crate::models::{
+    ApiError, AskQuery, AskResponse, CausalChainResponse, HitContext, QueryAnalysisResponse, RecentLogRow, SearchMode,
+    SearchQuery, SearchResult,
+};
+use crate::state::AppState;
+
+// Reciprocal Rank Fusion damping: with 60 (the usual value) rank 1 and rank 5 score close,
+// so a log both searches found beats one only a single search ranked first
+const RRF_K: f32 = 60.0;
+// at most this many words of the query become keyword conditions
+const MAX_KEYWORD_TERMS: usize = 10;
+// largest `context=N`: logs on each side of each hit
+const MAX_CONTEXT: u32 = 10;
+
+#[derive(Deserialize, clickhouse::Row)]
+struct KeywordRow {
+    log_id: String,
+    service: String,
+    level: String,
+    message: String,
+    timestamp_ms: i64,
+}
+
+// a log around one of the hits: `hit` is its index in the results, `side` "before" or "after"
+#[derive(Deserialize, clickhouse::Row)]
+struct ContextRow {
+    hit: u32,
+    side: String,
+    log_id: String,
+    service: String,
+    level: String,
+    message: String,
+    timestamp: String,
+}
 
 pub async fn search_logs(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<SearchQuery>,
-) -> Result<Json<Vec<SearchResult>>, (StatusCode, String)> {
-    info!(query = %params.q, limit = params.limit, "Search request");
-
-    let query_vector = {
-        let mut model = state.model.lock().unwrap();
-        let embeddings = model
-            .embed(vec![params.q.clone()], None)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-        embeddings.into_iter().next().ok_or((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "No embedding".to_string(),
-        ))?
+    query: Result<Query<SearchQuery>, QueryRejection>,
+) -> Result<Json<Vec<SearchResult>>, (StatusCode, Json<ApiError>)> {
+    let Query(params) = query.map_err(bad_query)?;
+    info!(
+        query = %params.q,
+        limit = params.limit,
+        level = ?params.level,
+        filters = ?params.filters,
+        mode = ?params.mode,
+        context = params.context,
+        "Search request"
+    );
+    if params.context > MAX_CONTEXT {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("context must be at most {}", MAX_CONTEXT),
+        ));
+    }
+
+    let level = match params.level.as_deref() {
+        Some(level) => Some(
+            LogLevel::from_str(level)
+                .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Unknown log level"))?,
+        ),
+        None => None,
+    };
+    let filters = parse_filters(params.filters.as_deref().unwrap_or(""))
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    // `q=error` asks for error logs, embedding the word itself only adds noise
+    let mut search_results = if let Some(bare) = bare_level(&params.q) {
+        recent_by_level(&state, &params, level.unwrap_or(bare), &filters).await?
+    } else {
+        let query_vector = embed_query(&state, &params.q)?;
+        match params.mode {
+            SearchMode::Semantic => vector_search(&state, &params, level, &filters, query_vector, params.limit).await?,
+            SearchMode::Hybrid => hybrid_search(&state, &params, level, &filters, query_vector).await?,
+        }
+    };
+
+    if params.highlight {
+        add_highlights(&params.q, &mut search_results);
+    }
+    if params.context > 0 {
+        add_context(&state, &mut search_results, params.context).await?;
+    }
+    info!(results = search_results.len(), "Search Complete");
+    Ok(Json(search_results))
+}
+
+fn add_highlights(query: &str, results: &mut [SearchResult]) {
+    for result in results {
+        result.highlights = Some(highlight_ranges(query, &result.message));
+    }
+}
+
+async fn add_context(
+    state: &AppState,
+    results: &mut [SearchResult],
+    n: u32,
+) -> Result<(), (StatusCode, Json<ApiError>)> {
+    let Some(query) = context_query(results, n) else {
+        return Ok(());
+    };
+    let rows: Vec<ContextRow> = state
+        .clickhouse
+        .query(&query)
+        .fetch_all()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    for result in results.iter_mut() {
+        result.context = Some(HitContext { before: Vec::new(), after: Vec::new() });
+    }
+    for row in rows {
+        let Some(context) = results.get_mut(row.hit as usize).and_then(|r| r.context.as_mut()) else {
+            continue;
+        };
+        let side = if row.side == "before" { &mut context.before } else { &mut context.after };
+        side.push(RecentLogRow {
+            log_id: row.log_id,
+            service: row.service,
+            level: row.level,
+            message: row.message,
+            timestamp: row.timestamp,
+        });
+    }
+    // the before side was read newest first
+    for context in results.iter_mut().filter_map(|r| r.context.as_mut()) {
+        for side in [&mut context.before, &mut context.after] {
+            side.sort_by(|a, b| (&a.timestamp, &a.log_id).cmp(&(&b.timestamp, &b.log_id)));
+        }
+    }
+    Ok(())
+}
+
+/// The `n` logs of each hit's service right before and right after it, as one UNION ALL
+/// (two branches per hit). Ties on the timestamp are broken by id, like the recent logs
+/// cursor, so nothing shows up on both sides. Hits without an id, service or readable
+/// timestamp get no context; None when that's all of them.
+fn context_query(hits: &[SearchResult], n: u32) -> Option<String> {
+    let mut branches = Vec::new();
+    for (i, hit) in hits.iter().enumerate() {
+        let Ok(at) = chrono::DateTime::parse_from_rfc3339(&hit.timestamp) else {
+            continue;
+        };
+        if hit.log_id.is_empty() || hit.service.is_empty() {
+            continue;
+        }
+        let position = format!(
+            "(fromUnixTimestamp64Milli(toInt64({})), {})",
+            at.timestamp_millis(),
+            sql_string(&hit.log_id)
+        );
+        for (side, op, order) in [("before", "<", "DESC"), ("after", ">", "ASC")] {
+            branches.push(format!(
+                "SELECT toUInt32({i}) AS hit, '{side}' AS side, toString(id) AS log_id, service, level, message,
+                        toString(timestamp) AS timestamp
+                 FROM (
+                     SELECT id, service, level, message, timestamp
+                     FROM logs
+                     WHERE service = {service} AND (timestamp, toString(id)) {op} {position}
+                     ORDER BY timestamp {order}, id {order}
+                     LIMIT {n}
+                 )",
+                service = sql_string(&hit.service),
+            ));
+        }
+    }
+    (!branches.is_empty()).then(|| branches.join("\nUNION ALL\n"))
+}
+
+async fn vector_search(
+    state: &AppState,
+    params: &SearchQuery,
+    level: Option<LogLevel>,
+    filters: &[FieldFilter],
+    query_vector: Vec<f32>,
+    limit: u64,
+) -> Result<Vec<SearchResult>, (StatusCode, Json<ApiError>)> {
+    let conditions = search_conditions(params, level, filters);
+    let filter = if conditions.is_empty() {
+        None
+    } else {
+        Some(Filter::must(conditions))
+    };
+
+    let collections = log_collections(state, params.service.as_deref()).await?;
+    let search = |collection: &str| {
+        let builder = SearchPointsBuilder::new(collection, query_vector.clone(), limit).with_payload(true);
+        match filter.clone() {
+            Some(f) => builder.filter(f),
+            None => builder,
+        }
+    };
+
+    let qdrant_start = Instant::now();
+    let results = search_collections(state, &collections, limit, search)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.metrics.qdrant_search_latency.observe(qdrant_start.elapsed());
+
+    Ok(results
+        .into_iter()
+        .map(|point| {
+            let payload = point.payload;
+            SearchResult {
+                score: point.score,
+                log_id: get_string(&payload, "log_id"),
+                service: get_string(&payload, "service"),
+                level: get_string(&payload, "level"),
+                message: get_string(&payload, "message"),
+                timestamp: get_string(&payload, "timestamp"),
+                highlights: None,
+                context: None,
+            }
+        })
+        .collect())
+}
+
+/// Qdrant and ClickHouse searched side by side, each for twice the limit, then fused with
+/// RRF; a log found by both appears once. Scores are RRF scores, not similarities.
+async fn hybrid_search(
+    state: &AppState,
+    params: &SearchQuery,
+    level: Option<LogLevel>,
+    filters: &[FieldFilter],
+    query_vector: Vec<f32>,
+) -> Result<Vec<SearchResult>, (StatusCode, Json<ApiError>)> {
+    let candidates = params.limit.saturating_mul(2);
+    let keyword = async {
+        let Some(query) = keyword_query(params, level, filters, candidates) else {
+            return Ok(Vec::new());
+        };
+        let rows: Vec<KeywordRow> = state
+            .clickhouse
+            .query(&query)
+            .fetch_all()
+            .await
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        Ok(rows.into_iter().map(keyword_result).collect::<Vec<_>>())
+    };
+    let (vector, keyword) = tokio::try_join!(
+        vector_search(state, params, level, filters, query_vector, candidates),
+        keyword
+    )?;
+    info!(vector = vector.len(), keyword = keyword.len(), "Hybrid candidates");
+
+    let ids = |results: &[SearchResult]| -> Vec<String> {
+        results.iter().map(|r| r.log_id.clone()).filter(|id| !id.is_empty()).collect()
     };
+    let fused = reciprocal_rank_fusion(&[ids(&vector), ids(&keyword)]);
+
+    // the vector hit wins when both have a log (its fields come from the same payload as semantic mode)
+    let mut by_id: HashMap<String, SearchResult> = HashMap::new();
+    for result in keyword.into_iter().chain(vector) {
+        by_id.insert(result.log_id.clone(), result);
+    }
+    Ok(fused
+        .into_iter()
+        .take(params.limit as usize)
+        .filter_map(|(id, score)| by_id.remove(&id).map(|result| SearchResult { score, ..result }))
+        .collect())
+}
+
+/// Logs whose message contains the query's words, most words matched first. Plain
+/// alphanumeric words use ClickHouse's token index (`hasTokenCaseInsensitive`), anything
+/// with punctuation (`ERR_CONN`, `/api/checkout`) an `ILIKE`. None when there are no words.
+fn keyword_query(params: &SearchQuery, level: Option<LogLevel>, filters: &[FieldFilter], limit: u64) -> Option<String> {
+    let mut terms: Vec<&str> = Vec::new();
+    for term in params.q.split_whitespace().map(|t| t.trim_matches(|c: char| !c.is_alphanumeric())) {
+        if !term.is_empty() && !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            terms.push(term);
+        }
+    }
+    if terms.is_empty() {
+        return None;
+    }
+    terms.truncate(MAX_KEYWORD_TERMS);
 
+    let hits = terms
+        .iter()
+        .map(|term| {
+            if term.chars().all(|c| c.is_ascii_alphanumeric()) {
+                format!("hasTokenCaseInsensitive(message, {})", sql_string(term))
+            } else {
+                let pattern = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+                format!("(message ILIKE {})", sql_string(&format!("%{}%", pattern)))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" + ");
+
+    let mut conditions = vec!["hits > 0".to_string()];
+    if let Some(from) = params.from {
+        conditions.push(format!("timestamp >= toDateTime64({}, 3)", from));
+    }
+    if let Some(to) = params.to {
+        conditions.push(format!("timestamp <= toDateTime64({}, 3)", to));
+    }
+    if let Some(ref service) = params.service {
+        conditions.push(format!("service = {}", sql_string(service)));
+    }
+    if let Some(level) = level {
+        conditions.push(format!("level = '{:?}'", level));
+    }
+    conditions.extend(filters.iter().map(field_condition));
+
+    Some(format!(
+        "WITH {} AS hits
+         SELECT toString(id) AS log_id, service, level, message,
+                toUnixTimestamp64Milli(timestamp) AS timestamp_ms
+         FROM logs
+         WHERE {}
+         ORDER BY hits DESC, timestamp DESC
+         LIMIT {}",
+        hits,
+        conditions.join(" AND "),
+        limit
+    ))
+}
+
+fn keyword_result(row: KeywordRow) -> SearchResult {
+    SearchResult {
+        score: 0.0,
+        log_id: row.log_id,
+        service: row.service,
+        level: row.level,
+        message: row.message,
+        // same format as the Qdrant payload's timestamp
+        timestamp: chrono::DateTime::from_timestamp_millis(row.timestamp_ms)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default(),
+        highlights: None,
+        context: None,
+    }
+}
+
+/// Reciprocal Rank Fusion: an id scores the sum of 1 / (RRF_K + rank) over the lists it is in
+/// (ranks start at 1). Highest score first; ties keep the order ids were first seen in.
+fn reciprocal_rank_fusion(lists: &[Vec<String>]) -> Vec<(String, f32)> {
+    let mut fused: Vec<(String, f32)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for list in lists {
+        let mut seen = HashSet::new();
+        for (rank, id) in list.iter().enumerate() {
+            // a repeat within one list doesn't count twice
+            if !seen.insert(id.as_str()) {
+                continue;
+            }
+            let score = 1.0 / (RRF_K + rank as f32 + 1.0);
+            match index.get(id.as_str()) {
+                Some(&i) => fused[i].1 += score,
+                None => {
+                    index.insert(id, fused.len());
+                    fused.push((id.clone(), score));
+                }
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+}
+
+/// Filtered scroll, newest first (no embedding, no similarity score)
+async fn recent_by_level(
+    state: &AppState,
+    params: &SearchQuery,
+    level: LogLevel,
+    filters: &[FieldFilter],
+) -> Result<Vec<SearchResult>, (StatusCode, Json<ApiError>)> {
+    let limit = params.limit.min(u32::MAX as u64) as u32;
+    let conditions = search_conditions(params, Some(level), filters);
+    let scroll = |collection: &str| {
+        ScrollPointsBuilder::new(collection)
+            .filter(Filter::must(conditions.clone()))
+            .order_by(OrderByBuilder::new("timestamp_unix").direction(Direction::Desc as i32))
+            .limit(limit)
+            .with_payload(true)
+    };
+
+    let collections = log_collections(state, params.service.as_deref()).await?;
+    let qdrant_start = Instant::now();
+    let results = scroll_collections(state, &collections, limit, scroll)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.metrics.qdrant_search_latency.observe(qdrant_start.elapsed());
+
+    let search_results = level_results(results, level);
+    info!(results = search_results.len(), level = ?level, "Level search complete");
+    Ok(search_results)
+}
+
+fn search_conditions(params: &SearchQuery, level: Option<LogLevel>, filters: &[FieldFilter]) -> Vec<Condition> {
     let mut conditions = vec![];
 
     if let Some(from) = params.from {
@@ -53,77 +434,114 @@ pub async fn search_logs(
     if let Some(ref service) = params.service {
         conditions.push(Condition::matches("service", service.clone()));
     }
-
-    let filter = if conditions.is_empty() {
-        None
-    } else {
-        Some(Filter::must(conditions))
-    };
-
-    let mut search_builder =
-        SearchPointsBuilder::new(COLLECTION_NAME, query_vector, params.limit).with_payload(true);
-
-    if let Some(f) = filter {
-        search_builder = search_builder.filter(f);
+    if let Some(level) = level {
+        // the worker stores levels as "Error", "Warn", ...
+        conditions.push(Condition::matches("level", format!("{:?}", level)));
+    }
+    // the worker copies allowlisted fields into payload.fields as strings (WORKER_PAYLOAD_FIELDS)
+    for (field, value) in filters {
+        conditions.push(Condition::matches(format!("fields.{}", field), value.clone()));
     }
+    conditions
+}
 
-    let results = state
-        .qdrant
-        .search_points(search_builder)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+// "error", "errors", "WARNINGS" - a query that is nothing but a level
+fn bare_level(q: &str) -> Option<LogLevel> {
+    let q = q.trim();
+    LogLevel::from_str(q).or_else(|| LogLevel::from_str(q.strip_suffix(['s', 'S'])?))
+}
 
-    let search_results: Vec<SearchResult> = results
-        .result
+// Scroll results as search results; points with another level never slip through
+fn level_results(points: Vec<RetrievedPoint>, level: LogLevel) -> Vec<SearchResult> {
+    let level = format!("{:?}", level);
+    points
         .into_iter()
         .map(|point| {
             let payload = point.payload;
             SearchResult {
-                score: point.score,
+                score: 1.0,
                 log_id: get_string(&payload, "log_id"),
                 service: get_string(&payload, "service"),
                 level: get_string(&payload, "level"),
                 message: get_string(&payload, "message"),
                 timestamp: get_string(&payload, "timestamp"),
+                highlights: None,
+                context: None,
             }
         })
-        .collect();
-
-    info!(results = search_results.len(), "Search Complete");
-    Ok(Json(search_results))
+        .filter(|r| r.level == level)
+        .collect()
 }
 
 pub async fn ask_logs(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<AskQuery>,
-) -> Result<Json<AskResponse>, (StatusCode, String)> {
+    query: Result<Query<AskQuery>, QueryRejection>,
+) -> Result<Json<AskResponse>, (StatusCode, Json<ApiError>)> {
+    let Query(params) = query.map_err(bad_query)?;
+    let min_score = min_score(params.min_score, state.min_score)?;
     let start = Instant::now();
-    info!(query = %params.q, "ASK request");
+    info!(query = %params.q, from = ?params.from, to = ?params.to, "ASK request");
 
     let analyzed = state.rag_engine.analyze_query(&params.q);
 
-    let query_vector = {
-        let mut model = state.model.lock().unwrap();
-        let embeddings = model
-            .embed(vec![analyzed.search_query.clone()], None)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-        embeddings.into_iter().next().ok_or((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "No embedding".to_string(),
-        ))?
-    };
+    // Trace questions with a concrete id skip semantic search and the LLM entirely
+    if let (QueryIntent::Trace, Some(trace_id)) = (&analyzed.intent, &analyzed.trace_id) {
+        let trace = handle_trace_query(&state, trace_id).await?;
+        if !trace.spans.is_empty() {
+            let elapsed = start.elapsed().as_millis();
+            info!(trace_id = %trace_id, spans = trace.spans.len(), time_ms = elapsed, "ASK answered from trace");
+            return Ok(Json(AskResponse {
+                answer: trace.summary(),
+                sources_count: trace.spans.len(),
+                response_time_ms: elapsed,
+                provider: "trace".to_string(),
+                query_analysis: QueryAnalysisResponse {
+                    search_query: analyzed.search_query,
+                    time_filter: None,
+                    service_filter: analyzed.service,
+                },
+                sources: Vec::new(),
+                causal_chain: None,
+                trace: Some(trace),
+            }));
+        }
+    }
+
+    let query_vector = embed_query(&state, &analyzed.search_query)?;
 
     let mut conditions = vec![];
-    if let Some(from) = analyzed.from {
+    // an explicit from/to wins over a window read from the question
+    if let Some(from) = params.from.or(analyzed.from.map(|t| t.timestamp())) {
         conditions.push(Condition::range(
             "timestamp_unix",
             Range {
-                gte: Some(from.timestamp() as f64),
+                gte: Some(from as f64),
                 ..Default::default()
             },
         ));
     }
-    // Note: service/level filters removed - semantic search handles relevance
+    if let Some(to) = params.to {
+        conditions.push(Condition::range(
+            "timestamp_unix",
+            Range {
+                lte: Some(to as f64),
+                ..Default::default()
+            },
+        ));
+    } else if let Some(to) = analyzed.to {
+        // "yesterday" ends at local midnight
+        conditions.push(Condition::range(
+            "timestamp_unix",
+            Range {
+                lt: Some(to.timestamp() as f64),
+                ..Default::default()
+            },
+        ));
+    }
+    // service/level are left to semantic search unless the caller wants them enforced
+    if params.strict_filters {
+        conditions.extend(strict_conditions(&analyzed));
+    }
 
     let filter = if conditions.is_empty() {
         None
@@ -131,53 +549,60 @@ pub async fn ask_logs(
         Some(Filter::must(conditions))
     };
 
-    let mut search_builder =
-        SearchPointsBuilder::new(COLLECTION_NAME, query_vector, 30).with_payload(true);
-    if let Some(f) = filter {
-        search_builder = search_builder.filter(f);
-    }
+    let width = state.retrieval.ask_search_width;
+    let search = |collection: &str| {
+        let builder = SearchPointsBuilder::new(collection, query_vector.clone(), width).with_payload(true);
+        match filter.clone() {
+            Some(f) => builder.filter(f),
+            None => builder,
+        }
+    };
 
-    let results = state
-        .qdrant
-        .search_points(search_builder)
+    let collections = log_collections(&state, analyzed.service.as_deref()).await?;
+    let qdrant_start = Instant::now();
+    let results = search_collections(&state, &collections, width, search)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.metrics.qdrant_search_latency.observe(qdrant_start.elapsed());
 
     // Build JSON log strings with full metadata for causal analysis
+    let mut sources = SourceIndex::default();
     let logs_with_scores: Vec<(String, f32)> = results
-        .result
         .iter()
-        .map(|point| {
-            let payload = &point.payload;
-            let log_json = serde_json::json!({
-                "timestamp": get_string(payload, "timestamp"),
-                "level": get_string(payload, "level"),
-                "service": get_string(payload, "service"),
-                "message": get_string(payload, "message"),
-            });
-            (log_json.to_string(), point.score)
-        })
+        .map(|point| (sources.line(&point.payload), point.score))
         .collect();
 
-    info!(logs_found = logs_with_scores.len(), "Logs retrieved from Qdrant");
+    let found = logs_with_scores.len();
+    let logs_with_scores = above_min_score(logs_with_scores, min_score, state.scores_are_distances);
+    info!(logs_found = found, relevant = logs_with_scores.len(), min_score, "Logs retrieved from Qdrant");
 
     if logs_with_scores.is_empty() {
-        return Err((StatusCode::NOT_FOUND, "No relevant logs found".to_string()));
+        return Err(no_relevant_logs());
     }
 
-    let reranked = state.reranker.rerank(&params.q, logs_with_scores, 10);
-    let logs: Vec<String> = reranked.into_iter().map(|r| r.message).collect();
+    let reranked = state.reranker.rerank(&params.q, logs_with_scores, state.retrieval.ask_rerank_k);
+    let logs: Vec<String> = reranked.iter().map(|r| r.context_line()).collect();
+    let sources = sources.sources(&logs);
 
     info!(reranked_count = logs.len(), "Logs reranked");
 
+    let llm_start = Instant::now();
     let rag_response = state
         .rag_engine
         .query(&params.q, logs)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.metrics.observe_llm(llm_start.elapsed());
 
     let elapsed = start.elapsed().as_millis();
-    info!(sources = rag_response.sources_count, provider = %rag_response.provider, time_ms = elapsed, "ASK complete");
+    info!(
+        sources = rag_response.sources_count,
+        in_context = rag_response.context_logs,
+        dropped = rag_response.dropped_logs,
+        provider = %rag_response.provider,
+        time_ms = elapsed,
+        "ASK complete"
+    );
 
     Ok(Json(AskResponse {
         answer: rag_response.answer,
@@ -189,6 +614,310 @@ pub async fn ask_logs(
             time_filter: rag_response.query_analysis.time_filter,
             service_filter: rag_response.query_analysis.service_filter,
         },
+        sources,
         causal_chain: rag_response.causal_chain.map(CausalChainResponse::from),
+        trace: None,
     }))
 }
+
+fn no_relevant_logs() -> (StatusCode, Json<ApiError>) {
+    ApiError::not_found("No relevant logs found")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use logai_rag::QueryAnalyzer;
+
+    #[tokio::test]
+    async fn test_ask_not_found_is_json() {
+        let response = no_relevant_logs().into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], 404);
+        assert_eq!(json["error"], "No relevant logs found");
+    }
+
+    #[test]
+    fn test_hits_below_min_score_dropped() {
+        let hits = vec![
+            ("payment timeout".to_string(), 0.82),
+            ("cache warmed".to_string(), 0.29),
+            ("payment retry".to_string(), 0.3),
+        ];
+        let kept: Vec<String> = above_min_score(hits.clone(), 0.3, false).into_iter().map(|(log, _)| log).collect();
+        assert_eq!(kept, ["payment timeout", "payment retry"]);
+        assert!(above_min_score(hits.clone(), 0.9, false).is_empty());
+        // Euclid distances: lower is closer, the floor is skipped
+        assert_eq!(above_min_score(hits.clone(), 0.9, true), hits);
+
+        // a request's own threshold wins; out-of-range ones are a 400
+        assert_eq!(min_score(None, 0.3).ok(), Some(0.3));
+        assert_eq!(min_score(Some(0.5), 0.3).ok(), Some(0.5));
+        let Err((status, _)) = min_score(Some(1.5), 0.3) else { panic!("1.5 is out of range") };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_bare_level_queries() {
+        assert_eq!(bare_level("error"), Some(LogLevel::Error));
+        assert_eq!(bare_level(" Errors "), Some(LogLevel::Error));
+        assert_eq!(bare_level("warnings"), Some(LogLevel::Warn));
+        assert_eq!(bare_level("database errors"), None);
+        assert_eq!(bare_level("timeout"), None);
+    }
+
+    #[test]
+    fn test_level_error_returns_only_errors() {
+        let point = |level: &str, message: &str| RetrievedPoint {
+            payload: [
+                ("level".to_string(), level.into()),
+                ("message".to_string(), message.into()),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let points = vec![
+            point("Error", "db connection refused"),
+            point("Info", "request ok"),
+            point("Error", "payment timeout"),
+            point("Warn", "slow query"),
+        ];
+
+        let results = level_results(points, bare_level("error").unwrap());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.level == "Error"));
+        assert_eq!(results[0].message, "db connection refused");
+    }
+
+    // in-memory stand-in for Qdrant: every keyword match condition must hold
+    fn matches_all(point: &RetrievedPoint, conditions: &[Condition]) -> bool {
+        use qdrant_client::qdrant::{condition::ConditionOneOf, r#match::MatchValue};
+
+        conditions.iter().all(|condition| match &condition.condition_one_of {
+            Some(ConditionOneOf::Field(field)) => {
+                let expected = match field.r#match.as_ref().and_then(|m| m.match_value.as_ref()) {
+                    Some(MatchValue::Keyword(keyword)) => keyword,
+                    _ => return false,
+                };
+                let mut path = field.key.split('.');
+                let top = path.next().and_then(|key| point.payload.get(key));
+                let value = path.fold(top, |value, key| {
+                    value
+                        .and_then(|v| v.as_struct())
+                        .and_then(|s| s.fields.get(key))
+                });
+                value.and_then(|v| v.as_str()) == Some(expected)
+            }
+            _ => false,
+        })
+    }
+
+    #[test]
+    fn test_filter_by_error_code() {
+        let point = |message: &str, error_code: &str| RetrievedPoint {
+            payload: qdrant_client::Payload::try_from(serde_json::json!({
+                "level": "Error",
+                "message": message,
+                "fields": { "error_code": error_code, "status_code": "500" },
+            }))
+            .unwrap()
+            .into(),
+            ..Default::default()
+        };
+        let points = [
+            point("card declined", "PAYMENT_FAILED"),
+            point("inventory lookup timed out", "UPSTREAM_TIMEOUT"),
+            point("3DS challenge failed", "PAYMENT_FAILED"),
+        ];
+
+        let params = SearchQuery {
+            q: "checkout failures".to_string(),
+            limit: 10,
+            from: None,
+            to: None,
+            service: None,
+            level: None,
+            filters: Some("error_code:PAYMENT_FAILED".to_string()),
+            mode: SearchMode::Semantic,
+            highlight: false,
+            context: 0,
+        };
+        let filters = parse_filters(params.filters.as_deref().unwrap()).unwrap();
+        let conditions = search_conditions(&params, None, &filters);
+
+        let messages: Vec<String> = points
+            .iter()
+            .filter(|p| matches_all(p, &conditions))
+            .map(|p| get_string(&p.payload, "message"))
+            .collect();
+        assert_eq!(messages, vec!["card declined", "3DS challenge failed"]);
+    }
+
+    #[test]
+    fn test_strict_filters_keep_only_error_points() {
+        let point = |level: &str, message: &str| RetrievedPoint {
+            payload: qdrant_client::Payload::try_from(serde_json::json!({
+                "level": level,
+                "service": "payment",
+                "message": message,
+            }))
+            .unwrap()
+            .into(),
+            ..Default::default()
+        };
+        let points = [
+            point("Error", "card declined"),
+            point("Info", "payment accepted"),
+            point("Error", "gateway timeout"),
+            point("Warn", "retrying charge"),
+        ];
+        let analyzed = QueryAnalyzer::new().analyze("errors in payment last hour");
+        let messages = |conditions: &[Condition]| -> Vec<String> {
+            points
+                .iter()
+                .filter(|p| matches_all(p, conditions))
+                .map(|p| get_string(&p.payload, "message"))
+                .collect()
+        };
+
+        let conditions = strict_conditions(&analyzed);
+        assert_eq!(messages(&conditions), ["card declined", "gateway timeout"]);
+        // without strict_filters nothing is added, so every point stays a candidate
+        assert_eq!(messages(&[]).len(), 4);
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        assert_eq!(
+            parse_filters("status_code:500, endpoint:/api/checkout").unwrap(),
+            vec![
+                ("status_code".to_string(), "500".to_string()),
+                ("endpoint".to_string(), "/api/checkout".to_string()),
+            ]
+        );
+        assert_eq!(parse_filters("").unwrap(), vec![]);
+        assert!(parse_filters("status_code").is_err());
+        assert!(parse_filters("status_code:").is_err());
+        assert!(parse_filters("a.b:1").is_err());
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let vector = ids(&["a", "b", "c", "a"]);
+        let keyword = ids(&["c", "d", "b"]);
+
+        let fused = reciprocal_rank_fusion(&[vector, keyword]);
+        let order: Vec<&str> = fused.iter().map(|(id, _)| id.as_str()).collect();
+        // c: 1/63 + 1/61, b: 1/62 + 1/63, a: 1/61 (its repeat ignored), d: 1/62
+        assert_eq!(order, vec!["c", "b", "a", "d"]);
+
+        let score = |id: &str| fused.iter().find(|(i, _)| i == id).unwrap().1;
+        assert!((score("c") - (1.0 / 63.0 + 1.0 / 61.0)).abs() < 1e-6);
+        assert!((score("b") - (1.0 / 62.0 + 1.0 / 63.0)).abs() < 1e-6);
+        assert!((score("a") - 1.0 / 61.0).abs() < 1e-6);
+        assert!((score("d") - 1.0 / 62.0).abs() < 1e-6);
+
+        assert!(reciprocal_rank_fusion(&[vec![], vec![]]).is_empty());
+    }
+
+    #[test]
+    fn test_highlights_only_when_asked() {
+        let result = |message: &str| SearchResult {
+            score: 0.9,
+            log_id: "0001".to_string(),
+            service: "checkout".to_string(),
+            level: "Error".to_string(),
+            message: message.to_string(),
+            timestamp: "2026-02-10T03:00:00+00:00".to_string(),
+            highlights: None,
+            context: None,
+        };
+
+        let plain = serde_json::to_value(result("db timeout")).unwrap();
+        assert!(plain.get("highlights").is_none());
+
+        let mut results = vec![result("DB timeout on checkout"), result("disk full")];
+        add_highlights("checkout timeout", &mut results);
+        let json = serde_json::to_value(&results).unwrap();
+        assert_eq!(json[0]["highlights"], serde_json::json!([[3, 10], [14, 22]]));
+        assert_eq!(json[1]["highlights"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_keyword_query() {
+        let params = SearchQuery {
+            q: "Timeout ERR_CONN timeout 50%_off".to_string(),
+            limit: 5,
+            from: Some(1770692400),
+            to: None,
+            service: Some("check'out".to_string()),
+            level: None,
+            filters: None,
+            mode: SearchMode::Hybrid,
+            highlight: false,
+            context: 0,
+        };
+
+        let query = keyword_query(&params, Some(LogLevel::Error), &[], 10).unwrap();
+        // repeated words count once; punctuation goes through ILIKE with LIKE wildcards escaped
+        assert!(query.contains(concat!(
+            "WITH hasTokenCaseInsensitive(message, 'Timeout')",
+            r" + (message ILIKE '%ERR\\_CONN%')",
+            r" + (message ILIKE '%50\\%\\_off%') AS hits"
+        )));
+        assert!(query.contains("timestamp >= toDateTime64(1770692400, 3)"));
+        assert!(query.contains(r"service = 'check\'out'"));
+        assert!(query.contains("level = 'Error'"));
+        assert!(query.contains("LIMIT 10"));
+
+        let punctuation_only = SearchQuery { q: " -- ".to_string(), ..params };
+        assert!(keyword_query(&punctuation_only, None, &[], 10).is_none());
+    }
+
+    #[test]
+    fn test_context_query() {
+        let hit = |log_id: &str, service: &str, timestamp: &str| SearchResult {
+            score: 0.9,
+            log_id: log_id.to_string(),
+            service: service.to_string(),
+            level: "Error".to_string(),
+            message: "payment timeout".to_string(),
+            timestamp: timestamp.to_string(),
+            highlights: None,
+            context: None,
+        };
+        let hits = [
+            hit("0001", "check'out", "2026-02-10T03:00:00.412+00:00"),
+            // from a chunk summary or an old payload: nothing to anchor a window on
+            hit("0002", "checkout", "yesterday"),
+            hit("0003", "auth", "2026-02-10T03:00:05+00:00"),
+        ];
+
+        let query = context_query(&hits, 3).unwrap();
+        let branches: Vec<&str> = query.split("\nUNION ALL\n").collect();
+        assert_eq!(branches.len(), 4);
+
+        let (before, after) = (branches[0], branches[1]);
+        assert!(before.contains("toUInt32(0) AS hit, 'before' AS side"));
+        assert!(before.contains(r"service = 'check\'out'"));
+        assert!(before.contains("(timestamp, toString(id)) < (fromUnixTimestamp64Milli(toInt64(1770692400412)), '0001')"));
+        assert!(before.contains("ORDER BY timestamp DESC, id DESC"));
+        assert!(after.contains("'after' AS side"));
+        assert!(after.contains("(timestamp, toString(id)) > (fromUnixTimestamp64Milli(toInt64(1770692400412)), '0001')"));
+        assert!(after.contains("ORDER BY timestamp ASC, id ASC"));
+        assert!(after.contains("LIMIT 3"));
+        // the third hit keeps its index in the results
+        assert!(branches[2].contains("toUInt32(2) AS hit") && branches[2].contains("service = 'auth'"));
+
+        assert!(context_query(&hits[1..2], 3).is_none());
+    }
+}
@@ -0,0 +1,239 @@
+// GET /api/health/services: one 0-100 health score per service over the last 15 minutes,
+// for dashboards. Built from the same level counts as /api/anomalies' level distribution.
+//
+// Each signal is turned into a penalty between 0 and 1:
+//   error rate  (error + fatal) / total, full penalty at 25%
+//   volume      |current - expected| / expected once it's off by half or more, capped at 1;
+//               expected is the rest of the hour scaled to 15 minutes (needs 20+ expected logs)
+//   fatal       fatal logs / 3, capped at 1
+// score = 100 * (1 - sum(weight * penalty) / sum(weight)), weights from `HealthWeights`.
+// 80+ is healthy, 50-79 degraded, below that critical. A service with no logs at all in the
+// window, where the baseline expects some, is down: score 0 (unless volume is weighted 0).
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::handlers::{level_counts_query, LevelRow};
+use crate::models::{ServiceHealth, ServiceHealthQuery, ServiceHealthResponse};
+use crate::state::{AppState, HealthWeights};
+
+const WINDOW_MINUTES: u64 = 15;
+// services in the response, the worst ones
+const MAX_SERVICES: usize = 200;
+// error share of the window at which the error penalty is full
+const ERROR_RATE_CRITICAL: f64 = 0.25;
+// relative change in volume below which it isn't a penalty at all
+const VOLUME_TOLERANCE: f64 = 0.5;
+// fewer expected logs than this is too little baseline to judge volume by
+const VOLUME_MIN_EXPECTED: f64 = 20.0;
+// fatal logs at which the fatal penalty is full
+const FATAL_CRITICAL: u64 = 3;
+
+/// What a service's score is computed from
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HealthInputs {
+    pub total: u64,
+    pub errors: u64,
+    pub fatal: u64,
+    /// Logs a normal window would have, from the baseline
+    pub expected: f64,
+}
+
+impl HealthInputs {
+    fn from_row(row: &LevelRow) -> Self {
+        let baseline_minutes = 60 - WINDOW_MINUTES;
+        Self {
+            total: row.total,
+            errors: row.error + row.fatal,
+            fatal: row.fatal,
+            expected: row.baseline_total as f64 * WINDOW_MINUTES as f64 / baseline_minutes as f64,
+        }
+    }
+}
+
+pub async fn get_service_health(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ServiceHealthQuery>,
+) -> Result<Json<ServiceHealthResponse>, (StatusCode, String)> {
+    info!(service = ?params.service, "Service health request");
+
+    let rows: Vec<LevelRow> = state
+        .clickhouse
+        // every service, so the cut below keeps the worst scores rather than the first names
+        .query(&level_counts_query(params.service.as_deref(), WINDOW_MINUTES, None))
+        .fetch_all()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut services: Vec<ServiceHealth> = rows
+        .iter()
+        .map(|row| health_score(&row.service, &HealthInputs::from_row(row), &state.health_weights))
+        .collect();
+    // worst first, that's what the dashboard is for
+    services.sort_by(|a, b| a.score.cmp(&b.score).then_with(|| a.service.cmp(&b.service)));
+    services.truncate(MAX_SERVICES);
+
+    Ok(Json(ServiceHealthResponse {
+        services,
+        window_minutes: WINDOW_MINUTES,
+        checked_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    }))
+}
+
+/// Score one service (see the formula at the top of this file)
+pub fn health_score(service: &str, inputs: &HealthInputs, weights: &HealthWeights) -> ServiceHealth {
+    // silence isn't a volume dip, nothing is getting through
+    if inputs.total == 0 && inputs.expected >= VOLUME_MIN_EXPECTED && weights.volume > 0.0 {
+        return ServiceHealth {
+            service: service.to_string(),
+            score: 0,
+            status: "critical".to_string(),
+            reasons: vec![format!(
+                "no logs in the last {} minutes (~{:.0} expected)",
+                WINDOW_MINUTES, inputs.expected
+            )],
+        };
+    }
+
+    let mut penalties: Vec<(f64, f64, String)> = Vec::new();
+
+    if inputs.total > 0 && inputs.errors > 0 {
+        let rate = inputs.errors as f64 / inputs.total as f64;
+        penalties.push((
+            weights.error_rate,
+            (rate / ERROR_RATE_CRITICAL).min(1.0),
+            format!("error rate {:.1}% ({} of {} logs)", rate * 100.0, inputs.errors, inputs.total),
+        ));
+    }
+    if inputs.expected >= VOLUME_MIN_EXPECTED {
+        let change = (inputs.total as f64 - inputs.expected) / inputs.expected;
+        if change.abs() >= VOLUME_TOLERANCE {
+            let direction = if change < 0.0 { "below" } else { "above" };
+            penalties.push((
+                weights.volume,
+                change.abs().min(1.0),
+                format!(
+                    "volume {:.0}% {} baseline ({} logs, ~{:.0} expected)",
+                    change.abs() * 100.0,
+                    direction,
+                    inputs.total,
+                    inputs.expected
+                ),
+            ));
+        }
+    }
+    if inputs.fatal > 0 {
+        penalties.push((
+            weights.fatal,
+            (inputs.fatal as f64 / FATAL_CRITICAL as f64).min(1.0),
+            format!("{} fatal log{}", inputs.fatal, if inputs.fatal == 1 { "" } else { "s" }),
+        ));
+    }
+
+    let total_weight = weights.error_rate + weights.volume + weights.fatal;
+    let lost = if total_weight > 0.0 {
+        penalties.iter().map(|(weight, penalty, _)| weight * penalty).sum::<f64>() / total_weight
+    } else {
+        0.0
+    };
+    let score = (100.0 * (1.0 - lost)).round().clamp(0.0, 100.0) as u8;
+    let status = match score {
+        80.. => "healthy",
+        50..=79 => "degraded",
+        _ => "critical",
+    };
+
+    // a signal weighted 0 doesn't explain anything
+    penalties.retain(|(weight, _, _)| *weight > 0.0);
+    penalties.sort_by(|a, b| (b.0 * b.1).total_cmp(&(a.0 * a.1)));
+
+    ServiceHealth {
+        service: service.to_string(),
+        score,
+        status: status.to_string(),
+        reasons: penalties.into_iter().map(|(_, _, reason)| reason).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(total: u64, errors: u64, fatal: u64, expected: f64) -> ServiceHealth {
+        let inputs = HealthInputs { total, errors, fatal, expected };
+        health_score("checkout", &inputs, &HealthWeights::default())
+    }
+
+    #[test]
+    fn test_health_score() {
+        let quiet = score(300, 0, 0, 280.0);
+        assert_eq!((quiet.score, quiet.status.as_str()), (100, "healthy"));
+        assert!(quiet.reasons.is_empty());
+
+        // 10% errors: 0.4 of the error weight (50 of 100) = -20
+        let erroring = score(300, 30, 0, 300.0);
+        assert_eq!((erroring.score, erroring.status.as_str()), (80, "healthy"));
+        assert_eq!(erroring.reasons, vec!["error rate 10.0% (30 of 300 logs)"]);
+
+        // volume down 80% (-20) and 2 fatal errors (2/3 of 25 = -16.7) on top of 25% errors (-50)
+        let down = score(60, 15, 2, 300.0);
+        assert_eq!((down.score, down.status.as_str()), (13, "critical"));
+        assert_eq!(
+            down.reasons,
+            vec![
+                "error rate 25.0% (15 of 60 logs)",
+                "volume 80% below baseline (60 logs, ~300 expected)",
+                "2 fatal logs",
+            ]
+        );
+
+        // within tolerance, or too little baseline to judge
+        assert_eq!(score(400, 0, 0, 300.0).score, 100);
+        assert_eq!(score(0, 0, 0, 10.0).score, 100);
+        let doubled = score(600, 0, 0, 300.0);
+        assert_eq!((doubled.score, doubled.status.as_str()), (75, "degraded"));
+
+        let silent = score(0, 0, 0, 300.0);
+        assert_eq!((silent.score, silent.status.as_str()), (0, "critical"));
+        assert_eq!(silent.reasons, vec!["no logs in the last 15 minutes (~300 expected)"]);
+        let volume_ignored = HealthWeights { volume: 0.0, ..HealthWeights::default() };
+        let inputs = HealthInputs { expected: 300.0, ..HealthInputs::default() };
+        assert_eq!(health_score("checkout", &inputs, &volume_ignored).score, 100);
+    }
+
+    #[test]
+    fn test_custom_weights() {
+        let inputs = HealthInputs { total: 100, errors: 5, fatal: 3, expected: 100.0 };
+        let fatal_only = HealthWeights { error_rate: 0.0, volume: 0.0, fatal: 1.0 };
+        let health = health_score("payments", &inputs, &fatal_only);
+        assert_eq!((health.score, health.status.as_str()), (0, "critical"));
+        assert_eq!(health.reasons, vec!["3 fatal logs"]);
+
+        let ignore_all = HealthWeights { error_rate: 0.0, volume: 0.0, fatal: 0.0 };
+        assert_eq!(health_score("payments", &inputs, &ignore_all).score, 100);
+    }
+
+    #[test]
+    fn test_expected_from_baseline() {
+        let row = LevelRow {
+            service: "checkout".to_string(),
+            total: 100,
+            info: 80,
+            warn: 10,
+            error: 8,
+            fatal: 2,
+            baseline_total: 450,
+            baseline_warn: 30,
+            baseline_error: 12,
+        };
+        let inputs = HealthInputs::from_row(&row);
+        assert_eq!((inputs.errors, inputs.fatal), (10, 2));
+        // 45 minutes of baseline scaled to the 15-minute window
+        assert_eq!(inputs.expected, 150.0);
+    }
+}
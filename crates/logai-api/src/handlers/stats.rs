@@ -1,13 +1,41 @@
 use axum::{
-    extract::{Query, State},
+    extract::{rejection::QueryRejection, Query, State},
     http::StatusCode,
     Json,
 };
+use serde::Deserialize;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::models::{RecentLogRow, RecentLogsQuery, StatsResponse};
-use crate::state::{AppState, COLLECTION_NAME};
+use crate::handlers::{bad_query, field_condition, log_collections, parse_filters};
+use crate::models::{
+    ApiError, EndpointLatency, RecentLogRow, RecentLogsQuery, RecentLogsResponse, ServiceVolume,
+    ServicesResponse, StatsResponse,
+};
+use crate::state::AppState;
+
+// how many endpoints the performance snapshot lists
+const SLOWEST_ENDPOINTS: usize = 5;
+// and how many services the volume ranking
+const TOP_SERVICES: usize = 5;
+
+// the worker's numeric copy of `fields` when it has one (it also covers "234" stored as a
+// string), else the JSON string; missing latencies come out as NULL and are skipped
+const LATENCY_EXPR: &str = "if(mapContains(numeric_fields, 'latency_ms'), numeric_fields['latency_ms'], \
+                            JSONExtract(fields, 'latency_ms', 'Nullable(Float64)'))";
+// simulated/app logs call it endpoint, access logs (nginx) path
+const ENDPOINT_EXPR: &str =
+    "coalesce(JSONExtract(fields, 'endpoint', 'Nullable(String)'), JSONExtract(fields, 'path', 'Nullable(String)'))";
+
+// quantile() over the Nullable LATENCY_EXPR is Nullable(Float64) too, so plain f64
+// fields would fail to decode
+#[derive(Deserialize, clickhouse::Row)]
+struct LatencyRow {
+    samples: u64,
+    p50: Option<f64>,
+    p95: Option<f64>,
+    p99: Option<f64>,
+}
 
 pub async fn get_stats(
     State(state): State<Arc<AppState>>,
@@ -38,13 +66,39 @@ pub async fn get_stats(
         .await
         .unwrap_or(0);
 
-    let embeddings_count = match state.qdrant.collection_info(COLLECTION_NAME).await {
-        Ok(info) => info.result.map(|r| r.points_count.unwrap_or(0)).unwrap_or(0),
-        Err(_) => 0,
-    };
+    // summed over every service's collection when embeddings are split per service
+    let mut embeddings_count = 0;
+    for collection in log_collections(&state, None).await.unwrap_or_default() {
+        if let Ok(info) = state.qdrant.collection_info(&collection).await {
+            embeddings_count += info.result.and_then(|r| r.points_count).unwrap_or(0);
+        }
+    }
 
     let storage_mb = (total_logs as f64 * 0.5) / 1024.0;
 
+    // quantiles of an empty set are NaN, so a zero sample count means "no latency data"
+    let latency = match state.clickhouse.query(&latency_quantiles_query()).fetch_one::<LatencyRow>().await {
+        Ok(row) if row.samples > 0 => Some(row),
+        Ok(_) => None,
+        Err(e) => {
+            warn!(error = %e, "Latency quantiles query failed");
+            None
+        }
+    };
+    let slowest_endpoints: Vec<EndpointLatency> = state.clickhouse
+        .query(&slowest_endpoints_query(SLOWEST_ENDPOINTS))
+        .fetch_all()
+        .await
+        .unwrap_or_default();
+    let top_services: Vec<ServiceVolume> = state.clickhouse
+        .query(&top_services_query(TOP_SERVICES))
+        .fetch_all()
+        .await
+        .unwrap_or_default();
+
+    let cache_stats = state.rag_engine.cache_stats();
+    let session_count = state.sessions.read().unwrap().len();
+
     Ok(Json(StatsResponse {
         total_logs,
         logs_24h,
@@ -52,29 +106,109 @@ pub async fn get_stats(
         services_count,
         embeddings_count,
         storage_mb,
+        llm_cache_hits: cache_stats.hits,
+        llm_cache_misses: cache_stats.misses,
+        session_count,
+        latency_p50_ms: latency.as_ref().and_then(|l| l.p50),
+        latency_p95_ms: latency.as_ref().and_then(|l| l.p95),
+        latency_p99_ms: latency.as_ref().and_then(|l| l.p99),
+        slowest_endpoints,
+        top_services,
     }))
 }
 
+/// p50/p95/p99 of `fields.latency_ms` over the last 24h
+fn latency_quantiles_query() -> String {
+    format!(
+        "SELECT count() AS samples, \
+                quantile(0.5)(latency) AS p50, \
+                quantile(0.95)(latency) AS p95, \
+                quantile(0.99)(latency) AS p99 \
+         FROM (SELECT {} AS latency FROM logs WHERE timestamp > now() - INTERVAL 1 DAY) \
+         WHERE latency IS NOT NULL",
+        LATENCY_EXPR
+    )
+}
+
+/// Endpoints with the highest p95 latency over the last 24h
+fn slowest_endpoints_query(limit: usize) -> String {
+    format!(
+        "SELECT assumeNotNull(endpoint) AS endpoint, \
+                quantile(0.95)(assumeNotNull(latency)) AS p95_ms, \
+                count() AS requests \
+         FROM (SELECT {} AS endpoint, {} AS latency FROM logs WHERE timestamp > now() - INTERVAL 1 DAY) \
+         WHERE endpoint IS NOT NULL AND latency IS NOT NULL \
+         GROUP BY endpoint \
+         ORDER BY p95_ms DESC \
+         LIMIT {}",
+        ENDPOINT_EXPR, LATENCY_EXPR, limit
+    )
+}
+
+/// Services with the most logs over the last hour
+fn top_services_query(limit: usize) -> String {
+    format!(
+        "SELECT service, count() AS logs \
+         FROM logs WHERE timestamp > now() - INTERVAL 1 HOUR \
+         GROUP BY service \
+         ORDER BY logs DESC \
+         LIMIT {}",
+        limit
+    )
+}
+
 pub async fn get_services(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Vec<String>>, (StatusCode, String)> {
+) -> Result<Json<ServicesResponse>, (StatusCode, String)> {
     info!("Services request");
 
+    // the first refresh hasn't finished yet (or failed): ask ClickHouse directly
+    let (cached, last_refreshed) = state.services.snapshot();
+    if last_refreshed.is_none() && cached.is_empty() {
+        refresh_services(&state)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    let (services, last_refreshed) = state.services.snapshot();
+    Ok(Json(ServicesResponse {
+        services,
+        last_refreshed: last_refreshed.map(|t| t.to_rfc3339()),
+    }))
+}
+
+/// Re-read the service list from ClickHouse into the registry, and let queries name
+/// any of those services
+pub async fn refresh_services(state: &AppState) -> Result<usize, clickhouse::error::Error> {
     let services: Vec<String> = state.clickhouse
-        .query("SELECT DISTINCT service FROM logs ORDER BY service")
+        .query("SELECT DISTINCT service FROM logs")
         .fetch_all()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    Ok(Json(services))
+        .await?;
+    let count = services.len();
+    state.services.refresh(services);
+    state.rag_engine.set_services(state.services.snapshot().0);
+    Ok(count)
 }
 
 pub async fn get_recent_logs(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<RecentLogsQuery>,
-) -> Result<Json<Vec<RecentLogRow>>, (StatusCode, String)> {
-    let limit = params.limit.unwrap_or(100).min(500);
-    info!(limit, service = ?params.service, level = ?params.level, "Recent logs request");
+    query: Result<Query<RecentLogsQuery>, QueryRejection>,
+) -> Result<Json<RecentLogsResponse>, (StatusCode, Json<ApiError>)> {
+    let Query(params) = query.map_err(bad_query)?;
+    let limit = params.limit.unwrap_or(100).clamp(1, 500) as usize;
+    info!(
+        limit,
+        service = ?params.service,
+        level = ?params.level,
+        before = ?params.before,
+        since_ts = ?params.since_ts,
+        from = ?params.from,
+        to = ?params.to,
+        field_filters = ?params.field_filters,
+        "Recent logs request"
+    );
+    let filters = parse_filters(params.field_filters.as_deref().unwrap_or(""))
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
 
     let mut conditions = vec!["1=1".to_string()];
     if let Some(ref service) = params.service {
@@ -83,22 +217,301 @@ pub async fn get_recent_logs(
     if let Some(ref level) = params.level {
         conditions.push(format!("level = '{}'", level.replace('\'', "''")));
     }
+    if let Some(ref before) = params.before {
+        let cursor = LogCursor::parse(before)
+            .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Invalid cursor"))?;
+        conditions.push(cursor.condition());
+    }
+    let since = match (&params.since_ts, &params.before) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::new(StatusCode::BAD_REQUEST, "since_ts and before can't be combined"));
+        }
+        (Some(since), None) => {
+            let cursor = LogCursor::parse(since)
+                .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Invalid since_ts"))?;
+            conditions.push(cursor.newer_condition());
+            true
+        }
+        (None, _) => false,
+    };
+    if let Some(from) = params.from {
+        conditions.push(format!("timestamp >= toDateTime64({}, 3)", from));
+    }
+    if let Some(to) = params.to {
+        conditions.push(format!("timestamp <= toDateTime64({}, 3)", to));
+    }
+    conditions.extend(filters.iter().map(field_condition));
 
+    // fetch one extra row to know whether another page exists; a poll reads forward instead
+    let order = if since { "ASC" } else { "DESC" };
     let query = format!(
         "SELECT toString(id) as log_id, service, level, message, toString(timestamp) as timestamp 
          FROM logs 
          WHERE {} 
-         ORDER BY timestamp DESC 
+         ORDER BY timestamp {order}, log_id {order} 
          LIMIT {}",
         conditions.join(" AND "),
-        limit
+        if since { limit } else { limit + 1 },
     );
 
     let logs: Vec<RecentLogRow> = state.clickhouse
         .query(&query)
         .fetch_all()
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    if since {
+        return Ok(Json(into_poll(logs)));
+    }
+    Ok(Json(into_page(logs, limit)))
+}
+
+/// Keyset pagination cursor: position of the last row on a page
+#[derive(Debug, Clone, PartialEq)]
+struct LogCursor {
+    timestamp: String,
+    log_id: Option<String>,
+}
+
+impl LogCursor {
+    fn from_row(row: &RecentLogRow) -> Self {
+        Self {
+            timestamp: row.timestamp.clone(),
+            log_id: Some(row.log_id.clone()),
+        }
+    }
 
-    Ok(Json(logs))
+    fn encode(&self) -> String {
+        match &self.log_id {
+            Some(id) => format!("{}|{}", self.timestamp, id),
+            None => self.timestamp.clone(),
+        }
+    }
+
+    /// Accepts "timestamp|id" cursors and bare timestamps; rejects anything that could break out of the SQL literal
+    fn parse(s: &str) -> Option<Self> {
+        let (timestamp, log_id) = match s.split_once('|') {
+            Some((ts, id)) => (ts, Some(id)),
+            None => (s, None),
+        };
+
+        let ts_ok = !timestamp.is_empty()
+            && timestamp.chars().all(|c| c.is_ascii_digit() || " -:.TZ+".contains(c));
+        let id_ok = log_id.is_none_or(|id| {
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        });
+        if !ts_ok || !id_ok {
+            return None;
+        }
+
+        Some(Self {
+            timestamp: timestamp.to_string(),
+            log_id: log_id.map(str::to_string),
+        })
+    }
+
+    /// WHERE clause selecting rows strictly older than the cursor, matching ORDER BY timestamp DESC, log_id DESC
+    fn condition(&self) -> String {
+        match &self.log_id {
+            Some(id) => format!(
+                "(timestamp, toString(id)) < (toDateTime64('{}', 3), '{}')",
+                self.timestamp, id
+            ),
+            None => format!("timestamp < parseDateTime64BestEffort('{}', 3)", self.timestamp),
+        }
+    }
+
+    /// WHERE clause selecting rows strictly newer than the cursor, for `since_ts` polls
+    fn newer_condition(&self) -> String {
+        match &self.log_id {
+            Some(id) => format!(
+                "(timestamp, toString(id)) > (toDateTime64('{}', 3), '{}')",
+                self.timestamp, id
+            ),
+            None => format!("timestamp > parseDateTime64BestEffort('{}', 3)", self.timestamp),
+        }
+    }
+}
+
+/// Trim the extra lookahead row and emit a cursor if there is another page
+fn into_page(mut logs: Vec<RecentLogRow>, limit: usize) -> RecentLogsResponse {
+    let has_more = logs.len() > limit;
+    logs.truncate(limit);
+    let next_cursor = if has_more {
+        logs.last().map(|row| LogCursor::from_row(row).encode())
+    } else {
+        None
+    };
+    RecentLogsResponse { logs, next_cursor }
+}
+
+/// Rows of a `since_ts` poll, oldest first; the cursor is the newest one, where the next poll starts
+fn into_poll(logs: Vec<RecentLogRow>) -> RecentLogsResponse {
+    let next_cursor = logs.last().map(|row| LogCursor::from_row(row).encode());
+    RecentLogsResponse { logs, next_cursor }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(timestamp: &str, log_id: &str) -> RecentLogRow {
+        RecentLogRow {
+            log_id: log_id.to_string(),
+            service: "api".to_string(),
+            level: "Info".to_string(),
+            message: format!("log {}", log_id),
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    // in-memory stand-in for the ClickHouse query: keyset filter, newest first, limit + 1
+    fn fetch(table: &[RecentLogRow], before: Option<&str>, limit: usize) -> RecentLogsResponse {
+        let cursor = before.map(|b| LogCursor::parse(b).unwrap());
+        let mut rows: Vec<RecentLogRow> = table
+            .iter()
+            .filter(|r| match &cursor {
+                Some(c) => {
+                    (r.timestamp.as_str(), r.log_id.as_str())
+                        < (c.timestamp.as_str(), c.log_id.as_deref().unwrap_or(""))
+                }
+                None => true,
+            })
+            .map(|r| row(&r.timestamp, &r.log_id))
+            .collect();
+        rows.sort_by(|a, b| (&b.timestamp, &b.log_id).cmp(&(&a.timestamp, &a.log_id)));
+        rows.truncate(limit + 1);
+        into_page(rows, limit)
+    }
+
+    // and for a since_ts poll: rows strictly newer than the cursor, oldest first, limit
+    fn poll(table: &[RecentLogRow], since: &str, limit: usize) -> RecentLogsResponse {
+        let cursor = LogCursor::parse(since).unwrap();
+        let mut rows: Vec<RecentLogRow> = table
+            .iter()
+            .filter(|r| match &cursor.log_id {
+                Some(id) => (r.timestamp.as_str(), r.log_id.as_str()) > (cursor.timestamp.as_str(), id.as_str()),
+                None => r.timestamp > cursor.timestamp,
+            })
+            .map(|r| row(&r.timestamp, &r.log_id))
+            .collect();
+        rows.sort_by(|a, b| (&a.timestamp, &a.log_id).cmp(&(&b.timestamp, &b.log_id)));
+        rows.truncate(limit);
+        into_poll(rows)
+    }
+
+    #[test]
+    fn test_pages_do_not_overlap_or_skip() {
+        // several rows share a timestamp so the id tiebreak matters
+        let table = vec![
+            row("2026-02-10 01:00:00.000", "0001"),
+            row("2026-02-10 02:00:00.000", "0002"),
+            row("2026-02-10 02:00:00.000", "0003"),
+            row("2026-02-10 02:00:00.000", "0004"),
+            row("2026-02-10 03:00:00.000", "0005"),
+            row("2026-02-10 04:00:00.000", "0006"),
+        ];
+
+        let first = fetch(&table, None, 3);
+        let cursor = first.next_cursor.clone().expect("more rows remain");
+        let second = fetch(&table, Some(&cursor), 3);
+
+        let ids: Vec<&str> = first
+            .logs
+            .iter()
+            .chain(second.logs.iter())
+            .map(|r| r.log_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["0006", "0005", "0004", "0003", "0002", "0001"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_since_polls_are_disjoint_and_ascending() {
+        let mut table = vec![
+            row("2026-02-10 01:00:00.000", "0001"),
+            row("2026-02-10 02:00:00.000", "0002"),
+            row("2026-02-10 02:00:00.000", "0003"),
+        ];
+        let ids = |page: &RecentLogsResponse| page.logs.iter().map(|r| r.log_id.clone()).collect::<Vec<_>>();
+
+        // a bare timestamp to start from, then each poll's cursor
+        let first = poll(&table, "2026-02-10 01:30:00", 10);
+        assert_eq!(ids(&first), ["0002", "0003"]);
+        let cursor = first.next_cursor.clone().unwrap();
+        assert_eq!(cursor, "2026-02-10 02:00:00.000|0003");
+
+        // nothing new yet: no cursor, the client keeps its own
+        assert!(poll(&table, &cursor, 10).next_cursor.is_none());
+
+        table.push(row("2026-02-10 03:00:00.000", "0005"));
+        table.push(row("2026-02-10 02:00:00.000", "0004"));
+        table.push(row("2026-02-10 04:00:00.000", "0006"));
+        // more new rows than the limit: the next poll picks up where this one stopped
+        let second = poll(&table, &cursor, 2);
+        assert_eq!(ids(&second), ["0004", "0005"]);
+        let third = poll(&table, second.next_cursor.as_deref().unwrap(), 2);
+        assert_eq!(ids(&third), ["0006"]);
+
+        let cursor = LogCursor::parse(&cursor).unwrap();
+        assert_eq!(
+            cursor.newer_condition(),
+            "(timestamp, toString(id)) > (toDateTime64('2026-02-10 02:00:00.000', 3), '0003')"
+        );
+    }
+
+    #[test]
+    fn test_latency_queries() {
+        let quantiles = latency_quantiles_query();
+        assert!(quantiles.contains("if(mapContains(numeric_fields, 'latency_ms'), numeric_fields['latency_ms'],"));
+        assert!(quantiles.contains("JSONExtract(fields, 'latency_ms', 'Nullable(Float64)')) AS latency"));
+        assert!(quantiles.contains("quantile(0.95)(latency) AS p95"));
+        assert!(quantiles.contains("WHERE timestamp > now() - INTERVAL 1 DAY"));
+        assert!(quantiles.contains("WHERE latency IS NOT NULL"));
+
+        let slowest = slowest_endpoints_query(5);
+        assert!(slowest.contains("JSONExtract(fields, 'path', 'Nullable(String)')"));
+        assert!(slowest.contains("GROUP BY endpoint"));
+        assert!(slowest.contains("ORDER BY p95_ms DESC"));
+        assert!(slowest.ends_with("LIMIT 5"));
+    }
+
+    #[test]
+    fn test_latency_row_decodes_nullable_quantiles() {
+        // what the quantiles come back as: Nullable(Float64), NULL while no log has a latency
+        let row: LatencyRow =
+            serde_json::from_value(serde_json::json!({"samples": 3, "p50": 12.5, "p95": 80.0, "p99": null})).unwrap();
+        assert_eq!((row.samples, row.p50, row.p95, row.p99), (3, Some(12.5), Some(80.0), None));
+
+        let empty: LatencyRow =
+            serde_json::from_value(serde_json::json!({"samples": 0, "p50": null, "p95": null, "p99": null})).unwrap();
+        assert_eq!(empty.p50, None);
+    }
+
+    #[test]
+    fn test_field_conditions() {
+        let filters = parse_filters("status_code:500,endpoint:/api/checkout").unwrap();
+        assert_eq!(
+            field_condition(&filters[0]),
+            "(JSONExtractString(fields, 'status_code') = '500' OR JSONExtractRaw(fields, 'status_code') = '500')"
+        );
+        assert_eq!(
+            field_condition(&filters[1]),
+            "(JSONExtractString(fields, 'endpoint') = '/api/checkout' OR JSONExtractRaw(fields, 'endpoint') = '/api/checkout')"
+        );
+
+        // quotes and backslashes in the value stay inside the literal
+        let sneaky = ("user".to_string(), r"x\' OR 1=1 --".to_string());
+        assert!(field_condition(&sneaky).starts_with(r"(JSONExtractString(fields, 'user') = 'x\\\' OR 1=1 --'"));
+        // and keys never reach the query unchecked
+        assert!(parse_filters("status_code') OR 1=1 --:500").is_err());
+    }
+
+    #[test]
+    fn test_cursor_rejects_injection() {
+        assert!(LogCursor::parse("2026-02-10 02:00:00.000|0003").is_some());
+        assert!(LogCursor::parse("2026-02-10T02:00:00Z").is_some());
+        assert!(LogCursor::parse("2026-02-10' OR 1=1 --").is_none());
+        assert!(LogCursor::parse("2026-02-10|abc'; DROP TABLE logs").is_none());
+    }
 }
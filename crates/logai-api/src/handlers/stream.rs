@@ -0,0 +1,141 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures::{SinkExt, StreamExt};
+use logai_core::{LogEntry, LogLevel};
+use std::sync::Arc;
+use tokio::sync::OwnedSemaphorePermit;
+use tracing::{info, warn};
+
+use crate::models::{ApiError, StreamQuery};
+use crate::state::AppState;
+
+/// Live tail of newly ingested logs over WebSocket.
+///
+/// Framing: every server message is a single text frame holding one JSON `LogEntry`,
+/// exactly as published on the NATS `logs.ingest` subject, e.g.
+/// `{"id":"…","timestamp":"2026-02-10T03:00:00Z","level":"error","service":"api",
+///   "message":"…","raw":"…","trace_id":null,"span_id":null,"error_category":null,
+///   "fields":{},"ingested_at":"…"}`.
+/// Clients don't need to send anything; closing the socket ends the subscription.
+/// Optional `service` and `level` query params are matched exactly, server-side.
+pub async fn stream_logs(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StreamQuery>,
+) -> Response {
+    let level = match params.level.as_deref().map(LogLevel::from_str) {
+        Some(None) => {
+            return ApiError::new(StatusCode::BAD_REQUEST, "Unknown log level").into_response();
+        }
+        Some(level) => level,
+        None => None,
+    };
+    let filter = StreamFilter {
+        service: params.service,
+        level,
+    };
+
+    // permit is held for the lifetime of the socket
+    let Ok(permit) = state.stream_slots.clone().try_acquire_owned() else {
+        return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Too many stream subscribers")
+            .into_response();
+    };
+
+    let subscriber = match state.nats.subscribe(state.ingest_subject.clone()).await {
+        Ok(subscriber) => subscriber,
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+
+    ws.on_upgrade(move |socket| forward_logs(socket, subscriber, filter, permit))
+}
+
+async fn forward_logs(
+    socket: WebSocket,
+    mut subscriber: async_nats::Subscriber,
+    filter: StreamFilter,
+    _permit: OwnedSemaphorePermit,
+) {
+    info!(service = ?filter.service, level = ?filter.level, "Stream client connected");
+    let (mut sender, mut receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            message = subscriber.next() => {
+                let Some(message) = message else { break };
+                let Ok(text) = std::str::from_utf8(&message.payload) else { continue };
+                match serde_json::from_str::<LogEntry>(text) {
+                    Ok(entry) if filter.matches(&entry) => {
+                        if sender.send(Message::Text(text.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!(error = %e, "Skipping malformed log on the ingest subject"),
+                }
+            }
+            incoming = receiver.next() => {
+                match incoming {
+                    // client went away
+                    None | Some(Err(_)) | Some(Ok(Message::Close(_))) => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+
+    let _ = subscriber.unsubscribe().await;
+    info!("Stream client disconnected");
+}
+
+/// Server-side filters for the live stream
+#[derive(Debug, Default)]
+struct StreamFilter {
+    service: Option<String>,
+    level: Option<LogLevel>,
+}
+
+impl StreamFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        self.service.as_ref().is_none_or(|s| *s == entry.service)
+            && self.level.is_none_or(|l| l == entry.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logai_core::RawLogEntry;
+
+    fn entry(service: &str, level: LogLevel) -> LogEntry {
+        LogEntry::from_raw(RawLogEntry {
+            message: "test".to_string(),
+            timestamp: None,
+            service: Some(service.to_string()),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_stream_filter() {
+        let all = StreamFilter::default();
+        assert!(all.matches(&entry("api", LogLevel::Info)));
+
+        let errors_from_api = StreamFilter {
+            service: Some("api".to_string()),
+            level: Some(LogLevel::Error),
+        };
+        assert!(errors_from_api.matches(&entry("api", LogLevel::Error)));
+        assert!(!errors_from_api.matches(&entry("api", LogLevel::Info)));
+        assert!(!errors_from_api.matches(&entry("db", LogLevel::Error)));
+    }
+}
@@ -0,0 +1,293 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use logai_core::PARENT_SPAN_FIELD;
+use logai_rag::{LogEvent, TraceResponse};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::models::{ApiError, SpanNode, TraceQuery, TraceTreeResponse};
+use crate::state::AppState;
+
+// upper bound so a runaway trace can't pull the whole table
+const MAX_TRACE_LOGS: usize = 1000;
+
+#[derive(Deserialize, clickhouse::Row)]
+struct TraceLogRow {
+    service: String,
+    level: String,
+    message: String,
+    timestamp_ms: i64,
+}
+
+#[derive(Deserialize, clickhouse::Row)]
+struct SpanLogRow {
+    service: String,
+    level: String,
+    message: String,
+    timestamp_ms: i64,
+    span_id: Option<String>,
+    // '' when the log has no parent
+    parent_span_id: String,
+}
+
+pub async fn get_trace(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TraceQuery>,
+) -> Result<Json<TraceResponse>, (StatusCode, Json<ApiError>)> {
+    info!(trace_id = %params.trace_id, "Trace request");
+
+    let trace = handle_trace_query(&state, &params.trace_id).await?;
+    if trace.spans.is_empty() {
+        return Err(ApiError::not_found(format!("No logs found for trace {}", params.trace_id)));
+    }
+    Ok(Json(trace))
+}
+
+/// Collect every log sharing `trace_id` (ClickHouse holds the trace_id column, Qdrant doesn't)
+pub async fn handle_trace_query(
+    state: &AppState,
+    trace_id: &str,
+) -> Result<TraceResponse, (StatusCode, Json<ApiError>)> {
+    let rows: Vec<TraceLogRow> = state
+        .clickhouse
+        .query(&format!(
+            "SELECT service, level, message, toUnixTimestamp64Milli(timestamp) AS timestamp_ms
+             FROM logs
+             WHERE trace_id = ?
+             ORDER BY timestamp
+             LIMIT {}",
+            MAX_TRACE_LOGS
+        ))
+        .bind(trace_id)
+        .fetch_all()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    let spans = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(LogEvent {
+                timestamp: DateTime::from_timestamp_millis(row.timestamp_ms)?,
+                level: row.level.to_uppercase(),
+                service: row.service,
+                message: row.message,
+            })
+        })
+        .collect();
+
+    let trace = TraceResponse::from_events(trace_id, spans);
+    info!(trace_id, spans = trace.spans.len(), services = trace.services_involved.len(), "Trace assembled");
+    Ok(trace)
+}
+
+pub async fn get_trace_tree(
+    State(state): State<Arc<AppState>>,
+    Path(trace_id): Path<String>,
+) -> Result<Json<TraceTreeResponse>, (StatusCode, Json<ApiError>)> {
+    info!(trace_id = %trace_id, "Trace tree request");
+
+    let rows: Vec<SpanLogRow> = state
+        .clickhouse
+        .query(&format!(
+            "SELECT service, level, message, toUnixTimestamp64Milli(timestamp) AS timestamp_ms,
+                    span_id, JSONExtractString(fields, '{}') AS parent_span_id
+             FROM logs
+             WHERE trace_id = ?
+             ORDER BY timestamp
+             LIMIT {}",
+            PARENT_SPAN_FIELD, MAX_TRACE_LOGS
+        ))
+        .bind(&trace_id)
+        .fetch_all()
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    if rows.is_empty() {
+        return Err(ApiError::not_found(format!("No logs found for trace {}", trace_id)));
+    }
+
+    let tree = build_trace_tree(&trace_id, rows);
+    info!(trace_id = %trace_id, roots = tree.spans.len(), unlinked = tree.timeline.len(), "Trace tree assembled");
+    Ok(Json(tree))
+}
+
+// one span's own logs while the tree is put together
+struct SpanLogs {
+    parent: Option<String>,
+    logs: Vec<LogEvent>,
+}
+
+/// Nest spans under their parent spans. Without a single parent link there is no
+/// hierarchy to show, so every log goes to the flat timeline instead.
+fn build_trace_tree(trace_id: &str, rows: Vec<SpanLogRow>) -> TraceTreeResponse {
+    let has_links = rows.iter().any(|row| row.span_id.is_some() && !row.parent_span_id.is_empty());
+    let first = rows.iter().map(|row| row.timestamp_ms).min().unwrap_or(0);
+    let last = rows.iter().map(|row| row.timestamp_ms).max().unwrap_or(0);
+
+    let mut order: Vec<String> = Vec::new(); // span ids by first log
+    let mut spans: HashMap<String, SpanLogs> = HashMap::new();
+    let mut timeline = Vec::new();
+    for row in rows {
+        let Some(timestamp) = DateTime::from_timestamp_millis(row.timestamp_ms) else { continue };
+        let event = LogEvent {
+            timestamp,
+            level: row.level.to_uppercase(),
+            service: row.service,
+            message: row.message,
+        };
+        match row.span_id.filter(|id| has_links && !id.is_empty()) {
+            Some(id) => {
+                let span = spans.entry(id.clone()).or_insert_with(|| {
+                    order.push(id);
+                    SpanLogs { parent: None, logs: Vec::new() }
+                });
+                if span.parent.is_none() && !row.parent_span_id.is_empty() {
+                    span.parent = Some(row.parent_span_id);
+                }
+                span.logs.push(event);
+            }
+            None => timeline.push(event),
+        }
+    }
+
+    // a parent that logged nothing in this trace makes its child a root
+    let parent_of = |id: &str| spans[id].parent.as_deref().filter(|p| *p != id && spans.contains_key(*p));
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    for id in &order {
+        if let Some(parent) = parent_of(id) {
+            children.entry(parent).or_default().push(id);
+        }
+    }
+
+    // roots first, then whatever a parent cycle left unreachable
+    let mut visited = HashSet::new();
+    let mut roots = Vec::new();
+    for id in order.iter().filter(|id| parent_of(id).is_none()).chain(&order) {
+        if !visited.contains(id.as_str()) {
+            roots.push(span_node(id, &spans, &children, &mut visited).0);
+        }
+    }
+
+    TraceTreeResponse {
+        trace_id: trace_id.to_string(),
+        duration_ms: last - first,
+        spans: roots,
+        timeline: timeline.into_iter().map(Into::into).collect(),
+    }
+}
+
+// the span with its children, and when it starts and ends counting the children
+fn span_node(
+    id: &str,
+    spans: &HashMap<String, SpanLogs>,
+    children: &HashMap<&str, Vec<&str>>,
+    visited: &mut HashSet<String>,
+) -> (SpanNode, DateTime<Utc>, DateTime<Utc>) {
+    visited.insert(id.to_string());
+    let span = &spans[id];
+    let mut start = span.logs.iter().map(|log| log.timestamp).min().unwrap_or_default();
+    let mut end = span.logs.iter().map(|log| log.timestamp).max().unwrap_or_default();
+
+    let mut nodes = Vec::new();
+    for child in children.get(id).into_iter().flatten() {
+        if visited.contains(*child) {
+            continue;
+        }
+        let (node, child_start, child_end) = span_node(child, spans, children, visited);
+        start = start.min(child_start);
+        end = end.max(child_end);
+        nodes.push(node);
+    }
+
+    let node = SpanNode {
+        span_id: id.to_string(),
+        parent_span_id: span.parent.clone(),
+        service: span.logs.first().map(|log| log.service.clone()).unwrap_or_default(),
+        start: start.to_rfc3339(),
+        duration_ms: (end - start).num_milliseconds(),
+        logs: span.logs.iter().cloned().map(Into::into).collect(),
+        children: nodes,
+    };
+    (node, start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ms after 2026-02-10T03:00:00Z
+    fn row(at: i64, service: &str, span: Option<&str>, parent: &str, message: &str) -> SpanLogRow {
+        SpanLogRow {
+            service: service.to_string(),
+            level: "Info".to_string(),
+            message: message.to_string(),
+            timestamp_ms: 1770692400000 + at,
+            span_id: span.map(str::to_string),
+            parent_span_id: parent.to_string(),
+        }
+    }
+
+    fn rows(with_parents: bool) -> Vec<SpanLogRow> {
+        let parent = |id: &'static str| if with_parents { id } else { "" };
+        vec![
+            row(0, "gateway", Some("a"), "", "POST /api/orders"),
+            row(100, "orders", Some("b"), parent("a"), "Creating order"),
+            row(200, "payments", Some("c"), parent("b"), "Charging card"),
+            row(250, "orders", None, "", "Cache miss for customer 7"),
+            row(350, "payments", Some("c"), parent("b"), "Card declined"),
+            row(400, "orders", Some("b"), parent("a"), "Order failed"),
+            row(900, "gateway", Some("a"), "", "POST /api/orders 402"),
+        ]
+    }
+
+    #[test]
+    fn test_three_span_tree() {
+        let tree = build_trace_tree("t1", rows(true));
+        assert_eq!(tree.duration_ms, 900);
+        assert_eq!(tree.spans.len(), 1);
+
+        let gateway = &tree.spans[0];
+        assert_eq!((gateway.span_id.as_str(), gateway.service.as_str()), ("a", "gateway"));
+        assert_eq!(gateway.parent_span_id, None);
+        assert_eq!(gateway.start, "2026-02-10T03:00:00+00:00");
+        assert_eq!(gateway.duration_ms, 900);
+        assert_eq!(gateway.logs.len(), 2);
+
+        let orders = &gateway.children[0];
+        assert_eq!(orders.span_id, "b");
+        assert_eq!(orders.parent_span_id.as_deref(), Some("a"));
+        assert_eq!(orders.duration_ms, 300);
+
+        let payments = &orders.children[0];
+        assert_eq!((payments.span_id.as_str(), payments.service.as_str()), ("c", "payments"));
+        assert_eq!(payments.duration_ms, 150);
+        assert_eq!(payments.logs[1].message, "Card declined");
+        assert!(payments.children.is_empty());
+
+        // the log without a span id stays on the timeline
+        assert_eq!(tree.timeline.len(), 1);
+        assert_eq!(tree.timeline[0].message, "Cache miss for customer 7");
+    }
+
+    #[test]
+    fn test_no_parent_links_is_a_timeline() {
+        let tree = build_trace_tree("t1", rows(false));
+        assert!(tree.spans.is_empty());
+        assert_eq!(tree.timeline.len(), 7);
+        assert_eq!(tree.timeline[6].message, "POST /api/orders 402");
+
+        // a parent cycle still shows every span once
+        let cycle = vec![
+            row(0, "orders", Some("b"), "c", "Creating order"),
+            row(10, "payments", Some("c"), "b", "Charging card"),
+        ];
+        let tree = build_trace_tree("t2", cycle);
+        assert_eq!(tree.spans.len(), 1);
+        assert_eq!(tree.spans[0].children[0].span_id, "c");
+    }
+}
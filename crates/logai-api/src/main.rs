@@ -1,22 +1,36 @@
+mod chat_gate;
 mod handlers;
+mod metrics;
 mod middleware;
 mod models;
 mod state;
 
-use axum::{middleware as axum_mw, routing::{get, post}, Router};
+use axum::{middleware as axum_mw, routing::{delete, get, post}, Router};
 use clickhouse::Client as ClickHouseClient;
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
-use logai_core::parser::{ApacheParser, NginxParser, ParserRegistry, ProxmoxParser, SyslogParser};
-use logai_rag::{RagConfig, RagEngine, Reranker};
+use logai_core::embedding::{is_log_collection, verify_embedding_dimensions, CollectionLayout, EmbeddingModelKind};
+use logai_core::nats::NatsConfig;
+use logai_core::parser::{
+    ApacheParser, CefParser, EnvoyParser, GelfParser, Log4jParser, LogbackParser, MysqlSlowLogParser,
+    NginxParser, ParserRegistry, PostgresCsvParser, ProxmoxParser, RedisParser, SyslogParser,
+    WindowsEventParser,
+};
+use logai_rag::{RagConfig, RagEngine, RerankConfig, Reranker};
+use qdrant_client::qdrant::vectors_config;
 use qdrant_client::Qdrant;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Semaphore;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::info;
+use tracing::{error, info, info_span, warn, Instrument};
 
+use chat_gate::ChatGate;
 use handlers::*;
-use middleware::require_api_key;
-use state::AppState;
+use metrics::Metrics;
+use middleware::{request_span, require_api_key, require_scope, track_requests, ApiKeys, Scope};
+use state::{
+    evict_sessions, AppState, EmbeddingCache, HealthWeights, ModelPool, RetrievalConfig, SamplingConfig, ServiceRegistry, SessionConfig,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -24,8 +38,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
 
     //logging setup
-    tracing_subscriber::fmt::init();
+    logai_core::logging::init_tracing();
 
+    // requests get their own span (see request_span), this one covers startup and background tasks
+    run().instrument(info_span!("api", service = "logai-api")).await
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
     // Read infrastructure URLs from environment
     let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "localhost:4222".to_string());
     let qdrant_url = std::env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".to_string());
@@ -34,7 +53,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // connect to NATS
     info!("Connecting to NATS at {}...", nats_url);
     let nats = async_nats::connect(&nats_url).await?;
-    info!("Connected to NATS!");
+    let ingest_subject = NatsConfig::from_env().ingest_subject;
+    info!(subject = %ingest_subject, "Connected to NATS!");
 
     // Connect to Qdrant
     info!("Connecting to Qdrant at {}...", qdrant_url);
@@ -49,9 +69,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Connected to ClickHouse!");
 
     // Load embedding model
-    info!("Loading embedding model...");
-    let model = TextEmbedding::try_new(InitOptions::new(EmbeddingModel::AllMiniLML6V2))?;
-    info!("Model loaded!");
+    // must match the worker, query vectors are searched against the indexed ones
+    let embedding_model = EmbeddingModelKind::from_env()?;
+    // a collection built for another model fails every search, so refuse to start instead
+    if let Err(e) = check_collection_dimensions(&qdrant, embedding_model).await {
+        error!("{}", e);
+        return Err(e);
+    }
+    // several copies so concurrent queries don't serialize on one (each costs the model's memory)
+    let pool_size = ModelPool::<TextEmbedding>::size_from_env();
+    info!(pool_size, "Loading embedding model...");
+    let models = (0..pool_size)
+        .map(|_| TextEmbedding::try_new(InitOptions::new(fastembed_model(embedding_model))))
+        .collect::<Result<Vec<_>, _>>()?;
+    info!(
+        model = embedding_model.name(),
+        dimension = embedding_model.dimension(),
+        pool_size,
+        "Model loaded!"
+    );
 
     // Setup parser registry
     info!("Setting up parser registry...");
@@ -60,61 +96,171 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     parser_registry.register(Box::new(NginxParser::new()));
     parser_registry.register(Box::new(SyslogParser::new()));
     parser_registry.register(Box::new(ProxmoxParser::new()));
-    info!("Parsers registered: apache, nginx, syslog, proxmox");
+    parser_registry.register(Box::new(WindowsEventParser::new()));
+    parser_registry.register(Box::new(CefParser::new()));
+    parser_registry.register(Box::new(PostgresCsvParser::new()));
+    parser_registry.register(Box::new(MysqlSlowLogParser::new()));
+    parser_registry.register(Box::new(EnvoyParser::new()));
+    parser_registry.register(Box::new(Log4jParser::new()));
+    parser_registry.register(Box::new(LogbackParser::new()));
+    parser_registry.register(Box::new(GelfParser::new()));
+    parser_registry.register(Box::new(RedisParser::new()));
+    info!(formats = ?parser_registry.list(), "Parsers registered");
 
     // Setup RAG engine (configurable via LOGAI_GROQ_MODEL env var)
-    let rag_config = RagConfig::from_env();
+    let rag_config = RagConfig::from_env()?;
     info!(
         model = %rag_config.groq_model,
+        timezone = %rag_config.timezone,
         "Setting up RAG engine with Groq..."
     );
-    let rag_engine = RagEngine::new(rag_config);
-    let reranker = Reranker::new();
+    let rag_engine = RagEngine::new(rag_config)?;
+    let reranker = Reranker::with_config(RerankConfig::from_env());
     info!("RAG engine ready!");
 
+    // Cap concurrent live-tail WebSocket clients (each holds its own NATS subscription)
+    let max_stream_clients = std::env::var("LOGAI_MAX_STREAM_CLIENTS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(50);
+
+    // Search hits scoring below this are treated as irrelevant by ask/chat
+    let min_score = std::env::var("LOGAI_MIN_SCORE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0.3);
+    // same variable the worker creates the collections with
+    let scores_are_distances = std::env::var("QDRANT_DISTANCE")
+        .is_ok_and(|distance| distance.trim().eq_ignore_ascii_case("euclid"));
+    if scores_are_distances {
+        info!("Euclid distance: LOGAI_MIN_SCORE doesn't apply, search hits aren't filtered by score");
+    }
+
+    // searches fan out over every service's collection unless scoped to one
+    let collections = CollectionLayout::from_env();
+    info!(layout = ?collections, "Qdrant embeddings collections");
+
     let state = Arc::new(AppState {
         nats,
         qdrant,
         clickhouse,
-        model: Mutex::new(model),
+        model: ModelPool::new(models),
+        embedding_cache: EmbeddingCache::from_env(),
         parser_registry,
         rag_engine,
         reranker,
         sessions: RwLock::new(HashMap::new()),
+        session_config: SessionConfig::from_env(),
+        retrieval: RetrievalConfig::from_env(),
+        health_weights: HealthWeights::from_env(),
+        sampling: SamplingConfig::from_env(),
+        chat_gate: ChatGate::from_env(),
+        ingest_subject,
+        metrics: Arc::new(Metrics::new()),
+        stream_slots: Arc::new(Semaphore::new(max_stream_clients)),
+        services: ServiceRegistry::default(),
+        min_score,
+        scores_are_distances,
+        collections,
     });
 
-    //routes - protected routes with API key
-    let protected_routes = Router::new()
+    // Keep the /api/services list in sync with ClickHouse (first tick fires right away)
+    let services_state = state.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ServiceRegistry::refresh_interval());
+        loop {
+            ticker.tick().await;
+            if let Err(e) = refresh_services(&services_state).await {
+                warn!("Service list refresh failed: {}", e);
+            }
+        }
+    });
+
+    // Background cleanup of stale chat sessions
+    let sweeper_state = state.clone();
+    tokio::spawn(async move {
+        let config = sweeper_state.session_config.clone();
+        let mut ticker = tokio::time::interval(config.sweep_interval);
+        loop {
+            ticker.tick().await;
+            let removed = {
+                let mut sessions = sweeper_state.sessions.write().unwrap();
+                let removed = evict_sessions(&mut sessions, config.ttl, config.max_sessions);
+                sweeper_state.metrics.set_sessions(sessions.len());
+                removed
+            };
+            if removed > 0 {
+                info!(removed, "Evicted stale chat sessions");
+            }
+        }
+    });
+
+    //routes - protected routes with API key; each group also needs its key scope
+    let api_keys = Arc::new(ApiKeys::from_env()?);
+    let ingest_routes = Router::new()
         .route("/api/logs", post(ingest_log))
         .route("/api/logs/raw", post(ingest_raw_log))
+        .route("/api/logs/stream", post(ingest_log_stream))
+        .route("/api/otlp/logs", post(ingest_otlp_logs))
+        .route_layer(axum_mw::from_fn_with_state(Scope::Ingest, require_scope));
+    let read_routes = Router::new()
+        .route("/api/formats", get(get_formats))
         .route("/api/logs/recent", get(get_recent_logs))
+        .route("/api/logs/export", get(export_logs))
+        .route("/api/logs/stream", get(stream_logs))
+        .route("/api/logs/{id}", get(get_log))
         .route("/api/search", get(search_logs))
         .route("/api/ask", get(ask_logs))
+        .route("/api/trace", get(get_trace))
+        .route("/api/trace/{trace_id}/tree", get(get_trace_tree))
         .route("/api/chat", post(chat_logs))
+        .route("/api/causal", post(causal_analysis))
+        .route("/api/explain", post(explain_log))
         .route("/api/session", get(get_session))
         .route("/api/stats", get(get_stats))
         .route("/api/alerts", get(get_alerts))
         .route("/api/anomalies", get(get_anomalies))
+        .route("/api/health/services", get(get_service_health))
+        .route("/api/diff", get(get_diff))
+        .route("/api/patterns", get(get_patterns))
         .route("/api/services", get(get_services))
-        .layer(axum_mw::from_fn(require_api_key));
+        .route_layer(axum_mw::from_fn_with_state(Scope::Read, require_scope));
+    let admin_routes = Router::new()
+        .route("/api/logs", delete(delete_logs))
+        .route("/api/sessions", get(get_sessions))
+        .route("/api/session", delete(delete_session))
+        .route_layer(axum_mw::from_fn_with_state(Scope::Admin, require_scope));
+    let protected_routes = Router::new()
+        .merge(ingest_routes)
+        .merge(read_routes)
+        .merge(admin_routes)
+        .layer(axum_mw::from_fn_with_state(api_keys.clone(), require_api_key))
+        .layer(axum_mw::from_fn_with_state(state.metrics.clone(), track_requests));
     
-    // Health endpoint without auth
+    // Health and metrics endpoints without auth
+    let metrics_routes = Router::new()
+        .route("/metrics", get(get_metrics))
+        .with_state(state.metrics.clone());
+
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
     
     let app = Router::new()
-        .route("/health", get(|| async { "ok" }))
+        .route("/health", get(health))
+        .route("/readyz", get(readyz))
+        .merge(metrics_routes)
         .merge(protected_routes)
+        .layer(axum_mw::from_fn(request_span))
         .layer(cors)
         .with_state(state);
     
     // Log if API key is enabled
-    if std::env::var("LOGAI_API_KEY").ok().filter(|k| !k.is_empty()).is_some() {
-        info!("API key authentication ENABLED");
+    if !api_keys.is_empty() {
+        info!(keys = api_keys.len(), "API key authentication ENABLED");
     } else {
-        info!("API key authentication DISABLED (set LOGAI_API_KEY to enable)");
+        info!("API key authentication DISABLED (set LOGAI_API_KEY or LOGAI_API_KEYS to enable)");
     }
 
     // Server start
@@ -127,3 +273,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Compare every log collection holding vectors with the model's dimension. An empty one is
+/// left to the worker, which recreates it; Qdrant being unreachable isn't fatal either,
+/// GET /api/health reports it.
+async fn check_collection_dimensions(
+    qdrant: &Qdrant,
+    model: EmbeddingModelKind,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let collections = match qdrant.list_collections().await {
+        Ok(response) => response.collections,
+        Err(e) => {
+            warn!("Could not list Qdrant collections to check their dimension: {}", e);
+            return Ok(());
+        }
+    };
+    for name in collections.into_iter().map(|c| c.name).filter(|name| is_log_collection(name)) {
+        let Ok(info) = qdrant.collection_info(&name).await else {
+            warn!(collection = %name, "Could not read the Qdrant collection dimension");
+            continue;
+        };
+        let Some(info) = info.result else { continue };
+        if info.points_count.unwrap_or(0) == 0 {
+            continue;
+        }
+        let size = info
+            .config
+            .and_then(|c| c.params)
+            .and_then(|p| p.vectors_config)
+            .and_then(|v| v.config);
+        if let Some(vectors_config::Config::Params(params)) = size {
+            verify_embedding_dimensions(&name, params.size, model)?;
+        }
+    }
+    Ok(())
+}
+
+fn fastembed_model(kind: EmbeddingModelKind) -> EmbeddingModel {
+    match kind {
+        EmbeddingModelKind::AllMiniLmL6V2 => EmbeddingModel::AllMiniLML6V2,
+        EmbeddingModelKind::AllMiniLmL12V2 => EmbeddingModel::AllMiniLML12V2,
+        EmbeddingModelKind::BgeSmallEn => EmbeddingModel::BGESmallENV15,
+        EmbeddingModelKind::BgeBaseEn => EmbeddingModel::BGEBaseENV15,
+        EmbeddingModelKind::BgeLargeEn => EmbeddingModel::BGELargeENV15,
+        EmbeddingModelKind::NomicEmbedText => EmbeddingModel::NomicEmbedTextV15,
+    }
+}
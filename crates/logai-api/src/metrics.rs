@@ -0,0 +1,161 @@
+// Prometheus metrics - lock-free counters/histograms rendered in the text exposition format
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Histogram bucket upper bounds in seconds (LLM calls can take tens of seconds)
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Monotonic counter
+#[derive(Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Fixed-bucket latency histogram, safe to observe from any handler without locking
+pub struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|bound| secs <= *bound) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        // buckets are stored per-range, prometheus wants them cumulative
+        let mut cumulative = 0;
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{}_sum {}", name, sum);
+        let _ = writeln!(out, "{}_count {}", name, count);
+    }
+}
+
+/// Metrics registry shared through `AppState`
+pub struct Metrics {
+    pub ingested_logs: Counter,
+    pub sampled_out_logs: Counter,
+    pub search_requests: Counter,
+    pub ask_requests: Counter,
+    pub chat_requests: Counter,
+    pub llm_calls: Counter,
+    pub ask_latency: Histogram,
+    pub chat_latency: Histogram,
+    pub llm_latency: Histogram,
+    pub qdrant_search_latency: Histogram,
+    sessions: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            ingested_logs: Counter::default(),
+            sampled_out_logs: Counter::default(),
+            search_requests: Counter::default(),
+            ask_requests: Counter::default(),
+            chat_requests: Counter::default(),
+            llm_calls: Counter::default(),
+            ask_latency: Histogram::new(),
+            chat_latency: Histogram::new(),
+            llm_latency: Histogram::new(),
+            qdrant_search_latency: Histogram::new(),
+            sessions: AtomicU64::new(0),
+        }
+    }
+
+    /// Record one RAG engine (LLM) round trip
+    pub fn observe_llm(&self, elapsed: Duration) {
+        self.llm_calls.inc();
+        self.llm_latency.observe(elapsed);
+    }
+
+    pub fn set_sessions(&self, count: usize) {
+        self.sessions.store(count as u64, Ordering::Relaxed);
+    }
+
+    /// Render everything in the Prometheus text format (only allocates on scrape)
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity(4096);
+
+        let counters = [
+            ("logai_ingested_logs_total", "Logs accepted for ingestion", &self.ingested_logs),
+            (
+                "logai_sampled_out_logs_total",
+                "Debug/Info logs dropped by ingest sampling",
+                &self.sampled_out_logs,
+            ),
+            ("logai_search_requests_total", "Semantic search requests", &self.search_requests),
+            ("logai_ask_requests_total", "Ask requests", &self.ask_requests),
+            ("logai_chat_requests_total", "Chat requests", &self.chat_requests),
+            ("logai_llm_calls_total", "RAG engine LLM calls", &self.llm_calls),
+        ];
+        for (name, help, counter) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, counter.get());
+        }
+
+        self.ask_latency
+            .render(&mut out, "logai_ask_duration_seconds", "Ask request latency");
+        self.chat_latency
+            .render(&mut out, "logai_chat_duration_seconds", "Chat request latency");
+        self.llm_latency
+            .render(&mut out, "logai_llm_duration_seconds", "RAG engine LLM call latency");
+        self.qdrant_search_latency.render(
+            &mut out,
+            "logai_qdrant_search_duration_seconds",
+            "Qdrant vector search latency",
+        );
+
+        let _ = writeln!(out, "# HELP logai_chat_sessions Chat sessions held in memory");
+        let _ = writeln!(out, "# TYPE logai_chat_sessions gauge");
+        let _ = writeln!(out, "logai_chat_sessions {}", self.sessions.load(Ordering::Relaxed));
+
+        out
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,32 +1,351 @@
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    extract::State,
+    http::{HeaderValue, Request, StatusCode},
     middleware::Next,
     response::Response,
 };
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use logai_core::REQUEST_ID_HEADER;
+use tracing::{info, info_span, Instrument};
+use uuid::Uuid;
 
+use crate::metrics::Metrics;
+
+/// What an API key may do. `admin` allows everything, including admin-only routes
+/// (alert ack/resolve, once the API has them); `read` and `ingest` are independent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Read,
+    Ingest,
+    Admin,
+}
+
+impl Scope {
+    fn from_name(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "read" => Some(Self::Read),
+            "ingest" => Some(Self::Ingest),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Scopes of the key that made the request, put in the request extensions by `require_api_key`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Granted(HashSet<Scope>);
+
+impl Granted {
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.0.contains(&Scope::Admin) || self.0.contains(&scope)
+    }
+}
+
+/// Accepted API keys and their scopes; empty means authentication is off
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    keys: HashMap<String, Granted>,
+}
+
+impl ApiKeys {
+    /// Environment variables:
+    /// - LOGAI_API_KEYS: Comma-separated key:scope pairs, e.g. `dash-key:read,shipper-key:ingest,ops-key:admin`
+    ///   (list a key twice to give it two scopes)
+    /// - LOGAI_API_KEY: A single key with the admin scope
+    ///
+    /// With neither set, every request is allowed
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(
+            std::env::var("LOGAI_API_KEYS").ok().as_deref(),
+            std::env::var("LOGAI_API_KEY").ok().as_deref(),
+        )
+    }
+
+    fn parse(scoped: Option<&str>, admin_key: Option<&str>) -> Result<Self, String> {
+        let mut keys: HashMap<String, Granted> = HashMap::new();
+        for pair in scoped.unwrap_or("").split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, scope) = pair
+                .rsplit_once(':')
+                .filter(|(key, _)| !key.trim().is_empty())
+                .ok_or_else(|| format!("LOGAI_API_KEYS: expected key:scope, got '{}'", pair))?;
+            let scope = Scope::from_name(scope)
+                .ok_or_else(|| format!("LOGAI_API_KEYS: unknown scope '{}' (read, ingest or admin)", scope))?;
+            keys.entry(key.trim().to_string()).or_default().0.insert(scope);
+        }
+        if let Some(key) = admin_key.map(str::trim).filter(|k| !k.is_empty()) {
+            keys.entry(key.to_string()).or_default().0.insert(Scope::Admin);
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+/// Resolve X-API-Key to its scopes (401 when missing or unknown); the per-route
+/// `require_scope` guards decide whether those scopes are enough
 pub async fn require_api_key(
+    State(keys): State<Arc<ApiKeys>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let granted = if keys.is_empty() {
+        Granted(HashSet::from([Scope::Admin]))
+    } else {
+        let provided = request
+            .headers()
+            .get("X-API-Key")
+            .and_then(|v| v.to_str().ok())
+            .ok_or((StatusCode::UNAUTHORIZED, "Missing X-API-Key header"))?;
+        keys.keys
+            .get(provided)
+            .cloned()
+            .ok_or((StatusCode::UNAUTHORIZED, "Invalid API key"))?
+    };
+
+    request.extensions_mut().insert(granted);
+    Ok(next.run(request).await)
+}
+
+/// Route guard: 403 unless the request's key has `scope` (or admin)
+pub async fn require_scope(
+    State(scope): State<Scope>,
     request: Request<Body>,
     next: Next,
 ) -> Result<Response, (StatusCode, &'static str)> {
-    let expected_key = std::env::var("LOGAI_API_KEY").ok();
+    let allowed = request
+        .extensions()
+        .get::<Granted>()
+        .is_some_and(|granted| granted.allows(scope));
+    if !allowed {
+        return Err((StatusCode::FORBIDDEN, "API key lacks the scope for this route"));
+    }
+    Ok(next.run(request).await)
+}
+
+/// The id `request_span` gave this request, in the request extensions for handlers
+/// that pass it on (ingest forwards it to the worker as a NATS header)
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+/// Run the request inside a `request` span carrying a request id, so every log line it
+/// produces can be correlated. A caller's X-Request-Id is kept, otherwise a UUID is
+/// generated; either way it's echoed back in the response header.
+pub async fn request_span(mut request: Request<Body>, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let span = info_span!(
+        "request",
+        service = "logai-api",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let start = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    span.in_scope(|| {
+        info!(
+            status = response.status().as_u16(),
+            latency_ms = start.elapsed().as_millis() as u64,
+            "Request finished"
+        )
+    });
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
 
-    let Some(expected) = expected_key else {
-        return Ok(next.run(request).await);
+/// Count query requests and time ask/chat end to end
+pub async fn track_requests(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let latency = match request.uri().path() {
+        "/api/search" => {
+            metrics.search_requests.inc();
+            None
+        }
+        "/api/ask" => {
+            metrics.ask_requests.inc();
+            Some(&metrics.ask_latency)
+        }
+        "/api/chat" => {
+            metrics.chat_requests.inc();
+            Some(&metrics.chat_latency)
+        }
+        _ => None,
     };
 
-    if expected.is_empty() {
-        return Ok(next.run(request).await);
+    let response = next.run(request).await;
+    if let Some(histogram) = latency {
+        histogram.observe(start.elapsed());
     }
+    response
+}
 
-    let provided = request
-        .headers()
-        .get("X-API-Key")
-        .and_then(|v| v.to_str().ok());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Extension, Router};
+    use std::io;
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    // collects the JSON log lines written while a test runs
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn handler(Extension(RequestId(id)): Extension<RequestId>) -> String {
+        info!("handling");
+        id
+    }
+
+    /// Send one request through `request_span` and return the response's request id
+    /// with the JSON log lines it produced
+    async fn request(incoming_id: Option<&str>) -> (String, Vec<serde_json::Value>) {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .flatten_event(true)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/api/search", get(handler))
+            .layer(middleware::from_fn(request_span));
+        let mut request = Request::builder().uri("/api/search?q=timeout");
+        if let Some(id) = incoming_id {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+
+        let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        // the handler saw the same id through the request extensions
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, request_id.as_bytes());
+        let lines = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let events = lines.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        (request_id, events)
+    }
+
+    #[tokio::test]
+    async fn test_request_id_in_span() {
+        let (request_id, events) = request(None).await;
+        assert!(Uuid::parse_str(&request_id).is_ok());
+
+        let handled = events.iter().find(|e| e["message"] == "handling").expect("handler logged");
+        assert_eq!(handled["span"]["request_id"], request_id.as_str());
+        assert_eq!(handled["span"]["service"], "logai-api");
+        assert_eq!(handled["span"]["path"], "/api/search");
+
+        let finished = events.iter().find(|e| e["message"] == "Request finished").expect("completion logged");
+        assert_eq!(finished["span"]["request_id"], request_id.as_str());
+        assert_eq!(finished["status"], 200);
+        assert!(finished["latency_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_caller_request_id_is_kept() {
+        let (request_id, events) = request(Some("checkout-7f3a")).await;
+        assert_eq!(request_id, "checkout-7f3a");
+        assert!(events.iter().all(|e| e["span"]["request_id"] == "checkout-7f3a"));
+    }
+
+    async fn ok() -> &'static str {
+        "ok"
+    }
+
+    /// Status of `method path` sent with `key` through the same layers as main.rs
+    async fn status(keys: &ApiKeys, method: &str, path: &str, key: Option<&str>) -> StatusCode {
+        let ingest = Router::new()
+            .route("/api/logs", axum::routing::post(ok))
+            .route_layer(middleware::from_fn_with_state(Scope::Ingest, require_scope));
+        let read = Router::new()
+            .route("/api/search", get(ok))
+            .route_layer(middleware::from_fn_with_state(Scope::Read, require_scope));
+        let app = Router::new()
+            .merge(ingest)
+            .merge(read)
+            .layer(middleware::from_fn_with_state(Arc::new(keys.clone()), require_api_key));
+
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(key) = key {
+            request = request.header("X-API-Key", key);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_scope_decisions() {
+        let keys = ApiKeys::parse(Some("dash:read, shipper:ingest,ops:admin,both:read,both:ingest"), None).unwrap();
+        let search = |key| status(&keys, "GET", "/api/search", key);
+        let ingest = |key| status(&keys, "POST", "/api/logs", key);
+
+        assert_eq!(search(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(search(Some("nope")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(ingest(Some("nope")).await, StatusCode::UNAUTHORIZED);
+
+        assert_eq!(search(Some("dash")).await, StatusCode::OK);
+        assert_eq!(ingest(Some("dash")).await, StatusCode::FORBIDDEN);
+
+        assert_eq!(ingest(Some("shipper")).await, StatusCode::OK);
+        assert_eq!(search(Some("shipper")).await, StatusCode::FORBIDDEN);
+
+        for key in ["ops", "both"] {
+            assert_eq!(search(Some(key)).await, StatusCode::OK);
+            assert_eq!(ingest(Some(key)).await, StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_key_is_admin() {
+        let keys = ApiKeys::parse(None, Some("secret")).unwrap();
+        assert_eq!(status(&keys, "GET", "/api/search", Some("secret")).await, StatusCode::OK);
+        assert_eq!(status(&keys, "POST", "/api/logs", Some("secret")).await, StatusCode::OK);
+        assert_eq!(status(&keys, "GET", "/api/search", Some("other")).await, StatusCode::UNAUTHORIZED);
+
+        // no keys at all: authentication is off
+        let open = ApiKeys::parse(None, Some("")).unwrap();
+        assert!(open.is_empty());
+        assert_eq!(status(&open, "POST", "/api/logs", None).await, StatusCode::OK);
+    }
 
-    match provided {
-        Some(key) if key == expected => Ok(next.run(request).await),
-        Some(_) => Err((StatusCode::UNAUTHORIZED, "Invalid API key")),
-        None => Err((StatusCode::UNAUTHORIZED, "Missing X-API-Key header")),
+    #[test]
+    fn test_bad_key_config() {
+        assert!(ApiKeys::parse(Some("dash:write"), None).is_err());
+        assert!(ApiKeys::parse(Some("dash"), None).is_err());
+        assert!(ApiKeys::parse(Some(":read"), None).is_err());
     }
 }
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use chrono::DateTime;
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
 use super::response::ChatMessage;
 
 #[derive(Deserialize)]
@@ -6,6 +8,9 @@ pub struct RawLogRequest {
     pub format: String,
     pub service: String,
     pub lines: Vec<String>,
+    // fold stack-trace continuation lines into the entry above them
+    #[serde(default)]
+    pub multiline: bool,
 }
 
 #[derive(Deserialize)]
@@ -13,18 +18,80 @@ pub struct SearchQuery {
     pub q: String,
     #[serde(default = "default_limit")]
     pub limit: u64,
+    /// Unix seconds or RFC 3339 (`2024-02-23T10:00:00Z`)
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
     pub from: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
     pub to: Option<i64>,
     pub service: Option<String>,
+    pub level: Option<String>, // "error", "warn", ... (case-insensitive)
+    /// Comma-separated `field:value` pairs matched against log fields, e.g. `status_code:500,endpoint:/api/checkout`
+    pub filters: Option<String>,
+    #[serde(default)]
+    pub mode: SearchMode,
+    /// Return where the query's words are in each message (off by default, it costs a pass per hit)
+    #[serde(default)]
+    pub highlight: bool,
+    /// Logs to return before and after each hit from the same service (0-10)
+    #[serde(default)]
+    pub context: u32,
 }
 
 fn default_limit() -> u64 {
     5
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Qdrant vector search only
+    #[default]
+    Semantic,
+    /// Vector search plus a ClickHouse keyword search, fused with Reciprocal Rank Fusion;
+    /// finds exact tokens (error codes, ids) that embeddings blur
+    Hybrid,
+}
+
 #[derive(Deserialize)]
 pub struct AskQuery {
     pub q: String,
+    /// Explicit window (unix seconds or RFC 3339); overrides one read from the question ("yesterday")
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub from: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub to: Option<i64>,
+    /// Drop search hits scoring below this (0-1); defaults to LOGAI_MIN_SCORE
+    pub min_score: Option<f32>,
+    /// Also filter on the service/level read from the question, not just semantic relevance
+    #[serde(default)]
+    pub strict_filters: bool,
+}
+
+#[derive(Deserialize)]
+pub struct TraceQuery {
+    pub trace_id: String,
+}
+
+/// GET /api/logs/export; from/to are unix seconds
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub service: Option<String>,
+    pub level: Option<String>,
+    #[serde(default)]
+    pub format: ExportFormat,
+    // lift the range cap (and the `from` requirement) - the caller asked for everything
+    #[serde(default)]
+    pub unbounded: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Ndjson,
+    Csv,
 }
 
 #[derive(Deserialize)]
@@ -32,11 +99,79 @@ pub struct RecentLogsQuery {
     pub limit: Option<u32>,
     pub service: Option<String>,
     pub level: Option<String>,
+    /// Only return logs older than this: a `next_cursor` from a previous page or a plain timestamp
+    pub before: Option<String>,
+    /// Only return logs newer than this, oldest first, for incremental polling: the
+    /// `next_cursor` of the previous poll or a plain timestamp. Not combined with `before`
+    pub since_ts: Option<String>,
+    /// Time range (unix seconds or RFC 3339), both ends inclusive
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub from: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub to: Option<i64>,
+    /// Comma-separated `field:value` pairs matched against the stored `fields` JSON, e.g. `status_code:500`
+    pub field_filters: Option<String>,
+}
+
+/// DELETE /api/logs: every log whose `field` equals `value` (`trace_id`, or a key of `fields`
+/// such as `user_id`). Both are required so a delete is never unscoped.
+#[derive(Deserialize)]
+pub struct DeleteLogsQuery {
+    #[serde(default)]
+    pub field: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Body of POST /api/explain: the log line itself, or the id of a stored log
+/// (`log` wins when both are given), plus optionally the lines logged around it
+#[derive(Deserialize)]
+pub struct ExplainRequest {
+    pub log: Option<String>,
+    pub log_id: Option<String>,
+    #[serde(default)]
+    pub context: Vec<String>,
+}
+
+/// GET /api/diff: range A is the "good" window, B the one being investigated.
+/// All four bounds are required (unix seconds or RFC 3339).
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub from_a: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub to_a: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub from_b: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub to_b: Option<i64>,
+    pub service: Option<String>,
+}
+
+/// GET /api/patterns; from/to are unix seconds or RFC 3339 (default: the last hour)
+#[derive(Deserialize)]
+pub struct PatternsQuery {
+    pub service: Option<String>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub from: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub to: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    pub service: Option<String>,
+    pub level: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct AlertsQuery {
     pub status: Option<String>,
+    /// Time range (unix seconds or RFC 3339) instead of the last hour (firing) or day
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub from: Option<i64>,
+    #[serde(default, deserialize_with = "unix_or_rfc3339")]
+    pub to: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -44,15 +179,144 @@ pub struct AnomaliesQuery {
     pub service: Option<String>,
 }
 
+/// GET /api/health/services; every service that logged in the last hour unless one is named
+#[derive(Deserialize)]
+pub struct ServiceHealthQuery {
+    pub service: Option<String>,
+}
+
+/// Body of POST /api/causal; without from/to the window ends at the worst error matching `query`
+#[derive(Deserialize)]
+pub struct CausalRequest {
+    pub query: String,
+    pub service: Option<String>,
+    pub from: Option<i64>, // unix seconds
+    pub to: Option<i64>,
+}
+
 #[derive(Deserialize)]
 pub struct ChatRequest {
     pub session_id: String,
     pub message: String,
     #[serde(default)]
     pub history: Vec<ChatMessage>,
+    /// Drop search hits scoring below this (0-1); defaults to LOGAI_MIN_SCORE
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Also filter on the service/level read from the message, not just semantic relevance
+    #[serde(default)]
+    pub strict_filters: bool,
 }
 
 #[derive(Deserialize)]
 pub struct SessionQuery {
     pub session_id: String,
 }
+
+#[derive(Deserialize)]
+pub struct HealthQuery {
+    // ping every dependency instead of just answering
+    #[serde(default)]
+    pub deep: bool,
+}
+
+/// `from`/`to` as unix seconds, given either as an integer or an RFC 3339 string.
+/// Query strings always arrive as strings, so "1708682400" is accepted too.
+fn unix_or_rfc3339<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    struct Timestamp;
+
+    impl<'v> de::Visitor<'v> for Timestamp {
+        type Value = Option<i64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("unix seconds or an RFC 3339 timestamp")
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(Some(v))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            i64::try_from(v).map(Some).map_err(|_| E::custom(format!("timestamp {} out of range", v)))
+        }
+
+        // `from=` with nothing after it is the same as leaving it out
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            if v.trim().is_empty() {
+                return Ok(None);
+            }
+            parse_timestamp(v).map(Some).map_err(E::custom)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'v>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+    }
+
+    deserializer.deserialize_option(Timestamp)
+}
+
+fn parse_timestamp(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    if let Ok(secs) = s.parse::<i64>() {
+        return Ok(secs);
+    }
+    DateTime::parse_from_rfc3339(s).map(|dt| dt.timestamp()).map_err(|_| {
+        format!(
+            "invalid timestamp '{}': expected unix seconds or RFC 3339 (e.g. 2024-02-23T10:00:00Z)",
+            s
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, http::{StatusCode, Uri}, response::IntoResponse};
+
+    fn search(query: &str) -> Result<SearchQuery, (StatusCode, axum::Json<crate::models::ApiError>)> {
+        let uri: Uri = format!("/api/search?{}", query).parse().unwrap();
+        Query::<SearchQuery>::try_from_uri(&uri)
+            .map(|Query(q)| q)
+            .map_err(crate::handlers::bad_query)
+    }
+
+    #[test]
+    fn test_epoch_timestamps() {
+        let q = search("q=timeout&from=1708682400&to=1708686000").ok().unwrap();
+        assert_eq!((q.from, q.to), (Some(1708682400), Some(1708686000)));
+
+        let q = search("q=timeout&from=").ok().unwrap();
+        assert_eq!((q.from, q.to), (None, None));
+    }
+
+    #[test]
+    fn test_rfc3339_timestamps() {
+        let q = search("q=timeout&from=2024-02-23T10:00:00Z&to=2024-02-23T12:00:00%2B02:00").ok().unwrap();
+        assert_eq!(q.from, Some(1708682400));
+        // the offset is honoured: 12:00 at +02:00 is 10:00 UTC
+        assert_eq!(q.to, Some(1708682400));
+
+        let recent: Uri = "/api/logs/recent?from=2024-02-23T10:00:00Z".parse().unwrap();
+        let Query(recent) = Query::<RecentLogsQuery>::try_from_uri(&recent).ok().unwrap();
+        assert_eq!(recent.from, Some(1708682400));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_timestamp_is_400() {
+        let Err(err) = search("q=timeout&from=yesterday") else { panic!("'yesterday' is not a timestamp") };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let message = json["error"].as_str().unwrap();
+        assert!(message.contains("invalid timestamp 'yesterday'"), "{}", message);
+        assert!(message.contains("RFC 3339"));
+        assert_eq!(json["code"], 400);
+    }
+}
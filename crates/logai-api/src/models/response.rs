@@ -1,6 +1,7 @@
 use axum::{http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
-use logai_rag::{CausalChain, CausalLink, LogEvent};
+use std::collections::BTreeMap;
+use logai_rag::{CausalChain, CausalLink, LogEvent, TraceResponse};
 
 /// JSON error response
 #[derive(Serialize)]
@@ -26,6 +27,23 @@ impl ApiError {
     }
 }
 
+/// Readiness report from GET /health?deep=true and /readyz
+#[derive(Serialize)]
+pub struct HealthReport {
+    pub status: String, // "ok" or "unavailable"
+    pub failed: Vec<String>,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+#[derive(Serialize)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[derive(Serialize)]
 pub struct IngestResponse {
     pub id: String,
@@ -39,6 +57,36 @@ pub struct RawIngestResponse {
     pub failed: usize,
 }
 
+/// OTLP's ExportLogsServiceResponse: empty means every record was accepted
+#[derive(Serialize)]
+pub struct OtlpExportResponse {}
+
+/// What DELETE /api/logs removed. A chunk point holding any deleted log counts once.
+#[derive(Serialize)]
+pub struct DeleteLogsResponse {
+    pub clickhouse_deleted: u64,
+    pub qdrant_deleted: u64,
+}
+
+/// POST /api/explain
+#[derive(Serialize)]
+pub struct ExplainResponse {
+    /// The line that was explained (for a `log_id`, rebuilt from the stored log)
+    pub log: String,
+    pub explanation: String,
+    pub next_steps: Vec<String>,
+    /// Served from the explanation cache, no LLM call
+    pub cached: bool,
+    pub response_time_ms: u128,
+    pub provider: String,
+}
+
+/// Formats POST /api/logs/raw accepts (JSON logs go to POST /api/logs instead)
+#[derive(Serialize)]
+pub struct FormatsResponse {
+    pub formats: Vec<&'static str>,
+}
+
 #[derive(Serialize)]
 pub struct SearchResult {
     pub score: f32,
@@ -47,6 +95,29 @@ pub struct SearchResult {
     pub level: String,
     pub message: String,
     pub timestamp: String,
+    /// `[start, end)` byte ranges of `message` matching the query's words, with `highlight=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<Vec<(usize, usize)>>,
+    /// The hit's neighbours in its service, with `context=N`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<HitContext>,
+}
+
+/// Up to N logs on either side of a search hit, oldest first (like `grep -C`)
+#[derive(Serialize)]
+pub struct HitContext {
+    pub before: Vec<RecentLogRow>,
+    pub after: Vec<RecentLogRow>,
+}
+
+/// A log the answer was given from, as stored (ids link to GET /api/logs/{id})
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceLog {
+    pub log_id: String,
+    pub timestamp: String,
+    pub service: String,
+    pub level: String,
+    pub message: String,
 }
 
 #[derive(Serialize)]
@@ -56,8 +127,12 @@ pub struct AskResponse {
     pub response_time_ms: u128,
     pub provider: String,
     pub query_analysis: QueryAnalysisResponse,
+    /// The logs handed to the LLM, best ranked first
+    pub sources: Vec<SourceLog>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub causal_chain: Option<CausalChainResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceResponse>,
 }
 
 /// Causal chain for "why" questions
@@ -68,6 +143,12 @@ pub struct CausalChainResponse {
     pub root_cause: Option<LogEventResponse>,
     pub summary: String,
     pub recommendation: Option<String>,
+    /// Product of the link confidences (each link has to hold for the chain to); None without links
+    pub overall_confidence: Option<f64>,
+    /// Seconds from the root cause to the effect; None without a root cause
+    pub time_span_seconds: Option<i64>,
+    /// Distinct services in the chain, in order of their first event (earliest first)
+    pub services_involved: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -86,9 +167,53 @@ pub struct LogEventResponse {
     pub message: String,
 }
 
+/// GET /api/trace/{trace_id}/tree: the trace's spans nested under their parents
+#[derive(Serialize)]
+pub struct TraceTreeResponse {
+    pub trace_id: String,
+    /// First to last log of the trace
+    pub duration_ms: i64,
+    /// Top-level spans (no parent, or a parent that logged nothing), earliest first
+    pub spans: Vec<SpanNode>,
+    /// Logs outside the tree in time order: those without a span id, or all of them
+    /// when no log links to a parent span
+    pub timeline: Vec<LogEventResponse>,
+}
+
+#[derive(Serialize)]
+pub struct SpanNode {
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub service: String,
+    pub start: String,
+    /// From the span's first log to the last log of the span or any span below it
+    pub duration_ms: i64,
+    pub logs: Vec<LogEventResponse>,
+    pub children: Vec<SpanNode>,
+}
+
 impl From<CausalChain> for CausalChainResponse {
     fn from(c: CausalChain) -> Self {
+        let overall_confidence = (!c.chain.is_empty()).then(|| c.chain.iter().map(|l| l.confidence).product());
+        let time_span_seconds = c
+            .root_cause
+            .as_ref()
+            .map(|root| (c.effect.timestamp - root.timestamp).num_seconds());
+
+        let mut events: Vec<&LogEvent> = c.chain.iter().flat_map(|l| [&l.effect, &l.cause]).collect();
+        events.extend([&c.effect].into_iter().chain(c.root_cause.as_ref()));
+        events.sort_by_key(|e| e.timestamp);
+        let mut services_involved: Vec<String> = Vec::new();
+        for event in events {
+            if !services_involved.contains(&event.service) {
+                services_involved.push(event.service.clone());
+            }
+        }
+
         Self {
+            overall_confidence,
+            time_span_seconds,
+            services_involved,
             effect: c.effect.into(),
             chain: c.chain.into_iter().map(|l| l.into()).collect(),
             root_cause: c.root_cause.map(|r| r.into()),
@@ -135,6 +260,36 @@ pub struct StatsResponse {
     pub services_count: u64,
     pub embeddings_count: u64,
     pub storage_mb: f64,
+    pub llm_cache_hits: u64,
+    pub llm_cache_misses: u64,
+    pub session_count: usize,
+    /// Percentiles of `fields.latency_ms` over the last 24h; null when no log carried a numeric latency
+    pub latency_p50_ms: Option<f64>,
+    pub latency_p95_ms: Option<f64>,
+    pub latency_p99_ms: Option<f64>,
+    pub slowest_endpoints: Vec<EndpointLatency>,
+    /// Busiest services over the last hour, most logs first
+    pub top_services: Vec<ServiceVolume>,
+}
+
+#[derive(Serialize, Deserialize, clickhouse::Row)]
+pub struct EndpointLatency {
+    pub endpoint: String,
+    pub p95_ms: f64,
+    pub requests: u64,
+}
+
+#[derive(Serialize, Deserialize, clickhouse::Row)]
+pub struct ServiceVolume {
+    pub service: String,
+    pub logs: u64,
+}
+
+#[derive(Serialize)]
+pub struct ServicesResponse {
+    pub services: Vec<String>,
+    /// When the list was last read from ClickHouse (RFC 3339); services ingested since are included too
+    pub last_refreshed: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, clickhouse::Row)]
@@ -146,6 +301,70 @@ pub struct RecentLogRow {
     pub timestamp: String,
 }
 
+/// One page of recent logs, newest first (oldest first when polling with `since_ts`)
+#[derive(Serialize)]
+pub struct RecentLogsResponse {
+    pub logs: Vec<RecentLogRow>,
+    /// Pass as `before` to fetch the next (older) page, None when there are no more rows.
+    /// With `since_ts`: the newest row's position, the `since_ts` of the next poll (None
+    /// when nothing new came in, keep the previous one)
+    pub next_cursor: Option<String>,
+}
+
+/// Error/fatal counts in two ranges, A (baseline) and B
+#[derive(Serialize)]
+pub struct DiffResponse {
+    pub from_a: i64,
+    pub to_a: i64,
+    pub from_b: i64,
+    pub to_b: i64,
+    pub service: Option<String>,
+    pub total_a: u64,
+    pub total_b: u64,
+    /// Every error category seen in either range, biggest increase first
+    pub categories: Vec<ErrorDelta>,
+    /// Message templates only seen in B, most frequent first
+    pub new_errors: Vec<ErrorDelta>,
+    /// Message templates only seen in A
+    pub disappeared_errors: Vec<ErrorDelta>,
+    /// Templates seen in both whose count went up, biggest increase first
+    pub biggest_increases: Vec<ErrorDelta>,
+}
+
+/// Message templates in a range, most frequent first
+#[derive(Serialize)]
+pub struct PatternsResponse {
+    pub from: i64,
+    pub to: i64,
+    pub service: Option<String>,
+    /// Logs read, across every template (not just the ones returned)
+    pub total: u64,
+    /// The range had too many distinct messages to read them all: the rarest were skipped,
+    /// so `total` and the counts are lower bounds
+    pub truncated: bool,
+    pub patterns: Vec<LogPattern>,
+}
+
+/// Logs sharing a message once numbers, UUIDs, IPs and paths are masked
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LogPattern {
+    pub template: String,
+    pub count: u64,
+    /// One of the messages behind the template, unmasked
+    pub example: String,
+    /// Count per level, e.g. {"Error": 12, "Info": 3}
+    pub level_distribution: BTreeMap<String, u64>,
+}
+
+/// One error category or message template with its count in each range
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ErrorDelta {
+    pub key: String,
+    pub count_a: u64,
+    pub count_b: u64,
+    pub change: i64,
+}
+
 #[derive(Serialize)]
 pub struct AlertsResponse {
     pub alerts: Vec<AlertItem>,
@@ -164,9 +383,39 @@ pub struct AlertItem {
 #[derive(Serialize)]
 pub struct AnomaliesResponse {
     pub anomalies: Vec<AnomalyItem>,
+    /// Per-service log counts by level over the last 5 minutes (services that logged in the last hour)
+    pub level_distribution: Vec<LevelCounts>,
+    pub checked_at: String,
+}
+
+#[derive(Serialize)]
+pub struct LevelCounts {
+    pub service: String,
+    pub info: u64,
+    pub warn: u64,
+    pub error: u64,
+    pub fatal: u64,
+}
+
+#[derive(Serialize)]
+pub struct ServiceHealthResponse {
+    pub services: Vec<ServiceHealth>,
+    /// The window scores are computed over
+    pub window_minutes: u64,
     pub checked_at: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ServiceHealth {
+    pub service: String,
+    /// 0 (down) to 100 (nothing wrong)
+    pub score: u8,
+    /// healthy (80+), degraded (50-79) or critical
+    pub status: String,
+    /// What cost the service points, worst first
+    pub reasons: Vec<String>,
+}
+
 #[derive(Serialize)]
 pub struct AnomalyItem {
     pub service: String,
@@ -191,7 +440,9 @@ pub struct ChatApiResponse {
     pub provider: String,
     pub context_logs: usize,
     pub conversation_turn: usize,
+    /// The context lines as sent to the LLM; `sources` has the same logs as records
     pub source_logs: Vec<String>,
+    pub sources: Vec<SourceLog>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub causal_chain: Option<CausalChainResponse>,
 }
@@ -203,3 +454,76 @@ pub struct SessionInfo {
     pub last_logs_count: usize,
     pub age_seconds: u64,
 }
+
+#[derive(Serialize)]
+pub struct SessionsResponse {
+    /// Newest first
+    pub sessions: Vec<SessionInfo>,
+    pub count: usize,
+    /// `LOGAI_MAX_SESSIONS`: past it the oldest session is evicted for a new one
+    pub max_sessions: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn event(timestamp: &str, service: &str, message: &str) -> LogEvent {
+        LogEvent {
+            timestamp: timestamp.parse::<DateTime<Utc>>().unwrap(),
+            level: "Error".to_string(),
+            service: service.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn link(effect: &LogEvent, cause: &LogEvent, confidence: f64) -> CausalLink {
+        CausalLink {
+            effect: effect.clone(),
+            cause: cause.clone(),
+            confidence,
+            explanation: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_chain_summary_fields() {
+        let crash = event("2026-02-10T02:47:30Z", "checkout", "Payment service crashed");
+        let timeouts = event("2026-02-10T02:46:00Z", "payments", "Upstream timeout");
+        let pool = event("2026-02-10T02:45:10Z", "postgres", "Connection pool exhausted");
+        let leak = event("2026-02-10T02:30:00Z", "payments", "Memory usage exceeded 90%");
+
+        let chain = CausalChain {
+            query: "why did checkout crash?".to_string(),
+            effect: crash.clone(),
+            chain: vec![link(&crash, &timeouts, 0.9), link(&timeouts, &pool, 0.8), link(&pool, &leak, 0.5)],
+            root_cause: Some(leak),
+            summary: String::new(),
+            recommendation: None,
+        };
+
+        let response = CausalChainResponse::from(chain);
+        assert_eq!(response.time_span_seconds, Some(17 * 60 + 30));
+        assert_eq!(response.services_involved, vec!["payments", "postgres", "checkout"]);
+        assert!((response.overall_confidence.unwrap() - 0.36).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chain_without_links() {
+        let crash = event("2026-02-10T02:47:30Z", "checkout", "Payment service crashed");
+        let chain = CausalChain {
+            query: String::new(),
+            effect: crash,
+            chain: vec![],
+            root_cause: None,
+            summary: String::new(),
+            recommendation: None,
+        };
+
+        let response = CausalChainResponse::from(chain);
+        assert_eq!(response.overall_confidence, None);
+        assert_eq!(response.time_span_seconds, None);
+        assert_eq!(response.services_involved, vec!["checkout"]);
+    }
+}
@@ -1,23 +1,429 @@
+use chrono::{DateTime, Utc};
 use clickhouse::Client as ClickHouseClient;
 use fastembed::TextEmbedding;
+use logai_core::embedding::CollectionLayout;
 use logai_core::parser::ParserRegistry;
+use logai_core::{LogEntry, LogLevel};
 use logai_rag::{RagEngine, Reranker};
 use qdrant_client::Qdrant;
-use std::collections::HashMap;
-use std::sync::{Mutex, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
-use crate::models::ChatMessage;
-
-pub const COLLECTION_NAME: &str = "log_embeddings";
+use crate::chat_gate::ChatGate;
+use crate::metrics::Metrics;
+use crate::models::{ChatMessage, SessionInfo, SourceLog};
 
 #[derive(Clone, Debug)]
 pub struct ChatSession {
     pub history: Vec<ChatMessage>,
     pub last_logs: Vec<String>,
+    /// Records of `last_logs`, returned again when a follow-up reuses them
+    pub last_sources: Vec<SourceLog>,
     pub last_query: String,
     pub created_at: std::time::Instant,
 }
 
+impl ChatSession {
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        self.created_at.elapsed() > ttl
+    }
+
+    pub fn info(&self, session_id: &str) -> SessionInfo {
+        SessionInfo {
+            session_id: session_id.to_string(),
+            turns: self.history.len() / 2,
+            last_logs_count: self.last_logs.len(),
+            age_seconds: self.created_at.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Chat session limits
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    pub ttl: Duration,
+    pub max_sessions: usize,
+    pub sweep_interval: Duration,
+}
+
+impl SessionConfig {
+    /// Environment variables:
+    /// - LOGAI_SESSION_TTL_SECS: Drop sessions older than this (default: 3600)
+    /// - LOGAI_MAX_SESSIONS: Max sessions kept in memory, oldest evicted first (default: 1000)
+    /// - LOGAI_SESSION_SWEEP_SECS: How often the cleanup task runs, at least 1 (default: 60)
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            ttl: Duration::from_secs(read("LOGAI_SESSION_TTL_SECS", 3600)),
+            max_sessions: read("LOGAI_MAX_SESSIONS", 1000) as usize,
+            // tokio's interval panics on a zero period
+            sweep_interval: Duration::from_secs(read("LOGAI_SESSION_SWEEP_SECS", 60).max(1)),
+        }
+    }
+}
+
+/// How wide each endpoint searches Qdrant and how many hits survive reranking: wider
+/// finds more (recall), narrower answers sooner (latency)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetrievalConfig {
+    pub ask_search_width: u64,
+    /// chat, and the semantic half of causal analysis
+    pub chat_search_width: u64,
+    pub ask_rerank_k: usize,
+    pub chat_rerank_k: usize,
+    /// Logs handed to the causal analyzer
+    pub causal_rerank_k: usize,
+}
+
+impl Default for RetrievalConfig {
+    fn default() -> Self {
+        Self {
+            ask_search_width: 30,
+            chat_search_width: 100,
+            ask_rerank_k: 10,
+            chat_rerank_k: 20,
+            causal_rerank_k: 50,
+        }
+    }
+}
+
+impl RetrievalConfig {
+    /// Environment variables:
+    /// - LOGAI_SEARCH_WIDTH: Qdrant hits per search for every endpoint (default: ask 30, chat/causal 100)
+    /// - LOGAI_RERANK_TOP_K: Hits kept after reranking for ask and chat (default: ask 10,
+    ///   chat LOGAI_MAX_CONTEXT_LOGS or 20)
+    /// - LOGAI_CAUSAL_RERANK_K: Logs kept for causal analysis (default: 50)
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let read = |key: &str| var(key).and_then(|s| s.trim().parse::<u64>().ok()).filter(|n| *n > 0);
+        let width = read("LOGAI_SEARCH_WIDTH");
+        let top_k = read("LOGAI_RERANK_TOP_K").map(|k| k as usize);
+
+        Self {
+            ask_search_width: width.unwrap_or(defaults.ask_search_width),
+            chat_search_width: width.unwrap_or(defaults.chat_search_width),
+            ask_rerank_k: top_k.unwrap_or(defaults.ask_rerank_k),
+            chat_rerank_k: top_k
+                .or(read("LOGAI_MAX_CONTEXT_LOGS").map(|k| k as usize))
+                .unwrap_or(defaults.chat_rerank_k),
+            causal_rerank_k: read("LOGAI_CAUSAL_RERANK_K")
+                .map(|k| k as usize)
+                .unwrap_or(defaults.causal_rerank_k),
+        }
+    }
+}
+
+/// How much each signal weighs in a service's health score (see `handlers::health_score`).
+/// Weights are relative: only their ratios matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthWeights {
+    pub error_rate: f64,
+    pub volume: f64,
+    pub fatal: f64,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            error_rate: 50.0,
+            volume: 25.0,
+            fatal: 25.0,
+        }
+    }
+}
+
+impl HealthWeights {
+    /// Environment variables:
+    /// - LOGAI_HEALTH_ERROR_WEIGHT: Weight of the error rate (default: 50)
+    /// - LOGAI_HEALTH_VOLUME_WEIGHT: Weight of log volume off its baseline (default: 25)
+    /// - LOGAI_HEALTH_FATAL_WEIGHT: Weight of fatal logs (default: 25)
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: f64| {
+            var(key)
+                .and_then(|s| s.trim().parse::<f64>().ok())
+                .filter(|w| w.is_finite() && *w >= 0.0)
+                .unwrap_or(default)
+        };
+        Self {
+            error_rate: read("LOGAI_HEALTH_ERROR_WEIGHT", defaults.error_rate),
+            volume: read("LOGAI_HEALTH_VOLUME_WEIGHT", defaults.volume),
+            fatal: read("LOGAI_HEALTH_FATAL_WEIGHT", defaults.fatal),
+        }
+    }
+}
+
+/// Fraction of Debug and Info logs kept at ingest (Trace counts as Debug); Notice and
+/// everything above always goes through, so the anomaly rules still see every error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingConfig {
+    pub debug_rate: f64,
+    pub info_rate: f64,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            debug_rate: 1.0,
+            info_rate: 1.0,
+        }
+    }
+}
+
+impl SamplingConfig {
+    /// Environment variables:
+    /// - LOGAI_SAMPLE_DEBUG_RATE: Fraction of Trace/Debug logs kept, 0.0-1.0 (default: 1.0)
+    /// - LOGAI_SAMPLE_INFO_RATE: Fraction of Info logs kept, 0.0-1.0 (default: 1.0)
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: f64| {
+            var(key)
+                .and_then(|s| s.trim().parse::<f64>().ok())
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .unwrap_or(default)
+        };
+        Self {
+            debug_rate: read("LOGAI_SAMPLE_DEBUG_RATE", defaults.debug_rate),
+            info_rate: read("LOGAI_SAMPLE_INFO_RATE", defaults.info_rate),
+        }
+    }
+
+    /// Whether `entry` is published. The draw comes from the low bits of its (random) id,
+    /// so no RNG is needed and a log is always sampled the same way.
+    pub fn keeps(&self, entry: &LogEntry) -> bool {
+        let rate = match entry.level {
+            LogLevel::Trace | LogLevel::Debug => self.debug_rate,
+            LogLevel::Info => self.info_rate,
+            _ => return true,
+        };
+        let draw = (entry.id.as_u128() & SAMPLE_MASK) as f64 / (SAMPLE_MASK + 1) as f64;
+        draw < rate
+    }
+}
+
+// the id's last 48 bits: random in both v4 and v7 uuids
+const SAMPLE_MASK: u128 = (1 << 48) - 1;
+
+/// Drop expired sessions, then evict the oldest until at most `max_sessions` remain.
+/// Returns how many sessions were removed.
+pub fn evict_sessions(
+    sessions: &mut HashMap<String, ChatSession>,
+    ttl: Duration,
+    max_sessions: usize,
+) -> usize {
+    let before = sessions.len();
+    sessions.retain(|_, session| !session.is_expired(ttl));
+
+    if sessions.len() > max_sessions {
+        let mut by_age: Vec<(String, Instant)> = sessions
+            .iter()
+            .map(|(id, session)| (id.clone(), session.created_at))
+            .collect();
+        by_age.sort_by_key(|(_, created_at)| *created_at);
+
+        let excess = sessions.len() - max_sessions;
+        for (id, _) in by_age.into_iter().take(excess) {
+            sessions.remove(&id);
+        }
+    }
+
+    before - sessions.len()
+}
+
+/// Every live session, newest first. The read lock is only held to copy out the counts,
+/// so chats waiting on the write lock aren't held up by the sort.
+pub fn list_sessions(sessions: &RwLock<HashMap<String, ChatSession>>) -> Vec<SessionInfo> {
+    let mut infos: Vec<SessionInfo> = {
+        let sessions = sessions.read().unwrap();
+        sessions.iter().map(|(id, session)| session.info(id)).collect()
+    };
+    infos.sort_by(|a, b| a.age_seconds.cmp(&b.age_seconds).then_with(|| a.session_id.cmp(&b.session_id)));
+    infos
+}
+
+/// Drop one session; the number left, or `None` if there was no such session
+pub fn remove_session(sessions: &RwLock<HashMap<String, ChatSession>>, id: &str) -> Option<usize> {
+    let mut sessions = sessions.write().unwrap();
+    sessions.remove(id)?;
+    Some(sessions.len())
+}
+
+/// Service names for /api/services, so listing them isn't a DISTINCT scan per request.
+/// Filled on ingest and by a periodic ClickHouse refresh (which also picks up services
+/// ingested through other API instances).
+#[derive(Default)]
+pub struct ServiceRegistry {
+    names: RwLock<HashSet<String>>,
+    last_refreshed: RwLock<Option<DateTime<Utc>>>,
+}
+
+impl ServiceRegistry {
+    /// Environment variables:
+    /// - LOGAI_SERVICES_REFRESH_SECS: How often the list is re-read from ClickHouse (default: 60)
+    pub fn refresh_interval() -> Duration {
+        let secs = std::env::var("LOGAI_SERVICES_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(60);
+        Duration::from_secs(secs)
+    }
+
+    /// A service seen on ingest, visible before the next refresh
+    pub fn record(&self, service: &str) {
+        if self.names.read().unwrap().contains(service) {
+            return;
+        }
+        self.names.write().unwrap().insert(service.to_string());
+    }
+
+    /// Merge the services ClickHouse knows about; logs still in flight keep theirs
+    pub fn refresh(&self, services: Vec<String>) {
+        self.names.write().unwrap().extend(services);
+        *self.last_refreshed.write().unwrap() = Some(Utc::now());
+    }
+
+    /// Sorted names and when ClickHouse was last read (None = never)
+    pub fn snapshot(&self) -> (Vec<String>, Option<DateTime<Utc>>) {
+        let mut names: Vec<String> = self.names.read().unwrap().iter().cloned().collect();
+        names.sort();
+        (names, *self.last_refreshed.read().unwrap())
+    }
+}
+
+/// N copies of the embedding model, so concurrent searches don't all queue on one mutex.
+/// Each copy costs the model's full memory (~90 MB for the default MiniLM, over 1 GB for
+/// the large models), hence a small, configured size.
+pub struct ModelPool<T> {
+    models: Vec<Mutex<T>>,
+    next: AtomicUsize,
+}
+
+impl<T> ModelPool<T> {
+    pub fn new(models: Vec<T>) -> Self {
+        assert!(!models.is_empty(), "model pool needs at least one model");
+        Self {
+            models: models.into_iter().map(Mutex::new).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Environment variables:
+    /// - LOGAI_EMBED_POOL_SIZE: Embedding model instances to load (default: 1)
+    pub fn size_from_env() -> usize {
+        std::env::var("LOGAI_EMBED_POOL_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(1)
+    }
+
+    /// Run `f` on a free model; when all are busy, wait for one (round robin)
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let n = self.models.len();
+        for i in 0..n {
+            if let Ok(mut model) = self.models[(start + i) % n].try_lock() {
+                return f(&mut model);
+            }
+        }
+        let mut model = self.models[start % n].lock().unwrap();
+        f(&mut model)
+    }
+}
+
+/// Query embeddings by query text, so a repeated search (CLI retry, dashboard refresh)
+/// doesn't queue on the model mutex again
+pub struct EmbeddingCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Vec<f32>, Instant)>>,
+}
+
+impl EmbeddingCache {
+    /// capacity of 0 disables caching
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Environment variables:
+    /// - LOGAI_EMBED_CACHE_SIZE: Max cached query embeddings, 0 disables (default: 512)
+    /// - LOGAI_EMBED_CACHE_TTL_SECS: How long an embedding is reused (default: 60)
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self::new(
+            read("LOGAI_EMBED_CACHE_SIZE", 512) as usize,
+            Duration::from_secs(read("LOGAI_EMBED_CACHE_TTL_SECS", 60)),
+        )
+    }
+
+    /// The cached vector for `query`, or `embed(query)` stored for next time. The cache lock
+    /// isn't held while embedding, so a slow embed doesn't block hits for other queries.
+    pub fn get_or_embed<E>(
+        &self,
+        query: &str,
+        embed: impl FnOnce(&str) -> Result<Vec<f32>, E>,
+    ) -> Result<Vec<f32>, E> {
+        if self.capacity == 0 {
+            return embed(query);
+        }
+        if let Some((vector, inserted_at)) = self.entries.lock().unwrap().get(query)
+            && inserted_at.elapsed() <= self.ttl
+        {
+            return Ok(vector.clone());
+        }
+
+        let vector = embed(query)?;
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(query) {
+            entries.retain(|_, (_, inserted_at)| inserted_at.elapsed() <= self.ttl);
+        }
+        // still full: drop the oldest
+        if entries.len() >= self.capacity && !entries.contains_key(query) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (_, inserted_at))| *inserted_at)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = oldest {
+                entries.remove(&key);
+            }
+        }
+        entries.insert(query.to_string(), (vector.clone(), Instant::now()));
+        Ok(vector)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum QueryIntent {
     NewSearch,
@@ -28,9 +434,274 @@ pub struct AppState {
     pub nats: async_nats::Client,
     pub qdrant: Qdrant,
     pub clickhouse: ClickHouseClient,
-    pub model: Mutex<TextEmbedding>,
+    pub model: ModelPool<TextEmbedding>,
+    pub embedding_cache: EmbeddingCache,
     pub parser_registry: ParserRegistry,
     pub rag_engine: RagEngine,
     pub reranker: Reranker,
     pub sessions: RwLock<HashMap<String, ChatSession>>,
+    pub session_config: SessionConfig,
+    pub retrieval: RetrievalConfig,
+    pub health_weights: HealthWeights,
+    pub sampling: SamplingConfig,
+    pub chat_gate: ChatGate,
+    pub metrics: Arc<Metrics>,
+    /// NATS subject logs are published on (`NATS_INGEST_SUBJECT`)
+    pub ingest_subject: String,
+    /// One permit per live /api/logs/stream subscriber
+    pub stream_slots: Arc<Semaphore>,
+    pub services: ServiceRegistry,
+    /// Default floor for ask/chat hit scores; hits below it never reach the LLM
+    pub min_score: f32,
+    /// Qdrant scores are Euclid distances (`QDRANT_DISTANCE=euclid`), lower is closer; the
+    /// `min_score` floor is skipped then
+    pub scores_are_distances: bool,
+    /// One embeddings collection, or one per service (`QDRANT_COLLECTION_PER_SERVICE`)
+    pub collections: CollectionLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logai_core::testing::vars;
+
+    fn session_aged(secs: u64) -> ChatSession {
+        ChatSession {
+            history: Vec::new(),
+            last_logs: Vec::new(),
+            last_sources: Vec::new(),
+            last_query: String::new(),
+            created_at: Instant::now() - Duration::from_secs(secs),
+        }
+    }
+
+    #[test]
+    fn test_retrieval_config_from_env() {
+        // nothing set: today's widths
+        assert_eq!(RetrievalConfig::from_vars(vars(&[])), RetrievalConfig::default());
+        let defaults = RetrievalConfig::default();
+        assert_eq!((defaults.ask_search_width, defaults.chat_search_width), (30, 100));
+        assert_eq!((defaults.ask_rerank_k, defaults.chat_rerank_k, defaults.causal_rerank_k), (10, 20, 50));
+
+        let tuned = RetrievalConfig::from_vars(vars(&[
+            ("LOGAI_SEARCH_WIDTH", "200"),
+            ("LOGAI_RERANK_TOP_K", " 15 "),
+            ("LOGAI_CAUSAL_RERANK_K", "80"),
+        ]));
+        assert_eq!(
+            tuned,
+            RetrievalConfig {
+                ask_search_width: 200,
+                chat_search_width: 200,
+                ask_rerank_k: 15,
+                chat_rerank_k: 15,
+                causal_rerank_k: 80,
+            }
+        );
+
+        // chat still honours LOGAI_MAX_CONTEXT_LOGS; junk and 0 fall back to the defaults
+        let legacy = RetrievalConfig::from_vars(vars(&[
+            ("LOGAI_MAX_CONTEXT_LOGS", "30"),
+            ("LOGAI_SEARCH_WIDTH", "0"),
+            ("LOGAI_CAUSAL_RERANK_K", "lots"),
+        ]));
+        assert_eq!(legacy.chat_rerank_k, 30);
+        assert_eq!(legacy.ask_rerank_k, 10);
+        assert_eq!(legacy.chat_search_width, 100);
+        assert_eq!(legacy.causal_rerank_k, 50);
+    }
+
+    #[test]
+    fn test_health_weights_from_env() {
+        assert_eq!(HealthWeights::from_vars(vars(&[])), HealthWeights::default());
+
+        // volume ignored entirely; a negative weight falls back to the default
+        let tuned = HealthWeights::from_vars(vars(&[
+            ("LOGAI_HEALTH_VOLUME_WEIGHT", "0"),
+            ("LOGAI_HEALTH_FATAL_WEIGHT", "-5"),
+            ("LOGAI_HEALTH_ERROR_WEIGHT", " 70 "),
+        ]));
+        assert_eq!(tuned, HealthWeights { error_rate: 70.0, volume: 0.0, fatal: 25.0 });
+    }
+
+    #[test]
+    fn test_sampling_drops_info_keeps_errors() {
+        let entry = |level: LogLevel| {
+            LogEntry::from_raw(logai_core::RawLogEntry {
+                message: "GET /health 200".to_string(),
+                timestamp: None,
+                service: Some("checkout".to_string()),
+                level: Some(level),
+                trace_id: None,
+                span_id: None,
+                parent_span_id: None,
+                fields: HashMap::new(),
+            })
+        };
+        let kept = |sampling: &SamplingConfig, level: LogLevel| (0..1000).filter(|_| sampling.keeps(&entry(level))).count();
+
+        // defaults keep everything
+        let all = SamplingConfig::from_vars(vars(&[]));
+        assert_eq!(all, SamplingConfig::default());
+        assert_eq!(kept(&all, LogLevel::Debug), 1000);
+        assert_eq!(kept(&all, LogLevel::Info), 1000);
+
+        // an out-of-range rate falls back to the default
+        let sampling = SamplingConfig::from_vars(vars(&[
+            ("LOGAI_SAMPLE_INFO_RATE", "0.0"),
+            ("LOGAI_SAMPLE_DEBUG_RATE", "1.5"),
+        ]));
+        assert_eq!(sampling, SamplingConfig { debug_rate: 1.0, info_rate: 0.0 });
+        assert_eq!(kept(&sampling, LogLevel::Info), 0);
+        for level in [LogLevel::Warn, LogLevel::Error, LogLevel::Fatal] {
+            assert_eq!(kept(&sampling, level), 1000);
+        }
+
+        // about half at 0.5
+        let half = SamplingConfig { debug_rate: 0.5, info_rate: 1.0 };
+        assert!((400..600).contains(&kept(&half, LogLevel::Debug)));
+    }
+
+    #[test]
+    fn test_evicts_expired_sessions() {
+        let mut sessions = HashMap::new();
+        sessions.insert("fresh".to_string(), session_aged(10));
+        sessions.insert("stale".to_string(), session_aged(7200));
+
+        let removed = evict_sessions(&mut sessions, Duration::from_secs(3600), 100);
+
+        assert_eq!(removed, 1);
+        assert!(sessions.contains_key("fresh"));
+        assert!(!sessions.contains_key("stale"));
+    }
+
+    #[test]
+    fn test_new_service_appears_after_refresh() {
+        let registry = ServiceRegistry::default();
+        registry.refresh(vec!["api".to_string(), "auth".to_string()]);
+        let (_, first_refresh) = registry.snapshot();
+
+        // "billing" reached ClickHouse through another instance; the next tick picks it up
+        std::thread::sleep(Duration::from_millis(2));
+        registry.refresh(vec!["api".to_string(), "auth".to_string(), "billing".to_string()]);
+        let (names, last_refreshed) = registry.snapshot();
+        assert_eq!(names, vec!["api", "auth", "billing"]);
+        assert!(last_refreshed > first_refresh);
+
+        // ingested here: visible right away, without waiting for a refresh
+        registry.record("checkout");
+        assert!(registry.snapshot().0.contains(&"checkout".to_string()));
+    }
+
+    #[test]
+    fn test_evicts_oldest_over_cap() {
+        let mut sessions = HashMap::new();
+        sessions.insert("oldest".to_string(), session_aged(300));
+        sessions.insert("middle".to_string(), session_aged(200));
+        sessions.insert("newest".to_string(), session_aged(100));
+
+        let removed = evict_sessions(&mut sessions, Duration::from_secs(3600), 2);
+
+        assert_eq!(removed, 1);
+        assert!(!sessions.contains_key("oldest"));
+        assert_eq!(sessions.len(), 2);
+    }
+
+    #[test]
+    fn test_list_and_delete_sessions() {
+        let sessions = RwLock::new(HashMap::new());
+        let mut chatted = session_aged(30);
+        chatted.history = vec![
+            ChatMessage { role: "user".to_string(), content: "errors in checkout?".to_string() },
+            ChatMessage { role: "assistant".to_string(), content: "Two timeouts.".to_string() },
+        ];
+        chatted.last_logs = vec!["{}".to_string(); 3];
+        sessions.write().unwrap().insert("dashboard-1".to_string(), chatted);
+        sessions.write().unwrap().insert("cli-2".to_string(), session_aged(5));
+
+        let listed = list_sessions(&sessions);
+        let ids: Vec<&str> = listed.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, ["cli-2", "dashboard-1"]);
+        assert_eq!((listed[1].turns, listed[1].last_logs_count), (1, 3));
+        assert!(listed[1].age_seconds >= 30);
+
+        assert_eq!(remove_session(&sessions, "dashboard-1"), Some(1));
+        let ids: Vec<String> = list_sessions(&sessions).into_iter().map(|s| s.session_id).collect();
+        assert_eq!(ids, ["cli-2"]);
+        assert_eq!(remove_session(&sessions, "dashboard-1"), None);
+    }
+
+    // a "model" that takes 50ms per embed, like a real one under load
+    fn slow_embed(_: &mut ()) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    fn embed_concurrently(pool: &ModelPool<()>, requests: usize) -> Duration {
+        let start = Instant::now();
+        std::thread::scope(|s| {
+            for _ in 0..requests {
+                s.spawn(|| pool.with(slow_embed));
+            }
+        });
+        start.elapsed()
+    }
+
+    #[test]
+    fn test_pool_embeds_concurrently() {
+        // one model: 4 requests queue up behind each other
+        let single = ModelPool::new(vec![()]);
+        assert!(embed_concurrently(&single, 4) >= Duration::from_millis(200));
+
+        // four models: they run side by side (generous bound for slow CI machines)
+        let pool = ModelPool::new(vec![(), (), (), ()]);
+        assert!(embed_concurrently(&pool, 4) < Duration::from_millis(175));
+    }
+
+    #[test]
+    fn test_repeat_query_skips_embedding() {
+        let cache = EmbeddingCache::new(2, Duration::from_secs(60));
+        let calls = std::cell::Cell::new(0);
+        // stands in for the fastembed model
+        let embed = |query: &str| -> Result<Vec<f32>, String> {
+            calls.set(calls.get() + 1);
+            Ok(vec![query.len() as f32, 1.0])
+        };
+
+        assert_eq!(cache.get_or_embed("db timeout", embed).unwrap(), vec![10.0, 1.0]);
+        assert_eq!(cache.get_or_embed("db timeout", embed).unwrap(), vec![10.0, 1.0]);
+        assert_eq!(calls.get(), 1);
+
+        // bounded: a third query pushes out the oldest
+        cache.get_or_embed("disk full", embed).unwrap();
+        cache.get_or_embed("oom", embed).unwrap();
+        assert_eq!(calls.get(), 3);
+        cache.get_or_embed("db timeout", embed).unwrap();
+        assert_eq!(calls.get(), 4);
+
+        // failures aren't cached
+        assert!(cache.get_or_embed("bad", |_| Err("model down".to_string())).is_err());
+        cache.get_or_embed("bad", embed).unwrap();
+        assert_eq!(calls.get(), 5);
+    }
+
+    #[test]
+    fn test_expired_embedding_is_recomputed() {
+        let cache = EmbeddingCache::new(8, Duration::ZERO);
+        let calls = std::cell::Cell::new(0);
+        let embed = |_: &str| -> Result<Vec<f32>, String> {
+            calls.set(calls.get() + 1);
+            Ok(vec![0.5])
+        };
+
+        cache.get_or_embed("db timeout", embed).unwrap();
+        std::thread::sleep(Duration::from_millis(2));
+        cache.get_or_embed("db timeout", embed).unwrap();
+        assert_eq!(calls.get(), 2);
+
+        // and a capacity of 0 turns caching off
+        let off = EmbeddingCache::new(0, Duration::from_secs(60));
+        off.get_or_embed("db timeout", embed).unwrap();
+        off.get_or_embed("db timeout", embed).unwrap();
+        assert_eq!(calls.get(), 4);
+    }
 }
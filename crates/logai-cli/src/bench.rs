@@ -0,0 +1,194 @@
+// `logai bench`: end-to-end latency of ask/search/chat against a running API, measured
+// from the client (network, embedding, Qdrant and the LLM all included). Ingest load is
+// logai-stress's job.
+
+use clap::ValueEnum;
+use futures::StreamExt;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::output::CsvRow;
+
+/// Same questions as the RAG criterion bench (crates/logai-rag/benches/rag.rs)
+pub const QUERIES: &[&str] = logai_rag::SAMPLE_QUERIES;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BenchTarget {
+    /// GET /api/ask (retrieval + LLM)
+    Ask,
+    /// GET /api/search (retrieval only)
+    Search,
+    /// POST /api/chat, a new session per request
+    Chat,
+}
+
+/// Latencies of the successful requests; failed ones only count towards `errors`
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub target: BenchTarget,
+    pub requests: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub requests_per_sec: f64,
+}
+
+impl CsvRow for BenchReport {
+    const HEADER: &'static [&'static str] = &[
+        "target",
+        "requests",
+        "errors",
+        "error_rate",
+        "p50_ms",
+        "p95_ms",
+        "p99_ms",
+        "mean_ms",
+        "requests_per_sec",
+    ];
+    fn csv_fields(&self) -> Vec<String> {
+        let ms = |v: Option<f64>| v.map(|v| format!("{:.1}", v)).unwrap_or_default();
+        vec![
+            format!("{:?}", self.target).to_lowercase(),
+            self.requests.to_string(),
+            self.errors.to_string(),
+            format!("{:.4}", self.error_rate),
+            ms(self.p50_ms),
+            ms(self.p95_ms),
+            ms(self.p99_ms),
+            ms(self.mean_ms),
+            format!("{:.2}", self.requests_per_sec),
+        ]
+    }
+}
+
+/// Nearest-rank percentile (`p` in 0-100) of sorted latencies; None when there are none
+pub fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+pub fn summarize(target: BenchTarget, mut latencies: Vec<Duration>, errors: usize, elapsed: Duration) -> BenchReport {
+    latencies.sort();
+    let requests = latencies.len() + errors;
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let mean_ms = (!latencies.is_empty())
+        .then(|| latencies.iter().map(|d| ms(*d)).sum::<f64>() / latencies.len() as f64);
+
+    BenchReport {
+        target,
+        requests,
+        errors,
+        error_rate: if requests > 0 { errors as f64 / requests as f64 } else { 0.0 },
+        p50_ms: percentile(&latencies, 50.0).map(ms),
+        p95_ms: percentile(&latencies, 95.0).map(ms),
+        p99_ms: percentile(&latencies, 99.0).map(ms),
+        mean_ms,
+        requests_per_sec: if elapsed.is_zero() { 0.0 } else { requests as f64 / elapsed.as_secs_f64() },
+    }
+}
+
+/// `warmup` unmeasured requests, then `requests` measured ones, `concurrency` at a time.
+/// Queries cycle through `QUERIES`.
+pub async fn run(
+    client: &reqwest::Client,
+    api_url: &str,
+    target: BenchTarget,
+    requests: usize,
+    concurrency: usize,
+    warmup: usize,
+) -> BenchReport {
+    let concurrency = concurrency.max(1);
+    fire(client, api_url, target, warmup, concurrency).await;
+
+    let start = Instant::now();
+    let results = fire(client, api_url, target, requests, concurrency).await;
+    let elapsed = start.elapsed();
+
+    let errors = results.iter().filter(|r| r.is_err()).count();
+    let latencies = results.into_iter().filter_map(Result::ok).collect();
+    summarize(target, latencies, errors, elapsed)
+}
+
+async fn fire(
+    client: &reqwest::Client,
+    api_url: &str,
+    target: BenchTarget,
+    count: usize,
+    concurrency: usize,
+) -> Vec<Result<Duration, String>> {
+    futures::stream::iter(0..count)
+        .map(|i| send(client, api_url, target, QUERIES[i % QUERIES.len()]))
+        .buffer_unordered(concurrency)
+        .collect()
+        .await
+}
+
+async fn send(client: &reqwest::Client, api_url: &str, target: BenchTarget, query: &str) -> Result<Duration, String> {
+    let request = match target {
+        BenchTarget::Ask => client.get(format!("{}/api/ask?q={}", api_url, urlencoding::encode(query))),
+        BenchTarget::Search => client.get(format!("{}/api/search?q={}", api_url, urlencoding::encode(query))),
+        BenchTarget::Chat => client.post(format!("{}/api/chat", api_url)).json(&serde_json::json!({
+            "session_id": format!("bench-{}", uuid::Uuid::new_v4()),
+            "message": query,
+        })),
+    };
+
+    let start = Instant::now();
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    // the answer has to arrive, not just the headers
+    response.bytes().await.map_err(|e| e.to_string())?;
+    let latency = start.elapsed();
+
+    if status.is_success() {
+        Ok(latency)
+    } else {
+        Err(status.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_millis(*v)).collect()
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let latencies = ms(&(1..=100).collect::<Vec<_>>());
+        assert_eq!(percentile(&latencies, 50.0), Some(Duration::from_millis(50)));
+        assert_eq!(percentile(&latencies, 95.0), Some(Duration::from_millis(95)));
+        assert_eq!(percentile(&latencies, 99.0), Some(Duration::from_millis(99)));
+        assert_eq!(percentile(&latencies, 100.0), Some(Duration::from_millis(100)));
+        assert_eq!(percentile(&latencies, 0.0), Some(Duration::from_millis(1)));
+
+        // few samples: the tail percentiles are the slowest request
+        let few = ms(&[10, 20, 30]);
+        assert_eq!(percentile(&few, 50.0), Some(Duration::from_millis(20)));
+        assert_eq!(percentile(&few, 99.0), Some(Duration::from_millis(30)));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let report = summarize(BenchTarget::Search, ms(&[40, 10, 30, 20]), 1, Duration::from_secs(1));
+        assert_eq!(report.requests, 5);
+        assert_eq!(report.error_rate, 0.2);
+        assert_eq!(report.p50_ms, Some(20.0));
+        assert_eq!(report.p99_ms, Some(40.0));
+        assert_eq!(report.mean_ms, Some(25.0));
+        assert_eq!(report.requests_per_sec, 5.0);
+
+        let failed = summarize(BenchTarget::Ask, Vec::new(), 3, Duration::from_secs(1));
+        assert_eq!((failed.error_rate, failed.p50_ms, failed.mean_ms), (1.0, None, None));
+    }
+}
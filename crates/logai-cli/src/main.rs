@@ -1,13 +1,25 @@
 // LogAI CLI - AI-Powered Log Analysis
 
+mod bench;
+mod output;
+mod timespec;
+mod watch;
+
+use bench::BenchTarget;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use comfy_table::{Table, presets::UTF8_FULL};
+use output::{CsvRow, OutputFormat};
 use serde::{Deserialize, Serialize};
-use std::io::{self, BufRead, Write};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead, Read, Write};
 use std::process::Command as ProcessCommand;
+use std::time::Duration;
+use timespec::{parse_time, TimeArgs, TimeRange};
 
 const DEFAULT_API_URL: &str = "http://localhost:3000";
+// the API logs it on every line about the request and forwards it to the worker
+const REQUEST_ID_HEADER: &str = "x-request-id";
 
 #[derive(Parser)]
 #[command(name = "logai")]
@@ -19,14 +31,22 @@ struct Cli {
     #[arg(short, long, default_value = DEFAULT_API_URL)]
     api_url: String,
 
-    /// API key for authentication (or set LOGAI_API_KEY env var)
-    #[arg(short = 'k', long, env = "LOGAI_API_KEY")]
+    /// API key for authentication (visible in shell history; prefer --api-key-file or LOGAI_API_KEY)
+    #[arg(short = 'k', long)]
     api_key: Option<String>,
 
+    /// Read the API key from this file, or from stdin with `-` (used when --api-key isn't given)
+    #[arg(long, value_name = "PATH")]
+    api_key_file: Option<String>,
+
     /// Enable verbose output (debug logging)
     #[arg(short, long)]
     verbose: bool,
 
+    /// Output format for search, logs, stats, alerts and anomalies (json/csv print no colors)
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -47,6 +67,17 @@ enum Commands {
         /// Maximum results to return
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Only logs whose field matches, as field:value (repeatable, e.g. -f status_code:500)
+        #[arg(short, long)]
+        filter: Vec<String>,
+
+        /// Also match the query's words exactly (error codes, ids) and merge with the semantic results
+        #[arg(long)]
+        hybrid: bool,
+
+        #[command(flatten)]
+        time: TimeArgs,
     },
 
     /// Check system health status
@@ -57,13 +88,31 @@ enum Commands {
         /// Path to log file
         file: String,
 
-        /// Log format (json, apache, nginx, syslog, proxmox)
+        /// Log format (json, apache, nginx, syslog, proxmox, winevent, cef, postgres, mysql-slow, envoy, log4j, logback, gelf, redis)
         #[arg(short, long, default_value = "json")]
         format: String,
 
         /// Service name for raw logs
         #[arg(short, long, default_value = "imported")]
         service: String,
+
+        /// Join stack-trace lines onto the log line above them (raw formats only)
+        #[arg(long)]
+        multiline: bool,
+    },
+
+    /// Re-ingest an NDJSON export, keeping the original gaps between logs
+    Replay {
+        /// NDJSON file, e.g. from `logai export`
+        file: String,
+
+        /// Replay this many times faster than real time (e.g. 10)
+        #[arg(long, default_value = "1.0")]
+        speed: f64,
+
+        /// Keep the original timestamps instead of stamping logs with the time they are sent
+        #[arg(long)]
+        preserve_timestamps: bool,
     },
 
     /// Show recent logs
@@ -75,6 +124,89 @@ enum Commands {
         /// Filter by level (error, warn, info, debug)
         #[arg(short = 'L', long)]
         level: Option<String>,
+
+        /// Show logs older than this cursor (printed at the end of the previous page)
+        #[arg(short, long, conflicts_with = "follow")]
+        before: Option<String>,
+
+        /// Stream new logs as they are ingested (Ctrl+C to stop)
+        #[arg(short, long, conflicts_with_all = ["since", "until"])]
+        follow: bool,
+
+        #[command(flatten)]
+        time: TimeArgs,
+    },
+
+    /// Follow new logs by polling the recent logs endpoint (Ctrl+C to stop)
+    Tail {
+        /// Only logs from this service
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// Filter by level (error, warn, info, debug)
+        #[arg(short = 'L', long)]
+        level: Option<String>,
+
+        /// Seconds between polls
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+
+        /// Number of existing logs to show before following
+        #[arg(short = 'n', long, default_value = "10")]
+        lines: usize,
+    },
+
+    /// Show one log's full record: raw line, trace/span ids and parsed fields
+    Get {
+        /// Log id (UUID), as shown in search results
+        id: String,
+    },
+
+    /// Ask the AI what one log means and what to check next
+    Explain {
+        /// Log id (UUID), as shown in search results
+        id: String,
+    },
+
+    /// Delete every log matching a field from ClickHouse and Qdrant (needs an admin key)
+    Delete {
+        /// Field to match: trace_id or a parsed field such as user_id
+        #[arg(long)]
+        field: String,
+
+        /// Value the field must equal
+        #[arg(long)]
+        value: String,
+    },
+
+    /// Export logs in a time range to a file (NDJSON or CSV)
+    Export {
+        /// Output file (stdout when omitted)
+        path: Option<String>,
+
+        /// Start of the range: RFC 3339, YYYY-MM-DD or unix seconds (default: 24 hours ago)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the range, same formats (default: now)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Only logs from this service
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// Filter by level (error, warn, info, debug)
+        #[arg(short = 'L', long)]
+        level: Option<String>,
+
+        /// File format (ndjson, csv)
+        #[arg(short, long, default_value = "ndjson")]
+        format: String,
+
+        /// Lift the 31-day range limit and export everything that matches
+        #[arg(long)]
+        all: bool,
     },
 
     /// Show system statistics
@@ -92,6 +224,9 @@ enum Commands {
         /// Filter by status (firing, acknowledged, resolved)
         #[arg(short, long)]
         status: Option<String>,
+
+        #[command(flatten)]
+        time: TimeArgs,
     },
 
     /// Check for anomalies now
@@ -101,11 +236,91 @@ enum Commands {
         service: Option<String>,
     },
 
+    /// Compare errors between a good range (A) and a bad one (B): new, gone and growing errors
+    Diff {
+        /// Start of range A: RFC 3339, YYYY-MM-DD or unix seconds
+        #[arg(long)]
+        from_a: String,
+
+        /// End of range A, same formats
+        #[arg(long)]
+        to_a: String,
+
+        /// Start of range B
+        #[arg(long)]
+        from_b: String,
+
+        /// End of range B
+        #[arg(long)]
+        to_b: String,
+
+        /// Only logs from this service
+        #[arg(short, long)]
+        service: Option<String>,
+    },
+
+    /// Group logs into message templates (numbers, ids, IPs and paths masked), most frequent first
+    Patterns {
+        /// Only logs from this service
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// Start of the range: RFC 3339, YYYY-MM-DD or unix seconds (default: an hour before --to)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the range, same formats (default: now)
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// Measure ask/search/chat latency against the running API (p50/p95/p99, error rate)
+    Bench {
+        /// Endpoint to measure
+        #[arg(short, long, value_enum, default_value = "search")]
+        target: BenchTarget,
+
+        /// Measured requests
+        #[arg(short = 'n', long, default_value = "50")]
+        requests: usize,
+
+        /// Requests in flight at once
+        #[arg(short, long, default_value = "4")]
+        concurrency: usize,
+
+        /// Unmeasured requests sent first; they also fill the API's embedding and LLM caches
+        #[arg(long, default_value = "5")]
+        warmup: usize,
+    },
+
+    /// Live dashboard: logs/sec, error rate, busiest services and active anomalies (q to quit)
+    Watch {
+        /// Seconds between refreshes
+        #[arg(short, long, default_value = "3")]
+        interval: u64,
+    },
+
     /// Interactive chat mode for debugging
     Chat {
         /// Initial question (optional)
         question: Option<String>,
     },
+
+    /// Work with the anomaly detection config
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check an anomaly rules file and report problems per rule (exits non-zero on any)
+    Validate {
+        /// Path to the rules file, or a directory of *.toml rule files
+        #[arg(default_value = "config/anomaly-rules.toml")]
+        path: String,
+    },
 }
 
 // API Response types
@@ -126,8 +341,7 @@ struct QueryAnalysis {
     service_filter: Option<String>,
 }
 
-#[derive(Deserialize)]
-#[allow(dead_code)]
+#[derive(Deserialize, Serialize)]
 struct SearchResult {
     score: f32,
     log_id: String,
@@ -137,6 +351,164 @@ struct SearchResult {
     timestamp: String,
 }
 
+impl CsvRow for SearchResult {
+    const HEADER: &'static [&'static str] = &["score", "log_id", "service", "level", "message", "timestamp"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.score.to_string(),
+            self.log_id.clone(),
+            self.service.clone(),
+            self.level.clone(),
+            self.message.clone(),
+            self.timestamp.clone(),
+        ]
+    }
+}
+
+/// Error body returned by the API: {"error": "...", "code": 404}
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ApiError {
+    error: String,
+    code: u16,
+}
+
+/// Pull the message out of an API error response, falling back to the raw body
+async fn error_message(response: reqwest::Response) -> String {
+    let text = response.text().await.unwrap_or_default();
+    match serde_json::from_str::<ApiError>(&text) {
+        Ok(err) => err.error,
+        Err(_) => text,
+    }
+}
+
+/// Tables print the error and carry on; json/csv fail so scripts see stderr and a non-zero exit
+fn report_error(output: OutputFormat, error: String) -> Result<(), Box<dyn std::error::Error>> {
+    if output.is_table() {
+        println!("{} {}", "Error:".red().bold(), error);
+        Ok(())
+    } else {
+        Err(error.into())
+    }
+}
+
+/// The API key: --api-key, then --api-key-file, then LOGAI_API_KEY. Whitespace around a
+/// key read from a file (or stdin, for `-`) is trimmed, and an empty one is an error.
+fn resolve_api_key(
+    flag: Option<String>,
+    file: Option<&str>,
+    env: Option<String>,
+    read: impl FnOnce(&str) -> io::Result<String>,
+) -> Result<Option<String>, String> {
+    if flag.is_some() {
+        return Ok(flag);
+    }
+    if let Some(path) = file {
+        let source = if path == "-" { "stdin" } else { path };
+        let contents = read(path).map_err(|e| format!("Cannot read the API key from {}: {}", source, e))?;
+        let key = contents.trim();
+        if key.is_empty() {
+            return Err(format!("No API key in {}", source));
+        }
+        return Ok(Some(key.to_string()));
+    }
+    Ok(env.filter(|key| !key.is_empty()))
+}
+
+fn read_key_file(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut key = String::new();
+        io::stdin().read_to_string(&mut key)?;
+        Ok(key)
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// Full stored record from GET /api/logs/{id}
+#[derive(Deserialize, Serialize)]
+struct LogRecord {
+    id: String,
+    timestamp: String,
+    level: String,
+    service: String,
+    message: String,
+    raw: String,
+    trace_id: Option<String>,
+    span_id: Option<String>,
+    error_category: Option<String>,
+    #[serde(default)]
+    fields: std::collections::BTreeMap<String, serde_json::Value>,
+    ingested_at: String,
+}
+
+impl CsvRow for LogRecord {
+    const HEADER: &'static [&'static str] = &[
+        "id",
+        "timestamp",
+        "level",
+        "service",
+        "message",
+        "raw",
+        "trace_id",
+        "span_id",
+        "error_category",
+        "fields",
+        "ingested_at",
+    ];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.timestamp.clone(),
+            self.level.clone(),
+            self.service.clone(),
+            self.message.clone(),
+            self.raw.clone(),
+            self.trace_id.clone().unwrap_or_default(),
+            self.span_id.clone().unwrap_or_default(),
+            self.error_category.clone().unwrap_or_default(),
+            serde_json::to_string(&self.fields).unwrap_or_default(),
+            self.ingested_at.clone(),
+        ]
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct DeleteResult {
+    clickhouse_deleted: u64,
+    qdrant_deleted: u64,
+}
+
+impl CsvRow for DeleteResult {
+    const HEADER: &'static [&'static str] = &["clickhouse_deleted", "qdrant_deleted"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![self.clickhouse_deleted.to_string(), self.qdrant_deleted.to_string()]
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct ExplainResult {
+    log: String,
+    explanation: String,
+    next_steps: Vec<String>,
+    cached: bool,
+    response_time_ms: u128,
+    provider: String,
+}
+
+impl CsvRow for ExplainResult {
+    const HEADER: &'static [&'static str] = &["log", "explanation", "next_steps", "cached", "provider"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.log.clone(),
+            self.explanation.clone(),
+            self.next_steps.join("; "),
+            self.cached.to_string(),
+            self.provider.clone(),
+        ]
+    }
+}
+
 #[derive(Serialize)]
 #[allow(dead_code)]
 struct LogEntry {
@@ -148,6 +520,7 @@ struct LogEntry {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    output::configure_colors(cli.output);
     
     // Set up logging based on verbose flag
     if cli.verbose {
@@ -155,11 +528,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("{}", "Verbose mode enabled".dimmed());
     }
     
+    let api_key = resolve_api_key(
+        cli.api_key.clone(),
+        cli.api_key_file.as_deref(),
+        std::env::var("LOGAI_API_KEY").ok(),
+        read_key_file,
+    )?;
+
     // Build client with optional API key header
     let mut headers = reqwest::header::HeaderMap::new();
-    if let Some(ref key) = cli.api_key {
+    if let Some(ref key) = api_key {
         headers.insert("X-API-Key", reqwest::header::HeaderValue::from_str(key)?);
     }
+    // one id for everything this invocation sends, so its API and worker logs can be grepped together
+    let request_id = uuid::Uuid::new_v4().to_string();
+    headers.insert(REQUEST_ID_HEADER, reqwest::header::HeaderValue::from_str(&request_id)?);
+    if cli.verbose {
+        eprintln!("{} {}", "Request id:".dimmed(), request_id);
+    }
     let client = reqwest::Client::builder()
         .default_headers(headers)
         .build()?;
@@ -168,33 +554,81 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Ask { question } => {
             ask_ai(&client, &cli.api_url, &question).await?;
         }
-        Commands::Search { query, limit } => {
-            search_logs(&client, &cli.api_url, &query, limit).await?;
+        Commands::Search { query, limit, filter, hybrid, time } => {
+            let range = time.range()?;
+            search_logs(&client, &cli.api_url, &query, limit, &filter, hybrid, range, cli.output).await?;
         }
         Commands::Status => {
             check_status(&client, &cli.api_url).await?;
         }
-        Commands::Ingest { file, format, service } => {
-            ingest_file(&client, &cli.api_url, &file, &format, &service, cli.verbose).await?;
+        Commands::Ingest { file, format, service, multiline } => {
+            ingest_file(&client, &cli.api_url, &file, &format, &service, multiline, cli.verbose).await?;
+        }
+        Commands::Replay { file, speed, preserve_timestamps } => {
+            replay_file(&client, &cli.api_url, &file, speed, preserve_timestamps, cli.verbose).await?;
+        }
+        Commands::Logs { limit, level, before, follow, time } => {
+            if follow {
+                follow_logs(&cli.api_url, api_key.as_deref(), level, cli.output).await?;
+            } else {
+                show_logs(&client, &cli.api_url, limit, level, before, time.range()?, cli.output).await?;
+            }
+        }
+        Commands::Tail { service, level, interval, lines } => {
+            let interval = Duration::from_secs(interval.max(1));
+            tail_logs(&client, &cli.api_url, service.as_deref(), level.as_deref(), interval, lines, cli.output).await?;
+        }
+        Commands::Get { id } => {
+            get_log(&client, &cli.api_url, &id, cli.output).await?;
         }
-        Commands::Logs { limit, level } => {
-            show_logs(&client, &cli.api_url, limit, level).await?;
+        Commands::Explain { id } => {
+            explain_log(&client, &cli.api_url, &id, cli.output).await?;
+        }
+        Commands::Delete { field, value } => {
+            delete_logs(&client, &cli.api_url, &field, &value, cli.output).await?;
+        }
+        Commands::Export { path, from, to, service, level, format, all } => {
+            let range = ExportRange {
+                from: from.as_deref().map(parse_time).transpose()?,
+                to: to.as_deref().map(parse_time).transpose()?,
+                all,
+            };
+            export_logs(&client, &cli.api_url, path.as_deref(), range, service, level, &format).await?;
         }
         Commands::Stats => {
-            show_stats(&client, &cli.api_url).await?;
+            show_stats(&client, &cli.api_url, cli.output).await?;
         }
         Commands::Serve { port } => {
             start_server(port)?;
         }
-        Commands::Alerts { status } => {
-            show_alerts(&client, &cli.api_url, status).await?;
+        Commands::Alerts { status, time } => {
+            show_alerts(&client, &cli.api_url, status, time.range()?, cli.output).await?;
         }
         Commands::Anomalies { service } => {
-            check_anomalies(&client, &cli.api_url, service).await?;
+            check_anomalies(&client, &cli.api_url, service, cli.output).await?;
+        }
+        Commands::Diff { from_a, to_a, from_b, to_b, service } => {
+            let range_a = (parse_time(&from_a)?, parse_time(&to_a)?);
+            let range_b = (parse_time(&from_b)?, parse_time(&to_b)?);
+            diff_errors(&client, &cli.api_url, range_a, range_b, service, cli.output).await?;
+        }
+        Commands::Patterns { service, from, to } => {
+            let from = from.as_deref().map(parse_time).transpose()?;
+            let to = to.as_deref().map(parse_time).transpose()?;
+            show_patterns(&client, &cli.api_url, service, from, to, cli.output).await?;
+        }
+        Commands::Bench { target, requests, concurrency, warmup } => {
+            run_bench(&client, &cli.api_url, target, requests, concurrency, warmup, cli.output).await?;
+        }
+        Commands::Watch { interval } => {
+            watch::run(&client, &cli.api_url, Duration::from_secs(interval.max(1))).await?;
         }
         Commands::Chat { question } => {
             interactive_chat(&client, &cli.api_url, question).await?;
         }
+        Commands::Config { command: ConfigCommand::Validate { path } } => {
+            validate_anomaly_config(&path)?;
+        }
     }
 
     Ok(())
@@ -215,7 +649,7 @@ async fn ask_ai(
         .await?;
 
     if !response.status().is_success() {
-        let error = response.text().await?;
+        let error = error_message(response).await;
         println!("{} {}", "Error:".red().bold(), error);
         return Ok(());
     }
@@ -248,29 +682,47 @@ async fn ask_ai(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn search_logs(
     client: &reqwest::Client,
     api_url: &str,
     query: &str,
     limit: usize,
+    filters: &[String],
+    hybrid: bool,
+    range: TimeRange,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("\n{} \"{}\"", "🔍 Searching:".cyan().bold(), query);
-    println!("{}", "─".repeat(60).dimmed());
+    if output.is_table() {
+        println!("\n{} \"{}\"", "🔍 Searching:".cyan().bold(), query);
+        println!("{}", "─".repeat(60).dimmed());
+    }
 
-    let url = format!("{}/api/search?q={}&limit={}", api_url, urlencoding::encode(query), limit);
+    let mut url = format!("{}/api/search?q={}&limit={}", api_url, urlencoding::encode(query), limit);
+    if !filters.is_empty() {
+        url.push_str(&format!("&filters={}", urlencoding::encode(&filters.join(","))));
+    }
+    if hybrid {
+        url.push_str("&mode=hybrid");
+    }
+    range.append_to(&mut url);
     let response = client
         .get(&url)
         .send()
         .await?;
 
     if !response.status().is_success() {
-        let error = response.text().await?;
-        println!("{} {}", "Error:".red().bold(), error);
-        return Ok(());
+        let error = error_message(response).await;
+        return report_error(output, error);
     }
 
     let results: Vec<SearchResult> = response.json().await?;
 
+    if !output.is_table() {
+        println!("{}", output::format_rows(output, &results)?);
+        return Ok(());
+    }
+
     if results.is_empty() {
         println!("{}", "No results found.".yellow());
         return Ok(());
@@ -285,6 +737,7 @@ async fn search_logs(
         let level_colored = match r.level.to_lowercase().as_str() {
             "error" => r.level.red().to_string(),
             "warn" => r.level.yellow().to_string(),
+            "notice" => r.level.cyan().to_string(),
             "info" => r.level.green().to_string(),
             _ => r.level.clone(),
         };
@@ -318,6 +771,20 @@ async fn search_logs(
     Ok(())
 }
 
+/// Body of GET /health?deep=true (200 when all dependencies answer, 503 otherwise)
+#[derive(Deserialize)]
+struct HealthReport {
+    dependencies: Vec<DependencyStatus>,
+}
+
+#[derive(Deserialize)]
+struct DependencyStatus {
+    name: String,
+    ok: bool,
+    latency_ms: u64,
+    error: Option<String>,
+}
+
 async fn check_status(
     client: &reqwest::Client,
     api_url: &str,
@@ -329,12 +796,28 @@ async fn check_status(
     print!("  API Server ({})... ", api_url);
     io::stdout().flush()?;
 
-    match client.get(format!("{}/api/search?q=test", api_url)).send().await {
-        Ok(resp) if resp.status().is_success() => {
-            println!("{}", "✓ Running".green());
-        }
+    // the deep health check reports on every dependency from the API's point of view
+    match client.get(format!("{}/health?deep=true", api_url)).send().await {
         Ok(resp) => {
-            println!("{} ({})", "✗ Error".red(), resp.status());
+            let status = resp.status();
+            match resp.json::<HealthReport>().await {
+                Ok(report) => {
+                    println!("{}", "✓ Running".green());
+                    for dep in &report.dependencies {
+                        print!("  {}... ", dep.name);
+                        if dep.ok {
+                            println!("{} {}", "✓ Running".green(), format!("({}ms)", dep.latency_ms).dimmed());
+                        } else {
+                            println!("{} ({})", "✗ Down".red(), dep.error.as_deref().unwrap_or("unknown error"));
+                        }
+                    }
+                    println!();
+                    return Ok(());
+                }
+                // older API without deep mode: fall through to probing each service directly
+                Err(_) if status.is_success() => println!("{}", "✓ Running".green()),
+                Err(_) => println!("{} ({})", "✗ Error".red(), status),
+            }
         }
         Err(e) => {
             println!("{} ({})", "✗ Down".red(), e);
@@ -384,17 +867,52 @@ async fn check_status(
     Ok(())
 }
 
+/// Body of GET /api/formats
+#[derive(Deserialize)]
+struct FormatsResponse {
+    formats: Vec<String>,
+}
+
+/// Body of POST /api/logs/stream (blank lines aren't counted)
+#[derive(Deserialize)]
+struct StreamIngestResponse {
+    parsed: usize,
+    failed: usize,
+}
+
+/// Fail before reading the file when the server has no parser for `format`. `json` is sent
+/// to /api/logs as-is; servers without /api/formats are trusted to reject unknown formats.
+async fn check_format(client: &reqwest::Client, api_url: &str, format: &str) -> Result<(), String> {
+    if format == "json" {
+        return Ok(());
+    }
+    let response = match client.get(format!("{}/api/formats", api_url)).send().await {
+        Ok(response) if response.status().is_success() => response,
+        _ => return Ok(()),
+    };
+    let Ok(FormatsResponse { formats }) = response.json().await else {
+        return Ok(());
+    };
+    if formats.iter().any(|f| f == format) {
+        return Ok(());
+    }
+    Err(format!("Unknown format '{}'. Available: json, {}", format, formats.join(", ")))
+}
+
 async fn ingest_file(
     client: &reqwest::Client,
     api_url: &str,
     file_path: &str,
     format: &str,
     service: &str,
+    multiline: bool,
     verbose: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use std::fs::File;
     use std::io::{BufRead, BufReader};
 
+    check_format(client, api_url, format).await?;
+
     println!("\n{} {}", "📥 Ingesting:".cyan().bold(), file_path);
     println!("{} {}", "Format:".dimmed(), format);
     println!("{} {}", "Service:".dimmed(), service);
@@ -402,85 +920,79 @@ async fn ingest_file(
     println!("{}", "─".repeat(40).dimmed());
 
     let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader
+    let lines = BufReader::new(file)
         .lines()
-        .filter_map(|l| l.ok())
-        .filter(|l| !l.trim().is_empty())
-        .collect();
-
-    let total = lines.len();
-    println!("Found {} lines to process", total);
-
-    if verbose && !lines.is_empty() {
-        println!("\n{}", "Sample lines:".yellow());
-        for (i, line) in lines.iter().take(3).enumerate() {
-            let preview = if line.len() > 80 { format!("{}...", &line[..77]) } else { line.clone() };
-            println!("  [{}] {}", i + 1, preview.dimmed());
+        .map_while(Result::ok)
+        .filter(|l| !l.trim().is_empty());
+    let print_sample = |sample: &[String]| {
+        if verbose && !sample.is_empty() {
+            println!("\n{}", "Sample lines:".yellow());
+            for (i, line) in sample.iter().take(3).enumerate() {
+                let preview = if line.len() > 80 { format!("{}...", &line[..77]) } else { line.clone() };
+                println!("  [{}] {}", i + 1, preview.dimmed());
+            }
+            println!();
         }
-        println!();
-    }
+    };
 
     if format == "json" {
-        // JSON format: send each line individually
-        let pb = indicatif::ProgressBar::new(total as u64);
-        pb.set_style(
-            indicatif::ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")?
-                .progress_chars("#>-"),
-        );
-
-        let mut success = 0;
-        let mut failed = 0;
-        let mut last_error: Option<String> = None;
-
-        for line in &lines {
-            let url = format!("{}/api/logs", api_url);
-            match client
-                .post(&url)
-                .header("Content-Type", "application/json")
-                .body(line.clone())
-                .send()
-                .await
-            {
-                Ok(resp) if resp.status().is_success() => success += 1,
-                Ok(resp) => {
-                    failed += 1;
-                    if verbose {
-                        let status = resp.status();
-                        let text = resp.text().await.unwrap_or_default();
-                        last_error = Some(format!("{}: {}", status, text));
-                    }
-                }
-                Err(e) => {
-                    failed += 1;
-                    if verbose {
-                        last_error = Some(e.to_string());
-                    }
-                }
-            }
-            pb.inc(1);
+        // JSON format: stream the file to /api/logs/stream, the API publishes each line as it
+        // arrives; only the sample lines are read here
+        if verbose {
+            print_sample(&lines.take(3).collect::<Vec<_>>());
         }
+        let pb = indicatif::ProgressBar::new_spinner();
+        pb.set_style(indicatif::ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {msg}")?);
+        pb.set_message(format!("Streaming {}...", file_path));
+        pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
-        pb.finish_with_message("Done!");
-        println!("\n{}", "Results:".green().bold());
-        println!("  {} {}", "Success:".dimmed(), success.to_string().green());
-        println!("  {} {}", "Failed:".dimmed(), failed.to_string().red());
-        
+        let url = format!("{}/api/logs/stream", api_url);
         if verbose {
-            if let Some(err) = last_error {
-                println!("\n{} {}", "Last error:".red(), err);
+            println!("{} POST {}", "Request:".yellow(), url);
+        }
+        let response = client
+            .post(&url)
+            .header("Content-Type", "application/x-ndjson")
+            .body(tokio::fs::File::open(file_path).await?)
+            .send()
+            .await;
+        pb.finish_with_message("Done!");
+
+        match response {
+            Ok(resp) if resp.status().is_success() => {
+                let counts: StreamIngestResponse = resp.json().await?;
+                println!("\n{}", "Results:".green().bold());
+                println!("  {} {}", "Success:".dimmed(), counts.parsed.to_string().green());
+                println!("  {} {}", "Failed:".dimmed(), counts.failed.to_string().red());
+            }
+            Ok(resp) => {
+                let status = resp.status();
+                let text = error_message(resp).await;
+                println!("\n{} Failed: {} - {}", "✗".red().bold(), status, text);
+            }
+            Err(e) => {
+                println!("\n{} Error: {}", "✗".red().bold(), e);
+                if verbose {
+                    println!("\n{}", "Connection troubleshooting:".yellow().bold());
+                    println!("  1. Verify API URL is correct: {}", api_url);
+                    println!("  2. Check if service is running");
+                }
             }
         }
     } else {
         // Raw format (apache, nginx, syslog): send all lines in one batch
+        let lines: Vec<String> = lines.collect();
+        let total = lines.len();
+        println!("Found {} lines to process", total);
+        print_sample(&lines);
         println!("Sending {} lines as batch...", total);
 
         let url = format!("{}/api/logs/raw", api_url);
         let body = serde_json::json!({
             "format": format,
             "service": service,
-            "lines": lines
+            "lines": lines,
+            "multiline": multiline
         });
 
         if verbose {
@@ -503,7 +1015,7 @@ async fn ingest_file(
                     }
                 } else {
                     let status = resp.status();
-                    let text = resp.text().await.unwrap_or_default();
+                    let text = error_message(resp).await;
                     println!("\n{} Failed: {} - {}", "✗".red().bold(), status, text);
                     if verbose {
                         println!("\n{}", "Troubleshooting tips:".yellow().bold());
@@ -528,73 +1040,923 @@ async fn ingest_file(
     Ok(())
 }
 
-async fn show_logs(
+async fn show_logs(
+    client: &reqwest::Client,
+    api_url: &str,
+    limit: usize,
+    level: Option<String>,
+    before: Option<String>,
+    range: TimeRange,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if output.is_table() {
+        println!("\n{}", "📋 Recent Logs".cyan().bold());
+        println!("{}", "─".repeat(80).dimmed());
+    }
+
+    // first page of a level filter: the search fast path (filtered scroll, no embedding)
+    if let (Some(level), None) = (&level, &before) {
+        return show_level_logs(client, api_url, limit, level, range, output).await;
+    }
+
+    let mut url = format!("{}/api/logs/recent?limit={}", api_url, limit);
+    if let Some(level) = level {
+        url.push_str(&format!("&level={}", urlencoding::encode(&stored_level(&level))));
+    }
+    if let Some(ref before) = before {
+        url.push_str(&format!("&before={}", urlencoding::encode(before)));
+    }
+    range.append_to(&mut url);
+
+    let response = client
+        .get(&url)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+
+    let page: RecentLogsResponse = response.json().await?;
+
+    if !output.is_table() {
+        println!("{}", output::format_rows(output, &page.logs)?);
+        return Ok(());
+    }
+
+    for r in page.logs {
+        print_log_line(&r.timestamp, &r.level, &r.service, &r.message);
+    }
+
+    if let Some(cursor) = page.next_cursor {
+        println!("{}", "─".repeat(80).dimmed());
+        println!("{} logai logs --before \"{}\"", "Older logs:".dimmed(), cursor);
+    }
+
+    Ok(())
+}
+
+async fn show_level_logs(
+    client: &reqwest::Client,
+    api_url: &str,
+    limit: usize,
+    level: &str,
+    range: TimeRange,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut url = format!(
+        "{}/api/search?q={}&level={}&limit={}",
+        api_url,
+        urlencoding::encode(level),
+        urlencoding::encode(level),
+        limit
+    );
+    range.append_to(&mut url);
+    let response = client
+        .get(&url)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+
+    let results: Vec<SearchResult> = response.json().await?;
+
+    if !output.is_table() {
+        // same shape as a /logs/recent page, the similarity score means nothing here
+        let logs: Vec<RecentLog> = results.into_iter().map(RecentLog::from).collect();
+        println!("{}", output::format_rows(output, &logs)?);
+        return Ok(());
+    }
+
+    for r in &results {
+        print_log_line(&r.timestamp, &r.level, &r.service, &r.message);
+    }
+
+    // a full page probably has more; a bare timestamp is a valid /logs/recent cursor
+    if results.len() == limit {
+        if let Some(last) = results.last() {
+            println!("{}", "─".repeat(80).dimmed());
+            println!(
+                "{} logai logs --level {} --before \"{}\"",
+                "Older logs:".dimmed(),
+                level,
+                last.timestamp
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn follow_logs(
+    api_url: &str,
+    api_key: Option<&str>,
+    level: Option<String>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+    let ws_base = api_url
+        .replacen("https://", "wss://", 1)
+        .replacen("http://", "ws://", 1);
+    let mut url = format!("{}/api/logs/stream", ws_base);
+    if let Some(ref level) = level {
+        url.push_str(&format!("?level={}", urlencoding::encode(level)));
+    }
+
+    let mut request = url.into_client_request()?;
+    if let Some(key) = api_key {
+        request.headers_mut().insert("X-API-Key", key.parse()?);
+    }
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await?;
+    match output {
+        OutputFormat::Table => {
+            println!("\n{}", "📡 Following logs (Ctrl+C to stop)".cyan().bold());
+            println!("{}", "─".repeat(80).dimmed());
+        }
+        OutputFormat::Csv => println!("{}", output::csv_line(StreamedLog::HEADER.iter().copied())),
+        OutputFormat::Json => {}
+    }
+
+    // server sends one JSON log entry per text frame
+    while let Some(message) = socket.next().await {
+        match message? {
+            Message::Text(text) => {
+                if let Ok(log) = serde_json::from_str::<StreamedLog>(&text) {
+                    match output {
+                        OutputFormat::Table => {
+                            print_log_line(&log.timestamp, &log.level, &log.service, &log.message)
+                        }
+                        // one object per line (NDJSON), since the stream never ends
+                        OutputFormat::Json => println!("{}", serde_json::to_string(&log)?),
+                        OutputFormat::Csv => {
+                            println!("{}", output::csv_line(log.csv_fields().iter().map(String::as_str)))
+                        }
+                    }
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// levels are stored capitalized ("Error", "Warn", ...)
+fn stored_level(level: &str) -> String {
+    let mut chars = level.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+        None => String::new(),
+    }
+}
+
+// a poll pages back at most TAIL_PAGE * TAIL_MAX_PAGES logs to reach the cursor
+const TAIL_PAGE: usize = 200;
+const TAIL_MAX_PAGES: usize = 10;
+
+async fn tail_logs(
+    client: &reqwest::Client,
+    api_url: &str,
+    service: Option<&str>,
+    level: Option<&str>,
+    interval: Duration,
+    lines: usize,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cursor = TailCursor::default();
+
+    // like tail -f: the last few logs first (one is still needed to place the cursor)
+    let initial = fetch_recent_page(client, api_url, service, level, lines.max(1), None).await?;
+    let backlog = cursor.take_new(initial.logs);
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n{}", "📡 Tailing logs (Ctrl+C to stop)".cyan().bold());
+            println!("{}", "─".repeat(80).dimmed());
+        }
+        OutputFormat::Csv => println!("{}", output::csv_line(RecentLog::HEADER.iter().copied())),
+        OutputFormat::Json => {}
+    }
+    for log in &backlog[backlog.len().saturating_sub(lines)..] {
+        print_tail_log(log, output)?;
+    }
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await; // the first tick is immediate
+
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            _ = ticker.tick() => {}
+        }
+        // the API may be restarting; keep following instead of bailing out
+        match poll_new_logs(client, api_url, service, level, &cursor).await {
+            Ok(logs) => {
+                for log in cursor.take_new(logs) {
+                    print_tail_log(&log, output)?;
+                }
+            }
+            Err(e) => eprintln!("{} {}", "Poll failed:".yellow(), e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything newer than the cursor, newest first: a page of recent logs, plus older pages
+/// while a whole page is new (more than a page arrived since the last poll)
+async fn poll_new_logs(
+    client: &reqwest::Client,
+    api_url: &str,
+    service: Option<&str>,
+    level: Option<&str>,
+    cursor: &TailCursor,
+) -> Result<Vec<RecentLog>, Box<dyn std::error::Error>> {
+    let mut logs = Vec::new();
+    let mut before = None;
+    for _ in 0..TAIL_MAX_PAGES {
+        let page = fetch_recent_page(client, api_url, service, level, TAIL_PAGE, before.as_deref()).await?;
+        let caught_up = page.logs.iter().any(|log| !cursor.is_new(log));
+        logs.extend(page.logs);
+        match page.next_cursor {
+            Some(next) if !caught_up => before = Some(next),
+            _ => return Ok(logs),
+        }
+    }
+
+    eprintln!(
+        "{} more than {} new logs since the last poll, older ones skipped (try a shorter --interval)",
+        "Warning:".yellow(),
+        TAIL_PAGE * TAIL_MAX_PAGES
+    );
+    Ok(logs)
+}
+
+async fn fetch_recent_page(
+    client: &reqwest::Client,
+    api_url: &str,
+    service: Option<&str>,
+    level: Option<&str>,
+    limit: usize,
+    before: Option<&str>,
+) -> Result<RecentLogsResponse, Box<dyn std::error::Error>> {
+    let mut url = format!("{}/api/logs/recent?limit={}", api_url, limit);
+    if let Some(service) = service {
+        url.push_str(&format!("&service={}", urlencoding::encode(service)));
+    }
+    if let Some(level) = level {
+        url.push_str(&format!("&level={}", urlencoding::encode(&stored_level(level))));
+    }
+    if let Some(before) = before {
+        url.push_str(&format!("&before={}", urlencoding::encode(before)));
+    }
+
+    let response = client
+        .get(&url)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(error_message(response).await.into());
+    }
+    Ok(response.json().await?)
+}
+
+fn print_tail_log(log: &RecentLog, output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        OutputFormat::Table => print_log_line(&log.timestamp, &log.level, &log.service, &log.message),
+        // one object per line (NDJSON), since tailing never ends
+        OutputFormat::Json => println!("{}", serde_json::to_string(log)?),
+        OutputFormat::Csv => println!("{}", output::csv_line(log.csv_fields().iter().map(String::as_str))),
+    }
+    Ok(())
+}
+
+/// How far `logai tail` has printed: the newest timestamp seen and the ids printed at it.
+/// Timestamps all come from ClickHouse in one format, so they order correctly as strings;
+/// the ids catch logs that show up later with the same timestamp as the cursor.
+#[derive(Default)]
+struct TailCursor {
+    timestamp: Option<String>,
+    ids: HashSet<String>,
+}
+
+impl TailCursor {
+    fn is_new(&self, log: &RecentLog) -> bool {
+        match &self.timestamp {
+            None => true,
+            Some(ts) => log.timestamp > *ts || (log.timestamp == *ts && !self.ids.contains(&log.log_id)),
+        }
+    }
+
+    /// The logs of a newest-first batch that weren't printed yet, oldest first; moves the cursor past them
+    fn take_new(&mut self, newest_first: Vec<RecentLog>) -> Vec<RecentLog> {
+        let mut batch = HashSet::new();
+        let mut fresh: Vec<RecentLog> = newest_first
+            .into_iter()
+            .filter(|log| self.is_new(log) && batch.insert(log.log_id.clone()))
+            .collect();
+        fresh.reverse();
+
+        for log in &fresh {
+            if self.timestamp.as_ref().is_none_or(|ts| log.timestamp > *ts) {
+                self.timestamp = Some(log.timestamp.clone());
+                self.ids.clear();
+            }
+            if self.timestamp.as_ref() == Some(&log.timestamp) {
+                self.ids.insert(log.log_id.clone());
+            }
+        }
+        fresh
+    }
+}
+
+/// Time window for `logai export`, unix seconds
+struct ExportRange {
+    from: Option<i64>,
+    to: Option<i64>,
+    all: bool,
+}
+
+// A log to replay: the ingest body and its original time
+struct ReplayLog {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    body: serde_json::Map<String, serde_json::Value>,
+}
+
+// Turn an exported NDJSON line back into an ingest request. Exports carry ClickHouse
+// timestamps ("2026-02-10 03:00:00.412", UTC) and Debug levels ("Error"); RFC 3339 works too.
+fn replay_log(line: &str) -> Result<ReplayLog, String> {
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let serde_json::Value::Object(mut body) = value else {
+        return Err("Expected a JSON object".to_string());
+    };
+
+    let raw = body
+        .get("timestamp")
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "Missing timestamp".to_string())?;
+    let timestamp = chrono::DateTime::parse_from_rfc3339(raw)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f").map(|dt| dt.and_utc()))
+        .map_err(|_| format!("Invalid timestamp '{}'", raw))?;
+
+    // the server assigns new ids
+    body.remove("log_id");
+    body.remove("id");
+    if let Some(level) = body.get("level").and_then(|l| l.as_str()) {
+        let level = level.to_lowercase();
+        body.insert("level".to_string(), level.into());
+    }
+    body.insert("timestamp".to_string(), timestamp.to_rfc3339().into());
+
+    Ok(ReplayLog { timestamp, body })
+}
+
+// How long to wait before sending each log: the gap to the previous one, divided by `speed`.
+// Out-of-order timestamps don't wait.
+fn replay_delays(timestamps: &[chrono::DateTime<chrono::Utc>], speed: f64) -> Vec<Duration> {
+    let mut delays = vec![Duration::ZERO; timestamps.len().min(1)];
+    delays.extend(timestamps.windows(2).map(|pair| {
+        let gap = (pair[1] - pair[0]).to_std().unwrap_or(Duration::ZERO);
+        gap.div_f64(speed)
+    }));
+    delays
+}
+
+async fn replay_file(
+    client: &reqwest::Client,
+    api_url: &str,
+    file_path: &str,
+    speed: f64,
+    preserve_timestamps: bool,
+    verbose: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(format!("--speed must be a positive number, got {}", speed).into());
+    }
+
+    let content = std::fs::read_to_string(file_path)?;
+    let mut logs = Vec::new();
+    let mut skipped = 0;
+    for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        match replay_log(line) {
+            Ok(log) => logs.push(log),
+            Err(e) => {
+                skipped += 1;
+                if verbose {
+                    println!("{} line {}: {}", "Skipping".yellow(), i + 1, e);
+                }
+            }
+        }
+    }
+
+    let timestamps: Vec<_> = logs.iter().map(|l| l.timestamp).collect();
+    let delays = replay_delays(&timestamps, speed);
+    let duration: Duration = delays.iter().sum();
+
+    println!("\n{} {}", "⏯ Replaying:".cyan().bold(), file_path);
+    println!("{} {}x", "Speed:".dimmed(), speed);
+    println!(
+        "{} {}",
+        "Timestamps:".dimmed(),
+        if preserve_timestamps { "original" } else { "rewritten to now" }
+    );
+    println!("{} {} logs over {:.1}s", "Replay:".dimmed(), logs.len(), duration.as_secs_f64());
+    println!("{}", "─".repeat(40).dimmed());
+
+    let pb = indicatif::ProgressBar::new(logs.len() as u64);
+    pb.set_style(
+        indicatif::ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len}")?
+            .progress_chars("#>-"),
+    );
+
+    let url = format!("{}/api/logs", api_url);
+    let mut success = 0;
+    let mut failed = 0;
+    let mut last_error: Option<String> = None;
+
+    for (mut log, delay) in logs.into_iter().zip(delays) {
+        tokio::time::sleep(delay).await;
+        if !preserve_timestamps {
+            log.body.remove("timestamp");
+        }
+        match client.post(&url).json(&log.body).send().await {
+            Ok(resp) if resp.status().is_success() => success += 1,
+            Ok(resp) => {
+                failed += 1;
+                let status = resp.status();
+                last_error = Some(format!("{}: {}", status, error_message(resp).await));
+            }
+            Err(e) => {
+                failed += 1;
+                last_error = Some(e.to_string());
+            }
+        }
+        pb.inc(1);
+    }
+
+    pb.finish_and_clear();
+    println!("{}", "Results:".green().bold());
+    println!("  {} {}", "Success:".dimmed(), success.to_string().green());
+    println!("  {} {}", "Failed:".dimmed(), failed.to_string().red());
+    if skipped > 0 {
+        println!("  {} {}", "Skipped:".dimmed(), skipped.to_string().yellow());
+    }
+    if let Some(err) = last_error {
+        println!("\n{} {}", "Last error:".red(), err);
+    }
+
+    Ok(())
+}
+
+async fn export_logs(
+    client: &reqwest::Client,
+    api_url: &str,
+    path: Option<&str>,
+    range: ExportRange,
+    service: Option<String>,
+    level: Option<String>,
+    format: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut url = format!("{}/api/logs/export?format={}", api_url, urlencoding::encode(format));
+    // without --all the server wants a start; default to the last day
+    let from = match range.from {
+        Some(from) => Some(from),
+        None if range.all => None,
+        None => Some(chrono::Utc::now().timestamp() - 24 * 3600),
+    };
+    if let Some(from) = from {
+        url.push_str(&format!("&from={}", from));
+    }
+    if let Some(to) = range.to {
+        url.push_str(&format!("&to={}", to));
+    }
+    if let Some(ref service) = service {
+        url.push_str(&format!("&service={}", urlencoding::encode(service)));
+    }
+    if let Some(ref level) = level {
+        url.push_str(&format!("&level={}", urlencoding::encode(level)));
+    }
+    if range.all {
+        url.push_str("&unbounded=true");
+    }
+
+    let mut response = client
+        .get(&url)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(error_message(response).await.into());
+    }
+
+    let mut out: Box<dyn Write> = match path {
+        Some(path) => Box::new(io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    // progress goes to stderr (and nowhere when it isn't a terminal), so stdout stays clean
+    let pb = indicatif::ProgressBar::new_spinner();
+    pb.set_style(indicatif::ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {bytes} {msg}")?);
+
+    let mut lines = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        out.write_all(&chunk)?;
+        lines += chunk.iter().filter(|b| **b == b'\n').count() as u64;
+        pb.set_position(pb.position() + chunk.len() as u64);
+        pb.set_message(format!("{} lines", lines));
+    }
+    out.flush()?;
+    pb.finish_and_clear();
+
+    // the CSV header line isn't a log
+    let logs = if format == "csv" { lines.saturating_sub(1) } else { lines };
+    eprintln!("{} {} logs to {}", "Exported".green().bold(), logs, path.unwrap_or("stdout"));
+    Ok(())
+}
+
+fn print_log_line(timestamp: &str, level: &str, service: &str, message: &str) {
+    let level_colored = match level.to_lowercase().as_str() {
+        "error" => format!("[{}]", level).red().to_string(),
+        "warn" => format!("[{}]", level).yellow().to_string(),
+        "notice" => format!("[{}]", level).cyan().to_string(),
+        "info" => format!("[{}]", level).green().to_string(),
+        "debug" => format!("[{}]", level).blue().to_string(),
+        _ => format!("[{}]", level),
+    };
+
+    let time = if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+        dt.format("%Y-%m-%d %H:%M:%S").to_string()
+    } else {
+        timestamp.to_string()
+    };
+
+    println!(
+        "{} {} {} {}",
+        time.dimmed(),
+        level_colored,
+        service.cyan(),
+        message
+    );
+}
+
+#[derive(Deserialize, Serialize)]
+struct StreamedLog {
+    timestamp: String,
+    level: String,
+    service: String,
+    message: String,
+}
+
+impl CsvRow for StreamedLog {
+    const HEADER: &'static [&'static str] = &["timestamp", "level", "service", "message"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.timestamp.clone(),
+            self.level.clone(),
+            self.service.clone(),
+            self.message.clone(),
+        ]
+    }
+}
+
+#[derive(Deserialize)]
+struct RecentLogsResponse {
+    logs: Vec<RecentLog>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RecentLog {
+    log_id: String,
+    service: String,
+    level: String,
+    message: String,
+    timestamp: String,
+}
+
+impl From<SearchResult> for RecentLog {
+    fn from(r: SearchResult) -> Self {
+        Self {
+            log_id: r.log_id,
+            service: r.service,
+            level: r.level,
+            message: r.message,
+            timestamp: r.timestamp,
+        }
+    }
+}
+
+impl CsvRow for RecentLog {
+    const HEADER: &'static [&'static str] = &["log_id", "service", "level", "message", "timestamp"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.log_id.clone(),
+            self.service.clone(),
+            self.level.clone(),
+            self.message.clone(),
+            self.timestamp.clone(),
+        ]
+    }
+}
+
+// Response types for stats API
+#[derive(Deserialize, Serialize)]
+struct StatsResponse {
+    total_logs: u64,
+    logs_24h: u64,
+    error_count: u64,
+    services_count: u64,
+    embeddings_count: u64,
+    storage_mb: f64,
+    // null when no log in the last 24h had a numeric latency_ms (or the API predates them)
+    #[serde(default)]
+    latency_p50_ms: Option<f64>,
+    #[serde(default)]
+    latency_p95_ms: Option<f64>,
+    #[serde(default)]
+    latency_p99_ms: Option<f64>,
+    #[serde(default)]
+    slowest_endpoints: Vec<EndpointLatency>,
+    #[serde(default)]
+    top_services: Vec<ServiceVolume>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct EndpointLatency {
+    endpoint: String,
+    p95_ms: f64,
+    requests: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ServiceVolume {
+    service: String,
+    logs: u64,
+}
+
+impl CsvRow for StatsResponse {
+    const HEADER: &'static [&'static str] = &[
+        "total_logs",
+        "logs_24h",
+        "error_count",
+        "services_count",
+        "embeddings_count",
+        "storage_mb",
+        "latency_p50_ms",
+        "latency_p95_ms",
+        "latency_p99_ms",
+    ];
+    fn csv_fields(&self) -> Vec<String> {
+        let ms = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        vec![
+            self.total_logs.to_string(),
+            self.logs_24h.to_string(),
+            self.error_count.to_string(),
+            self.services_count.to_string(),
+            self.embeddings_count.to_string(),
+            self.storage_mb.to_string(),
+            ms(self.latency_p50_ms),
+            ms(self.latency_p95_ms),
+            ms(self.latency_p99_ms),
+        ]
+    }
+}
+
+async fn get_log(
+    client: &reqwest::Client,
+    api_url: &str,
+    id: &str,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let response = client
+        .get(format!("{}/api/logs/{}", api_url, urlencoding::encode(id)))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+
+    let log: LogRecord = response.json().await?;
+    if !output.is_table() {
+        println!("{}", output::format_record(output, &log)?);
+        return Ok(());
+    }
+
+    let level = match log.level.to_lowercase().as_str() {
+        "fatal" | "error" => log.level.to_uppercase().red().bold(),
+        "warn" => log.level.to_uppercase().yellow().bold(),
+        "notice" => log.level.to_uppercase().cyan().bold(),
+        "info" => log.level.to_uppercase().green().bold(),
+        _ => log.level.to_uppercase().normal(),
+    };
+    println!("\n{} {}", "📄 Log".cyan().bold(), log.id.dimmed());
+    println!("{}", "─".repeat(60).dimmed());
+    println!("  {} {}", "Time:".dimmed(), log.timestamp);
+    println!("  {} {}", "Level:".dimmed(), level);
+    println!("  {} {}", "Service:".dimmed(), log.service.cyan());
+    println!("  {} {}", "Message:".dimmed(), log.message);
+    if let Some(category) = &log.error_category {
+        println!("  {} {}", "Category:".dimmed(), category.magenta());
+    }
+    if let Some(trace_id) = &log.trace_id {
+        println!("  {} {}", "Trace:".dimmed(), trace_id);
+    }
+    if let Some(span_id) = &log.span_id {
+        println!("  {} {}", "Span:".dimmed(), span_id);
+    }
+    println!("  {} {}", "Ingested:".dimmed(), log.ingested_at.dimmed());
+
+    if !log.fields.is_empty() {
+        println!("\n{}", "Fields".cyan().bold());
+        let width = log.fields.keys().map(|k| k.len()).max().unwrap_or(0);
+        for (key, value) in &log.fields {
+            // strings without their JSON quotes; numbers, objects etc. as JSON
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            println!("  {}  {}", format!("{:<width$}", key, width = width).dimmed(), value);
+        }
+    }
+
+    // the raw line is usually JSON (the parsed input); pretty-print it when it is
+    let raw = serde_json::from_str::<serde_json::Value>(&log.raw)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| log.raw.clone());
+    println!("\n{}", "Raw".cyan().bold());
+    for line in raw.lines() {
+        println!("  {}", line.dimmed());
+    }
+    println!();
+    Ok(())
+}
+
+async fn explain_log(
     client: &reqwest::Client,
     api_url: &str,
-    limit: usize,
-    level: Option<String>,
+    id: &str,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let query = level.unwrap_or_else(|| "*".to_string());
-    
-    println!("\n{}", "📋 Recent Logs".cyan().bold());
-    println!("{}", "─".repeat(80).dimmed());
+    if output.is_table() {
+        println!("\n{}", "🤖 Explaining log...".cyan().bold());
+        println!("{}", "─".repeat(60).dimmed());
+    }
 
-    let url = format!("{}/api/search?q={}&limit={}", api_url, urlencoding::encode(&query), limit);
     let response = client
-        .get(&url)
+        .post(format!("{}/api/explain", api_url))
+        .json(&serde_json::json!({ "log_id": id }))
         .send()
         .await?;
 
     if !response.status().is_success() {
-        let error = response.text().await?;
-        println!("{} {}", "Error:".red().bold(), error);
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+
+    let result: ExplainResult = response.json().await?;
+    if !output.is_table() {
+        println!("{}", output::format_record(output, &result)?);
         return Ok(());
     }
 
-    let results: Vec<SearchResult> = response.json().await?;
+    println!("  {}", result.log.dimmed());
+    println!("\n{}", "Explanation:".green().bold());
+    println!("{}", result.explanation);
+    if !result.next_steps.is_empty() {
+        println!("\n{}", "Next steps:".green().bold());
+        for step in &result.next_steps {
+            println!("  • {}", step);
+        }
+    }
 
-    for r in results {
-        let level_colored = match r.level.to_lowercase().as_str() {
-            "error" => format!("[{}]", r.level).red().to_string(),
-            "warn" => format!("[{}]", r.level).yellow().to_string(),
-            "info" => format!("[{}]", r.level).green().to_string(),
-            "debug" => format!("[{}]", r.level).blue().to_string(),
-            _ => format!("[{}]", r.level),
-        };
+    println!("\n{}", "─".repeat(60).dimmed());
+    let time = if result.cached { "cached".to_string() } else { format!("{}ms", result.response_time_ms) };
+    println!("{} {} | {} {}", "Provider:".dimmed(), result.provider.cyan(), "Time:".dimmed(), time.yellow());
+    println!();
+    Ok(())
+}
 
-        let time = if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&r.timestamp) {
-            dt.format("%Y-%m-%d %H:%M:%S").to_string()
-        } else {
-            r.timestamp.clone()
-        };
+async fn delete_logs(
+    client: &reqwest::Client,
+    api_url: &str,
+    field: &str,
+    value: &str,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let response = client
+        .delete(format!(
+            "{}/api/logs?field={}&value={}",
+            api_url,
+            urlencoding::encode(field),
+            urlencoding::encode(value)
+        ))
+        .send()
+        .await?;
 
-        println!(
-            "{} {} {} {}",
-            time.dimmed(),
-            level_colored,
-            r.service.cyan(),
-            r.message
-        );
+    if !response.status().is_success() {
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+
+    let result: DeleteResult = response.json().await?;
+    if !output.is_table() {
+        println!("{}", output::format_record(output, &result)?);
+        return Ok(());
     }
 
+    println!("\n{} {} = {}", "🗑  Deleted logs where".cyan().bold(), field, value);
+    println!("{}", "─".repeat(60).dimmed());
+    println!("  {} {}", "ClickHouse rows:".dimmed(), result.clickhouse_deleted);
+    println!("  {} {}", "Qdrant points:  ".dimmed(), result.qdrant_deleted);
+    println!();
     Ok(())
 }
 
-// Response types for stats API
-#[derive(Deserialize)]
-struct StatsResponse {
-    total_logs: u64,
-    logs_24h: u64,
-    error_count: u64,
-    services_count: u64,
-    embeddings_count: u64,
-    storage_mb: f64,
+async fn run_bench(
+    client: &reqwest::Client,
+    api_url: &str,
+    target: BenchTarget,
+    requests: usize,
+    concurrency: usize,
+    warmup: usize,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if output.is_table() {
+        println!(
+            "\n{} {:?}: {} requests, {} at a time ({} warmup)",
+            "⏱  Benchmarking".cyan().bold(),
+            target,
+            requests,
+            concurrency.max(1),
+            warmup
+        );
+        println!("{}", "─".repeat(60).dimmed());
+    }
+
+    let report = bench::run(client, api_url, target, requests, concurrency, warmup).await;
+    if !output.is_table() {
+        println!("{}", output::format_record(output, &report)?);
+        return Ok(());
+    }
+
+    let ms = |v: Option<f64>| v.map(|v| format!("{:.0}ms", v)).unwrap_or_else(|| "-".to_string());
+    println!(
+        "  {} {}  {} {}  {} {}  {} {}",
+        "p50:".dimmed(),
+        ms(report.p50_ms).green(),
+        "p95:".dimmed(),
+        ms(report.p95_ms).yellow(),
+        "p99:".dimmed(),
+        ms(report.p99_ms).red(),
+        "mean:".dimmed(),
+        ms(report.mean_ms)
+    );
+    let errors = format!("{} ({:.1}%)", report.errors, report.error_rate * 100.0);
+    println!(
+        "  {} {}  {} {:.1}",
+        "Errors:".dimmed(),
+        if report.errors > 0 { errors.red() } else { errors.green() },
+        "Requests/s:".dimmed(),
+        report.requests_per_sec
+    );
+    println!();
+    Ok(())
 }
 
 async fn show_stats(
     client: &reqwest::Client,
     api_url: &str,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    if !output.is_table() {
+        // no ClickHouse fallback here: scripts get the API's numbers or an error
+        let stats: StatsResponse = client
+            .get(format!("{}/api/stats", api_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        println!("{}", output::format_record(output, &stats)?);
+        return Ok(());
+    }
+
     println!("\n{}", "📊 System Statistics".cyan().bold());
     println!("{}", "─".repeat(50).dimmed());
 
@@ -609,6 +1971,7 @@ async fn show_stats(
             println!("  {} {}", "Services:".dimmed(), stats.services_count.to_string().cyan());
             println!("  {} {}", "Embeddings:".dimmed(), stats.embeddings_count.to_string().magenta());
             println!("  {} {:.2} MB", "Storage:".dimmed(), stats.storage_mb);
+            print_latency_stats(&stats);
         }
         _ => {
             // Fallback: Query ClickHouse directly
@@ -670,6 +2033,36 @@ async fn show_stats(
     Ok(())
 }
 
+fn print_latency_stats(stats: &StatsResponse) {
+    println!("\n{}", "⏱  Latency (24h)".cyan().bold());
+    match (stats.latency_p50_ms, stats.latency_p95_ms, stats.latency_p99_ms) {
+        (Some(p50), Some(p95), Some(p99)) => {
+            println!(
+                "  {} {:.0}ms  {} {}  {} {}",
+                "p50:".dimmed(),
+                p50,
+                "p95:".dimmed(),
+                format!("{:.0}ms", p95).yellow(),
+                "p99:".dimmed(),
+                format!("{:.0}ms", p99).red()
+            );
+        }
+        _ => println!("  {}", "No latency_ms values in the last 24h".dimmed()),
+    }
+
+    if !stats.slowest_endpoints.is_empty() {
+        println!("  {}", "Slowest endpoints (p95):".dimmed());
+        for endpoint in &stats.slowest_endpoints {
+            println!(
+                "    {:<30} {} {}",
+                endpoint.endpoint,
+                format!("{:>8.0}ms", endpoint.p95_ms).yellow(),
+                format!("({} requests)", endpoint.requests).dimmed()
+            );
+        }
+    }
+}
+
 fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n{}", "🚀 Starting LogAI API Server...".cyan().bold());
     println!("{}", "─".repeat(40).dimmed());
@@ -708,8 +2101,7 @@ struct AlertResponse {
     alerts: Vec<AlertItem>,
 }
 
-#[derive(Deserialize)]
-#[allow(dead_code)]
+#[derive(Deserialize, Serialize)]
 struct AlertItem {
     id: String,
     service: String,
@@ -719,19 +2111,42 @@ struct AlertItem {
     fired_at: String,
 }
 
+impl CsvRow for AlertItem {
+    const HEADER: &'static [&'static str] = &["id", "service", "severity", "message", "status", "fired_at"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.service.clone(),
+            self.severity.clone(),
+            self.message.clone(),
+            self.status.clone(),
+            self.fired_at.clone(),
+        ]
+    }
+}
+
 async fn show_alerts(
     client: &reqwest::Client,
     api_url: &str,
     status_filter: Option<String>,
+    range: TimeRange,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("\n{}", "🚨 Active Alerts".cyan().bold());
-    println!("{}", "─".repeat(60).dimmed());
-
     // Try API first
-    let url = match &status_filter {
+    let mut url = match &status_filter {
         Some(s) => format!("{}/api/alerts?status={}", api_url, s),
         None => format!("{}/api/alerts", api_url),
     };
+    range.append_to(&mut url);
+
+    if !output.is_table() {
+        let data: AlertResponse = client.get(&url).send().await?.error_for_status()?.json().await?;
+        println!("{}", output::format_rows(output, &data.alerts)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "🚨 Active Alerts".cyan().bold());
+    println!("{}", "─".repeat(60).dimmed());
 
     match client.get(&url).send().await {
         Ok(resp) if resp.status().is_success() => {
@@ -795,7 +2210,7 @@ struct AnomalyResponse {
     checked_at: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct AnomalyItem {
     service: String,
     rule: String,
@@ -805,20 +2220,42 @@ struct AnomalyItem {
     expected_value: f64,
 }
 
+impl CsvRow for AnomalyItem {
+    const HEADER: &'static [&'static str] =
+        &["service", "rule", "severity", "message", "current_value", "expected_value"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.service.clone(),
+            self.rule.clone(),
+            self.severity.clone(),
+            self.message.clone(),
+            self.current_value.to_string(),
+            self.expected_value.to_string(),
+        ]
+    }
+}
+
 async fn check_anomalies(
     client: &reqwest::Client,
     api_url: &str,
     service_filter: Option<String>,
+    output: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("\n{}", "🔎 Anomaly Detection".cyan().bold());
-    println!("{}", "─".repeat(60).dimmed());
-
     // Try API
     let url = match &service_filter {
         Some(s) => format!("{}/api/anomalies?service={}", api_url, s),
         None => format!("{}/api/anomalies", api_url),
     };
 
+    if !output.is_table() {
+        let data: AnomalyResponse = client.get(&url).send().await?.error_for_status()?.json().await?;
+        println!("{}", output::format_rows(output, &data.anomalies)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "🔎 Anomaly Detection".cyan().bold());
+    println!("{}", "─".repeat(60).dimmed());
+
     match client.get(&url).send().await {
         Ok(resp) if resp.status().is_success() => {
             let data: AnomalyResponse = resp.json().await?;
@@ -868,6 +2305,253 @@ async fn check_anomalies(
     Ok(())
 }
 
+/// Body of GET /api/diff
+#[derive(Deserialize)]
+struct DiffResponse {
+    total_a: u64,
+    total_b: u64,
+    categories: Vec<ErrorDelta>,
+    new_errors: Vec<ErrorDelta>,
+    disappeared_errors: Vec<ErrorDelta>,
+    biggest_increases: Vec<ErrorDelta>,
+}
+
+#[derive(Deserialize)]
+struct ErrorDelta {
+    key: String,
+    count_a: u64,
+    count_b: u64,
+    change: i64,
+}
+
+/// One line of the diff for json/csv output; `kind` says which list it came from
+#[derive(Serialize)]
+struct DiffRow<'a> {
+    kind: &'static str,
+    key: &'a str,
+    count_a: u64,
+    count_b: u64,
+    change: i64,
+}
+
+impl CsvRow for DiffRow<'_> {
+    const HEADER: &'static [&'static str] = &["kind", "key", "count_a", "count_b", "change"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.kind.to_string(),
+            self.key.to_string(),
+            self.count_a.to_string(),
+            self.count_b.to_string(),
+            self.change.to_string(),
+        ]
+    }
+}
+
+async fn diff_errors(
+    client: &reqwest::Client,
+    api_url: &str,
+    (from_a, to_a): (i64, i64),
+    (from_b, to_b): (i64, i64),
+    service: Option<String>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut url = format!(
+        "{}/api/diff?from_a={}&to_a={}&from_b={}&to_b={}",
+        api_url, from_a, to_a, from_b, to_b
+    );
+    if let Some(ref service) = service {
+        url.push_str(&format!("&service={}", urlencoding::encode(service)));
+    }
+
+    let response = client.get(&url).send().await?;
+    if !response.status().is_success() {
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+    let diff: DiffResponse = response.json().await?;
+
+    let sections = [
+        ("category", "By category", &diff.categories),
+        ("new", "New errors", &diff.new_errors),
+        ("disappeared", "Disappeared errors", &diff.disappeared_errors),
+        ("increased", "Biggest increases", &diff.biggest_increases),
+    ];
+
+    if !output.is_table() {
+        let rows: Vec<DiffRow> = sections
+            .iter()
+            .flat_map(|(kind, _, deltas)| {
+                deltas.iter().map(move |d| DiffRow {
+                    kind,
+                    key: &d.key,
+                    count_a: d.count_a,
+                    count_b: d.count_b,
+                    change: d.change,
+                })
+            })
+            .collect();
+        println!("{}", output::format_rows(output, &rows)?);
+        return Ok(());
+    }
+
+    let format_range = |from: i64, to: i64| {
+        let time = |secs: i64| {
+            chrono::DateTime::from_timestamp(secs, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| secs.to_string())
+        };
+        format!("{} → {}", time(from), time(to))
+    };
+
+    println!("\n{}", "🔀 Error Diff".cyan().bold());
+    println!("{}", "─".repeat(60).dimmed());
+    println!("  {} {}  ({} errors)", "A:".dimmed(), format_range(from_a, to_a), diff.total_a);
+    println!("  {} {}  ({} errors)", "B:".dimmed(), format_range(from_b, to_b), diff.total_b);
+    if let Some(ref service) = service {
+        println!("  {} {}", "Service:".dimmed(), service.cyan());
+    }
+
+    for (_, title, deltas) in sections {
+        println!("\n{}", title.cyan().bold());
+        if deltas.is_empty() {
+            println!("  {}", "none".dimmed());
+            continue;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Error", "A", "B", "Change"]);
+        for d in deltas.iter() {
+            let change = match d.change {
+                c if c > 0 => format!("+{}", c).red().to_string(),
+                c if c < 0 => c.to_string().green().to_string(),
+                c => c.to_string(),
+            };
+            let key = if d.key.len() > 60 {
+                format!("{}...", d.key.chars().take(57).collect::<String>())
+            } else {
+                d.key.clone()
+            };
+            table.add_row(vec![key, d.count_a.to_string(), d.count_b.to_string(), change]);
+        }
+        println!("{table}");
+    }
+
+    println!();
+    Ok(())
+}
+
+async fn show_patterns(
+    client: &reqwest::Client,
+    api_url: &str,
+    service: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut params = Vec::new();
+    if let Some(ref service) = service {
+        params.push(format!("service={}", urlencoding::encode(service)));
+    }
+    if let Some(from) = from {
+        params.push(format!("from={}", from));
+    }
+    if let Some(to) = to {
+        params.push(format!("to={}", to));
+    }
+    let url = format!("{}/api/patterns?{}", api_url, params.join("&"));
+
+    let response = client.get(&url).send().await?;
+    if !response.status().is_success() {
+        let error = error_message(response).await;
+        return report_error(output, error);
+    }
+    let result: PatternsResponse = response.json().await?;
+
+    if !output.is_table() {
+        println!("{}", output::format_rows(output, &result.patterns)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "🧩 Log Patterns".cyan().bold());
+    println!("{}", "─".repeat(60).dimmed());
+    let time = |secs: i64| {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| secs.to_string())
+    };
+    println!("  {} {} → {}  ({} logs)", "Range:".dimmed(), time(result.from), time(result.to), result.total);
+    if let Some(ref service) = service {
+        println!("  {} {}", "Service:".dimmed(), service.cyan());
+    }
+    if result.truncated {
+        println!("  {}", "Too many distinct messages, the rarest were left out".yellow());
+    }
+    if result.patterns.is_empty() {
+        println!("\n  {}\n", "No logs in this range".dimmed());
+        return Ok(());
+    }
+
+    let truncate = |text: &str| {
+        if text.chars().count() > 60 {
+            format!("{}...", text.chars().take(57).collect::<String>())
+        } else {
+            text.to_string()
+        }
+    };
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Count", "Template", "Levels", "Example"]);
+    for pattern in &result.patterns {
+        table.add_row(vec![
+            pattern.count.to_string(),
+            truncate(&pattern.template),
+            pattern.levels(),
+            truncate(&pattern.example).dimmed().to_string(),
+        ]);
+    }
+    println!("{table}");
+    println!();
+    Ok(())
+}
+
+/// Body of GET /api/patterns
+#[derive(Deserialize)]
+struct PatternsResponse {
+    from: i64,
+    to: i64,
+    total: u64,
+    #[serde(default)]
+    truncated: bool,
+    patterns: Vec<LogPattern>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct LogPattern {
+    template: String,
+    count: u64,
+    example: String,
+    level_distribution: BTreeMap<String, u64>,
+}
+
+impl LogPattern {
+    /// "Error 20, Fatal 10"
+    fn levels(&self) -> String {
+        self.level_distribution
+            .iter()
+            .map(|(level, count)| format!("{} {}", level, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl CsvRow for LogPattern {
+    const HEADER: &'static [&'static str] = &["count", "template", "levels", "example"];
+    fn csv_fields(&self) -> Vec<String> {
+        vec![self.count.to_string(), self.template.clone(), self.levels(), self.example.clone()]
+    }
+}
+
 // Chat types
 #[derive(Serialize)]
 struct ChatRequest {
@@ -895,6 +2579,43 @@ struct ChatResponse {
     source_logs: Vec<String>,
 }
 
+fn validate_anomaly_config(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let report = logai_anomaly::config::validate_config(path)
+        .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+
+    println!("\n{} {}", "🔎 Validating".cyan().bold(), path);
+    println!("{}", "─".repeat(80).dimmed());
+
+    for error in &report.errors {
+        println!("{} {}", "✗".red().bold(), error);
+    }
+    for rule in &report.rules {
+        if rule.errors.is_empty() {
+            println!("{} {}", "✓".green().bold(), rule.name);
+        } else {
+            println!("{} {}", "✗".red().bold(), rule.name.bold());
+            for error in &rule.errors {
+                println!("    {} {}", "-".dimmed(), error);
+            }
+        }
+    }
+
+    let failed = report.rules.iter().filter(|r| !r.errors.is_empty()).count();
+    println!("{}", "─".repeat(80).dimmed());
+    if report.is_valid() {
+        println!("{} {} rules OK", "Valid:".green().bold(), report.rules.len());
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} rules invalid, {} config errors",
+            failed,
+            report.rules.len(),
+            report.errors.len()
+        )
+        .into())
+    }
+}
+
 /// Interactive chat mode - the core debugging experience
 async fn interactive_chat(
     client: &reqwest::Client,
@@ -943,7 +2664,11 @@ async fn interactive_chat(
 
         // Read input
         let mut input = String::new();
-        stdin.lock().read_line(&mut input)?;
+        // end of input (Ctrl-D, or stdin already used up by --api-key-file -) ends the chat
+        if stdin.lock().read_line(&mut input)? == 0 {
+            println!();
+            break;
+        }
         let input = input.trim();
 
         // Skip empty
@@ -1113,7 +2838,7 @@ async fn process_chat_message(
         Ok(resp) => {
             print!("\x1B[1A\x1B[2K");
             let status = resp.status();
-            let error = resp.text().await.unwrap_or_default();
+            let error = error_message(resp).await;
             
             if status.as_u16() == 404 {
                 // Fallback to /api/ask if /api/chat not available
@@ -1185,3 +2910,129 @@ fn print_wrapped(text: &str, width: usize) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_json_output_parses() {
+        let cli = Cli::try_parse_from(["logai", "search", "timeout", "--output", "json"]).unwrap();
+        assert_eq!(cli.output, OutputFormat::Json);
+
+        let results = vec![SearchResult {
+            score: 0.87,
+            log_id: "3f2c9a1e-0000-4000-8000-000000000001".to_string(),
+            service: "checkout".to_string(),
+            level: "Error".to_string(),
+            message: "upstream \"payments\" timed out, retrying".to_string(),
+            timestamp: "2026-02-10T03:00:00Z".to_string(),
+        }];
+
+        let rendered = output::format_rows(cli.output, &results).unwrap();
+        assert!(!rendered.contains('\u{1b}'), "no ANSI escapes in machine output");
+        let parsed: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed[0]["service"], "checkout");
+        assert_eq!(parsed[0]["message"], "upstream \"payments\" timed out, retrying");
+    }
+
+    #[test]
+    fn test_tail_cursor_skips_printed_logs() {
+        let log = |id: &str, timestamp: &str| RecentLog {
+            log_id: id.to_string(),
+            service: "checkout".to_string(),
+            level: "Info".to_string(),
+            message: format!("log {}", id),
+            timestamp: timestamp.to_string(),
+        };
+        let ids = |logs: Vec<RecentLog>| logs.into_iter().map(|l| l.log_id).collect::<Vec<_>>();
+        let mut cursor = TailCursor::default();
+
+        // pages come newest first, output goes oldest first
+        let first = vec![
+            log("c", "2026-02-10 03:00:02.000"),
+            log("b", "2026-02-10 03:00:01.000"),
+            log("a", "2026-02-10 03:00:01.000"),
+        ];
+        assert_eq!(ids(cursor.take_new(first)), ["a", "b", "c"]);
+
+        // "d" landed late with the cursor's timestamp; "c" and "b" were already printed
+        let second = vec![
+            log("e", "2026-02-10 03:00:03.000"),
+            log("e", "2026-02-10 03:00:03.000"),
+            log("d", "2026-02-10 03:00:02.000"),
+            log("c", "2026-02-10 03:00:02.000"),
+            log("b", "2026-02-10 03:00:01.000"),
+        ];
+        assert!(!cursor.is_new(&second[3]));
+        assert_eq!(ids(cursor.take_new(second)), ["d", "e"]);
+        assert_eq!(cursor.timestamp.as_deref(), Some("2026-02-10 03:00:03.000"));
+
+        assert!(cursor.take_new(vec![log("e", "2026-02-10 03:00:03.000")]).is_empty());
+    }
+
+    #[test]
+    fn test_export_time_formats() {
+        assert_eq!(parse_time("1770692400"), Ok(1770692400));
+        assert_eq!(parse_time("2026-02-10T03:00:00Z"), Ok(1770692400));
+        assert_eq!(parse_time("2026-02-10T05:00:00+02:00"), Ok(1770692400));
+        assert_eq!(parse_time("2026-02-10"), Ok(1770681600));
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_replay_delays() {
+        let at = |t: &str| chrono::DateTime::parse_from_rfc3339(t).unwrap().with_timezone(&chrono::Utc);
+        let timestamps = [
+            at("2026-02-10T03:00:00Z"),
+            at("2026-02-10T03:00:02Z"),
+            at("2026-02-10T03:00:02.500Z"),
+            at("2026-02-10T03:00:01Z"), // out of order
+            at("2026-02-10T03:00:11Z"),
+        ];
+
+        let ms = |delays: Vec<Duration>| delays.iter().map(|d| d.as_millis()).collect::<Vec<_>>();
+        assert_eq!(ms(replay_delays(&timestamps, 1.0)), [0, 2000, 500, 0, 10000]);
+        assert_eq!(ms(replay_delays(&timestamps, 10.0)), [0, 200, 50, 0, 1000]);
+        assert!(replay_delays(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_replay_log_from_export_line() {
+        let line = r#"{"log_id":"id-1","service":"checkout","level":"Error","message":"timeout","timestamp":"2026-02-10 03:00:00.412"}"#;
+        let log = replay_log(line).unwrap();
+        assert_eq!(log.timestamp.to_rfc3339(), "2026-02-10T03:00:00.412+00:00");
+        assert_eq!(log.body["level"], "error");
+        assert_eq!(log.body["timestamp"], "2026-02-10T03:00:00.412+00:00");
+        assert!(!log.body.contains_key("log_id"));
+
+        assert!(replay_log(r#"{"message":"no time"}"#).is_err());
+    }
+
+    #[test]
+    fn test_api_key_precedence() {
+        let key = |flag: Option<&str>, file: Option<&str>, env: Option<&str>| {
+            resolve_api_key(flag.map(String::from), file, env.map(String::from), |path| match path {
+                "-" => Ok("stdin-key\n".to_string()),
+                "key.txt" => Ok("  file-key \n".to_string()),
+                "empty.txt" => Ok("\n".to_string()),
+                _ => Err(io::Error::new(io::ErrorKind::NotFound, "No such file")),
+            })
+        };
+        let ok = |k: &str| Ok(Some(k.to_string()));
+
+        assert_eq!(key(Some("flag-key"), Some("key.txt"), Some("env-key")), ok("flag-key"));
+        assert_eq!(key(None, Some("key.txt"), Some("env-key")), ok("file-key"));
+        assert_eq!(key(None, Some("-"), Some("env-key")), ok("stdin-key"));
+        assert_eq!(key(None, None, Some("env-key")), ok("env-key"));
+        assert_eq!(key(None, None, Some("")), Ok(None));
+        assert_eq!(key(None, None, None), Ok(None));
+
+        // a file that was asked for but can't be used doesn't fall back to the env
+        assert_eq!(
+            key(None, Some("missing.txt"), Some("env-key")),
+            Err("Cannot read the API key from missing.txt: No such file".to_string())
+        );
+        assert_eq!(key(None, Some("empty.txt"), None), Err("No API key in empty.txt".to_string()));
+    }
+}
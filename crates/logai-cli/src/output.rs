@@ -0,0 +1,113 @@
+// Output modes for scripting: `--output json|csv` prints plain data instead of tables
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored tables for humans
+    #[default]
+    Table,
+    /// A JSON array (or object for single records)
+    Json,
+    /// Comma-separated rows with a header line
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+}
+
+/// Rows that can be written as CSV, one column per header entry
+pub trait CsvRow {
+    const HEADER: &'static [&'static str];
+    fn csv_fields(&self) -> Vec<String>;
+}
+
+/// Colors only make sense for a human at a terminal
+pub fn configure_colors(format: OutputFormat) {
+    if !format.is_table() || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
+/// Render a list in a machine format (Table renders nothing, callers draw their own)
+pub fn format_rows<T: Serialize + CsvRow>(format: OutputFormat, rows: &[T]) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Table => Ok(String::new()),
+        OutputFormat::Json => serde_json::to_string_pretty(rows),
+        OutputFormat::Csv => {
+            let mut out = csv_line(T::HEADER.iter().copied());
+            for row in rows {
+                out.push('\n');
+                out.push_str(&csv_line(row.csv_fields().iter().map(String::as_str)));
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Single record (stats): a JSON object, or a header plus one CSV row
+pub fn format_record<T: Serialize + CsvRow>(format: OutputFormat, record: &T) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(record),
+        _ => format_rows(format, std::slice::from_ref(record)),
+    }
+}
+
+pub fn csv_line<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    fields.map(csv_escape).collect::<Vec<_>>().join(",")
+}
+
+// RFC 4180: quote fields holding commas, quotes or newlines; double embedded quotes
+fn csv_escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        service: String,
+        message: String,
+    }
+
+    impl CsvRow for Row {
+        const HEADER: &'static [&'static str] = &["service", "message"];
+        fn csv_fields(&self) -> Vec<String> {
+            vec![self.service.clone(), self.message.clone()]
+        }
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let rows = vec![
+            Row { service: "api".into(), message: "plain".into() },
+            Row { service: "db".into(), message: "key \"id\", line 1\nline 2".into() },
+        ];
+
+        let csv = format_rows(OutputFormat::Csv, &rows).unwrap();
+        assert_eq!(
+            csv,
+            "service,message\napi,plain\ndb,\"key \"\"id\"\", line 1\nline 2\""
+        );
+    }
+
+    #[test]
+    fn test_record_is_json_object() {
+        let row = Row { service: "api".into(), message: "ok".into() };
+        let json: serde_json::Value = serde_json::from_str(&format_record(OutputFormat::Json, &row).unwrap()).unwrap();
+        assert_eq!(json["service"], "api");
+        assert_eq!(format_record(OutputFormat::Csv, &row).unwrap(), "service,message\napi,ok");
+    }
+}
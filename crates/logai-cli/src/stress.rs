@@ -39,6 +39,32 @@ struct Args {
     /// Log format (structured, apache, nginx, syslog)
     #[arg(short, long, default_value = "structured")]
     format: String,
+
+    /// Pause sending (exponential backoff with jitter) after a run of failed batches,
+    /// instead of hammering a server that is shedding load
+    #[arg(long)]
+    backoff: bool,
+
+    /// Retry a failed batch up to N times, backing off between attempts
+    #[arg(long, default_value = "0")]
+    retry: u32,
+}
+
+// Consecutive failed batches that count as a burst and trigger --backoff
+const BACKOFF_AFTER_FAILURES: u64 = 3;
+const BACKOFF_BASE: Duration = Duration::from_millis(100);
+const BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+/// Delay before retry/pause number `attempt` (0-based): base * 2^attempt capped at `max`, of
+/// which the upper half is scaled by `jitter` (0..1) so workers don't retry in lockstep
+fn backoff_delay(attempt: u32, base: Duration, max: Duration, jitter: f64) -> Duration {
+    let exponential = base.saturating_mul(2u32.saturating_pow(attempt)).min(max);
+    let half = exponential / 2;
+    half + half.mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+fn jittered_backoff(attempt: u32) -> Duration {
+    backoff_delay(attempt, BACKOFF_BASE, BACKOFF_MAX, rand::rng().random())
 }
 
 #[derive(Serialize)]
@@ -64,6 +90,9 @@ struct Metrics {
     failed: AtomicU64,
     latency_sum_us: AtomicU64,
     latency_count: AtomicU64,
+    retries: AtomicU64,
+    backoff_us: AtomicU64,
+    consecutive_failures: AtomicU64,
 }
 
 impl Metrics {
@@ -74,6 +103,9 @@ impl Metrics {
             failed: AtomicU64::new(0),
             latency_sum_us: AtomicU64::new(0),
             latency_count: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            backoff_us: AtomicU64::new(0),
+            consecutive_failures: AtomicU64::new(0),
         }
     }
 
@@ -83,11 +115,23 @@ impl Metrics {
         self.latency_sum_us
             .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
         self.latency_count.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
     }
 
     fn record_failure(&self, count: u64) {
         self.sent.fetch_add(count, Ordering::Relaxed);
         self.failed.fetch_add(count, Ordering::Relaxed);
+        self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    async fn back_off(&self, delay: Duration) {
+        tokio::time::sleep(delay).await;
+        self.backoff_us
+            .fetch_add(delay.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn backoff_time(&self) -> Duration {
+        Duration::from_micros(self.backoff_us.load(Ordering::Relaxed))
     }
 
     fn get_stats(&self) -> (u64, u64, u64, f64) {
@@ -224,23 +268,11 @@ async fn send_structured_batch(
     endpoint: &str,
     batch: Vec<LogEntry>,
     metrics: &Metrics,
+    retry: u32,
 ) {
     let count = batch.len() as u64;
-    let start = Instant::now();
-
-    match client
-        .post(format!("{}/api/logs", endpoint))
-        .json(&batch)
-        .send()
-        .await
-    {
-        Ok(resp) if resp.status().is_success() => {
-            metrics.record_success(count, start.elapsed());
-        }
-        _ => {
-            metrics.record_failure(count);
-        }
-    }
+    let url = format!("{}/api/logs", endpoint);
+    send_batch(client, &url, &batch, count, metrics, retry).await;
 }
 
 async fn send_raw_batch(
@@ -249,29 +281,44 @@ async fn send_raw_batch(
     format: &str,
     lines: Vec<String>,
     metrics: &Metrics,
+    retry: u32,
 ) {
     let count = lines.len() as u64;
-    let start = Instant::now();
-
     let req = RawLogRequest {
         format: format.to_string(),
         service: format!("stress-test-{}", format),
         lines,
     };
+    let url = format!("{}/api/logs/raw", endpoint);
+    send_batch(client, &url, &req, count, metrics, retry).await;
+}
 
-    match client
-        .post(format!("{}/api/logs/raw", endpoint))
-        .json(&req)
-        .send()
-        .await
-    {
-        Ok(resp) if resp.status().is_success() => {
-            metrics.record_success(count, start.elapsed());
+// Latency is that of the attempt that succeeded; the logs only count as failed once
+// every retry has
+async fn send_batch<T: Serialize>(
+    client: &reqwest::Client,
+    url: &str,
+    body: &T,
+    count: u64,
+    metrics: &Metrics,
+    retry: u32,
+) {
+    for attempt in 0..=retry {
+        if attempt > 0 {
+            metrics.retries.fetch_add(1, Ordering::Relaxed);
+            metrics.back_off(jittered_backoff(attempt - 1)).await;
         }
-        _ => {
-            metrics.record_failure(count);
+
+        let start = Instant::now();
+        match client.post(url).json(body).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                metrics.record_success(count, start.elapsed());
+                return;
+            }
+            _ => {}
         }
     }
+    metrics.record_failure(count);
 }
 
 #[tokio::main]
@@ -298,6 +345,10 @@ async fn main() {
         args.workers
     );
     println!("║ Format:         {:>10}                                ║", args.format);
+    println!(
+        "║ Backoff/Retry:  {:>10}                                ║",
+        format!("{}/{}", if args.backoff { "on" } else { "off" }, args.retry)
+    );
     println!(
         "║ Endpoint:       {:>42} ║",
         args.endpoint
@@ -391,20 +442,30 @@ async fn main() {
             tokio::time::sleep(interval).await;
         }
 
+        // Server is failing batch after batch: give it room before the next one
+        if args.backoff {
+            let failures = metrics.consecutive_failures.load(Ordering::Relaxed);
+            if failures >= BACKOFF_AFTER_FAILURES {
+                let attempt = (failures - BACKOFF_AFTER_FAILURES).min(u32::MAX as u64) as u32;
+                metrics.back_off(jittered_backoff(attempt)).await;
+            }
+        }
+
         let permit = semaphore.clone().acquire_owned().await.unwrap();
         let client = client.clone();
         let endpoint = args.endpoint.clone();
         let format = args.format.clone();
         let batch_size = args.batch;
+        let retry = args.retry;
         let metrics = metrics.clone();
 
         let handle = tokio::spawn(async move {
             if format == "structured" {
                 let batch = generate_structured_batch(batch_size);
-                send_structured_batch(&client, &endpoint, batch, &metrics).await;
+                send_structured_batch(&client, &endpoint, batch, &metrics, retry).await;
             } else {
                 let lines = generate_raw_batch(&format, batch_size);
-                send_raw_batch(&client, &endpoint, &format, lines, &metrics).await;
+                send_raw_batch(&client, &endpoint, &format, lines, &metrics, retry).await;
             }
             drop(permit);
         });
@@ -461,6 +522,15 @@ async fn main() {
         "║ Avg Latency:    {:>10.2} ms                           ║",
         avg_latency
     );
+    println!(
+        "║ Retries:        {:>10} batches                       ║",
+        metrics.retries.load(Ordering::Relaxed)
+    );
+    // summed over workers, so it can exceed the wall-clock duration
+    println!(
+        "║ Backing Off:    {:>10.2} seconds                      ║",
+        metrics.backoff_time().as_secs_f64()
+    );
     println!(
         "║ Success Rate:   {:>10.2}%                             ║",
         if sent > 0 {
@@ -471,3 +541,31 @@ async fn main() {
     );
     println!("╚═══════════════════════════════════════════════════════════╝");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        let base = Duration::from_millis(100);
+        let max = Duration::from_secs(10);
+
+        // no jitter: half the exponential delay, full jitter: all of it
+        assert_eq!(backoff_delay(0, base, max, 0.0), Duration::from_millis(50));
+        assert_eq!(backoff_delay(0, base, max, 1.0), Duration::from_millis(100));
+        assert_eq!(backoff_delay(3, base, max, 0.0), Duration::from_millis(400));
+        assert_eq!(backoff_delay(3, base, max, 0.5), Duration::from_millis(600));
+        assert_eq!(backoff_delay(3, base, max, 1.0), Duration::from_millis(800));
+
+        // capped, however many failures
+        assert_eq!(backoff_delay(10, base, max, 1.0), max);
+        assert_eq!(backoff_delay(u32::MAX, base, max, 0.0), max / 2);
+        assert_eq!(backoff_delay(2, base, max, 7.0), Duration::from_millis(400));
+
+        for _ in 0..100 {
+            let delay = jittered_backoff(2);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400), "{:?}", delay);
+        }
+    }
+}
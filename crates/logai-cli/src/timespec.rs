@@ -0,0 +1,147 @@
+// Times on the command line: absolute (RFC 3339, a date, unix seconds) or, for
+// --since/--until, relative to now ("30m", "2h", "1d"). The API takes unix seconds.
+
+use clap::Args;
+
+/// `--since`/`--until` for commands that can be limited to a time range
+#[derive(Args, Debug, Default)]
+pub struct TimeArgs {
+    /// Only logs from this time on: a duration ago (30m, 2h, 1d) or RFC 3339, YYYY-MM-DD, unix seconds
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only logs up to this time, same formats
+    #[arg(long)]
+    pub until: Option<String>,
+}
+
+impl TimeArgs {
+    pub fn range(&self) -> Result<TimeRange, String> {
+        TimeRange::parse(self.since.as_deref(), self.until.as_deref(), chrono::Utc::now().timestamp())
+    }
+}
+
+/// The API's `from`/`to` parameters, in unix seconds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl TimeRange {
+    pub fn parse(since: Option<&str>, until: Option<&str>, now: i64) -> Result<Self, String> {
+        let from = since.map(|value| parse_time_arg(value, now)).transpose()?;
+        let to = until.map(|value| parse_time_arg(value, now)).transpose()?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err("--since must be before --until".to_string());
+            }
+        }
+        Ok(Self { from, to })
+    }
+
+    /// Add `from`/`to` to a URL that may already have a query string
+    pub fn append_to(&self, url: &mut String) {
+        for (name, value) in [("from", self.from), ("to", self.to)] {
+            if let Some(secs) = value {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                url.push_str(&format!("{}{}={}", separator, name, secs));
+            }
+        }
+    }
+}
+
+/// An absolute time: RFC 3339, YYYY-MM-DD (midnight UTC) or unix seconds
+pub fn parse_time(value: &str) -> Result<i64, String> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return Ok(secs);
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.timestamp());
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+        .map_err(|_| format!("Invalid time '{}': use RFC 3339, YYYY-MM-DD or unix seconds", value))
+}
+
+/// A `--since`/`--until` value: a duration before `now`, or an absolute time
+pub fn parse_time_arg(value: &str, now: i64) -> Result<i64, String> {
+    let value = value.trim();
+    if let Some(secs) = duration_secs(value) {
+        return Ok(now - secs);
+    }
+    parse_time(value).map_err(|_| {
+        format!(
+            "Invalid time '{}': use a duration (30m, 2h, 1d), RFC 3339, YYYY-MM-DD or unix seconds",
+            value
+        )
+    })
+}
+
+// "90s", "30m", "2h", "1d", "1w" -> seconds
+fn duration_secs(value: &str) -> Option<i64> {
+    let unit = value.chars().last()?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse::<u32>().ok()?.into();
+    let unit_secs = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        _ => return None,
+    };
+    Some(amount * unit_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1770692400; // 2026-02-10T03:00:00Z
+
+    #[test]
+    fn test_relative_times() {
+        assert_eq!(parse_time_arg("30m", NOW), Ok(NOW - 30 * 60));
+        assert_eq!(parse_time_arg("2h", NOW), Ok(NOW - 2 * 3600));
+        assert_eq!(parse_time_arg("1d", NOW), Ok(NOW - 86_400));
+        assert_eq!(parse_time_arg(" 90s ", NOW), Ok(NOW - 90));
+        assert_eq!(parse_time_arg("1w", NOW), Ok(NOW - 7 * 86_400));
+    }
+
+    #[test]
+    fn test_absolute_times() {
+        assert_eq!(parse_time_arg("2026-02-10T01:00:00Z", NOW), Ok(NOW - 2 * 3600));
+        assert_eq!(parse_time_arg("2026-02-10T03:00:00+02:00", NOW), Ok(NOW - 2 * 3600));
+        assert_eq!(parse_time_arg("2026-02-10", NOW), Ok(1770681600));
+        assert_eq!(parse_time_arg("1770692400", NOW), Ok(NOW));
+    }
+
+    #[test]
+    fn test_invalid_times() {
+        for value in ["yesterday", "2x", "-5m", "h", "", "2026-13-40"] {
+            let err = parse_time_arg(value, NOW).unwrap_err();
+            assert!(err.starts_with("Invalid time"), "{}: {}", value, err);
+        }
+
+        assert_eq!(
+            TimeRange::parse(Some("1h"), Some("2h"), NOW),
+            Err("--since must be before --until".to_string())
+        );
+        assert_eq!(
+            TimeRange::parse(Some("soon"), None, NOW).unwrap_err(),
+            "Invalid time 'soon': use a duration (30m, 2h, 1d), RFC 3339, YYYY-MM-DD or unix seconds"
+        );
+    }
+
+    #[test]
+    fn test_range_query_params() {
+        let range = TimeRange::parse(Some("2h"), Some("1h"), NOW).unwrap();
+        let mut url = "http://localhost:3000/api/alerts".to_string();
+        range.append_to(&mut url);
+        assert_eq!(url, format!("http://localhost:3000/api/alerts?from={}&to={}", NOW - 7200, NOW - 3600));
+
+        let mut url = "http://localhost:3000/api/search?q=timeout".to_string();
+        TimeRange { from: Some(NOW), to: None }.append_to(&mut url);
+        assert_eq!(url, format!("http://localhost:3000/api/search?q=timeout&from={}", NOW));
+    }
+}
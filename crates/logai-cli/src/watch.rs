@@ -0,0 +1,275 @@
+// `logai watch`: a terminal dashboard over GET /api/stats and GET /api/anomalies, redrawn
+// every few seconds. Logs/sec and the error rate come from the difference between two polls
+// of the stats counters, so the first refresh shows totals only.
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::{AnomalyItem, AnomalyResponse, StatsResponse};
+
+// error rate samples kept for the sparkline, oldest dropped first
+const HISTORY: usize = 120;
+// how long a key press may wait for the next redraw
+const INPUT_POLL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy)]
+struct Counters {
+    total: u64,
+    errors: u64,
+    at: Instant,
+}
+
+/// What the dashboard shows, updated from each poll of the API
+#[derive(Default)]
+pub struct WatchState {
+    last: Option<Counters>,
+    pub total_logs: u64,
+    /// None until two polls are in (or after the counters went backwards, e.g. retention)
+    pub logs_per_sec: Option<f64>,
+    /// Errors per log between consecutive polls, in basis points (100 = 1%)
+    pub error_rates: VecDeque<u64>,
+    pub top_services: Vec<(String, u64)>,
+    pub anomalies: Vec<AnomalyItem>,
+    /// Why the last refresh failed; the rest keeps the previous values
+    pub error: Option<String>,
+    pub updated: Option<chrono::DateTime<chrono::Local>>,
+}
+
+impl WatchState {
+    pub fn apply_stats(&mut self, stats: StatsResponse, at: Instant) {
+        let now = Counters {
+            total: stats.total_logs,
+            errors: stats.error_count,
+            at,
+        };
+        match self.last {
+            Some(prev) if now.total >= prev.total && now.errors >= prev.errors && now.at > prev.at => {
+                let logs = now.total - prev.total;
+                let errors = now.errors - prev.errors;
+                self.logs_per_sec = Some(logs as f64 / (now.at - prev.at).as_secs_f64());
+                // an idle interval is a zero on the sparkline, not a gap
+                let rate = (errors * 10_000).checked_div(logs).unwrap_or(0).min(10_000);
+                if self.error_rates.len() == HISTORY {
+                    self.error_rates.pop_front();
+                }
+                self.error_rates.push_back(rate);
+            }
+            _ => self.logs_per_sec = None,
+        }
+        self.last = Some(now);
+        self.total_logs = stats.total_logs;
+        self.top_services = stats.top_services.into_iter().map(|s| (s.service, s.logs)).collect();
+    }
+
+    pub fn apply_anomalies(&mut self, response: AnomalyResponse) {
+        self.anomalies = response.anomalies;
+    }
+
+    /// Error rate of the latest interval, as a percentage
+    pub fn error_rate(&self) -> Option<f64> {
+        self.error_rates.back().map(|bp| *bp as f64 / 100.0)
+    }
+}
+
+/// Runs until `q`, Esc or Ctrl+C; the terminal is restored either way
+pub async fn run(client: &reqwest::Client, api_url: &str, interval: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    let mut terminal = ratatui::init();
+    let result = watch_loop(&mut terminal, client, api_url, interval).await;
+    ratatui::restore();
+    result
+}
+
+async fn watch_loop(
+    terminal: &mut DefaultTerminal,
+    client: &reqwest::Client,
+    api_url: &str,
+    interval: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut state = WatchState::default();
+    let mut next_refresh = Instant::now();
+
+    loop {
+        if Instant::now() >= next_refresh {
+            refresh(&mut state, client, api_url).await;
+            next_refresh = Instant::now() + interval;
+        }
+        // a resize needs nothing more than this redraw: layouts come from the frame size
+        terminal.draw(|frame| render(frame, &state, interval))?;
+
+        let wait = next_refresh.saturating_duration_since(Instant::now()).min(INPUT_POLL);
+        if event::poll(wait)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+async fn refresh(state: &mut WatchState, client: &reqwest::Client, api_url: &str) {
+    let (stats, anomalies) = tokio::join!(
+        fetch::<StatsResponse>(client, format!("{}/api/stats", api_url)),
+        fetch::<AnomalyResponse>(client, format!("{}/api/anomalies", api_url)),
+    );
+    let mut errors = Vec::new();
+    match stats {
+        Ok(stats) => state.apply_stats(stats, Instant::now()),
+        Err(e) => errors.push(format!("stats: {}", e)),
+    }
+    match anomalies {
+        Ok(anomalies) => state.apply_anomalies(anomalies),
+        Err(e) => errors.push(format!("anomalies: {}", e)),
+    }
+    state.error = (!errors.is_empty()).then(|| errors.join("; "));
+    state.updated = Some(chrono::Local::now());
+}
+
+async fn fetch<T: serde::de::DeserializeOwned>(client: &reqwest::Client, url: String) -> Result<T, String> {
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+    response.json().await.map_err(|e| e.to_string())
+}
+
+fn render(frame: &mut Frame, state: &WatchState, interval: Duration) {
+    let [header, sparkline, lists, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(8),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [services, anomalies] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(lists);
+
+    let rate = |v: Option<f64>, unit: &str| v.map(|v| format!("{:.1}{}", v, unit)).unwrap_or_else(|| "-".to_string());
+    let summary = Line::from(vec![
+        "Logs/sec ".dim(),
+        rate(state.logs_per_sec, "").bold(),
+        "   Error rate ".dim(),
+        rate(state.error_rate(), "%").red(),
+        "   Total logs ".dim(),
+        state.total_logs.to_string().cyan(),
+        "   Anomalies ".dim(),
+        state.anomalies.len().to_string().yellow(),
+    ]);
+    frame.render_widget(Paragraph::new(summary).block(Block::bordered().title(" LogAI ")), header);
+
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(format!(" Error rate, every {}s ", interval.as_secs())))
+            .data(state.error_rates.iter().copied())
+            .style(Style::new().fg(Color::Red)),
+        sparkline,
+    );
+
+    let service_items: Vec<ListItem> = state
+        .top_services
+        .iter()
+        .map(|(service, logs)| ListItem::from(Line::from(vec![Span::raw(format!("{:<24}", service)), logs.to_string().cyan()])))
+        .collect();
+    frame.render_widget(
+        List::new(service_items).block(Block::bordered().title(" Top services (last hour) ")),
+        services,
+    );
+
+    let anomaly_items: Vec<ListItem> = state
+        .anomalies
+        .iter()
+        .map(|a| {
+            let severity = match a.severity.to_lowercase().as_str() {
+                "critical" => format!("[{}]", a.severity).red(),
+                "warning" => format!("[{}]", a.severity).yellow(),
+                _ => format!("[{}]", a.severity).blue(),
+            };
+            ListItem::from(Line::from(vec![
+                severity,
+                Span::raw(format!(" {} ", a.service)),
+                a.message.clone().dim(),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(anomaly_items).block(Block::bordered().title(" Active anomalies ")), anomalies);
+
+    let status = match (&state.error, state.updated) {
+        (Some(error), _) => Line::from(error.clone().red()),
+        (None, Some(at)) => Line::from(format!("Updated {}  ·  q to quit", at.format("%H:%M:%S")).dim()),
+        (None, None) => Line::from("Loading...  ·  q to quit".dim()),
+    };
+    frame.render_widget(Paragraph::new(status), footer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(total_logs: u64, error_count: u64) -> StatsResponse {
+        serde_json::from_value(serde_json::json!({
+            "total_logs": total_logs,
+            "logs_24h": total_logs,
+            "error_count": error_count,
+            "services_count": 2,
+            "embeddings_count": total_logs,
+            "storage_mb": 0.0,
+            "top_services": [{"service": "checkout", "logs": 900}, {"service": "auth", "logs": 100}]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_watch_state_updates() {
+        let start = Instant::now();
+        let mut state = WatchState::default();
+
+        state.apply_stats(stats(1_000, 10), start);
+        assert_eq!(state.logs_per_sec, None);
+        assert_eq!(state.error_rate(), None);
+        assert_eq!(state.total_logs, 1_000);
+        assert_eq!(state.top_services, vec![("checkout".to_string(), 900), ("auth".to_string(), 100)]);
+
+        // 600 logs, 30 of them errors, over 3 seconds
+        state.apply_stats(stats(1_600, 40), start + Duration::from_secs(3));
+        assert_eq!(state.logs_per_sec, Some(200.0));
+        assert_eq!(state.error_rate(), Some(5.0));
+
+        // nothing new: a zero on the sparkline, not a gap
+        state.apply_stats(stats(1_600, 40), start + Duration::from_secs(6));
+        assert_eq!(state.logs_per_sec, Some(0.0));
+        assert_eq!(state.error_rates, VecDeque::from([500, 0]));
+
+        // counters went backwards (retention dropped old logs): no rate this time
+        state.apply_stats(stats(800, 5), start + Duration::from_secs(9));
+        assert_eq!(state.logs_per_sec, None);
+        assert_eq!(state.error_rates.len(), 2);
+
+        let anomalies: AnomalyResponse = serde_json::from_value(serde_json::json!({
+            "checked_at": "2026-02-10T03:00:00Z",
+            "anomalies": [{
+                "service": "checkout", "rule": "error_spike", "severity": "critical",
+                "message": "Error rate 5x baseline", "current_value": 50.0, "expected_value": 10.0
+            }]
+        }))
+        .unwrap();
+        state.apply_anomalies(anomalies);
+        assert_eq!(state.anomalies.len(), 1);
+        assert_eq!(state.anomalies[0].rule, "error_spike");
+    }
+
+    #[test]
+    fn test_error_rate_history_is_bounded() {
+        let start = Instant::now();
+        let mut state = WatchState::default();
+        for i in 0..=HISTORY as u64 + 10 {
+            state.apply_stats(stats(i * 100, i), start + Duration::from_secs(i));
+        }
+        assert_eq!(state.error_rates.len(), HISTORY);
+        assert_eq!(state.error_rate(), Some(1.0));
+    }
+}
@@ -66,6 +66,8 @@ fn bench_log_entry_conversion(c: &mut Criterion) {
         service: Some("test-service".to_string()),
         level: Some(logai_core::LogLevel::Error),
         trace_id: Some("abc-123-xyz".to_string()),
+        span_id: None,
+        parent_span_id: None,
         fields: HashMap::from([
             ("user_id".to_string(), serde_json::json!("u123")),
             ("endpoint".to_string(), serde_json::json!("/api/test")),
@@ -113,6 +115,8 @@ fn bench_json_serialization(c: &mut Criterion) {
         service: Some("payment-service".to_string()),
         level: Some(logai_core::LogLevel::Error),
         trace_id: Some("trace-123".to_string()),
+        span_id: None,
+        parent_span_id: None,
         fields: HashMap::from([
             ("user_id".to_string(), serde_json::json!("u999")),
             ("amount".to_string(), serde_json::json!(99.99)),
@@ -0,0 +1,253 @@
+// Chunking - groups consecutive logs from one service into a LogChunk so a burst
+// ("db down, 40 timeouts, pool exhausted") is embedded as one story instead of 40 points.
+
+use crate::{LogChunk, LogEntry, LogLevel};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How much goes into one chunk
+#[derive(Debug, Clone)]
+pub struct ChunkConfig {
+    /// Max span between the first and last log of a chunk (by log timestamp).
+    /// Also how long a chunk stays open without new logs before it's flushed.
+    pub window: Duration,
+    pub max_logs: usize,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            max_logs: 50,
+        }
+    }
+}
+
+struct OpenChunk {
+    entries: Vec<LogEntry>,
+    last_push: Instant,
+}
+
+/// Streaming grouper: one open chunk per service, closed when a log falls outside
+/// the window, when it's full, or when the service goes quiet
+pub struct Chunker {
+    config: ChunkConfig,
+    open: HashMap<String, OpenChunk>,
+}
+
+impl Chunker {
+    pub fn new(config: ChunkConfig) -> Self {
+        Self {
+            config,
+            open: HashMap::new(),
+        }
+    }
+
+    /// Add a log; returns the chunk it closed, if any
+    pub fn push(&mut self, entry: LogEntry) -> Option<LogChunk> {
+        self.push_at(entry, Instant::now())
+    }
+
+    fn push_at(&mut self, entry: LogEntry, now: Instant) -> Option<LogChunk> {
+        let closed = match self.open.get(&entry.service) {
+            Some(chunk) if !self.fits(&chunk.entries, &entry) => self.open.remove(&entry.service),
+            _ => None,
+        };
+
+        let chunk = self.open.entry(entry.service.clone()).or_insert_with(|| OpenChunk {
+            entries: Vec::new(),
+            last_push: now,
+        });
+        chunk.entries.push(entry);
+        chunk.last_push = now;
+
+        closed.map(|c| build_chunk(c.entries))
+    }
+
+    // room left, and the chunk would still span at most `window` (out-of-order logs count too)
+    fn fits(&self, entries: &[LogEntry], entry: &LogEntry) -> bool {
+        if entries.len() >= self.config.max_logs {
+            return false;
+        }
+        let start = entries.iter().map(|e| e.timestamp).min().unwrap_or(entry.timestamp);
+        let end = entries.iter().map(|e| e.timestamp).max().unwrap_or(entry.timestamp);
+        let span = end.max(entry.timestamp) - start.min(entry.timestamp);
+        span.to_std().map(|span| span <= self.config.window).unwrap_or(false)
+    }
+
+    /// Close chunks that haven't received a log for `window`
+    pub fn flush_idle(&mut self, now: Instant) -> Vec<LogChunk> {
+        let window = self.config.window;
+        let idle: Vec<String> = self
+            .open
+            .iter()
+            .filter(|(_, chunk)| now.saturating_duration_since(chunk.last_push) >= window)
+            .map(|(service, _)| service.clone())
+            .collect();
+
+        idle.into_iter()
+            .filter_map(|service| self.open.remove(&service))
+            .map(|chunk| build_chunk(chunk.entries))
+            .collect()
+    }
+
+    /// Close everything still open (shutdown, end of a batch)
+    pub fn flush(&mut self) -> Vec<LogChunk> {
+        self.open.drain().map(|(_, chunk)| build_chunk(chunk.entries)).collect()
+    }
+}
+
+/// Group a batch of logs in one go
+pub fn chunk_logs(entries: impl IntoIterator<Item = LogEntry>, config: ChunkConfig) -> Vec<LogChunk> {
+    let mut chunker = Chunker::new(config);
+    let mut chunks: Vec<LogChunk> = entries.into_iter().filter_map(|e| chunker.push(e)).collect();
+    chunks.extend(chunker.flush());
+    chunks.sort_by_key(|c| c.start_time);
+    chunks
+}
+
+/// Text that gets embedded: a header with counts by level, then each distinct message once
+///
+/// ```text
+/// checkout: 5 logs (3 error, 2 info)
+/// Connection refused to db:5432 (x3)
+/// Retrying payment
+/// ```
+pub fn summarize(entries: &[LogEntry]) -> String {
+    let service = entries.first().map(|e| e.service.as_str()).unwrap_or("unknown");
+
+    let mut by_level: HashMap<LogLevel, usize> = HashMap::new();
+    let mut messages: Vec<(&str, usize)> = Vec::new();
+    for entry in entries {
+        *by_level.entry(entry.level).or_default() += 1;
+        match messages.iter_mut().find(|(m, _)| *m == entry.message) {
+            Some((_, count)) => *count += 1,
+            None => messages.push((&entry.message, 1)),
+        }
+    }
+
+    let mut levels: Vec<(LogLevel, usize)> = by_level.into_iter().collect();
+    levels.sort_by_key(|(level, _)| std::cmp::Reverse(*level)); // most severe first
+    let levels: Vec<String> = levels
+        .iter()
+        .map(|(level, count)| format!("{} {}", count, format!("{:?}", level).to_lowercase()))
+        .collect();
+
+    let mut summary = format!("{}: {} logs ({})", service, entries.len(), levels.join(", "));
+    for (message, count) in messages {
+        summary.push('\n');
+        summary.push_str(message);
+        if count > 1 {
+            summary.push_str(&format!(" (x{})", count));
+        }
+    }
+    summary
+}
+
+fn build_chunk(entries: Vec<LogEntry>) -> LogChunk {
+    let first = &entries[0];
+    LogChunk {
+        id: Uuid::new_v4(),
+        log_ids: entries.iter().map(|e| e.id).collect(),
+        start_time: entries.iter().map(|e| e.timestamp).min().unwrap_or(first.timestamp),
+        end_time: entries.iter().map(|e| e.timestamp).max().unwrap_or(first.timestamp),
+        service: first.service.clone(),
+        summary: summarize(&entries),
+        embedding: None,
+        log_count: entries.len(),
+        max_level: entries.iter().map(|e| e.level).max().unwrap_or(LogLevel::Info),
+        relevance_score: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RawLogEntry;
+    use chrono::{TimeZone, Utc};
+
+    fn log(service: &str, secs: i64, level: LogLevel, message: &str) -> LogEntry {
+        LogEntry::from_raw(RawLogEntry {
+            message: message.to_string(),
+            timestamp: Some(Utc.timestamp_opt(1_770_000_000 + secs, 0).unwrap()),
+            service: Some(service.to_string()),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields: HashMap::new(),
+        })
+    }
+
+    #[test]
+    fn test_window_and_service_boundaries() {
+        let logs = vec![
+            log("checkout", 0, LogLevel::Info, "Retrying payment"),
+            log("auth", 5, LogLevel::Info, "Token issued"),
+            log("checkout", 30, LogLevel::Error, "Connection refused"),
+            log("checkout", 60, LogLevel::Error, "Connection refused"), // exactly at the window edge
+            log("checkout", 61, LogLevel::Warn, "Pool exhausted"),      // 61s after the first: new chunk
+        ];
+
+        let chunks = chunk_logs(logs, ChunkConfig::default());
+        let shape: Vec<(&str, usize)> = chunks.iter().map(|c| (c.service.as_str(), c.log_count)).collect();
+        assert_eq!(shape, vec![("checkout", 3), ("auth", 1), ("checkout", 1)]);
+
+        let first = &chunks[0];
+        assert_eq!(first.max_level, LogLevel::Error);
+        assert_eq!((first.end_time - first.start_time).num_seconds(), 60);
+        assert_eq!(first.log_ids.len(), 3);
+    }
+
+    #[test]
+    fn test_full_chunk_and_out_of_order() {
+        let config = ChunkConfig {
+            window: Duration::from_secs(60),
+            max_logs: 2,
+        };
+        let logs = (0..5).map(|i| log("api", i, LogLevel::Info, "GET /health"));
+        let sizes: Vec<usize> = chunk_logs(logs, config).iter().map(|c| c.log_count).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+
+        // a late log that still fits the window joins the open chunk
+        let logs = vec![
+            log("api", 30, LogLevel::Info, "b"),
+            log("api", 0, LogLevel::Info, "a"),
+            log("api", 100, LogLevel::Info, "c"),
+        ];
+        let chunks = chunk_logs(logs, ChunkConfig::default());
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].log_count, 2);
+        assert_eq!(chunks[0].start_time, Utc.timestamp_opt(1_770_000_000, 0).unwrap());
+    }
+
+    #[test]
+    fn test_flush_idle_services_only() {
+        let mut chunker = Chunker::new(ChunkConfig::default());
+        let start = Instant::now();
+        assert!(chunker.push_at(log("checkout", 0, LogLevel::Info, "a"), start).is_none());
+        assert!(chunker.push_at(log("auth", 0, LogLevel::Info, "b"), start + Duration::from_secs(50)).is_none());
+
+        let idle = chunker.flush_idle(start + Duration::from_secs(60));
+        assert_eq!(idle.len(), 1);
+        assert_eq!(idle[0].service, "checkout");
+        assert_eq!(chunker.flush().len(), 1);
+    }
+
+    #[test]
+    fn test_summary_dedups_and_counts_levels() {
+        let logs = vec![
+            log("checkout", 0, LogLevel::Error, "Connection refused to db:5432"),
+            log("checkout", 1, LogLevel::Info, "Retrying payment"),
+            log("checkout", 2, LogLevel::Error, "Connection refused to db:5432"),
+            log("checkout", 3, LogLevel::Info, "Retrying payment"),
+            log("checkout", 4, LogLevel::Error, "Connection refused to db:5432"),
+        ];
+
+        assert_eq!(
+            summarize(&logs),
+            "checkout: 5 logs (3 error, 2 info)\nConnection refused to db:5432 (x3)\nRetrying payment (x2)"
+        );
+    }
+}
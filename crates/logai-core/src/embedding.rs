@@ -0,0 +1,218 @@
+// Embedding model selection and the Qdrant collection layout - shared by the worker (indexing)
+// and the API (query embedding).
+// Both sides must load the same model, vectors of a different size are rejected by Qdrant.
+
+/// Models we can run locally through fastembed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmbeddingModelKind {
+    #[default]
+    AllMiniLmL6V2,
+    AllMiniLmL12V2,
+    BgeSmallEn,
+    BgeBaseEn,
+    BgeLargeEn,
+    NomicEmbedText,
+}
+
+impl EmbeddingModelKind {
+    pub const ALL: [Self; 6] = [
+        Self::AllMiniLmL6V2,
+        Self::AllMiniLmL12V2,
+        Self::BgeSmallEn,
+        Self::BgeBaseEn,
+        Self::BgeLargeEn,
+        Self::NomicEmbedText,
+    ];
+
+    /// Name accepted in `EMBEDDING_MODEL`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AllMiniLmL6V2 => "all-MiniLM-L6-v2",
+            Self::AllMiniLmL12V2 => "all-MiniLM-L12-v2",
+            Self::BgeSmallEn => "bge-small-en",
+            Self::BgeBaseEn => "bge-base-en",
+            Self::BgeLargeEn => "bge-large-en",
+            Self::NomicEmbedText => "nomic-embed-text",
+        }
+    }
+
+    /// Output vector size, also the Qdrant collection dimension
+    pub fn dimension(&self) -> u64 {
+        match self {
+            Self::AllMiniLmL6V2 | Self::AllMiniLmL12V2 | Self::BgeSmallEn => 384,
+            Self::BgeBaseEn | Self::NomicEmbedText => 768,
+            Self::BgeLargeEn => 1024,
+        }
+    }
+
+    /// Case-insensitive; also takes HuggingFace ids like `BAAI/bge-small-en-v1.5`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let name = name.rsplit('/').next().unwrap_or(&name);
+        let name = name.trim_end_matches("-v1.5");
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().to_lowercase() == name)
+    }
+
+    /// Read `EMBEDDING_MODEL` (default: all-MiniLM-L6-v2)
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("EMBEDDING_MODEL") {
+            Ok(name) if !name.trim().is_empty() => Self::from_name(&name).ok_or_else(|| {
+                let supported: Vec<&str> = Self::ALL.iter().map(|kind| kind.name()).collect();
+                format!(
+                    "Unknown EMBEDDING_MODEL '{}' (supported: {})",
+                    name,
+                    supported.join(", ")
+                )
+            }),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+/// Checks a collection's vector size against what `model` produces. The error is meant to
+/// be read by whoever changed EMBEDDING_MODEL: both sizes, and the two ways out.
+pub fn verify_embedding_dimensions(
+    collection: &str,
+    collection_dimension: u64,
+    model: EmbeddingModelKind,
+) -> Result<(), String> {
+    if collection_dimension == model.dimension() {
+        return Ok(());
+    }
+    let matching: Vec<&str> = EmbeddingModelKind::ALL
+        .iter()
+        .filter(|kind| kind.dimension() == collection_dimension)
+        .map(|kind| kind.name())
+        .collect();
+    let switch_back = if matching.is_empty() {
+        format!("set EMBEDDING_MODEL to the model the collection was built with ({} dims)", collection_dimension)
+    } else {
+        format!("set EMBEDDING_MODEL back to a {}-dim model ({})", collection_dimension, matching.join(", "))
+    };
+    Err(format!(
+        "Qdrant collection '{}' holds {}-dim vectors, but EMBEDDING_MODEL={} produces {}. Either {}, \
+         or delete the collection and re-ingest the logs to embed them with {}",
+        collection,
+        collection_dimension,
+        model.name(),
+        model.dimension(),
+        switch_back,
+        model.name()
+    ))
+}
+
+/// The Qdrant collection holding log embeddings, and the prefix of the per-service ones
+pub const COLLECTION_NAME: &str = "log_embeddings";
+
+// longest service name kept in a per-service collection name
+const MAX_SERVICE_CHARS: usize = 64;
+
+/// Where the worker stores embeddings and the API searches them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollectionLayout {
+    /// Everything in `log_embeddings`
+    #[default]
+    Single,
+    /// One `log_embeddings_{service}` collection per service, created on first use
+    PerService,
+}
+
+impl CollectionLayout {
+    /// Read `QDRANT_COLLECTION_PER_SERVICE` (default: false, a single collection)
+    pub fn from_env() -> Self {
+        let per_service = std::env::var("QDRANT_COLLECTION_PER_SERVICE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or(false);
+        if per_service { Self::PerService } else { Self::Single }
+    }
+
+    /// The collection holding `service`'s logs
+    pub fn collection_for(&self, service: &str) -> String {
+        match self {
+            Self::Single => COLLECTION_NAME.to_string(),
+            Self::PerService => service_collection(service),
+        }
+    }
+}
+
+/// `log_embeddings_{service}`: the name lowercased, anything but `a-z`, `0-9`, `_` and `-`
+/// replaced by `_`, cut to 64 characters. A name with nothing usable left maps to `_unknown`.
+pub fn service_collection(service: &str) -> String {
+    let sanitized: String = service
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .take(MAX_SERVICE_CHARS)
+        .collect();
+    let sanitized = sanitized.trim_matches('_');
+    let service = if sanitized.is_empty() { "unknown" } else { sanitized };
+    format!("{}_{}", COLLECTION_NAME, service)
+}
+
+/// Whether `name` is a log embeddings collection, the single one or a per-service one
+pub fn is_log_collection(name: &str) -> bool {
+    name.strip_prefix(COLLECTION_NAME)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_names() {
+        assert_eq!(EmbeddingModelKind::from_name("bge-small-en"), Some(EmbeddingModelKind::BgeSmallEn));
+        assert_eq!(EmbeddingModelKind::from_name("BAAI/bge-base-en-v1.5"), Some(EmbeddingModelKind::BgeBaseEn));
+        assert_eq!(EmbeddingModelKind::from_name("all-minilm-l12-v2"), Some(EmbeddingModelKind::AllMiniLmL12V2));
+        assert_eq!(EmbeddingModelKind::from_name("text-embedding-3-small"), None);
+
+        for kind in EmbeddingModelKind::ALL {
+            assert_eq!(EmbeddingModelKind::from_name(kind.name()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(EmbeddingModelKind::default().dimension(), 384);
+        assert_eq!(EmbeddingModelKind::BgeBaseEn.dimension(), 768);
+        assert_eq!(EmbeddingModelKind::BgeLargeEn.dimension(), 1024);
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        assert!(verify_embedding_dimensions("log_embeddings", 384, EmbeddingModelKind::BgeSmallEn).is_ok());
+
+        let error = verify_embedding_dimensions("log_embeddings", 384, EmbeddingModelKind::BgeBaseEn).unwrap_err();
+        assert!(error.contains("holds 384-dim vectors, but EMBEDDING_MODEL=bge-base-en produces 768"));
+        assert!(error.contains("all-MiniLM-L6-v2, all-MiniLM-L12-v2, bge-small-en"));
+        assert!(error.contains("delete the collection"));
+
+        // a size none of our models has
+        let error = verify_embedding_dimensions("log_embeddings_checkout", 1536, EmbeddingModelKind::default()).unwrap_err();
+        assert!(error.starts_with("Qdrant collection 'log_embeddings_checkout' holds 1536-dim vectors"));
+        assert!(error.contains("the model the collection was built with (1536 dims)"));
+    }
+
+    #[test]
+    fn test_service_collection_names() {
+        assert_eq!(service_collection("checkout"), "log_embeddings_checkout");
+        assert_eq!(service_collection("Payment-API"), "log_embeddings_payment-api");
+        assert_eq!(service_collection("billing.v2 eu/west"), "log_embeddings_billing_v2_eu_west");
+        assert_eq!(service_collection("  auth  "), "log_embeddings_auth");
+        assert_eq!(service_collection("café"), "log_embeddings_caf");
+        assert_eq!(service_collection(""), "log_embeddings_unknown");
+        assert_eq!(service_collection("../.."), "log_embeddings_unknown");
+        assert_eq!(service_collection(&"a".repeat(300)), format!("log_embeddings_{}", "a".repeat(64)));
+
+        assert_eq!(CollectionLayout::Single.collection_for("checkout"), COLLECTION_NAME);
+        assert_eq!(CollectionLayout::PerService.collection_for("Checkout"), "log_embeddings_checkout");
+
+        assert!(is_log_collection("log_embeddings"));
+        assert!(is_log_collection("log_embeddings_checkout"));
+        assert!(!is_log_collection("log_embeddings2"));
+        assert!(!is_log_collection("traces"));
+    }
+}
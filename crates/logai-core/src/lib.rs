@@ -1,11 +1,25 @@
 //! Core types for log intelligence system
 //! this crate contains shared data strcture used acrosss all components.
+pub mod chunking;
+pub mod embedding;
+pub mod logging;
+pub mod nats;
+pub mod otlp;
 pub mod parser;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Header carrying a request id: HTTP (API requests and responses, sent by the CLI)
+/// and NATS (logs published by the API, so worker logs can be matched to the request)
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// `fields` key holding a log's parent span id (see `RawLogEntry::parent_span_id`)
+pub const PARENT_SPAN_FIELD: &str = "parent_span_id";
+
 // LOG LEVEL //
 
 /// Log severity levels (ordered from lowest to highest)
@@ -17,6 +31,7 @@ pub enum LogLevel {
     Trace,
     Debug,
     Info,
+    Notice, // normal but significant (syslog severity 5, Apache [notice])
     Warn,
     Error,
     Fatal,
@@ -29,6 +44,7 @@ impl LogLevel {
             "trace" => Some(Self::Trace),
             "debug" => Some(Self::Debug),
             "info" => Some(Self::Info),
+            "notice" => Some(Self::Notice),
             "warn" | "warning" => Some(Self::Warn),
             "error" | "err" => Some(Self::Error),
             "fatal" | "critical" | "crit" => Some(Self::Fatal),
@@ -58,6 +74,13 @@ pub struct RawLogEntry {
     #[serde(default)]
     pub trace_id: Option<String>,
 
+    #[serde(default)]
+    pub span_id: Option<String>,
+
+    // the span this one was started from; kept in `fields` once stored
+    #[serde(default)]
+    pub parent_span_id: Option<String>,
+
     #[serde(default)]
     pub fields: std::collections::HashMap<String, serde_json::Value>,
 }
@@ -102,6 +125,11 @@ impl LogEntry {
         let now = Utc::now();
         
         let raw_json = serde_json::to_string(&raw).unwrap_or_else(|_| raw.message.clone());
+        // ClickHouse has no parent column, the trace tree reads it from fields
+        let mut fields = raw.fields;
+        if let Some(parent) = raw.parent_span_id {
+            fields.insert(PARENT_SPAN_FIELD.to_string(), serde_json::Value::String(parent));
+        }
         Self {
             id: Uuid::new_v4(),
             timestamp: raw.timestamp.unwrap_or(now),
@@ -110,9 +138,9 @@ impl LogEntry {
             message: raw.message.clone(),
             raw: raw_json,
             trace_id: raw.trace_id,
-            span_id: None,
+            span_id: raw.span_id,
             error_category: None,
-            fields: raw.fields,
+            fields,
             ingested_at: now,
         }
     }
@@ -134,6 +162,22 @@ pub enum ErrorCategory {
     Unknown,
 }
 
+impl ErrorCategory {
+    /// Parse a category as stored in ClickHouse ("OutOfMemory") or serialized ("out_of_memory")
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().replace('_', "").as_str() {
+            "outofmemory" => Some(Self::OutOfMemory),
+            "timeout" => Some(Self::Timeout),
+            "connectionerror" => Some(Self::ConnectionError),
+            "httperror" => Some(Self::HttpError),
+            "databaseerror" => Some(Self::DatabaseError),
+            "autherror" => Some(Self::AuthError),
+            "unknown" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+}
+
 // LOG chunk (for embeddings/ vector storage)
 
 // A chunk of logs gruped tgether for embeddings explained in .md planfile
@@ -149,7 +193,7 @@ pub struct LogChunk {
     pub summary: String,           // Summary text for embedding
 
     #[serde(default)]
-    pub embedding: Option<Vec<f32>>, // the mebedding vector (384 dimensioons for fastemebed)
+    pub embedding: Option<Vec<f32>>, // the mebedding vector (size depends on EMBEDDING_MODEL)
     pub log_count: usize, // Number of logs in chunk
     pub max_level: LogLevel,
 
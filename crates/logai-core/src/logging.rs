@@ -0,0 +1,12 @@
+// Tracing setup shared by the API and the worker
+
+/// LOG_FORMAT=json: one JSON object per line, with the enclosing span's fields
+/// (service, request_id, ...), for shipping to an aggregator or back into LogAI.
+/// Anything else keeps the human-readable text format.
+pub fn init_tracing() {
+    if std::env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json")) {
+        tracing_subscriber::fmt().json().flatten_event(true).init();
+    } else {
+        tracing_subscriber::fmt::init();
+    }
+}
@@ -0,0 +1,75 @@
+// Where logs travel between the API and the workers. Both read the same variables, so
+// they agree on the subject; the queue group only matters to the workers.
+
+/// Subject the API publishes logs on when NATS_INGEST_SUBJECT isn't set
+pub const DEFAULT_INGEST_SUBJECT: &str = "logs.ingest";
+/// Queue group workers join when NATS_QUEUE_GROUP isn't set
+pub const DEFAULT_QUEUE_GROUP: &str = "logai-workers";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NatsConfig {
+    pub ingest_subject: String,
+    /// Workers in the same group share the subject's messages, each log goes to one of them.
+    /// Logs are dealt out without regard to service, so with chunking on (WORKER_CHUNK_LOGS)
+    /// each of N workers sees roughly 1/N of a service's logs and builds its own, thinner
+    /// chunks for the same window. Run one worker per deployment when chunk quality matters
+    pub queue_group: String,
+}
+
+impl Default for NatsConfig {
+    fn default() -> Self {
+        Self {
+            ingest_subject: DEFAULT_INGEST_SUBJECT.to_string(),
+            queue_group: DEFAULT_QUEUE_GROUP.to_string(),
+        }
+    }
+}
+
+impl NatsConfig {
+    /// Environment variables:
+    /// - NATS_INGEST_SUBJECT: Subject logs are published on and consumed from (default: logs.ingest)
+    /// - NATS_QUEUE_GROUP: Queue group the workers subscribe in (default: logai-workers)
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        // subjects and group names can't hold whitespace, so a blank value means unset
+        let read = |key: &str, default: &str| {
+            var(key)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| default.to_string())
+        };
+        Self {
+            ingest_subject: read("NATS_INGEST_SUBJECT", DEFAULT_INGEST_SUBJECT),
+            queue_group: read("NATS_QUEUE_GROUP", DEFAULT_QUEUE_GROUP),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::vars;
+
+    #[test]
+    fn test_nats_config_from_env() {
+        let defaults = NatsConfig::from_vars(vars(&[]));
+        assert_eq!(defaults, NatsConfig::default());
+        assert_eq!(
+            (defaults.ingest_subject.as_str(), defaults.queue_group.as_str()),
+            ("logs.ingest", "logai-workers")
+        );
+
+        let custom = NatsConfig::from_vars(vars(&[
+            ("NATS_INGEST_SUBJECT", " prod.logs.ingest "),
+            ("NATS_QUEUE_GROUP", "eu-workers"),
+        ]));
+        assert_eq!(custom.ingest_subject, "prod.logs.ingest");
+        assert_eq!(custom.queue_group, "eu-workers");
+
+        let blank = NatsConfig::from_vars(vars(&[("NATS_INGEST_SUBJECT", "  "), ("NATS_QUEUE_GROUP", "")]));
+        assert_eq!(blank, NatsConfig::default());
+    }
+}
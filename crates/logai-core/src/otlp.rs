@@ -0,0 +1,290 @@
+// OTLP/HTTP JSON logs (the body of POST /v1/logs in OpenTelemetry's exporters), mapped to
+// RawLogEntry: resourceLogs[].scopeLogs[].logRecords[]. Protobuf payloads aren't handled.
+
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Resource attribute naming the service, per the OpenTelemetry semantic conventions
+pub const SERVICE_NAME_ATTRIBUTE: &str = "service.name";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportLogsRequest {
+    #[serde(default)]
+    pub resource_logs: Vec<ResourceLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLogs {
+    #[serde(default)]
+    pub resource: Resource,
+    #[serde(default)]
+    pub scope_logs: Vec<ScopeLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Resource {
+    #[serde(default)]
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeLogs {
+    #[serde(default)]
+    pub log_records: Vec<LogRecord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    // uint64 nanoseconds, sent as a string by the protobuf JSON mapping (numbers also accepted)
+    #[serde(default)]
+    pub time_unix_nano: Option<Value>,
+    #[serde(default)]
+    pub observed_time_unix_nano: Option<Value>,
+    #[serde(default)]
+    pub severity_number: Option<u32>,
+    #[serde(default)]
+    pub severity_text: Option<String>,
+    /// An OTLP AnyValue, e.g. `{"stringValue": "..."}`
+    #[serde(default)]
+    pub body: Option<Value>,
+    #[serde(default)]
+    pub attributes: Vec<KeyValue>,
+    #[serde(default)]
+    pub trace_id: Option<String>,
+    #[serde(default)]
+    pub span_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyValue {
+    pub key: String,
+    #[serde(default)]
+    pub value: Value,
+}
+
+impl ExportLogsRequest {
+    /// One entry per log record. Resource attributes other than `service.name` go into
+    /// `fields` along with the record's own, which win on a clash.
+    pub fn into_raw_entries(self) -> Vec<RawLogEntry> {
+        let mut entries = Vec::new();
+        for resource_logs in self.resource_logs {
+            let mut resource_fields = attributes_to_fields(resource_logs.resource.attributes);
+            let service = match resource_fields.remove(SERVICE_NAME_ATTRIBUTE) {
+                Some(Value::String(name)) if !name.is_empty() => Some(name),
+                _ => None,
+            };
+
+            for record in resource_logs.scope_logs.into_iter().flat_map(|scope| scope.log_records) {
+                let mut fields = resource_fields.clone();
+                fields.extend(attributes_to_fields(record.attributes));
+                let level = record
+                    .severity_number
+                    .and_then(map_severity)
+                    .or_else(|| record.severity_text.as_deref().and_then(LogLevel::from_str));
+                // observed time is when the collector read it: better than the time we got it
+                let timestamp = record
+                    .time_unix_nano
+                    .as_ref()
+                    .and_then(parse_unix_nano)
+                    .or_else(|| record.observed_time_unix_nano.as_ref().and_then(parse_unix_nano));
+
+                entries.push(RawLogEntry {
+                    message: record.body.map(body_text).unwrap_or_default(),
+                    timestamp,
+                    service: service.clone(),
+                    level,
+                    trace_id: non_empty(record.trace_id),
+                    span_id: non_empty(record.span_id),
+                    parent_span_id: None,
+                    fields,
+                });
+            }
+        }
+        entries
+    }
+}
+
+/// OTLP severity numbers come in groups of four per level (TRACE 1-4 ... FATAL 21-24);
+/// 0 is "unspecified"
+pub fn map_severity(number: u32) -> Option<LogLevel> {
+    match number {
+        1..=4 => Some(LogLevel::Trace),
+        5..=8 => Some(LogLevel::Debug),
+        9..=12 => Some(LogLevel::Info),
+        13..=16 => Some(LogLevel::Warn),
+        17..=20 => Some(LogLevel::Error),
+        21..=24 => Some(LogLevel::Fatal),
+        _ => None,
+    }
+}
+
+// 0 means unset
+fn parse_unix_nano(value: &Value) -> Option<DateTime<Utc>> {
+    let nanos = match value {
+        Value::String(s) => s.trim().parse::<i64>().ok(),
+        Value::Number(n) => n.as_i64(),
+        _ => None,
+    }?;
+    (nanos > 0).then(|| DateTime::from_timestamp_nanos(nanos))
+}
+
+// ids are hex; an empty string is how the JSON mapping says "no trace"
+fn non_empty(id: Option<String>) -> Option<String> {
+    id.filter(|id| !id.is_empty())
+}
+
+// A string body is the message; anything else (a map from a structured logger) its JSON
+fn body_text(body: Value) -> String {
+    match any_value(body) {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn attributes_to_fields(attributes: Vec<KeyValue>) -> HashMap<String, Value> {
+    attributes
+        .into_iter()
+        .map(|kv| (kv.key, any_value(kv.value)))
+        .collect()
+}
+
+/// An OTLP AnyValue as plain JSON: `{"intValue": "42"}` becomes `42`, kvlists become objects
+pub fn any_value(value: Value) -> Value {
+    let Value::Object(mut object) = value else {
+        return value;
+    };
+    if let Some(s) = object.remove("stringValue") {
+        return s;
+    }
+    if let Some(b) = object.remove("boolValue") {
+        return b;
+    }
+    if let Some(i) = object.remove("intValue") {
+        // int64 is a string in the JSON mapping
+        return match &i {
+            Value::String(s) => s.parse::<i64>().map(Value::from).unwrap_or(i),
+            _ => i,
+        };
+    }
+    if let Some(d) = object.remove("doubleValue") {
+        return d;
+    }
+    if let Some(bytes) = object.remove("bytesValue") {
+        return bytes;
+    }
+    if let Some(array) = object.remove("arrayValue") {
+        let values = match array.get("values") {
+            Some(Value::Array(values)) => values.iter().cloned().map(any_value).collect(),
+            _ => Vec::new(),
+        };
+        return Value::Array(values);
+    }
+    if let Some(kvlist) = object.remove("kvlistValue") {
+        let values: Vec<KeyValue> = kvlist
+            .get("values")
+            .cloned()
+            .and_then(|values| serde_json::from_value(values).ok())
+            .unwrap_or_default();
+        let map: Map<String, Value> = attributes_to_fields(values).into_iter().collect();
+        return Value::Object(map);
+    }
+    // an empty AnyValue
+    Value::Null
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_two_log_records() {
+        let payload = json!({
+            "resourceLogs": [{
+                "resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": "checkout"}},
+                    {"key": "host.name", "value": {"stringValue": "checkout-1"}}
+                ]},
+                "scopeLogs": [{
+                    "scope": {"name": "checkout.payments"},
+                    "logRecords": [
+                        {
+                            "timeUnixNano": "1770692400412000000",
+                            "severityNumber": 17,
+                            "severityText": "ERROR",
+                            "body": {"stringValue": "Payment failed: card declined"},
+                            "attributes": [
+                                {"key": "http.status_code", "value": {"intValue": "502"}},
+                                {"key": "retry", "value": {"boolValue": false}},
+                                {"key": "host.name", "value": {"stringValue": "pod-7"}}
+                            ],
+                            "traceId": "5b8efff798038103d269b633813fc60c",
+                            "spanId": "eee19b7ec3c1b174"
+                        },
+                        {
+                            "observedTimeUnixNano": "1770692401000000000",
+                            "severityText": "warning",
+                            "body": {"kvlistValue": {"values": [
+                                {"key": "latency_ms", "value": {"doubleValue": 812.5}}
+                            ]}},
+                            "traceId": "",
+                            "spanId": ""
+                        }
+                    ]
+                }]
+            }]
+        });
+
+        let request: ExportLogsRequest = serde_json::from_value(payload).unwrap();
+        let entries = request.into_raw_entries();
+        assert_eq!(entries.len(), 2);
+
+        let first = &entries[0];
+        assert_eq!(first.message, "Payment failed: card declined");
+        assert_eq!(first.service.as_deref(), Some("checkout"));
+        assert_eq!(first.level, Some(LogLevel::Error));
+        assert_eq!(first.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:00.412+00:00");
+        assert_eq!(first.trace_id.as_deref(), Some("5b8efff798038103d269b633813fc60c"));
+        assert_eq!(first.span_id.as_deref(), Some("eee19b7ec3c1b174"));
+        assert_eq!(first.fields.get("http.status_code"), Some(&json!(502)));
+        assert_eq!(first.fields.get("retry"), Some(&json!(false)));
+        // the record's attribute wins over the resource's
+        assert_eq!(first.fields.get("host.name"), Some(&json!("pod-7")));
+        assert!(!first.fields.contains_key(SERVICE_NAME_ATTRIBUTE));
+
+        let second = &entries[1];
+        assert_eq!(second.level, Some(LogLevel::Warn));
+        assert_eq!(second.message, r#"{"latency_ms":812.5}"#);
+        assert_eq!(second.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:01+00:00");
+        assert_eq!(second.trace_id, None);
+        assert_eq!(second.fields.get("host.name"), Some(&json!("checkout-1")));
+    }
+
+    #[test]
+    fn test_severity_numbers() {
+        assert_eq!(map_severity(1), Some(LogLevel::Trace));
+        assert_eq!(map_severity(5), Some(LogLevel::Debug));
+        assert_eq!(map_severity(9), Some(LogLevel::Info));
+        assert_eq!(map_severity(13), Some(LogLevel::Warn));
+        assert_eq!(map_severity(20), Some(LogLevel::Error));
+        assert_eq!(map_severity(24), Some(LogLevel::Fatal));
+        assert_eq!(map_severity(0), None);
+        assert_eq!(map_severity(25), None);
+
+        // nothing to go on: left to LogEntry's default
+        let request: ExportLogsRequest = serde_json::from_value(json!({
+            "resourceLogs": [{"scopeLogs": [{"logRecords": [{"body": {"stringValue": "hi"}}]}]}]
+        }))
+        .unwrap();
+        let entries = request.into_raw_entries();
+        assert_eq!((entries[0].level, entries[0].service.as_deref()), (None, None));
+    }
+}
@@ -8,14 +8,101 @@ use chrono::{DateTime, NaiveDateTime, Utc};
 pub struct ApacheParser {
     // apache error log pattern
     error_pattern: Regex,
+    // access log, common format (%h %l %u %t "%r" %>s %b) with combined's
+    // "%{Referer}i" "%{User-agent}i" optional on the end
+    access_pattern: Regex,
 }
 
 impl ApacheParser {
     pub fn new() -> Self {
         Self {
             error_pattern: Regex::new(r"^\[([^\]]+)\] \[(\w+)\] (.+)$").unwrap(),
+            access_pattern: Regex::new(
+                r#"^(\S+) \S+ (\S+) \[([^\]]+)\] "([^"]*)" (\d{3}) (\S+)(?: "([^"]*)" "([^"]*)")?"#
+            ).unwrap(),
         }
     }
+
+    fn parse_error_line(&self, raw: &str) -> Option<RawLogEntry> {
+        let caps = self.error_pattern.captures(raw)?;
+        let timestamp_str = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let level_str = caps.get(2).map(|m| m.as_str()).unwrap_or("info");
+        let message = caps.get(3).map(|m| m.as_str()).unwrap_or(raw);
+
+        let level = LogLevel::from_str(level_str);
+        let timestamp = NaiveDateTime::parse_from_str(timestamp_str, "%a %b %d %H:%M:%S %Y")
+        .ok()
+        .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+
+        Some(RawLogEntry {
+            message: message.to_string(),
+            timestamp: timestamp, 
+            service: Some("apache".to_string()),
+            level,
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields: HashMap::new(),
+        })
+    }
+
+    fn parse_access_line(&self, raw: &str) -> Option<RawLogEntry> {
+        let caps = self.access_pattern.captures(raw)?;
+        let field = |i: usize| caps.get(i).map(|m| m.as_str()).filter(|v| *v != "-");
+        let status: u16 = caps.get(5)?.as_str().parse().ok()?;
+        // %b logs "-" for an empty body
+        let bytes: u64 = field(6).and_then(|b| b.parse().ok()).unwrap_or(0);
+
+        let mut fields = HashMap::new();
+        fields.insert("ip".to_string(), serde_json::json!(field(1).unwrap_or("")));
+        if let Some(user) = field(2) {
+            fields.insert("user".to_string(), serde_json::json!(user));
+        }
+        // "%r" is "GET /path HTTP/1.1", or just "-" when the client sent garbage
+        let request = field(4).unwrap_or("");
+        let mut parts = request.split_whitespace();
+        let method = parts.next().unwrap_or("");
+        let path = parts.next().unwrap_or("");
+        if !method.is_empty() {
+            fields.insert("method".to_string(), serde_json::json!(method));
+            fields.insert("path".to_string(), serde_json::json!(path));
+        }
+        if let Some(protocol) = parts.next() {
+            fields.insert("protocol".to_string(), serde_json::json!(protocol));
+        }
+        fields.insert("status".to_string(), serde_json::json!(status));
+        fields.insert("bytes".to_string(), serde_json::json!(bytes));
+        if let Some(referer) = field(7) {
+            fields.insert("referer".to_string(), serde_json::json!(referer));
+        }
+        if let Some(user_agent) = field(8) {
+            fields.insert("user_agent".to_string(), serde_json::json!(user_agent));
+        }
+
+        let level = match status {
+            500..=599 => LogLevel::Error,
+            400..=499 => LogLevel::Warn,
+            _ => LogLevel::Info,
+        };
+        // 10/Oct/2000:13:55:36 -0700
+        let timestamp = caps.get(3)
+            .and_then(|m| DateTime::parse_from_str(m.as_str(), "%d/%b/%Y:%H:%M:%S %z").ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
+        // same shape as nginx access entries: "GET /path 200 2326"
+        let target = if method.is_empty() { "-".to_string() } else { format!("{} {}", method, path) };
+
+        Some(RawLogEntry {
+            message: format!("{} {} {}", target, status, bytes),
+            timestamp,
+            service: Some("apache".to_string()),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
 }
 
 impl LogParser for ApacheParser {
@@ -24,35 +111,21 @@ impl LogParser for ApacheParser {
     }
 
     fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
-        // try to match apache error log format
-        if let Some(caps) = self.error_pattern.captures(raw) {
-            let timestamp_str = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            let level_str = caps.get(2).map(|m| m.as_str()).unwrap_or("info");
-            let message = caps.get(3).map(|m| m.as_str()).unwrap_or(raw);
-
-            let level = LogLevel::from_str(level_str);
-            let timestamp = NaiveDateTime::parse_from_str(timestamp_str, "%a %b %d %H:%M:%S %Y")
-            .ok()
-            .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
-
-            Ok(RawLogEntry {
-                message: message.to_string(),
-                timestamp: timestamp, 
-                service: Some("apache".to_string()),
-                level,
-                trace_id: None,
-                fields: HashMap::new(),
-            })
-        } else {
-            // fallback treat as plain message
-            Ok(RawLogEntry {
-                message: raw.to_string(),
-                timestamp: None,
-                service: Some("apache".to_string()),
-                level: Some(LogLevel::Info),
-                trace_id: None,
-                fields: HashMap::new(),
-            })
+        // error log lines start with "[timestamp]", access log lines with the client address
+        if let Some(entry) = self.parse_error_line(raw).or_else(|| self.parse_access_line(raw)) {
+            return Ok(entry);
         }
+
+        // fallback treat as plain message
+        Ok(RawLogEntry {
+            message: raw.to_string(),
+            timestamp: None,
+            service: Some("apache".to_string()),
+            level: Some(LogLevel::Info),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields: HashMap::new(),
+        })
     }
 }
@@ -0,0 +1,217 @@
+// CEF (Common Event Format) parser - firewalls, IDS/IPS, WAFs
+// CEF:Version|Device Vendor|Device Product|Device Version|Signature ID|Name|Severity|Extension
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+const HEADER_FIELDS: usize = 7;
+
+pub struct CefParser {
+    // start of each `key=` in the extension (escaped `\=` never matches, keys can't contain `\`)
+    extension_key_pattern: Regex,
+}
+
+impl CefParser {
+    pub fn new() -> Self {
+        Self {
+            extension_key_pattern: Regex::new(r"(?:^|\s)([A-Za-z0-9_.\[\]-]+)=").unwrap(),
+        }
+    }
+
+    // Split on unescaped pipes; everything after the 7th pipe is the extension
+    fn split_header(s: &str) -> Option<(Vec<String>, &str)> {
+        let mut parts = Vec::with_capacity(HEADER_FIELDS);
+        let mut current = String::new();
+        let mut chars = s.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, next @ ('|' | '\\'))) => current.push(next),
+                    Some((_, next)) => {
+                        current.push('\\');
+                        current.push(next);
+                    }
+                    None => current.push('\\'),
+                },
+                '|' => {
+                    parts.push(std::mem::take(&mut current));
+                    if parts.len() == HEADER_FIELDS {
+                        return Some((parts, &s[i + 1..]));
+                    }
+                }
+                _ => current.push(c),
+            }
+        }
+
+        None
+    }
+
+    fn parse_extension(&self, ext: &str) -> HashMap<String, serde_json::Value> {
+        let keys: Vec<(usize, usize, &str)> = self
+            .extension_key_pattern
+            .captures_iter(ext)
+            .map(|caps| {
+                let whole = caps.get(0).unwrap();
+                let key = caps.get(1).unwrap();
+                (whole.start(), whole.end(), key.as_str())
+            })
+            .collect();
+
+        // a value runs until the next key starts, so values may contain spaces
+        let mut fields = HashMap::new();
+        for (i, (_, value_start, key)) in keys.iter().enumerate() {
+            let value_end = keys.get(i + 1).map_or(ext.len(), |(start, _, _)| *start);
+            let value = Self::unescape_value(ext[*value_start..value_end].trim());
+            fields.insert(key.to_string(), serde_json::json!(value));
+        }
+        fields
+    }
+
+    fn unescape_value(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        }
+        out
+    }
+
+    // 0-10 per spec, some vendors send Low/Medium/High/Very-High instead
+    fn map_severity(severity: &str) -> LogLevel {
+        match severity.trim().parse::<u8>() {
+            Ok(n) if n >= 7 => LogLevel::Error,
+            Ok(n) if n >= 4 => LogLevel::Warn,
+            Ok(_) => LogLevel::Info,
+            Err(_) => match severity.trim().to_lowercase().as_str() {
+                "high" | "very-high" => LogLevel::Error,
+                "medium" => LogLevel::Warn,
+                _ => LogLevel::Info,
+            },
+        }
+    }
+
+    // rt is either epoch millis or "MMM dd yyyy HH:mm:ss"
+    fn parse_receipt_time(rt: &str) -> Option<DateTime<Utc>> {
+        if let Ok(ms) = rt.parse::<i64>() {
+            return DateTime::from_timestamp_millis(ms);
+        }
+        NaiveDateTime::parse_from_str(rt, "%b %d %Y %H:%M:%S")
+            .ok()
+            .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+    }
+}
+
+impl Default for CefParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for CefParser {
+    fn name(&self) -> &'static str {
+        "cef"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        // CEF is often wrapped in a syslog header, skip to the marker
+        let start = raw
+            .find("CEF:")
+            .ok_or_else(|| ParseError::new("Missing CEF: marker"))?;
+        let (header, extension) = Self::split_header(&raw[start + 4..])
+            .ok_or_else(|| ParseError::new("CEF header needs 7 pipe-delimited fields"))?;
+
+        let mut fields = self.parse_extension(extension);
+        let header_names = [
+            "cef_version",
+            "device_vendor",
+            "device_product",
+            "device_version",
+            "signature_id",
+            "name",
+            "severity",
+        ];
+        for (name, value) in header_names.iter().zip(&header) {
+            fields.insert(name.to_string(), serde_json::json!(value));
+        }
+
+        let timestamp = fields
+            .get("rt")
+            .and_then(|v| v.as_str())
+            .and_then(Self::parse_receipt_time);
+
+        Ok(RawLogEntry {
+            message: header[5].clone(),
+            timestamp,
+            service: Some(header[2].clone()),
+            level: Some(Self::map_severity(&header[6])),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_firewall_deny() {
+        let parser = CefParser::new();
+        let line = "Feb 10 03:00:00 fw01 CEF:0|Palo Alto Networks|PAN-OS|10.1.0|100|Deny|5|\
+            src=10.0.4.17 dst=203.0.113.9 spt=51544 dpt=443 proto=TCP act=deny \
+            rt=1770692400000 msg=Blocked by policy allow-web cs1Label=Rule cs1=block-outbound";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.message, "Deny");
+        assert_eq!(result.service, Some("PAN-OS".to_string()));
+        assert_eq!(result.level, Some(LogLevel::Warn));
+        assert_eq!(result.fields.get("src"), Some(&serde_json::json!("10.0.4.17")));
+        assert_eq!(result.fields.get("dpt"), Some(&serde_json::json!("443")));
+        assert_eq!(result.fields.get("msg"), Some(&serde_json::json!("Blocked by policy allow-web")));
+        assert_eq!(result.fields.get("cs1"), Some(&serde_json::json!("block-outbound")));
+        assert_eq!(result.fields.get("device_vendor"), Some(&serde_json::json!("Palo Alto Networks")));
+        assert_eq!(result.timestamp.unwrap().timestamp_millis(), 1770692400000);
+    }
+
+    #[test]
+    fn test_escaped_pipes_in_header() {
+        let parser = CefParser::new();
+        let line = r"CEF:0|Security|threat\|manager|1.0|4000|Worm successfully stopped|10|src=10.0.0.1 fname=C:\\temp\\a\=b.exe";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.service, Some("threat|manager".to_string()));
+        assert_eq!(result.message, "Worm successfully stopped");
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(result.fields.get("fname"), Some(&serde_json::json!(r"C:\temp\a=b.exe")));
+    }
+
+    #[test]
+    fn test_severity_mapping() {
+        assert_eq!(CefParser::map_severity("3"), LogLevel::Info);
+        assert_eq!(CefParser::map_severity("4"), LogLevel::Warn);
+        assert_eq!(CefParser::map_severity("7"), LogLevel::Error);
+        assert_eq!(CefParser::map_severity("Very-High"), LogLevel::Error);
+    }
+
+    #[test]
+    fn test_rejects_short_header() {
+        let parser = CefParser::new();
+        assert!(parser.parse("CEF:0|Vendor|Product|1.0").is_err());
+        assert!(parser.parse("not a cef line").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// Envoy (and similar gateway) JSON access log parser
+// {"start_time":"...","method":"GET","path":"/api","response_code":503,"duration":12,"upstream_host":"10.0.0.5:8080",...}
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+// Envoy writes these as numbers or, with typed_json_format off, as strings
+const NUMERIC_FIELDS: [&str; 6] = [
+    "response_code",
+    "duration",
+    "upstream_service_time",
+    "bytes_received",
+    "bytes_sent",
+    "request_duration",
+];
+
+pub struct EnvoyParser;
+
+impl EnvoyParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn as_u64(value: &Value) -> Option<u64> {
+        match value {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_str<'a>(log: &'a Map<String, Value>, key: &str) -> Option<&'a str> {
+        log.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty() && *s != "-")
+    }
+
+    fn status_to_level(code: u64) -> LogLevel {
+        match code {
+            500..=599 => LogLevel::Error,
+            400..=499 => LogLevel::Warn,
+            _ => LogLevel::Info,
+        }
+    }
+}
+
+impl Default for EnvoyParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for EnvoyParser {
+    fn name(&self) -> &'static str {
+        "envoy"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let log: Map<String, Value> = serde_json::from_str(raw.trim())
+            .map_err(|e| ParseError::new(&format!("Invalid access log JSON: {}", e)))?;
+
+        let response_code = log
+            .get("response_code")
+            .and_then(Self::as_u64)
+            .ok_or_else(|| ParseError::new("Missing response_code"))?;
+        let duration = log.get("duration").and_then(Self::as_u64);
+
+        let mut fields = HashMap::new();
+        for (key, value) in &log {
+            if value.is_null() || value.as_str() == Some("-") {
+                continue;
+            }
+            let value = if NUMERIC_FIELDS.contains(&key.as_str()) {
+                match Self::as_u64(value) {
+                    Some(n) => serde_json::json!(n),
+                    None => continue,
+                }
+            } else {
+                value.clone()
+            };
+            fields.insert(key.clone(), value);
+        }
+
+        let method = Self::as_str(&log, "method").unwrap_or("-");
+        let path = Self::as_str(&log, "path")
+            .or_else(|| Self::as_str(&log, "x-envoy-original-path"))
+            .unwrap_or("-");
+        let message = match duration {
+            Some(ms) => format!("{} {} -> {} ({}ms)", method, path, response_code, ms),
+            None => format!("{} {} -> {}", method, path, response_code),
+        };
+
+        let timestamp = Self::as_str(&log, "start_time")
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
+        Ok(RawLogEntry {
+            message,
+            timestamp,
+            service: Some("envoy".to_string()),
+            level: Some(Self::status_to_level(response_code)),
+            trace_id: Self::as_str(&log, "x-request-id").map(str::to_string),
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upstream_unavailable() {
+        let parser = EnvoyParser::new();
+        let line = r#"{"start_time":"2026-02-10T03:00:00.412Z","method":"POST","path":"/api/orders","protocol":"HTTP/1.1","response_code":"503","response_flags":"UF","bytes_received":"312","bytes_sent":"91","duration":"5002","upstream_service_time":"-","upstream_host":"10.0.7.21:8080","upstream_cluster":"orders","x-request-id":"8f0c2b1e-6f0d-4c4e-9a53-0f3c1f2d7e11"}"#;
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(result.message, "POST /api/orders -> 503 (5002ms)");
+        assert_eq!(result.fields.get("response_code"), Some(&serde_json::json!(503)));
+        assert_eq!(result.fields.get("duration"), Some(&serde_json::json!(5002)));
+        assert_eq!(result.fields.get("upstream_host"), Some(&serde_json::json!("10.0.7.21:8080")));
+        assert!(!result.fields.contains_key("upstream_service_time"));
+        assert_eq!(result.trace_id.as_deref(), Some("8f0c2b1e-6f0d-4c4e-9a53-0f3c1f2d7e11"));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:00.412+00:00");
+    }
+
+    #[test]
+    fn test_ok_request() {
+        let parser = EnvoyParser::new();
+        let line = r#"{"start_time":"2026-02-10T03:00:01.000Z","method":"GET","path":"/healthz","response_code":200,"duration":3,"upstream_host":"10.0.7.22:8080"}"#;
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Info));
+        assert_eq!(result.message, "GET /healthz -> 200 (3ms)");
+        assert_eq!(result.fields.get("duration"), Some(&serde_json::json!(3)));
+        assert_eq!(result.trace_id, None);
+    }
+
+    #[test]
+    fn test_client_error_and_rejects() {
+        assert_eq!(EnvoyParser::status_to_level(404), LogLevel::Warn);
+
+        let parser = EnvoyParser::new();
+        assert!(parser.parse("[2026-02-10T03:00:00.000Z] \"GET / HTTP/1.1\" 200").is_err());
+        assert!(parser.parse(r#"{"method":"GET","path":"/"}"#).is_err());
+    }
+}
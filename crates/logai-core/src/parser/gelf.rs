@@ -0,0 +1,150 @@
+// GELF (Graylog Extended Log Format) parser - one JSON message per line
+// {"version":"1.1","host":"checkout-1","short_message":"Payment failed","level":3,"timestamp":1770692400.412,"_user_id":"42"}
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub struct GelfParser;
+
+impl GelfParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // GELF levels are syslog severities; some shippers send them as strings
+    fn map_level(level: &Value) -> LogLevel {
+        let severity = match level {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        match severity {
+            Some(0..=2) => LogLevel::Fatal, // Emergency, Alert, Critical
+            Some(3) => LogLevel::Error,
+            Some(4) => LogLevel::Warn,
+            Some(5) => LogLevel::Notice,
+            Some(7) => LogLevel::Debug,
+            _ => LogLevel::Info, // Informational
+        }
+    }
+
+    // seconds since the epoch with decimal milliseconds; an f64 only holds about
+    // microsecond precision at today's epoch, so round there
+    fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+        let secs = value.as_f64()?;
+        DateTime::from_timestamp_micros((secs * 1e6).round() as i64)
+    }
+}
+
+impl Default for GelfParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for GelfParser {
+    fn name(&self) -> &'static str {
+        "gelf"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let log: Map<String, Value> = serde_json::from_str(raw.trim())
+            .map_err(|e| ParseError::new(&format!("Invalid GELF JSON: {}", e)))?;
+
+        let message = log
+            .get("short_message")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ParseError::new("Missing short_message"))?;
+
+        // additional fields are the `_`-prefixed keys; the long form (often a stack trace)
+        // and the legacy facility are kept too
+        let mut fields: HashMap<String, Value> = log
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .filter_map(|(key, value)| {
+                let name = key.strip_prefix('_').filter(|name| !name.is_empty())?;
+                Some((name.to_string(), value.clone()))
+            })
+            .collect();
+        for key in ["full_message", "facility"] {
+            if let Some(value) = log.get(key).filter(|v| v.as_str().is_some_and(|s| !s.is_empty())) {
+                fields.insert(key.to_string(), value.clone());
+            }
+        }
+
+        let id = |key: &str| fields.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let (trace_id, span_id, parent_span_id) = (id("trace_id"), id("span_id"), id("parent_span_id"));
+
+        Ok(RawLogEntry {
+            message: message.to_string(),
+            timestamp: log.get("timestamp").and_then(Self::parse_timestamp),
+            service: log.get("host").and_then(|v| v.as_str()).map(str::to_string),
+            level: Some(log.get("level").map_or(LogLevel::Info, Self::map_level)),
+            trace_id,
+            span_id,
+            parent_span_id,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graylog_message() {
+        let parser = GelfParser::new();
+        let line = r#"{"version":"1.1","host":"checkout-1","short_message":"Payment failed","full_message":"PaymentException: card declined\n\tat Checkout.pay(Checkout.java:42)","timestamp":1770692400.412,"level":3,"_user_id":"42","_status_code":502,"_trace_id":"abc123","_span_id":"b7ad6b71","_parent_span_id":"5fe865a1"}"#;
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.message, "Payment failed");
+        assert_eq!(result.service, Some("checkout-1".to_string()));
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:00.412+00:00");
+        assert_eq!(result.trace_id, Some("abc123".to_string()));
+        assert_eq!(result.span_id.as_deref(), Some("b7ad6b71"));
+        assert_eq!(result.parent_span_id.as_deref(), Some("5fe865a1"));
+        assert!(result.fields["full_message"].as_str().unwrap().starts_with("PaymentException"));
+    }
+
+    #[test]
+    fn test_syslog_level_mapping() {
+        let parser = GelfParser::new();
+        let level = |level: &str| {
+            let line = format!(r#"{{"host":"h","short_message":"m","level":{}}}"#, level);
+            parser.parse(&line).unwrap().level.unwrap()
+        };
+
+        assert_eq!(level("0"), LogLevel::Fatal);
+        assert_eq!(level("2"), LogLevel::Fatal);
+        assert_eq!(level("3"), LogLevel::Error);
+        assert_eq!(level("4"), LogLevel::Warn);
+        assert_eq!(level("5"), LogLevel::Notice);
+        assert_eq!(level("6"), LogLevel::Info);
+        assert_eq!(level("7"), LogLevel::Debug);
+        assert_eq!(level(r#""4""#), LogLevel::Warn);
+
+        let no_level = parser.parse(r#"{"host":"h","short_message":"m"}"#).unwrap();
+        assert_eq!(no_level.level, Some(LogLevel::Info));
+    }
+
+    #[test]
+    fn test_custom_fields_strip_underscore() {
+        let parser = GelfParser::new();
+        let line = r#"{"version":"1.1","host":"api","short_message":"GET /orders","_endpoint":"/orders","_duration_ms":12.5,"_":"ignored","_empty":null,"line":17}"#;
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.fields.get("endpoint"), Some(&serde_json::json!("/orders")));
+        assert_eq!(result.fields.get("duration_ms"), Some(&serde_json::json!(12.5)));
+        // standard keys and unnamed/null additional fields are not custom fields
+        assert_eq!(result.fields.len(), 2);
+        assert!(result.timestamp.is_none());
+
+        assert!(parser.parse(r#"{"host":"api","message":"no short_message"}"#).is_err());
+        assert!(parser.parse("not json").is_err());
+    }
+}
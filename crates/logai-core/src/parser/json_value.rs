@@ -0,0 +1,144 @@
+// Level and timestamp values as JSON loggers write them. Go's zap and zerolog in particular:
+// {"level":"info","ts":1770692400.412,"msg":"..."}   zap production config
+// {"level":2,"ts":"2026-02-10T03:00:00.412Z",...}    zap with a numeric level encoder
+// {"level":"warn","time":1770692400412,...}           zerolog, unix-ms time format
+
+use crate::LogLevel;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// A level as a word ("warn", "ERROR", zap's "dpanic"/"panic") or a zap level number
+/// (-1 debug, 0 info, 1 warn, 2 error, 3-5 dpanic/panic/fatal), also when sent as a string.
+/// None for anything else.
+pub fn parse_level_value(value: &Value) -> Option<LogLevel> {
+    match value {
+        Value::Number(n) => n
+            .as_i64()
+            .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+            .and_then(zap_level),
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(number) = s.parse::<i64>() {
+                return zap_level(number);
+            }
+            LogLevel::from_str(s).or_else(|| match s.to_lowercase().as_str() {
+                "dpanic" | "panic" => Some(LogLevel::Fatal),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn zap_level(number: i64) -> Option<LogLevel> {
+    match number {
+        -1 => Some(LogLevel::Debug),
+        0 => Some(LogLevel::Info),
+        1 => Some(LogLevel::Warn),
+        2 => Some(LogLevel::Error),
+        3..=5 => Some(LogLevel::Fatal),
+        _ => None,
+    }
+}
+
+/// A timestamp as RFC 3339 (with or without the colon in the offset) or epoch time. Epoch
+/// numbers may be seconds (with a fraction, zap's `ts`), milliseconds, microseconds or
+/// nanoseconds, told apart by magnitude; numeric strings count too.
+pub fn parse_timestamp_value(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(n) => match n.as_i64() {
+            Some(whole) => epoch_integer(whole),
+            None => epoch_seconds(n.as_f64()?),
+        },
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(whole) = s.parse::<i64>() {
+                return epoch_integer(whole);
+            }
+            if let Ok(secs) = s.parse::<f64>() {
+                return epoch_seconds(secs);
+            }
+            DateTime::parse_from_rfc3339(s)
+                .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z"))
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        }
+        _ => None,
+    }
+}
+
+// seconds until 5138 AD, then milli-, micro- and nanoseconds
+fn epoch_integer(value: i64) -> Option<DateTime<Utc>> {
+    match value.unsigned_abs() {
+        0..100_000_000_000 => DateTime::from_timestamp(value, 0),
+        100_000_000_000..100_000_000_000_000 => DateTime::from_timestamp_millis(value),
+        100_000_000_000_000..100_000_000_000_000_000 => DateTime::from_timestamp_micros(value),
+        _ => Some(DateTime::from_timestamp_nanos(value)),
+    }
+}
+
+// an f64 only holds about microsecond precision at today's epoch, so round there
+fn epoch_seconds(secs: f64) -> Option<DateTime<Utc>> {
+    if !secs.is_finite() {
+        return None;
+    }
+    DateTime::from_timestamp_micros((secs * 1e6).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_numeric_levels() {
+        assert_eq!(parse_level_value(&json!(-1)), Some(LogLevel::Debug));
+        assert_eq!(parse_level_value(&json!(0)), Some(LogLevel::Info));
+        assert_eq!(parse_level_value(&json!(1)), Some(LogLevel::Warn));
+        assert_eq!(parse_level_value(&json!(2)), Some(LogLevel::Error));
+        assert_eq!(parse_level_value(&json!(5)), Some(LogLevel::Fatal));
+        assert_eq!(parse_level_value(&json!(2.0)), Some(LogLevel::Error));
+        assert_eq!(parse_level_value(&json!("1")), Some(LogLevel::Warn));
+        assert_eq!(parse_level_value(&json!(6)), None);
+        assert_eq!(parse_level_value(&json!(1.5)), None);
+    }
+
+    #[test]
+    fn test_string_levels() {
+        assert_eq!(parse_level_value(&json!("debug")), Some(LogLevel::Debug));
+        assert_eq!(parse_level_value(&json!("INFO")), Some(LogLevel::Info));
+        assert_eq!(parse_level_value(&json!(" warning ")), Some(LogLevel::Warn));
+        assert_eq!(parse_level_value(&json!("error")), Some(LogLevel::Error));
+        assert_eq!(parse_level_value(&json!("dpanic")), Some(LogLevel::Fatal));
+        assert_eq!(parse_level_value(&json!("trace")), Some(LogLevel::Trace));
+        assert_eq!(parse_level_value(&json!("verbose")), None);
+        assert_eq!(parse_level_value(&json!(null)), None);
+        assert_eq!(parse_level_value(&json!(true)), None);
+    }
+
+    #[test]
+    fn test_epoch_timestamps() {
+        let expected = "2026-02-10T03:00:00.412+00:00";
+        let rfc3339 = |value: Value| parse_timestamp_value(&value).unwrap().to_rfc3339();
+
+        // zap's float seconds, zerolog's unix/unix-ms/unix-micro/unix-nano formats
+        assert_eq!(rfc3339(json!(1770692400.412)), expected);
+        assert_eq!(rfc3339(json!(1770692400)), "2026-02-10T03:00:00+00:00");
+        assert_eq!(rfc3339(json!(1770692400412_i64)), expected);
+        assert_eq!(rfc3339(json!(1770692400412000_i64)), expected);
+        assert_eq!(rfc3339(json!(1770692400412000000_i64)), expected);
+        assert_eq!(rfc3339(json!("1770692400.412")), expected);
+    }
+
+    #[test]
+    fn test_rfc3339_timestamps() {
+        let rfc3339 = |value: Value| parse_timestamp_value(&value).unwrap().to_rfc3339();
+        assert_eq!(rfc3339(json!("2026-02-10T03:00:00.412Z")), "2026-02-10T03:00:00.412+00:00");
+        assert_eq!(rfc3339(json!("2026-02-10T04:00:00+01:00")), "2026-02-10T03:00:00+00:00");
+        // zap's development encoder leaves the colon out of the offset
+        assert_eq!(rfc3339(json!("2026-02-10T04:00:00.412+0100")), "2026-02-10T03:00:00.412+00:00");
+
+        assert_eq!(parse_timestamp_value(&json!("yesterday")), None);
+        assert_eq!(parse_timestamp_value(&json!({"sec": 1})), None);
+    }
+}
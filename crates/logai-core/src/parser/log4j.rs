@@ -0,0 +1,131 @@
+// log4j-style parser (Kafka brokers, ZooKeeper, most Java apps on the default PatternLayout)
+// [2024-02-23 10:23:45,123] ERROR [KafkaApi-1] Error when handling request (kafka.server.KafkaApis)
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+pub struct Log4jParser {
+    // [timestamp] LEVEL [component] message (logger.class) - component and logger are optional
+    pattern: Regex,
+}
+
+impl Log4jParser {
+    pub fn new() -> Self {
+        Self {
+            // the logger needs at least one dot, so "failed (timeout)" stays part of the message
+            pattern: Regex::new(
+                r"^\[(\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:[,.]\d{1,6})?)\]\s+(TRACE|DEBUG|INFO|WARN|WARNING|ERROR|FATAL)\s+(?:\[([^\]]*)\]\s+)?(.*?)(?:\s+\(([A-Za-z_$][\w$]*(?:\.[\w$]+)+)\))?\s*$"
+            ).unwrap(),
+        }
+    }
+
+    // "2024-02-23 10:23:45,123" - log4j uses a comma before the milliseconds
+    fn parse_timestamp(ts: &str) -> Option<DateTime<Utc>> {
+        let normalized = ts.replacen('T', " ", 1).replacen(',', ".", 1);
+        NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+    }
+}
+
+impl Default for Log4jParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for Log4jParser {
+    fn name(&self) -> &'static str {
+        "log4j"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let caps = self
+            .pattern
+            .captures(raw.trim_end())
+            .ok_or_else(|| ParseError::new("Not a log4j line"))?;
+
+        let timestamp = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let level = caps.get(2).map(|m| m.as_str()).unwrap_or("INFO");
+        let message = caps.get(4).map(|m| m.as_str()).unwrap_or("");
+
+        let mut fields = HashMap::new();
+        if let Some(component) = caps.get(3) {
+            fields.insert("component".to_string(), serde_json::json!(component.as_str()));
+        }
+        if let Some(logger) = caps.get(5) {
+            fields.insert("logger".to_string(), serde_json::json!(logger.as_str()));
+        }
+
+        Ok(RawLogEntry {
+            message: message.to_string(),
+            timestamp: Self::parse_timestamp(timestamp),
+            service: Some("log4j".to_string()),
+            // FATAL, ERROR, WARN(ING), INFO, DEBUG, TRACE all map by name
+            level: Some(LogLevel::from_str(level).unwrap_or(LogLevel::Info)),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_line() {
+        let parser = Log4jParser::new();
+        let line = "[2024-02-23 10:23:45,123] INFO [ReplicaFetcher replicaId=1, leaderId=2, fetcherId=0] Truncating partition orders-3 to offset 1842 (kafka.server.ReplicaFetcherThread)";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Info));
+        assert_eq!(result.message, "Truncating partition orders-3 to offset 1842");
+        assert_eq!(
+            result.fields.get("component"),
+            Some(&serde_json::json!("ReplicaFetcher replicaId=1, leaderId=2, fetcherId=0"))
+        );
+        assert_eq!(
+            result.fields.get("logger"),
+            Some(&serde_json::json!("kafka.server.ReplicaFetcherThread"))
+        );
+    }
+
+    #[test]
+    fn test_error_line() {
+        let parser = Log4jParser::new();
+        let line = "[2024-02-23 10:23:46,007] ERROR [KafkaApi-1] Error when handling request: clientId=producer-7, correlationId=42 (failed after 3 retries) (kafka.server.KafkaApis)";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(
+            result.message,
+            "Error when handling request: clientId=producer-7, correlationId=42 (failed after 3 retries)"
+        );
+        assert_eq!(result.fields.get("component"), Some(&serde_json::json!("KafkaApi-1")));
+        assert_eq!(result.fields.get("logger"), Some(&serde_json::json!("kafka.server.KafkaApis")));
+
+        // no component, no logger
+        let bare = parser.parse("[2024-02-23 10:23:47,000] FATAL Shutting down broker").unwrap();
+        assert_eq!(bare.level, Some(LogLevel::Fatal));
+        assert_eq!(bare.message, "Shutting down broker");
+        assert!(bare.fields.is_empty());
+    }
+
+    #[test]
+    fn test_millisecond_comma_timestamp() {
+        let parser = Log4jParser::new();
+        let result = parser
+            .parse("[2024-02-23 10:23:45,123] WARN Session expired (org.apache.zookeeper.ClientCnxn)")
+            .unwrap();
+
+        assert_eq!(result.level, Some(LogLevel::Warn));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2024-02-23T10:23:45.123+00:00");
+        assert!(parser.parse("2024-02-23 10:23:45 INFO not bracketed").is_err());
+    }
+}
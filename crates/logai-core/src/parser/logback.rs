@@ -0,0 +1,137 @@
+// Spring Boot / logback default console pattern
+// 2024-02-23 10:23:45.123  INFO 12345 --- [nio-8080-exec-1] c.e.MyClass                              : message
+// Spring Boot 3 uses an ISO timestamp with offset and may add [application-name] before the thread.
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+pub struct LogbackParser {
+    // timestamp LEVEL pid --- [app] [thread] logger : message - padding varies, app is optional
+    pattern: Regex,
+}
+
+impl LogbackParser {
+    pub fn new() -> Self {
+        Self {
+            // level is right-aligned to 5 chars and the thread to 15, so allow any run of spaces
+            pattern: Regex::new(
+                r"^(\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}[.,]\d{1,9}(?:Z|[+-]\d{2}:?\d{2})?)\s+(TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\s+(\d+)\s+---\s+(?:\[\s*([^\]]*?)\s*\]\s+)?\[\s*([^\]]*?)\s*\]\s+(\S+)\s+:\s?(.*)$"
+            ).unwrap(),
+        }
+    }
+
+    // "2024-02-23 10:23:45.123" (local time, taken as UTC) or "2024-02-23T10:23:45.123+01:00"
+    fn parse_timestamp(ts: &str) -> Option<DateTime<Utc>> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+            return Some(dt.with_timezone(&Utc));
+        }
+        let normalized = ts.replacen('T', " ", 1).replacen(',', ".", 1);
+        NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+    }
+}
+
+impl Default for LogbackParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for LogbackParser {
+    fn name(&self) -> &'static str {
+        "logback"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let caps = self
+            .pattern
+            .captures(raw.trim_end())
+            .ok_or_else(|| ParseError::new("Not a logback line"))?;
+
+        let timestamp = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let level = caps.get(2).map(|m| m.as_str()).unwrap_or("INFO");
+        let pid = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+        let thread = caps.get(5).map(|m| m.as_str()).unwrap_or("");
+        let logger = caps.get(6).map(|m| m.as_str()).unwrap_or("");
+        let message = caps.get(7).map(|m| m.as_str()).unwrap_or("");
+
+        let mut fields = HashMap::new();
+        fields.insert("pid".to_string(), serde_json::json!(pid));
+        fields.insert("thread".to_string(), serde_json::json!(thread));
+        fields.insert("logger".to_string(), serde_json::json!(logger));
+        if let Some(app) = caps.get(4).filter(|m| !m.as_str().is_empty()) {
+            fields.insert("application".to_string(), serde_json::json!(app.as_str()));
+        }
+
+        Ok(RawLogEntry {
+            message: message.to_string(),
+            timestamp: Self::parse_timestamp(timestamp),
+            service: Some("logback".to_string()),
+            level: Some(LogLevel::from_str(level).unwrap_or(LogLevel::Info)),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_line() {
+        let parser = LogbackParser::new();
+        let line = "2024-02-23 10:23:45.123  INFO 12345 --- [nio-8080-exec-1] c.e.d.web.OrderController                : Order 1842 created";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Info));
+        assert_eq!(result.message, "Order 1842 created");
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2024-02-23T10:23:45.123+00:00");
+        assert_eq!(result.fields.get("pid"), Some(&serde_json::json!("12345")));
+        assert_eq!(result.fields.get("thread"), Some(&serde_json::json!("nio-8080-exec-1")));
+        assert_eq!(result.fields.get("logger"), Some(&serde_json::json!("c.e.d.web.OrderController")));
+    }
+
+    #[test]
+    fn test_error_line() {
+        let parser = LogbackParser::new();
+        let line = "2024-02-23 10:23:46.007 ERROR 12345 --- [           main] o.s.boot.SpringApplication               : Application run failed";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(result.message, "Application run failed");
+        // thread names are padded on the left
+        assert_eq!(result.fields.get("thread"), Some(&serde_json::json!("main")));
+
+        // Spring Boot 3: ISO timestamp with offset, application name before the thread
+        let boot3 = parser
+            .parse("2024-02-23T10:23:47.500+01:00  WARN 4242 --- [orders] [           main] o.s.b.a.l.ConditionEvaluationReportLogger : Error starting ApplicationContext")
+            .unwrap();
+        assert_eq!(boot3.level, Some(LogLevel::Warn));
+        assert_eq!(boot3.timestamp.unwrap().to_rfc3339(), "2024-02-23T09:23:47.500+00:00");
+        assert_eq!(boot3.fields.get("application"), Some(&serde_json::json!("orders")));
+        assert_eq!(boot3.fields.get("thread"), Some(&serde_json::json!("main")));
+    }
+
+    #[test]
+    fn test_long_logger_name() {
+        let parser = LogbackParser::new();
+        // past 40 chars logback stops padding, a single space is left before the colon
+        let line = "2024-02-23 10:23:48.001 DEBUG 12345 --- [pool-3-thread-1] com.example.payments.gateway.internal.retry.ExponentialBackoffRetryHandler : Retry 2/5 in 400ms: key=a:b";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Debug));
+        assert_eq!(
+            result.fields.get("logger"),
+            Some(&serde_json::json!("com.example.payments.gateway.internal.retry.ExponentialBackoffRetryHandler"))
+        );
+        assert_eq!(result.message, "Retry 2/5 in 400ms: key=a:b");
+        assert!(parser.parse("[2024-02-23 10:23:45,123] INFO log4j style").is_err());
+    }
+}
@@ -1,14 +1,36 @@
 //! log parser registry - parse raw logs into structured format
 
 pub mod apache;
+pub mod cef;
+pub mod envoy;
+pub mod gelf;
+pub mod json_value;
+pub mod log4j;
+pub mod logback;
+pub mod multiline;
+pub mod mysql;
 pub mod nginx;
+pub mod postgres;
 pub mod proxmox;
+pub mod redis;
 pub mod syslog;
+pub mod winevent;
 
 pub use apache::ApacheParser;
+pub use cef::CefParser;
+pub use envoy::EnvoyParser;
+pub use gelf::GelfParser;
+pub use json_value::{parse_level_value, parse_timestamp_value};
+pub use log4j::Log4jParser;
+pub use logback::LogbackParser;
+pub use multiline::MultilineJoiner;
+pub use mysql::MysqlSlowLogParser;
 pub use nginx::NginxParser;
+pub use postgres::PostgresCsvParser;
 pub use proxmox::ProxmoxParser;
+pub use redis::RedisParser;
 pub use syslog::SyslogParser;
+pub use winevent::WindowsEventParser;
 
 use crate::RawLogEntry;
 use std::{collections::HashMap};
@@ -30,17 +52,24 @@ impl ParseError {
 pub trait LogParser: Send + Sync {
     fn name(&self) -> &'static str; 
     fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError>; 
+
+    // formats whose entries span several lines (MySQL slow log) group them into
+    // records here, each one then goes to `parse`; None means one line per record
+    fn records(&self, _lines: &[&str]) -> Option<Vec<String>> {
+        None
+    }
 }
 
 // Registry to hold all parsers
 
 pub struct ParserRegistry {
     parsers: HashMap<String, Box<dyn LogParser>>,
+    multiline: MultilineJoiner,
 }
 
 impl ParserRegistry {
     pub fn new() -> Self {
-        Self { parsers: HashMap::new(), }
+        Self { parsers: HashMap::new(), multiline: MultilineJoiner::new() }
     }
 
     // register a parser
@@ -49,6 +78,13 @@ impl ParserRegistry {
     
     }
 
+    // names of the registered parsers, sorted
+    pub fn list(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.parsers.values().map(|p| p.name()).collect();
+        names.sort_unstable();
+        names
+    }
+
     // Get parser by name
     pub fn get(&self, name: &str) -> Option<&dyn LogParser> {
         self.parsers.get(name).map(|p| p.as_ref())
@@ -61,4 +97,43 @@ impl ParserRegistry {
             None => Err(ParseError::new(&format!("Unknown format: {}", format))),
         }
     }
+
+    // parse a batch line by line, or record by record for multi-line formats
+    pub fn parse_lines<S: AsRef<str>>(&self, format: &str, lines: &[S]) -> Vec<Result<RawLogEntry, ParseError>> {
+        match self.records(format, lines) {
+            Some(records) => records.iter().map(|record| self.parse(format, record)).collect(),
+            None => lines.iter().map(|line| self.parse(format, line.as_ref())).collect(),
+        }
+    }
+
+    // the format's own grouping of lines into records, if it has one
+    fn records<S: AsRef<str>>(&self, format: &str, lines: &[S]) -> Option<Vec<String>> {
+        let lines: Vec<&str> = lines.iter().map(|l| l.as_ref()).collect();
+        self.get(format)?.records(&lines)
+    }
+
+    // parse a batch where stack traces span several lines: the first line of each
+    // record is parsed and the continuation lines are appended to its message.
+    // Formats that group their own records ignore the stack trace rules
+    pub fn parse_multiline<S: AsRef<str>>(&self, format: &str, lines: &[S]) -> Vec<Result<RawLogEntry, ParseError>> {
+        if let Some(records) = self.records(format, lines) {
+            return records.iter().map(|record| self.parse(format, record)).collect();
+        }
+        self.multiline
+            .join(lines)
+            .into_iter()
+            .map(|record| {
+                let (head, rest) = match record.split_once('\n') {
+                    Some((head, rest)) => (head, Some(rest)),
+                    None => (record.as_str(), None),
+                };
+                let mut entry = self.parse(format, head)?;
+                if let Some(rest) = rest {
+                    entry.message.push('\n');
+                    entry.message.push_str(rest);
+                }
+                Ok(entry)
+            })
+            .collect()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+// Multiline aggregation - stitches Java/Python stack traces back onto the line that logged them
+
+use regex::Regex;
+
+pub struct MultilineJoiner {
+    // 2026-02-10T03:00:00, 10/Feb/2026, [Tue Feb 10 14:00:00, Feb 10 14:00:00, 14:00:00
+    timestamp_prefix: Regex,
+}
+
+impl MultilineJoiner {
+    pub fn new() -> Self {
+        Self {
+            timestamp_prefix: Regex::new(
+                r"^\[?(?:\d{4}-\d{2}-\d{2}|\d{2}/[A-Z][a-z]{2}/\d{4}|(?:[A-Z][a-z]{2},? )?[A-Z][a-z]{2} +\d{1,2} |\d{2}:\d{2}:\d{2})",
+            )
+            .unwrap(),
+        }
+    }
+
+    fn is_continuation(&self, line: &str, record_has_timestamp: bool) -> bool {
+        line.starts_with(char::is_whitespace)
+            || line.starts_with("at ")
+            || line.starts_with("Caused by")
+            || line.starts_with("Traceback")
+            // a missing timestamp only means anything if the record itself had one
+            // (nginx/CEF/XML lines never start with a timestamp)
+            || (record_has_timestamp && !self.timestamp_prefix.is_match(line))
+    }
+
+    /// Group lines into records, continuation lines joined onto the previous one with '\n'
+    pub fn join<S: AsRef<str>>(&self, lines: &[S]) -> Vec<String> {
+        let mut records: Vec<String> = Vec::new();
+        let mut record_has_timestamp = false;
+
+        for line in lines {
+            let line = line.as_ref();
+            match records.last_mut() {
+                Some(current) if self.is_continuation(line, record_has_timestamp) => {
+                    current.push('\n');
+                    current.push_str(line);
+                }
+                _ => {
+                    record_has_timestamp = self.timestamp_prefix.is_match(line);
+                    records.push(line.to_string());
+                }
+            }
+        }
+
+        records
+    }
+}
+
+impl Default for MultilineJoiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{ParserRegistry, SyslogParser};
+
+    fn registry() -> ParserRegistry {
+        let mut registry = ParserRegistry::new();
+        registry.register(Box::new(SyslogParser::new()));
+        registry
+    }
+
+    #[test]
+    fn test_java_exception_is_one_entry() {
+        let lines = [
+            "Feb 10 03:00:00 app01 java[812]: Exception in thread \"main\" java.lang.IllegalStateException: pool exhausted",
+            "\tat com.example.db.Pool.acquire(Pool.java:88)",
+            "\tat com.example.api.OrderService.create(OrderService.java:41)",
+            "Caused by: java.net.SocketTimeoutException: connect timed out",
+            "\t... 12 more",
+            "Feb 10 03:00:05 app01 java[812]: Retrying order creation",
+        ];
+
+        let results = registry().parse_multiline("syslog", &lines);
+        assert_eq!(results.len(), 2);
+
+        let exception = results[0].as_ref().unwrap();
+        assert!(exception.message.starts_with("Exception in thread \"main\""));
+        assert!(exception.message.contains("\n\tat com.example.db.Pool.acquire"));
+        assert!(exception.message.ends_with("\t... 12 more"));
+        assert_eq!(results[1].as_ref().unwrap().message, "Retrying order creation");
+    }
+
+    #[test]
+    fn test_python_traceback_is_one_entry() {
+        let lines = [
+            "Feb 10 03:00:01 worker02 python3[4242]: ERROR Unhandled exception in job 17",
+            "Traceback (most recent call last):",
+            "  File \"/app/jobs.py\", line 52, in run",
+            "    total = compute(batch)",
+            "ZeroDivisionError: division by zero",
+        ];
+
+        let results = registry().parse_multiline("syslog", &lines);
+        assert_eq!(results.len(), 1);
+
+        let entry = results[0].as_ref().unwrap();
+        assert_eq!(entry.message.lines().count(), 5);
+        assert!(entry.message.ends_with("ZeroDivisionError: division by zero"));
+        assert_eq!(entry.service, Some("python3".to_string()));
+    }
+
+    #[test]
+    fn test_lines_without_timestamps_stay_separate() {
+        let joiner = MultilineJoiner::new();
+        let lines = [
+            "10.0.0.1 - - [10/Feb/2026:14:00:00 +0000] \"GET / HTTP/1.1\" 200 512",
+            "10.0.0.2 - - [10/Feb/2026:14:00:01 +0000] \"GET /api HTTP/1.1\" 500 0",
+        ];
+        assert_eq!(joiner.join(&lines).len(), 2);
+    }
+}
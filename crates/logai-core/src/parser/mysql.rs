@@ -0,0 +1,232 @@
+// MySQL slow query log (slow_query_log = ON). Each entry is a block of lines:
+// # Time: 2026-02-10T03:00:00.412345Z
+// # User@Host: app[app] @ web01 [10.0.0.5]  Id:    42
+// # Query_time: 2.500000  Lock_time: 0.000120 Rows_sent: 1  Rows_examined: 500000
+// use shop;
+// SET timestamp=1770692400;
+// SELECT * FROM orders WHERE customer_id = 7;
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+// queries at least this slow are logged as errors instead of warnings
+const DEFAULT_ERROR_SECS: f64 = 10.0;
+
+pub struct MysqlSlowLogParser {
+    // app[app] @ web01 [10.0.0.5]  Id: 42 - host or ip may be empty
+    user_host: Regex,
+    error_secs: f64,
+}
+
+impl MysqlSlowLogParser {
+    pub fn new() -> Self {
+        Self {
+            user_host: Regex::new(r"^(\S*?)\[[^\]]*\]\s+@\s+(\S*)\s*\[([^\]]*)\](?:\s+Id:\s+(\d+))?").unwrap(),
+            error_secs: DEFAULT_ERROR_SECS,
+        }
+    }
+
+    /// Log queries taking at least `secs` as errors (default 10s)
+    pub fn with_error_threshold(mut self, secs: f64) -> Self {
+        self.error_secs = secs;
+        self
+    }
+
+    // "2026-02-10T03:00:00.412345Z" (5.7+) or "260210  3:00:00" (5.6 and older, server time)
+    fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+            return Some(dt.with_timezone(&Utc));
+        }
+        let compact = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        NaiveDateTime::parse_from_str(&compact, "%y%m%d %H:%M:%S")
+            .ok()
+            .map(|naive| naive.and_utc())
+    }
+
+    // "Query_time: 2.5  Lock_time: 0.0001 Rows_sent: 1" -> (name, value) pairs
+    fn stats(line: &str) -> Vec<(&str, &str)> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        tokens
+            .chunks(2)
+            .filter_map(|pair| match pair {
+                [name, value] => name.strip_suffix(':').map(|name| (name, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Default for MysqlSlowLogParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for MysqlSlowLogParser {
+    fn name(&self) -> &'static str {
+        "mysql-slow"
+    }
+
+    // a block starts at "# Time:", or at "# User@Host:" when the server skipped the
+    // time line (several queries in the same second); the startup banner is dropped
+    fn records(&self, lines: &[&str]) -> Option<Vec<String>> {
+        let mut records: Vec<String> = Vec::new();
+        let mut after_time = false;
+
+        for line in lines {
+            let starts_block =
+                line.starts_with("# Time:") || (line.starts_with("# User@Host:") && !after_time);
+            if starts_block {
+                records.push(line.to_string());
+            } else if let Some(current) = records.last_mut() {
+                current.push('\n');
+                current.push_str(line);
+            }
+            after_time = line.starts_with("# Time:");
+        }
+
+        Some(records)
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let mut timestamp = None;
+        let mut fields = HashMap::new();
+        let mut query_time = None;
+        let mut sql = Vec::new();
+
+        for line in raw.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(time) = line.strip_prefix("# Time:") {
+                timestamp = timestamp.or(Self::parse_time(time.trim()));
+            } else if let Some(user_host) = line.strip_prefix("# User@Host:") {
+                if let Some(caps) = self.user_host.captures(user_host.trim()) {
+                    let parts = [(1, "user"), (2, "host"), (3, "ip"), (4, "thread_id")];
+                    for (group, name) in parts {
+                        if let Some(m) = caps.get(group).filter(|m| !m.as_str().is_empty()) {
+                            fields.insert(name.to_string(), serde_json::json!(m.as_str()));
+                        }
+                    }
+                }
+            } else if let Some(stats) = line.strip_prefix("# ") {
+                for (name, value) in Self::stats(stats) {
+                    let key = name.to_lowercase();
+                    // *_time in seconds, everything else (Rows_sent, Rows_examined, ...) a count
+                    let parsed = match (key.ends_with("_time"), value.parse::<f64>()) {
+                        (true, Ok(secs)) => serde_json::json!(secs),
+                        _ => value.parse::<u64>().map_or_else(|_| serde_json::json!(value), |n| serde_json::json!(n)),
+                    };
+                    if key == "query_time" {
+                        query_time = parsed.as_f64();
+                    }
+                    fields.insert(key, parsed);
+                }
+            } else if let Some(database) = line.strip_prefix("use ").and_then(|db| db.strip_suffix(';')) {
+                fields.insert("database".to_string(), serde_json::json!(database));
+            } else if let Some(secs) = line.strip_prefix("SET timestamp=").and_then(|s| s.strip_suffix(';')) {
+                // when the query started, more precise than "# Time:" on 5.6
+                if let Some(start) = secs.parse().ok().and_then(|s| DateTime::from_timestamp(s, 0)) {
+                    timestamp = Some(start);
+                }
+            } else {
+                sql.push(line);
+            }
+        }
+
+        let query_time = query_time.ok_or_else(|| ParseError::new("Missing # Query_time line"))?;
+        if sql.is_empty() {
+            return Err(ParseError::new("Slow log entry without a query"));
+        }
+        let level = if query_time >= self.error_secs { LogLevel::Error } else { LogLevel::Warn };
+
+        Ok(RawLogEntry {
+            message: sql.join("\n"),
+            timestamp,
+            service: Some("mysql".to_string()),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParserRegistry;
+
+    const SLOW_LOG: &str = "\
+/usr/sbin/mysqld, Version: 8.0.36 (MySQL Community Server - GPL). started with:
+Tcp port: 3306  Unix socket: /var/run/mysqld/mysqld.sock
+Time                 Id Command    Argument
+# Time: 2026-02-10T03:00:00.412345Z
+# User@Host: app[app] @ web01 [10.0.0.5]  Id:    42
+# Query_time: 2.500000  Lock_time: 0.000120 Rows_sent: 1  Rows_examined: 500000
+use shop;
+SET timestamp=1770692400;
+SELECT * FROM orders
+  WHERE customer_id = 7;
+# Time: 2026-02-10T03:00:05.000000Z
+# User@Host: report[report] @  [10.0.0.9]  Id:    57
+# Query_time: 14.002000  Lock_time: 0.000310 Rows_sent: 12  Rows_examined: 9800000
+SET timestamp=1770692405;
+SELECT customer_id, SUM(total) FROM orders GROUP BY customer_id;";
+
+    #[test]
+    fn test_two_block_slow_log() {
+        let mut registry = ParserRegistry::new();
+        registry.register(Box::new(MysqlSlowLogParser::new()));
+        let lines: Vec<&str> = SLOW_LOG.lines().collect();
+
+        let entries: Vec<RawLogEntry> = registry
+            .parse_lines("mysql-slow", &lines)
+            .into_iter()
+            .map(|r| r.expect("block parses"))
+            .collect();
+        assert_eq!(entries.len(), 2, "banner dropped, one entry per block");
+
+        let first = &entries[0];
+        assert_eq!(first.message, "SELECT * FROM orders\nWHERE customer_id = 7;");
+        assert_eq!(first.level, Some(LogLevel::Warn));
+        assert_eq!(first.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:00+00:00");
+        assert_eq!(first.fields["query_time"], serde_json::json!(2.5));
+        assert_eq!(first.fields["lock_time"], serde_json::json!(0.00012));
+        assert_eq!(first.fields["rows_examined"], serde_json::json!(500000));
+        assert_eq!(first.fields["user"], serde_json::json!("app"));
+        assert_eq!(first.fields["host"], serde_json::json!("web01"));
+        assert_eq!(first.fields["database"], serde_json::json!("shop"));
+
+        // over the 10s threshold, and no host name
+        let second = &entries[1];
+        assert_eq!(second.level, Some(LogLevel::Error));
+        assert_eq!(second.message, "SELECT customer_id, SUM(total) FROM orders GROUP BY customer_id;");
+        assert_eq!(second.fields["ip"], serde_json::json!("10.0.0.9"));
+        assert!(!second.fields.contains_key("host"));
+        assert_eq!(second.fields["rows_examined"], serde_json::json!(9800000));
+    }
+
+    #[test]
+    fn test_block_without_time_line() {
+        let parser = MysqlSlowLogParser::new().with_error_threshold(1.0);
+        let lines = [
+            "# User@Host: app[app] @ localhost []  Id:     8",
+            "# Query_time: 1.200000  Lock_time: 0.000000 Rows_sent: 0  Rows_examined: 0",
+            "SET timestamp=1770692400;",
+            "DELETE FROM sessions WHERE expires_at < NOW();",
+            "# User@Host: app[app] @ localhost []  Id:     8",
+            "# Query_time: 0.400000  Lock_time: 0.000000 Rows_sent: 0  Rows_examined: 0",
+            "SET timestamp=1770692400;",
+            "SELECT 1;",
+        ];
+
+        let records = parser.records(&lines).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(parser.parse(&records[0]).unwrap().level, Some(LogLevel::Error));
+        assert_eq!(parser.parse(&records[1]).unwrap().level, Some(LogLevel::Warn));
+
+        assert!(parser.parse("SELECT 1;").is_err());
+    }
+}
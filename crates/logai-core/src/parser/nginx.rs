@@ -6,9 +6,14 @@ use chrono::{DateTime, NaiveDateTime, Utc};
 use regex::Regex;
 use std::collections::HashMap;
 
+// requests slower than this are Warn even when the status is fine
+const DEFAULT_SLOW_THRESHOLD_SECS: f64 = 1.0;
+
 pub struct NginxParser {
     error_pattern: Regex,  // Nginx error log: 2024/02/08 10:30:00 [error] 12345#0: ...
     access_pattern: Regex, // Nginx access log (combined): IP - - [timestamp] "method path" status size
+    request_time_pattern: Regex, // custom log_format ending in $request_time / $upstream_response_time
+    slow_threshold: f64,
 }
 
 impl NginxParser {
@@ -22,9 +27,18 @@ impl NginxParser {
             access_pattern: Regex::new(
                 r#"^(\S+) \S+ \S+ \[([^\]]+)\] "(\S+) ([^"]*)" (\d+) (\d+)"#
             ).unwrap(),
+            // nginx always prints times with a decimal point (0.005), so a plain size never matches
+            request_time_pattern: Regex::new(r#"\s"?(\d+\.\d+)"?\s*$"#).unwrap(),
+            slow_threshold: DEFAULT_SLOW_THRESHOLD_SECS,
         }
     }
 
+    /// Seconds above which a successful request is logged as Warn (default 1.0)
+    pub fn with_slow_threshold(mut self, secs: f64) -> Self {
+        self.slow_threshold = secs;
+        self
+    }
+
     fn parse_error_timestamp(ts: &str) -> Option<DateTime<Utc>> {
         NaiveDateTime::parse_from_str(ts, "%Y/%m/%d %H:%M:%S")
             .ok()
@@ -75,6 +89,8 @@ impl LogParser for NginxParser {
                 service: Some("nginx".to_string()),
                 level: Some(LogLevel::from_str(level_str).unwrap_or(LogLevel::Info)),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
@@ -89,6 +105,10 @@ impl LogParser for NginxParser {
                 .and_then(|m| m.as_str().parse().ok())
                 .unwrap_or(200);
             let size = caps.get(6).map(|m| m.as_str()).unwrap_or("0");
+            let request_time: Option<f64> = self
+                .request_time_pattern
+                .captures(&raw[caps.get(0).map_or(0, |m| m.end())..])
+                .and_then(|c| c.get(1)?.as_str().parse().ok());
 
             let mut fields = HashMap::new();
             fields.insert("ip".to_string(), serde_json::json!(ip));
@@ -97,14 +117,24 @@ impl LogParser for NginxParser {
             fields.insert("status".to_string(), serde_json::json!(status));
             fields.insert("size".to_string(), serde_json::json!(size));
 
+            let mut level = Self::status_to_level(status);
+            if let Some(secs) = request_time {
+                fields.insert("request_time".to_string(), serde_json::json!(secs));
+                if secs > self.slow_threshold && level == LogLevel::Info {
+                    level = LogLevel::Warn;
+                }
+            }
+
             let message = format!("{} {} {} {}", method, path, status, size);
 
             return Ok(RawLogEntry {
                 message,
                 timestamp: Self::parse_access_timestamp(timestamp_str),
                 service: Some("nginx".to_string()),
-                level: Some(Self::status_to_level(status)),
+                level: Some(level),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
@@ -116,6 +146,8 @@ impl LogParser for NginxParser {
             service: Some("nginx".to_string()),
             level: Some(LogLevel::Info),
             trace_id: None,
+            span_id: None,
+            parent_span_id: None,
             fields: HashMap::new(),
         })
     }
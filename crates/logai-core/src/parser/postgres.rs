@@ -0,0 +1,186 @@
+// PostgreSQL csvlog parser (log_destination = 'csvlog')
+// Columns: log_time, user_name, database_name, process_id, connection_from, session_id,
+// session_line_num, command_tag, session_start_time, virtual_transaction_id, transaction_id,
+// error_severity, sql_state_code, message, detail, hint, internal_query, internal_query_pos,
+// context, query, query_pos, location, application_name, backend_type, leader_pid, query_id
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashMap;
+
+const LOG_TIME: usize = 0;
+const ERROR_SEVERITY: usize = 11;
+const MESSAGE: usize = 13;
+
+// columns copied into fields when non-empty (older versions stop after application_name)
+const FIELD_COLUMNS: [(usize, &str); 13] = [
+    (1, "user_name"),
+    (2, "database_name"),
+    (3, "process_id"),
+    (4, "connection_from"),
+    (5, "session_id"),
+    (7, "command_tag"),
+    (12, "sql_state_code"),
+    (14, "detail"),
+    (15, "hint"),
+    (18, "context"),
+    (19, "query"),
+    (22, "application_name"),
+    (23, "backend_type"),
+];
+
+pub struct PostgresCsvParser;
+
+impl PostgresCsvParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // RFC 4180: quoted fields may hold commas, newlines and "" for a literal quote
+    fn split_csv(row: &str) -> Result<Vec<String>, ParseError> {
+        let mut columns = Vec::new();
+        let mut current = String::new();
+        let mut in_quotes = false;
+        let mut chars = row.trim_end_matches(['\r', '\n']).chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    current.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = !in_quotes,
+                ',' if !in_quotes => columns.push(std::mem::take(&mut current)),
+                _ => current.push(c),
+            }
+        }
+
+        if in_quotes {
+            return Err(ParseError::new("Unterminated quoted CSV field"));
+        }
+        columns.push(current);
+        Ok(columns)
+    }
+
+    fn map_severity(severity: &str) -> LogLevel {
+        match severity {
+            "PANIC" | "FATAL" => LogLevel::Fatal,
+            "ERROR" => LogLevel::Error,
+            "WARNING" => LogLevel::Warn,
+            s if s.starts_with("DEBUG") => LogLevel::Debug,
+            // LOG, INFO, NOTICE
+            _ => LogLevel::Info,
+        }
+    }
+
+    // "2026-02-10 03:00:00.123 UTC" or with a numeric zone ("+03", "-0530");
+    // other abbreviations (EST, CET) are ambiguous, so those rows get no timestamp
+    fn parse_log_time(ts: &str) -> Option<DateTime<Utc>> {
+        let (local, zone) = ts.trim().rsplit_once(' ')?;
+        match zone {
+            "UTC" | "GMT" => NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc)),
+            _ => DateTime::parse_from_str(ts.trim(), "%Y-%m-%d %H:%M:%S%.f %#z")
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl Default for PostgresCsvParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for PostgresCsvParser {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let columns = Self::split_csv(raw)?;
+        if columns.len() <= MESSAGE {
+            return Err(ParseError::new("Not a PostgreSQL csvlog row (too few columns)"));
+        }
+
+        let mut fields = HashMap::new();
+        for (index, name) in FIELD_COLUMNS {
+            if let Some(value) = columns.get(index).filter(|v| !v.is_empty()) {
+                fields.insert(name.to_string(), serde_json::json!(value));
+            }
+        }
+        fields.insert(
+            "error_severity".to_string(),
+            serde_json::json!(columns[ERROR_SEVERITY]),
+        );
+
+        Ok(RawLogEntry {
+            message: columns[MESSAGE].clone(),
+            timestamp: Self::parse_log_time(&columns[LOG_TIME]),
+            service: Some("postgres".to_string()),
+            level: Some(Self::map_severity(&columns[ERROR_SEVERITY])),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_message_with_commas() {
+        let parser = PostgresCsvParser::new();
+        let line = r#"2026-02-10 03:00:00.123 UTC,"app","orders",4242,"10.0.4.17:51544",65aa1f2c.1092,3,"INSERT",2026-02-10 02:59:58 UTC,4/120,0,ERROR,23505,"duplicate key value violates unique constraint ""orders_pkey"", table orders, schema public","Key (id)=(17) already exists.",,,,,"INSERT INTO orders (id, total) VALUES (17, 9.99)",,,"checkout-api","client backend",,0"#;
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(
+            result.message,
+            r#"duplicate key value violates unique constraint "orders_pkey", table orders, schema public"#
+        );
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:00.123+00:00");
+        assert_eq!(result.fields.get("database_name"), Some(&serde_json::json!("orders")));
+        assert_eq!(result.fields.get("user_name"), Some(&serde_json::json!("app")));
+        assert_eq!(result.fields.get("sql_state_code"), Some(&serde_json::json!("23505")));
+        assert_eq!(
+            result.fields.get("query"),
+            Some(&serde_json::json!("INSERT INTO orders (id, total) VALUES (17, 9.99)"))
+        );
+        assert!(!result.fields.contains_key("hint"));
+    }
+
+    #[test]
+    fn test_embedded_newline_and_offset() {
+        let parser = PostgresCsvParser::new();
+        let line = "2026-02-10 06:00:00.000 +03,,,812,,65aa1f2c.1,1,,2026-02-10 05:00:00 +03,,0,LOG,00000,\"checkpoint complete:\nwrote 42 buffers\",,,,,,,,,\"\",\"checkpointer\"";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.message, "checkpoint complete:\nwrote 42 buffers");
+        assert_eq!(result.level, Some(LogLevel::Info));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:00:00+00:00");
+        assert!(!result.fields.contains_key("database_name"));
+    }
+
+    #[test]
+    fn test_severity_mapping() {
+        assert_eq!(PostgresCsvParser::map_severity("PANIC"), LogLevel::Fatal);
+        assert_eq!(PostgresCsvParser::map_severity("FATAL"), LogLevel::Fatal);
+        assert_eq!(PostgresCsvParser::map_severity("WARNING"), LogLevel::Warn);
+        assert_eq!(PostgresCsvParser::map_severity("DEBUG2"), LogLevel::Debug);
+        assert_eq!(PostgresCsvParser::map_severity("NOTICE"), LogLevel::Info);
+    }
+
+    #[test]
+    fn test_rejects_non_csv() {
+        let parser = PostgresCsvParser::new();
+        assert!(parser.parse("Feb 10 14:00:00 server sshd[1234]: Failed password").is_err());
+        assert!(parser.parse("2026-02-10,\"unterminated").is_err());
+    }
+}
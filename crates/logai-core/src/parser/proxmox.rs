@@ -1,5 +1,6 @@
 // Proxmox VE log parser (pve-proxy, pveproxy, pvedaemon, etc.)
-// Supports both BSD syslog format and systemd journal ISO8601 format
+// Supports both BSD syslog format and systemd journal ISO8601 format, plus task log lines
+// starting with a UPID (UPID:node:pid:pstart:starttime:type:id:user:)
 
 use super::{LogParser, ParseError};
 use crate::{LogLevel, RawLogEntry};
@@ -14,6 +15,10 @@ pub struct ProxmoxParser {
     bsd_pattern: Regex,
     // Simple format: Feb 23 10:23:45 process[pid]: message (no hostname)
     simple_pattern: Regex,
+    // Task line: UPID:pve:0000A1B2:001C3D4E:65D8A1B2:vzdump:100:root@pam: 65D8A1F0 OK
+    upid_pattern: Regex,
+    // what follows a finished task's UPID in the task index: end time (hex) and status
+    task_end_pattern: Regex,
 }
 
 impl ProxmoxParser {
@@ -31,7 +36,80 @@ impl ProxmoxParser {
             simple_pattern: Regex::new(
                 r"^(\w{3}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2})\s+([^\[\s]+)(?:\[(\d+)\])?:\s*(.+)$"
             ).unwrap(),
+            // node, pid, pstart, starttime, task type, task id (often a VM id, may be empty), user
+            upid_pattern: Regex::new(
+                r"^UPID:([^:\s]+):([0-9A-Fa-f]{8}):([0-9A-Fa-f]{8,}):([0-9A-Fa-f]{8}):([^:\s]+):([^:\s]*):([^:\s]+):\s*(.*)$"
+            ).unwrap(),
+            task_end_pattern: Regex::new(r"^([0-9A-Fa-f]{8})\s+(.+)$").unwrap(),
+        }
+    }
+
+    // UPID times are unix seconds in hex
+    fn parse_hex_timestamp(hex: &str) -> Option<DateTime<Utc>> {
+        i64::from_str_radix(hex, 16)
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+    }
+
+    // "OK" and "WARNINGS: 2" are how Proxmox ends a task that went fine (or nearly);
+    // anything else is the error it stopped with
+    fn task_status_level(status: &str) -> LogLevel {
+        if status == "OK" {
+            LogLevel::Info
+        } else if status.starts_with("WARNINGS") {
+            LogLevel::Warn
+        } else {
+            LogLevel::Error
+        }
+    }
+
+    fn parse_task(&self, raw: &str) -> Option<RawLogEntry> {
+        let caps = self.upid_pattern.captures(raw)?;
+        let node = &caps[1];
+        let task_type = &caps[5];
+        let task_id = &caps[6];
+        let user = &caps[7];
+        let rest = caps[8].trim();
+
+        let mut fields = HashMap::new();
+        fields.insert("hostname".to_string(), serde_json::json!(node));
+        if let Ok(pid) = u32::from_str_radix(&caps[2], 16) {
+            fields.insert("pid".to_string(), serde_json::json!(pid.to_string()));
+        }
+        fields.insert("task_type".to_string(), serde_json::json!(task_type));
+        if !task_id.is_empty() {
+            fields.insert("task_id".to_string(), serde_json::json!(task_id));
         }
+        fields.insert("user".to_string(), serde_json::json!(user));
+
+        // "vzdump 100", or just "aptupdate" for tasks not about one guest
+        let task = if task_id.is_empty() {
+            task_type.to_string()
+        } else {
+            format!("{} {}", task_type, task_id)
+        };
+        let mut timestamp = Self::parse_hex_timestamp(&caps[4]);
+        let (message, level) = match self.task_end_pattern.captures(rest) {
+            Some(end) => {
+                let status = end[2].trim();
+                fields.insert("task_status".to_string(), serde_json::json!(status));
+                timestamp = Self::parse_hex_timestamp(&end[1]).or(timestamp);
+                (format!("{} task by {}: {}", task, user, status), Self::task_status_level(status))
+            }
+            None if rest.is_empty() => (format!("{} task started by {}", task, user), LogLevel::Info),
+            None => (rest.to_string(), Self::detect_level(rest)),
+        };
+
+        Some(RawLogEntry {
+            message,
+            timestamp,
+            service: Some("proxmox".to_string()),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
     }
 
     fn parse_iso_timestamp(ts: &str) -> Option<DateTime<Utc>> {
@@ -96,6 +174,8 @@ impl LogParser for ProxmoxParser {
                 service: Some(process.to_string()),
                 level: Some(Self::detect_level(message)),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
@@ -121,6 +201,8 @@ impl LogParser for ProxmoxParser {
                 service: Some(process.to_string()),
                 level: Some(Self::detect_level(message)),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
@@ -144,10 +226,16 @@ impl LogParser for ProxmoxParser {
                 service: Some(process.to_string()),
                 level: Some(Self::detect_level(message)),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
 
+        if let Some(entry) = self.parse_task(raw) {
+            return Ok(entry);
+        }
+
         // Fallback: treat entire line as message
         Ok(RawLogEntry {
             message: raw.to_string(),
@@ -155,6 +243,8 @@ impl LogParser for ProxmoxParser {
             service: Some("proxmox".to_string()),
             level: Some(Self::detect_level(raw)),
             trace_id: None,
+            span_id: None,
+            parent_span_id: None,
             fields: HashMap::new(),
         })
     }
@@ -225,4 +315,28 @@ mod tests {
         assert_eq!(result.message, "Some random log line without timestamp");
         assert_eq!(result.service, Some("proxmox".to_string()));
     }
+
+    #[test]
+    fn test_task_lines() {
+        let parser = ProxmoxParser::new();
+
+        let started = parser.parse("UPID:pve:0000A1B2:001C3D4E:65D8A1B2:qmstart:101:root@pam:").unwrap();
+        assert_eq!(started.message, "qmstart 101 task started by root@pam");
+        assert_eq!(started.level, Some(LogLevel::Info));
+        assert_eq!(started.fields.get("pid"), Some(&serde_json::json!("41394")));
+        assert_eq!(started.timestamp.unwrap().to_rfc3339(), "2024-02-23T13:46:26+00:00");
+
+        let failed = parser
+            .parse("UPID:pve:0000A1B2:001C3D4E:65D8A1B2:vzdump:100:root@pam: 65D8A1F0 job errors")
+            .unwrap();
+        assert_eq!(failed.level, Some(LogLevel::Error));
+        assert_eq!(failed.fields.get("task_status"), Some(&serde_json::json!("job errors")));
+        // the end time, not the start
+        assert_eq!(failed.timestamp.unwrap().to_rfc3339(), "2024-02-23T13:47:28+00:00");
+
+        let warned = parser.parse("UPID:pve:00001F2E:0004A1B2:65D8A1B2:aptupdate::root@pam: 65D8A1C0 WARNINGS: 1").unwrap();
+        assert_eq!(warned.level, Some(LogLevel::Warn));
+        assert_eq!(warned.message, "aptupdate task by root@pam: WARNINGS: 1");
+        assert!(!warned.fields.contains_key("task_id"));
+    }
 }
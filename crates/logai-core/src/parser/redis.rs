@@ -0,0 +1,161 @@
+// Redis server log (redis.conf `logfile`), 3.0 and later
+// 12345:M 23 Feb 2024 10:23:45.123 * Ready to accept connections
+// pid:role timestamp level message - role is M master, S replica, C child (fork), X sentinel;
+// level is . debug, - verbose, * notice, # warning
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+pub struct RedisParser {
+    pattern: Regex,
+}
+
+impl RedisParser {
+    pub fn new() -> Self {
+        Self {
+            pattern: Regex::new(
+                r"^(\d+):([MSCX])\s+(\d{1,2} \w{3} \d{4} \d{2}:\d{2}:\d{2}(?:\.\d+)?)\s+([.\-*#])\s(.*)$"
+            ).unwrap(),
+        }
+    }
+
+    // "23 Feb 2024 10:23:45.123", server local time taken as UTC
+    fn parse_timestamp(ts: &str) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(ts, "%d %b %Y %H:%M:%S%.f")
+            .ok()
+            .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+    }
+
+    fn role(role: &str) -> &'static str {
+        match role {
+            "M" => "master",
+            "S" => "replica",
+            "C" => "child",
+            _ => "sentinel",
+        }
+    }
+
+    // Redis logs failures and plain warnings alike at `#`
+    fn level(symbol: &str, message: &str) -> LogLevel {
+        match symbol {
+            "." => LogLevel::Debug,
+            "-" => LogLevel::Info,
+            "*" => LogLevel::Notice,
+            // advisories say so up front, whatever they go on to mention ("... may fail")
+            _ if message.starts_with("WARNING") => LogLevel::Warn,
+            _ => {
+                let msg_lower = message.to_lowercase();
+                // whole words only: "room" isn't "oom", sentinel's "failover" isn't a failure
+                let failed = msg_lower
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .any(|word| {
+                        matches!(
+                            word,
+                            "error" | "errors" | "fail" | "failed" | "failing" | "failure" | "unable" | "denied" | "oom"
+                        )
+                    })
+                    || ["can't", "out of memory"].iter().any(|phrase| msg_lower.contains(phrase));
+                if failed { LogLevel::Error } else { LogLevel::Warn }
+            }
+        }
+    }
+}
+
+impl Default for RedisParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for RedisParser {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        let caps = self
+            .pattern
+            .captures(raw.trim_end())
+            .ok_or_else(|| ParseError::new("Not a Redis log line"))?;
+
+        let pid = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let role = caps.get(2).map(|m| m.as_str()).unwrap_or("M");
+        let timestamp = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+        let symbol = caps.get(4).map(|m| m.as_str()).unwrap_or("*");
+        let message = caps.get(5).map(|m| m.as_str()).unwrap_or("");
+
+        let mut fields = HashMap::new();
+        fields.insert("pid".to_string(), serde_json::json!(pid));
+        fields.insert("role".to_string(), serde_json::json!(Self::role(role)));
+
+        Ok(RawLogEntry {
+            message: message.to_string(),
+            timestamp: Self::parse_timestamp(timestamp),
+            service: Some("redis".to_string()),
+            level: Some(Self::level(symbol, message)),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_line() {
+        let parser = RedisParser::new();
+        let result = parser.parse("12345:M 23 Feb 2024 10:23:45.123 * Ready to accept connections tcp").unwrap();
+
+        assert_eq!(result.level, Some(LogLevel::Notice));
+        assert_eq!(result.message, "Ready to accept connections tcp");
+        assert_eq!(result.service.as_deref(), Some("redis"));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2024-02-23T10:23:45.123+00:00");
+        assert_eq!(result.fields.get("pid"), Some(&serde_json::json!("12345")));
+        assert_eq!(result.fields.get("role"), Some(&serde_json::json!("master")));
+
+        let child = parser.parse("12346:C 23 Feb 2024 10:24:00.001 - Fork CoW for RDB: current 2 MB").unwrap();
+        assert_eq!(child.level, Some(LogLevel::Info));
+        assert_eq!(child.fields.get("role"), Some(&serde_json::json!("child")));
+    }
+
+    #[test]
+    fn test_warning_line() {
+        let parser = RedisParser::new();
+        let line = "12345:M 23 Feb 2024 10:23:45.124 # WARNING: The TCP backlog setting of 511 cannot be enforced because /proc/sys/net/core/somaxconn is set to the lower value of 128.";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.level, Some(LogLevel::Warn));
+        assert!(result.message.starts_with("WARNING: The TCP backlog"));
+
+        // failures share the `#` symbol, their wording makes them errors
+        let replica = parser
+            .parse("6789:S 23 Feb 2024 10:25:01.500 # Error condition on socket for SYNC: Connection refused")
+            .unwrap();
+        assert_eq!(replica.level, Some(LogLevel::Error));
+        assert_eq!(replica.fields.get("role"), Some(&serde_json::json!("replica")));
+
+        let oom = parser.parse("12345:M 23 Feb 2024 10:26:00.000 # Out Of Memory allocating 16 bytes!").unwrap();
+        assert_eq!(oom.level, Some(LogLevel::Error));
+        let killed = parser.parse("12345:M 23 Feb 2024 10:26:00.000 # OOM command not allowed").unwrap();
+        assert_eq!(killed.level, Some(LogLevel::Error));
+        let room = parser.parse("12345:M 23 Feb 2024 10:26:00.000 # No room left in the client buffer").unwrap();
+        assert_eq!(room.level, Some(LogLevel::Warn));
+        let rdb = parser.parse("12345:M 23 Feb 2024 10:27:00.000 # Failed opening the temp RDB file temp-12345.rdb").unwrap();
+        assert_eq!(rdb.level, Some(LogLevel::Error));
+        let failover = parser.parse("12345:X 23 Feb 2024 10:28:00.000 # +failover-end master mymaster 10.0.0.5 6379").unwrap();
+        assert_eq!(failover.level, Some(LogLevel::Warn));
+        let overcommit = parser
+            .parse("12345:M 23 Feb 2024 10:23:45.125 # WARNING overcommit_memory is set to 0! Background save may fail under low memory condition.")
+            .unwrap();
+        assert_eq!(overcommit.level, Some(LogLevel::Warn));
+
+        assert!(parser.parse("[12345] 23 Feb 10:23:45 * pre-3.0 format").is_err());
+    }
+}
@@ -47,7 +47,7 @@ impl SyslogParser {
             2 => LogLevel::Error,  // Critical
             3 => LogLevel::Error,  // Error
             4 => LogLevel::Warn,   // Warning
-            5 => LogLevel::Info,   // Notice
+            5 => LogLevel::Notice, // Notice
             6 => LogLevel::Info,   // Informational
             7 => LogLevel::Debug,  // Debug
             _ => LogLevel::Info,
@@ -92,6 +92,8 @@ impl LogParser for SyslogParser {
                 service: Some(process.to_string()),
                 level: Some(level),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
@@ -121,6 +123,8 @@ impl LogParser for SyslogParser {
                 service: Some(process.to_string()),
                 level: Some(level),
                 trace_id: None,
+                span_id: None,
+                parent_span_id: None,
                 fields,
             });
         }
@@ -132,6 +136,8 @@ impl LogParser for SyslogParser {
             service: Some("syslog".to_string()),
             level: Some(LogLevel::Info),
             trace_id: None,
+            span_id: None,
+            parent_span_id: None,
             fields: HashMap::new(),
         })
     }
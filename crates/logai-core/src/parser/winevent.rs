@@ -0,0 +1,217 @@
+// Windows Event Log parser - one exported <Event> XML record per line
+// (e.g. `wevtutil qe Security /f:xml` or Get-WinEvent ... | ForEach-Object { $_.ToXml() })
+
+use super::{LogParser, ParseError};
+use crate::{LogLevel, RawLogEntry};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+pub struct WindowsEventParser {
+    system_pattern: Regex,
+    provider_pattern: Regex,
+    time_pattern: Regex,
+    // <Level>2</Level>, <EventID Qualifiers='0'>1000</EventID>, ...
+    element_pattern: Regex,
+    event_data_pattern: Regex,
+    // <Data Name='TargetUserName'>bob</Data>, <Data>text</Data>, <Data Name='X'/>
+    data_pattern: Regex,
+    rendered_message_pattern: Regex,
+}
+
+impl WindowsEventParser {
+    pub fn new() -> Self {
+        Self {
+            system_pattern: Regex::new(r"(?s)<System>(.*?)</System>").unwrap(),
+            provider_pattern: Regex::new(r#"<Provider\b[^>]*?\bName=['"]([^'"]*)['"]"#).unwrap(),
+            time_pattern: Regex::new(r#"<TimeCreated\b[^>]*?\bSystemTime=['"]([^'"]*)['"]"#).unwrap(),
+            element_pattern: Regex::new(
+                r"<(Level|EventID|Computer|Channel|Task|Keywords|EventRecordID)\b[^>]*>([^<]*)</",
+            )
+            .unwrap(),
+            event_data_pattern: Regex::new(r"(?s)<(?:EventData|UserData)>(.*?)</(?:EventData|UserData)>").unwrap(),
+            data_pattern: Regex::new(
+                r#"(?s)<Data(?:\s+Name=['"]([^'"]*)['"])?\s*(?:/>|>(.*?)</Data>)"#,
+            )
+            .unwrap(),
+            rendered_message_pattern: Regex::new(r"(?s)<RenderingInfo\b.*?<Message>(.*?)</Message>").unwrap(),
+        }
+    }
+
+    // Level 0 (LogAlways) is what Security audit events use
+    fn map_level(level: &str) -> LogLevel {
+        match level.trim() {
+            "1" => LogLevel::Fatal,
+            "2" => LogLevel::Error,
+            "3" => LogLevel::Warn,
+            "5" => LogLevel::Debug,
+            _ => LogLevel::Info,
+        }
+    }
+
+    fn unescape(s: &str) -> String {
+        s.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    }
+}
+
+impl Default for WindowsEventParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogParser for WindowsEventParser {
+    fn name(&self) -> &'static str {
+        "winevent"
+    }
+
+    fn parse(&self, raw: &str) -> Result<RawLogEntry, ParseError> {
+        if !raw.contains("<Event") {
+            return Err(ParseError::new("Not a Windows <Event> XML record"));
+        }
+
+        // only look at <System>, RenderingInfo repeats Level/Task as display text
+        let system = self
+            .system_pattern
+            .captures(raw)
+            .and_then(|caps| caps.get(1))
+            .map_or(raw, |m| m.as_str());
+
+        let mut fields = HashMap::new();
+        let mut level = LogLevel::Info;
+        let mut event_id = None;
+
+        for caps in self.element_pattern.captures_iter(system) {
+            let value = Self::unescape(caps[2].trim());
+            match &caps[1] {
+                "Level" => level = Self::map_level(&value),
+                "EventID" => event_id = Some(value.clone()),
+                _ => {}
+            }
+            let key = match &caps[1] {
+                "EventID" => "event_id",
+                "Computer" => "computer",
+                "Channel" => "channel",
+                "Task" => "task",
+                "Keywords" => "keywords",
+                "EventRecordID" => "record_id",
+                _ => continue,
+            };
+            fields.insert(key.to_string(), serde_json::json!(value));
+        }
+
+        // EventData children become fields; unnamed <Data> entries are numbered
+        let mut data_summary = Vec::new();
+        if let Some(event_data) = self.event_data_pattern.captures(raw) {
+            for (i, caps) in self.data_pattern.captures_iter(&event_data[1]).enumerate() {
+                let name = caps
+                    .get(1)
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_else(|| format!("data_{}", i));
+                let value = caps.get(2).map(|m| Self::unescape(m.as_str().trim())).unwrap_or_default();
+                if !value.is_empty() && value != "-" {
+                    data_summary.push(format!("{}={}", name, value));
+                }
+                fields.insert(name, serde_json::json!(value));
+            }
+        }
+
+        let service = self
+            .provider_pattern
+            .captures(raw)
+            .map(|caps| caps[1].to_string());
+
+        let timestamp = self
+            .time_pattern
+            .captures(raw)
+            .and_then(|caps| DateTime::parse_from_rfc3339(&caps[1]).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
+        // Prefer the rendered message (RenderedXml exports), else describe the event from its data
+        let message = match self.rendered_message_pattern.captures(raw) {
+            Some(caps) => Self::unescape(caps[1].trim()),
+            None => {
+                let id = event_id.as_deref().unwrap_or("?");
+                if data_summary.is_empty() {
+                    format!("Event {}", id)
+                } else {
+                    format!("Event {}: {}", id, data_summary.join(", "))
+                }
+            }
+        };
+
+        Ok(RawLogEntry {
+            message,
+            timestamp,
+            service: Some(service.unwrap_or_else(|| "windows".to_string())),
+            level: Some(level),
+            trace_id: None,
+            span_id: None,
+            parent_span_id: None,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_logon_failure() {
+        let parser = WindowsEventParser::new();
+        let line = "<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'><System>\
+            <Provider Name='Microsoft-Windows-Security-Auditing' Guid='{54849625-5478-4994-a5ba-3e3b0328c30d}'/>\
+            <EventID>4625</EventID><Version>0</Version><Level>0</Level><Task>12544</Task><Opcode>0</Opcode>\
+            <Keywords>0x8010000000000000</Keywords><TimeCreated SystemTime='2026-02-10T03:14:07.5223345Z'/>\
+            <EventRecordID>184221</EventRecordID><Channel>Security</Channel><Computer>DC01.corp.local</Computer>\
+            </System><EventData><Data Name='TargetUserName'>administrator</Data>\
+            <Data Name='TargetDomainName'>CORP</Data><Data Name='Status'>0xc000006d</Data>\
+            <Data Name='LogonType'>3</Data><Data Name='IpAddress'>10.0.4.17</Data><Data Name='IpPort'>-</Data>\
+            </EventData></Event>";
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.service, Some("Microsoft-Windows-Security-Auditing".to_string()));
+        assert_eq!(result.level, Some(LogLevel::Info));
+        assert_eq!(result.timestamp.unwrap().to_rfc3339(), "2026-02-10T03:14:07.522334500+00:00");
+        assert_eq!(result.fields.get("event_id"), Some(&serde_json::json!("4625")));
+        assert_eq!(result.fields.get("channel"), Some(&serde_json::json!("Security")));
+        assert_eq!(result.fields.get("TargetUserName"), Some(&serde_json::json!("administrator")));
+        assert_eq!(result.fields.get("IpAddress"), Some(&serde_json::json!("10.0.4.17")));
+        assert!(result.message.starts_with("Event 4625: TargetUserName=administrator"));
+        assert!(!result.message.contains("IpPort"));
+    }
+
+    #[test]
+    fn test_application_error() {
+        let parser = WindowsEventParser::new();
+        let line = r#"<Event xmlns="http://schemas.microsoft.com/win/2004/08/events/event"><System><Provider Name="Application Error"/><EventID Qualifiers="0">1000</EventID><Level>2</Level><Task>100</Task><TimeCreated SystemTime="2026-02-10T09:30:00.000000000Z"/><Channel>Application</Channel><Computer>WEB02</Computer></System><EventData><Data>w3wp.exe</Data><Data>10.0.17763.1</Data><Data>KERNELBASE.dll</Data><Data>0xe0434352</Data></EventData><RenderingInfo Culture="en-US"><Message>Faulting application name: w3wp.exe &amp; module KERNELBASE.dll</Message><Level>Error</Level><Task>Application Crashing Events</Task></RenderingInfo></Event>"#;
+
+        let result = parser.parse(line).unwrap();
+        assert_eq!(result.service, Some("Application Error".to_string()));
+        assert_eq!(result.level, Some(LogLevel::Error));
+        assert_eq!(result.fields.get("event_id"), Some(&serde_json::json!("1000")));
+        assert_eq!(result.fields.get("task"), Some(&serde_json::json!("100")));
+        assert_eq!(result.fields.get("data_0"), Some(&serde_json::json!("w3wp.exe")));
+        assert_eq!(result.fields.get("data_3"), Some(&serde_json::json!("0xe0434352")));
+        assert_eq!(result.message, "Faulting application name: w3wp.exe & module KERNELBASE.dll");
+    }
+
+    #[test]
+    fn test_level_mapping() {
+        assert_eq!(WindowsEventParser::map_level("1"), LogLevel::Fatal);
+        assert_eq!(WindowsEventParser::map_level("3"), LogLevel::Warn);
+        assert_eq!(WindowsEventParser::map_level("4"), LogLevel::Info);
+        assert_eq!(WindowsEventParser::map_level("0"), LogLevel::Info);
+    }
+
+    #[test]
+    fn test_rejects_non_xml() {
+        let parser = WindowsEventParser::new();
+        assert!(parser.parse("Feb 10 14:00:00 server sshd[1234]: Failed password").is_err());
+    }
+}
@@ -0,0 +1,6 @@
+// Test helpers shared by the crates built on these types
+
+/// A `from_vars` lookup over fixed pairs, standing in for the environment
+pub fn vars(pairs: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+    move |key: &str| pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+}
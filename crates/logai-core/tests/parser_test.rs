@@ -1,4 +1,4 @@
-use logai_core::parser::{ApacheParser, NginxParser, SyslogParser, LogParser};
+use logai_core::parser::{ApacheParser, NginxParser, ProxmoxParser, SyslogParser, LogParser, ParserRegistry};
 
 #[test]
 fn test_apache_parser(){
@@ -37,6 +37,50 @@ fn test_apache_parser_fallback() {
     assert!(entry.timestamp.is_none()); // could not parse
 }
 
+#[test]
+fn test_apache_combined_access_ok() {
+    let parser = ApacheParser::new();
+
+    let raw = r#"203.0.113.7 - frank [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif HTTP/1.0" 200 2326 "http://www.example.com/start.html" "Mozilla/4.08 [en] (Win98; I ;Nav)""#;
+    let entry = parser.parse(raw).unwrap();
+
+    assert_eq!(entry.message, "GET /apache_pb.gif 200 2326");
+    assert_eq!(entry.level, Some(logai_core::LogLevel::Info));
+    // the -0700 offset is applied
+    assert_eq!(entry.timestamp.unwrap().to_rfc3339(), "2000-10-10T20:55:36+00:00");
+    assert_eq!(entry.fields["ip"], serde_json::json!("203.0.113.7"));
+    assert_eq!(entry.fields["user"], serde_json::json!("frank"));
+    assert_eq!(entry.fields["method"], serde_json::json!("GET"));
+    assert_eq!(entry.fields["path"], serde_json::json!("/apache_pb.gif"));
+    assert_eq!(entry.fields["protocol"], serde_json::json!("HTTP/1.0"));
+    assert_eq!(entry.fields["status"], serde_json::json!(200));
+    assert_eq!(entry.fields["bytes"], serde_json::json!(2326));
+    assert_eq!(entry.fields["referer"], serde_json::json!("http://www.example.com/start.html"));
+    assert_eq!(entry.fields["user_agent"], serde_json::json!("Mozilla/4.08 [en] (Win98; I ;Nav)"));
+}
+
+#[test]
+fn test_apache_combined_access_not_found() {
+    let parser = ApacheParser::new();
+
+    let raw = r#"10.0.0.4 - - [08/Feb/2024:10:30:00 +0000] "GET /wp-login.php HTTP/1.1" 404 - "-" "curl/8.5.0""#;
+    let entry = parser.parse(raw).unwrap();
+
+    assert_eq!(entry.level, Some(logai_core::LogLevel::Warn));
+    assert_eq!(entry.fields["status"], serde_json::json!(404));
+    // "-" means no body, no user and no referer
+    assert_eq!(entry.fields["bytes"], serde_json::json!(0));
+    assert!(!entry.fields.contains_key("user"));
+    assert!(!entry.fields.contains_key("referer"));
+    assert_eq!(entry.fields["user_agent"], serde_json::json!("curl/8.5.0"));
+
+    // common format (no referer/user agent) and 5xx
+    let common = r#"10.0.0.4 - - [08/Feb/2024:10:30:01 +0000] "POST /cgi-bin/app HTTP/1.1" 503 299"#;
+    let entry = parser.parse(common).unwrap();
+    assert_eq!(entry.level, Some(logai_core::LogLevel::Error));
+    assert!(!entry.fields.contains_key("user_agent"));
+}
+
 #[test]
 fn test_loghub_apache_logs() {
     use std::fs::File;
@@ -62,7 +106,7 @@ fn test_loghub_apache_logs() {
                     // count by level
                     match entry.level {
                         Some(logai_core::LogLevel::Error) => errors += 1,
-                        Some(logai_core::LogLevel::Info) => notices += 1, // notice maps to info
+                        Some(logai_core::LogLevel::Notice) => notices += 1,
                         _ => {}
                     }
                 }
@@ -141,6 +185,36 @@ fn test_nginx_access_error_status() {
     assert_eq!(entry.fields.get("status").unwrap(), &serde_json::json!(500));
 }
 
+#[test]
+fn test_nginx_access_fast_request() {
+    let parser = NginxParser::new();
+
+    // log_format with $request_time at the end
+    let raw = r#"10.0.0.1 - - [08/Feb/2024:10:30:00 +0000] "GET /api/users HTTP/1.1" 200 1234 "-" "curl/8.5.0" 0.042"#;
+    let entry = parser.parse(raw).unwrap();
+
+    assert_eq!(entry.level, Some(logai_core::LogLevel::Info));
+    assert_eq!(entry.fields.get("request_time").unwrap(), &serde_json::json!(0.042));
+}
+
+#[test]
+fn test_nginx_access_slow_request() {
+    let parser = NginxParser::new();
+
+    // 200, but 2.5s is over the default 1s threshold
+    let raw = r#"10.0.0.1 - - [08/Feb/2024:10:30:00 +0000] "GET /api/report HTTP/1.1" 200 88012 "-" "Mozilla/5.0" 2.513"#;
+    let entry = parser.parse(raw).unwrap();
+
+    assert_eq!(entry.level, Some(logai_core::LogLevel::Warn));
+    assert_eq!(entry.fields.get("request_time").unwrap(), &serde_json::json!(2.513));
+
+    // a looser threshold keeps it Info; plain combined lines have no request_time
+    let relaxed = NginxParser::new().with_slow_threshold(5.0);
+    assert_eq!(relaxed.parse(raw).unwrap().level, Some(logai_core::LogLevel::Info));
+    let plain = r#"10.0.0.1 - - [08/Feb/2024:10:30:00 +0000] "GET / HTTP/1.1" 200 612"#;
+    assert!(!parser.parse(plain).unwrap().fields.contains_key("request_time"));
+}
+
 // ============ SYSLOG PARSER TESTS ============
 
 #[test]
@@ -194,6 +268,22 @@ fn test_syslog_priority_to_level() {
 
     assert_eq!(entry.level, Some(logai_core::LogLevel::Error));
 }
+
+#[test]
+fn test_syslog_notice_level() {
+    let parser = SyslogParser::new();
+
+    // Priority 13 = facility 1 (user), severity 5 (Notice)
+    let raw = "<13>Feb 08 10:00:00 host app: configuration reloaded";
+    let entry = parser.parse(raw).unwrap();
+    assert_eq!(entry.level, Some(logai_core::LogLevel::Notice));
+
+    assert_eq!(logai_core::LogLevel::from_str("notice"), Some(logai_core::LogLevel::Notice));
+    assert_eq!(logai_core::LogLevel::from_str("NOTICE"), Some(logai_core::LogLevel::Notice));
+    // more severe than info, less than a warning
+    assert!(logai_core::LogLevel::Info < logai_core::LogLevel::Notice);
+    assert!(logai_core::LogLevel::Notice < logai_core::LogLevel::Warn);
+}
 #[test]
 fn test_loghub_syslog_sample() {
     let parser = SyslogParser::new();
@@ -211,3 +301,34 @@ fn test_loghub_syslog_sample() {
     
     assert!(entry.message.contains("authentication failure"));
 }
+
+#[test]
+fn test_registry_lists_registered_parsers() {
+    let mut registry = ParserRegistry::new();
+    assert!(registry.list().is_empty());
+
+    registry.register(Box::new(SyslogParser::new()));
+    registry.register(Box::new(ApacheParser::new()));
+    registry.register(Box::new(NginxParser::new()));
+    // registering a name again replaces the parser, it isn't listed twice
+    registry.register(Box::new(NginxParser::new()));
+
+    let names = registry.list();
+    assert_eq!(names, vec!["apache", "nginx", "syslog"]);
+    assert!(names.iter().all(|name| registry.get(name).is_some()));
+}
+
+#[test]
+fn test_registry_parses_proxmox_tasks() {
+    let mut registry = ParserRegistry::new();
+    registry.register(Box::new(ProxmoxParser::new()));
+
+    let entry = registry
+        .parse("proxmox", "UPID:pve:0000A1B2:001C3D4E:65D8A1B2:vzdump:100:root@pam: 65D8A1F0 OK")
+        .expect("proxmox is registered");
+    assert_eq!(entry.fields.get("task_type"), Some(&serde_json::json!("vzdump")));
+    assert_eq!(entry.fields.get("task_id"), Some(&serde_json::json!("100")));
+    assert_eq!(entry.fields.get("hostname"), Some(&serde_json::json!("pve")));
+    assert_eq!(entry.message, "vzdump 100 task by root@pam: OK");
+    assert!(registry.parse("proxmox-ve", "UPID:pve").is_err());
+}
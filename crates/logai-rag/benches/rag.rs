@@ -8,18 +8,7 @@ use std::hint::black_box;
 // For full RAG benchmarks with LLM, use the stress test tool
 
 // Query samples for benchmarking
-const QUERIES: &[&str] = &[
-    "show me errors in nginx last 2 hours",
-    "why did the payment service crash at 3am yesterday",
-    "what caused the database timeout this week",
-    "summarize all authentication failures today",
-    "trace request abc-123-xyz through the system",
-    "find connection refused errors in redis",
-    "list all warnings from api-gateway past 30 minutes",
-    "what happened before the OOM kill at 2:30am",
-    "show me kafka consumer lag alerts",
-    "why are users getting 502 errors on checkout",
-];
+const QUERIES: &[&str] = logai_rag::SAMPLE_QUERIES;
 
 fn bench_query_analysis(c: &mut Criterion) {
     // Import at runtime to avoid compilation issues
@@ -92,7 +92,7 @@ impl LogEvent {
             "FATAL" | "CRITICAL" => 5,
             "ERROR" | "ERR" => 4,
             "WARN" | "WARNING" => 3,
-            "INFO" => 2,
+            "NOTICE" | "INFO" => 2,
             "DEBUG" => 1,
             _ => 0,
         }
@@ -117,20 +117,81 @@ struct CausalityScore {
     explanation: String,
 }
 
+/// How far back the analyzer walks and how sure it must be about each link
+#[derive(Debug, Clone)]
+pub struct CausalConfig {
+    pub max_chain_depth: usize,
+    pub min_confidence: f64,
+    /// Earlier logs scored per link; each one is an LLM call
+    pub max_candidates: usize,
+    /// How far before the failing log the API pulls in every log as candidate causes
+    pub window_secs: i64,
+    /// How long `RagEngine` reuses a chain for the same effect and logs (0 disables)
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for CausalConfig {
+    fn default() -> Self {
+        Self {
+            max_chain_depth: 3,   // Reduced from 10
+            min_confidence: 0.5,  // Lowered slightly
+            max_candidates: 3,
+            window_secs: 300,
+            cache_ttl_secs: 300,
+        }
+    }
+}
+
+impl CausalConfig {
+    /// Create config from environment variables; out-of-range values fall back to the default
+    ///
+    /// Environment variables:
+    /// - LOGAI_CAUSAL_MAX_DEPTH: Links to follow back from the error, 1-10 (default: 3)
+    /// - LOGAI_CAUSAL_MIN_CONFIDENCE: Minimum LLM causality score to keep a link, 0.0-1.0 (default: 0.5)
+    /// - LOGAI_CAUSAL_MAX_CANDIDATES: Earlier logs scored per link, 1-20 (default: 3)
+    /// - LOGAI_CAUSAL_WINDOW_SECS: Time before the error searched for causes, 10-3600 (default: 300)
+    /// - LOGAI_CAUSAL_CACHE_TTL_SECS: Reuse a chain for the same effect and logs, 0-3600, 0 disables (default: 300)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_chain_depth: read_in_range("LOGAI_CAUSAL_MAX_DEPTH", defaults.max_chain_depth, 1, 10),
+            min_confidence: read_in_range("LOGAI_CAUSAL_MIN_CONFIDENCE", defaults.min_confidence, 0.0, 1.0),
+            max_candidates: read_in_range("LOGAI_CAUSAL_MAX_CANDIDATES", defaults.max_candidates, 1, 20),
+            window_secs: read_in_range("LOGAI_CAUSAL_WINDOW_SECS", defaults.window_secs, 10, 3600),
+            cache_ttl_secs: read_in_range("LOGAI_CAUSAL_CACHE_TTL_SECS", defaults.cache_ttl_secs, 0, 3600),
+        }
+    }
+}
+
+fn read_in_range<T>(key: &str, default: T, min: T, max: T) -> T
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let Ok(raw) = std::env::var(key) else {
+        return default;
+    };
+    match raw.trim().parse::<T>() {
+        Ok(value) if value >= min && value <= max => value,
+        _ => {
+            tracing::warn!(key, value = %raw, "Expected a value between {} and {}, using {}", min, max, default);
+            default
+        }
+    }
+}
+
 /// Causal Chain Analyzer
 pub struct CausalChainAnalyzer {
     client: Arc<dyn LlmClient>,
-    max_chain_depth: usize,
-    min_confidence: f64,
+    config: CausalConfig,
 }
 
 impl CausalChainAnalyzer {
     pub fn new(client: Arc<dyn LlmClient>) -> Self {
-        Self {
-            client,
-            max_chain_depth: 3,   // Reduced from 10
-            min_confidence: 0.5,  // Lowered slightly
-        }
+        Self::with_config(client, CausalConfig::default())
+    }
+
+    pub fn with_config(client: Arc<dyn LlmClient>, config: CausalConfig) -> Self {
+        Self { client, config }
     }
     
     /// Main entry point: analyze logs and build causal chain
@@ -140,6 +201,14 @@ impl CausalChainAnalyzer {
         logs: Vec<String>,
         service_filter: Option<&str>,
     ) -> Result<CausalChain, CausalError> {
+        let events = self.parse_events(&logs, service_filter)?;
+        let effect = self.find_effect(&events)?;
+        self.analyze_events(query, effect, &events).await
+    }
+
+    /// The logs as events, newest first, narrowed to `service_filter`. No LLM calls yet, so
+    /// callers can look at the effect (`find_effect`) before paying for the chain
+    pub fn parse_events(&self, logs: &[String], service_filter: Option<&str>) -> Result<Vec<LogEvent>, CausalError> {
         if logs.is_empty() {
             return Err(CausalError::NoLogsFound);
         }
@@ -171,12 +240,18 @@ impl CausalChainAnalyzer {
         
         // Sort by timestamp descending (newest first)
         events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        
-        // Step 1: Find the effect (most severe recent error)
-        let effect = self.find_effect(&events)?;
-        
-        // Step 2: Build chain backward
-        let chain = self.build_chain_backward(&effect, &events).await?;
+        Ok(events)
+    }
+
+    /// The chain behind `effect`, found among `events` (from `parse_events`)
+    pub async fn analyze_events(
+        &self,
+        query: &str,
+        effect: LogEvent,
+        events: &[LogEvent],
+    ) -> Result<CausalChain, CausalError> {
+        // Step 2: Build chain backward (step 1, the effect, is the caller's `find_effect`)
+        let chain = self.build_chain_backward(&effect, events).await?;
         
         // Step 3: Identify root cause (oldest in chain, or last cause)
         let root_cause = chain.last().map(|link| link.cause.clone());
@@ -198,7 +273,7 @@ impl CausalChainAnalyzer {
     }
     
     /// Find the most severe recent error (the "effect" we're investigating)
-    fn find_effect(&self, events: &[LogEvent]) -> Result<LogEvent, CausalError> {
+    pub fn find_effect(&self, events: &[LogEvent]) -> Result<LogEvent, CausalError> {
         events.iter()
             .filter(|e| e.severity_score() >= 4) // ERROR or higher
             .max_by_key(|e| (e.severity_score(), e.timestamp))
@@ -215,7 +290,7 @@ impl CausalChainAnalyzer {
         let mut chain = Vec::new();
         let mut current_effect = effect.clone();
         
-        for _ in 0..self.max_chain_depth {
+        for _ in 0..self.config.max_chain_depth {
             // Find candidate causes (logs BEFORE current effect)
             let candidates: Vec<&LogEvent> = events.iter()
                 .filter(|e| e.timestamp < current_effect.timestamp)
@@ -223,7 +298,7 @@ impl CausalChainAnalyzer {
                     // Same service or related
                     e.service == current_effect.service || e.severity_score() >= 3
                 })
-                .take(3) // Limit to 3 candidates to reduce LLM calls
+                .take(self.config.max_candidates) // each candidate costs an LLM call
                 .collect();
             
             if candidates.is_empty() {
@@ -236,7 +311,7 @@ impl CausalChainAnalyzer {
             for candidate in candidates {
                 match self.score_causality(&current_effect, candidate).await {
                     Ok((score, explanation)) => {
-                        if score >= self.min_confidence {
+                        if score >= self.config.min_confidence {
                             if best_cause.is_none() || score > best_cause.as_ref().unwrap().1 {
                                 best_cause = Some((candidate.clone(), score, explanation));
                             }
@@ -454,4 +529,33 @@ mod tests {
         };
         assert_eq!(error.severity_score(), 4);
     }
+
+    #[tokio::test]
+    async fn test_depth_one_stops_after_single_link() {
+        let event = |secs: i64, level: &str, message: &str| LogEvent {
+            timestamp: DateTime::from_timestamp(1_770_692_400 + secs, 0).unwrap(),
+            level: level.to_string(),
+            service: "payment".to_string(),
+            message: message.to_string(),
+        };
+        // newest first, as analyze() sorts them; every pair scores 90
+        let events = vec![
+            event(30, "ERROR", "OOMKilled"),
+            event(20, "WARN", "Memory at 95%"),
+            event(10, "WARN", "Cache eviction disabled"),
+        ];
+        let score = r#"{"score": 90, "explanation": "memory pressure"}"#;
+
+        let client = Arc::new(crate::MockLlmClient::new().with_default(score));
+        let shallow = CausalChainAnalyzer::with_config(
+            client.clone(),
+            CausalConfig { max_chain_depth: 1, ..CausalConfig::default() },
+        );
+        let chain = shallow.build_chain_backward(&events[0], &events).await.unwrap();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].cause.message, "Memory at 95%");
+
+        let deep = CausalChainAnalyzer::new(Arc::new(crate::MockLlmClient::new().with_default(score)));
+        assert_eq!(deep.build_chain_backward(&events[0], &events).await.unwrap().len(), 2);
+    }
 }
@@ -1,12 +1,15 @@
 // RAG Engine - Routes queries to appropriate handler based on intent
 
-use std::sync::Arc;
-use crate::causal::{CausalChain, CausalChainAnalyzer};
+use std::sync::{Arc, RwLock};
+use crate::causal::{CausalChain, CausalChainAnalyzer, CausalConfig, CausalError, LogEvent};
+use crate::explain::{Explanation, LogExplainer};
+use crate::llm_cache::{CacheStats, LlmCache};
 use crate::llm_client::{LlmClient, LlmError, LlmProvider};
 use crate::groq_client::GroqClient;
 use crate::ollama_client::OllamaClient;
 use crate::query_analyzer::{AnalyzedQuery, QueryAnalyzer, QueryIntent};
 use serde::{Deserialize, Serialize};
+use chrono_tz::Tz;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -32,6 +35,14 @@ pub struct RagConfig {
     pub ollama_model: String,
     pub ollama_url: String,
     pub max_context_logs: usize,
+    /// Token budget for the logs in the prompt (estimated at 4 chars per token)
+    pub max_context_tokens: usize,
+    pub llm_cache_size: usize,
+    pub llm_cache_ttl_secs: u64,
+    pub timezone: Tz,
+    pub causal: CausalConfig,
+    /// Custom answer prompt with `{logs}` and `{question}` placeholders (None = built-in prompt)
+    pub prompt_template: Option<String>,
 }
 
 impl Default for RagConfig {
@@ -42,6 +53,12 @@ impl Default for RagConfig {
             ollama_model: "llama3.2:3b".to_string(),
             ollama_url: "http://localhost:11434".to_string(),
             max_context_logs: 10,
+            max_context_tokens: 6000,
+            llm_cache_size: 256,
+            llm_cache_ttl_secs: 600,
+            timezone: Tz::UTC,
+            causal: CausalConfig::default(),
+            prompt_template: None,
         }
     }
 }
@@ -55,7 +72,15 @@ impl RagConfig {
     /// - OLLAMA_URL: Ollama base URL (default: "http://localhost:11434")
     /// - OLLAMA_MODEL: Ollama model name (default: "llama3.2:3b")
     /// - LOGAI_MAX_CONTEXT_LOGS: Max logs in context (default: 10)
-    pub fn from_env() -> Self {
+    /// - LOGAI_MAX_CONTEXT_TOKENS: Token budget for those logs, long lines get cut (default: 6000)
+    /// - LOGAI_LLM_CACHE_SIZE: Max cached LLM responses, 0 disables (default: 256)
+    /// - LOGAI_LLM_CACHE_TTL_SECS: How long a cached response stays valid (default: 600)
+    /// - LOGAI_TIMEZONE: IANA zone for "today"/"yesterday"/"this week" (default: "UTC")
+    /// - LOGAI_CAUSAL_*: causal chain limits, see `CausalConfig::from_env`
+    /// - LOGAI_PROMPT_TEMPLATE: answer prompt, or a path to a file holding it, with
+    ///   `{logs}` and `{question}` placeholders (default: built-in SRE prompt). A path that
+    ///   exists but can't be read is an error
+    pub fn from_env() -> Result<Self, String> {
         let provider = LlmProvider::from_env();
         
         let groq_model = std::env::var("GROQ_MODEL")
@@ -73,13 +98,68 @@ impl RagConfig {
             .and_then(|s| s.parse().ok())
             .unwrap_or(10);
 
-        Self {
+        let max_context_tokens = std::env::var("LOGAI_MAX_CONTEXT_TOKENS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(6000);
+
+        let llm_cache_size = std::env::var("LOGAI_LLM_CACHE_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(256);
+
+        let llm_cache_ttl_secs = std::env::var("LOGAI_LLM_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(600);
+
+        let timezone = match std::env::var("LOGAI_TIMEZONE") {
+            Ok(name) => name.parse::<Tz>().unwrap_or_else(|_| {
+                tracing::warn!(timezone = %name, "Unknown LOGAI_TIMEZONE, using UTC");
+                Tz::UTC
+            }),
+            Err(_) => Tz::UTC,
+        };
+
+        // a value naming an existing file is read from disk, anything else is the template itself
+        let prompt_template = std::env::var("LOGAI_PROMPT_TEMPLATE")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|value| match std::path::Path::new(&value).is_file() {
+                true => std::fs::read_to_string(&value)
+                    .map_err(|e| format!("Can't read LOGAI_PROMPT_TEMPLATE file '{}': {}", value, e)),
+                false => Ok(value),
+            })
+            .transpose()?;
+
+        Ok(Self {
             provider,
             groq_model,
             ollama_model,
             ollama_url,
             max_context_logs,
+            max_context_tokens,
+            llm_cache_size,
+            llm_cache_ttl_secs,
+            timezone,
+            causal: CausalConfig::from_env(),
+            prompt_template,
+        })
+    }
+
+    /// Check settings that can't fall back to a default, i.e. the prompt template placeholders
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(template) = &self.prompt_template {
+            let missing: Vec<&str> = [LOGS_PLACEHOLDER, QUESTION_PLACEHOLDER]
+                .into_iter()
+                .filter(|p| !template.contains(p))
+                .collect();
+            if !missing.is_empty() {
+                return Err(format!("Prompt template is missing {}", missing.join(" and ")));
+            }
         }
+        Ok(())
     }
     
     /// Get the active model name
@@ -91,18 +171,65 @@ impl RagConfig {
     }
 }
 
+const LOGS_PLACEHOLDER: &str = "{logs}";
+const QUESTION_PLACEHOLDER: &str = "{question}";
 
+// Fill both placeholders in one pass, so a `{question}` inside the logs (or vice versa) stays literal
+fn render_template(template: &str, logs: &str, question: &str) -> String {
+    let mut out = String::with_capacity(template.len() + logs.len() + question.len());
+    let mut rest = template;
+    loop {
+        let next = [(LOGS_PLACEHOLDER, logs), (QUESTION_PLACEHOLDER, question)]
+            .into_iter()
+            .filter_map(|(p, value)| rest.find(p).map(|i| (i, p, value)))
+            .min_by_key(|(i, _, _)| *i);
+        let Some((i, placeholder, value)) = next else {
+            out.push_str(rest);
+            return out;
+        };
+        out.push_str(&rest[..i]);
+        out.push_str(value);
+        rest = &rest[i + placeholder.len()..];
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RagResponse {
     pub answer: String,
     pub query_analysis: QueryAnalysis,
     pub sources_count: usize,
+    /// Logs that made it into the prompt / were left out to stay within the token budget
+    pub context_logs: usize,
+    pub dropped_logs: usize,
     pub provider: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub causal_chain: Option<CausalChain>,  // Present when intent is Causal
 }
 
+/// Logs packed into the prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextWindow {
+    pub text: String,
+    pub included: usize,
+    pub dropped: usize,
+}
+
+/// Rough token count: ~4 characters per token for English text and log lines
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+// Cut a line to about `max_tokens`, marking the cut with an ellipsis
+fn truncate_to_tokens(line: &str, max_tokens: usize) -> String {
+    if estimate_tokens(line) <= max_tokens {
+        return line.to_string();
+    }
+    let keep = (max_tokens * 4).saturating_sub(1);
+    let mut cut: String = line.chars().take(keep).collect();
+    cut.push('…');
+    cut
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryAnalysis {
     pub original_query: String,
@@ -116,12 +243,18 @@ pub struct QueryAnalysis {
 pub struct RagEngine {
     config: RagConfig,
     client: Arc<dyn LlmClient>,
-    analyzer: QueryAnalyzer,
+    analyzer: RwLock<QueryAnalyzer>,
     causal_analyzer: CausalChainAnalyzer,
+    cache: LlmCache,
+    /// Finished causal chains as JSON, keyed by `causal_cache_key`
+    causal_cache: LlmCache,
+    explainer: LogExplainer,
 }
 
 impl RagEngine {
-    pub fn new(config: RagConfig) -> Self {
+    /// Engine with LLM clients for the configured provider; fails on an invalid config or a
+    /// client that can't be set up (e.g. no GROQ_API_KEY)
+    pub fn new(config: RagConfig) -> Result<Self, String> {
         let (client, causal_client): (Arc<dyn LlmClient>, Arc<dyn LlmClient>) = match config.provider {
             LlmProvider::Ollama => {
                 tracing::info!(
@@ -130,8 +263,9 @@ impl RagEngine {
                     url = %config.ollama_url,
                     "Using Ollama LLM"
                 );
-                let c1 = Arc::new(OllamaClient::from_env().expect("Failed to create Ollama client"));
-                let c2 = Arc::new(OllamaClient::from_env().expect("Failed to create Ollama client"));
+                let ollama = || OllamaClient::from_env().map_err(|e| format!("Failed to create Ollama client: {}", e));
+                let c1 = Arc::new(ollama()?);
+                let c2 = Arc::new(ollama()?);
                 (c1, c2)
             }
             LlmProvider::Groq => {
@@ -140,21 +274,53 @@ impl RagEngine {
                     model = %config.groq_model,
                     "Using Groq LLM"
                 );
-                let c1 = Arc::new(GroqClient::from_env(&config.groq_model).expect("GROQ_API_KEY must be set"));
-                let c2 = Arc::new(GroqClient::from_env(&config.groq_model).expect("GROQ_API_KEY must be set"));
+                let groq = || GroqClient::from_env(&config.groq_model).map_err(|e| format!("Failed to create Groq client: {}", e));
+                let c1 = Arc::new(groq()?);
+                let c2 = Arc::new(groq()?);
                 (c1, c2)
             }
         };
-        
-        let analyzer = QueryAnalyzer::new();
-        let causal_analyzer = CausalChainAnalyzer::new(causal_client);
 
-        Self {
+        Self::with_clients(config, client, causal_client)
+    }
+
+    /// Create an engine around an existing LLM client (no env lookups)
+    pub fn with_client(config: RagConfig, client: Arc<dyn LlmClient>) -> Result<Self, String> {
+        Self::with_clients(config, client.clone(), client)
+    }
+
+    /// Create an engine with separate clients for answers and causal scoring
+    pub fn with_clients(
+        config: RagConfig,
+        client: Arc<dyn LlmClient>,
+        causal_client: Arc<dyn LlmClient>,
+    ) -> Result<Self, String> {
+        config.validate().map_err(|e| format!("Invalid RAG config: {}", e))?;
+        let analyzer = QueryAnalyzer::with_timezone(config.timezone);
+        let causal_analyzer = CausalChainAnalyzer::with_config(causal_client, config.causal.clone());
+        let cache = LlmCache::new(
+            config.llm_cache_size,
+            std::time::Duration::from_secs(config.llm_cache_ttl_secs),
+        );
+        // a TTL of 0 turns it off, like a size of 0 does for the others
+        let causal_cache = LlmCache::new(
+            if config.causal.cache_ttl_secs == 0 { 0 } else { config.llm_cache_size },
+            std::time::Duration::from_secs(config.causal.cache_ttl_secs),
+        );
+        let explainer = LogExplainer::new(
+            client.clone(),
+            LlmCache::new(config.llm_cache_size, std::time::Duration::from_secs(config.llm_cache_ttl_secs)),
+        );
+
+        Ok(Self {
             config,
             client,
-            analyzer,
+            analyzer: RwLock::new(analyzer),
             causal_analyzer,
-        }
+            cache,
+            causal_cache,
+            explainer,
+        })
     }
     
     /// Get the active provider name and model
@@ -162,6 +328,16 @@ impl RagEngine {
         (self.client.provider(), self.client.model())
     }
 
+    /// Causal analysis limits, including the retrieval window callers should use
+    pub fn causal_config(&self) -> &CausalConfig {
+        &self.config.causal
+    }
+
+    /// LLM response cache hits/misses
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
     pub async fn query(
         &self,
         user_query: &str,
@@ -177,7 +353,7 @@ impl RagEngine {
         logs: Vec<String>,
         intent_override: Option<QueryIntent>,
     ) -> Result<RagResponse, RagError> {
-        let analyzed = self.analyzer.analyze(user_query);
+        let analyzed = self.analyze_query(user_query);
         
         // Use override intent if provided, otherwise use analyzed intent
         let intent = intent_override.clone().unwrap_or(analyzed.intent.clone());
@@ -207,6 +383,45 @@ impl RagEngine {
         }
     }
 
+    /// Causal chain analysis with no intent routing and no fallback to a plain answer
+    /// (for callers that explicitly asked for a root cause)
+    pub async fn analyze_causal(
+        &self,
+        user_query: &str,
+        logs: Vec<String>,
+        service_filter: Option<&str>,
+    ) -> Result<CausalChain, CausalError> {
+        self.cached_causal(user_query, &logs, service_filter).await
+    }
+
+    /// The causal chain, reused when the same effect was analyzed against the same logs within
+    /// `LOGAI_CAUSAL_CACHE_TTL_SECS`: a follow-up "why did payment crash?" costs no LLM calls
+    async fn cached_causal(
+        &self,
+        user_query: &str,
+        logs: &[String],
+        service_filter: Option<&str>,
+    ) -> Result<CausalChain, CausalError> {
+        let events = self.causal_analyzer.parse_events(logs, service_filter)?;
+        let effect = self.causal_analyzer.find_effect(&events)?;
+        let key = causal_cache_key(&effect, &events);
+
+        let cached = self.causal_cache.get(&key, self.client.model());
+        if let Some(mut chain) = cached.and_then(|json| serde_json::from_str::<CausalChain>(&json).ok()) {
+            tracing::info!(service = %effect.service, links = chain.chain.len(), "Causal cache hit");
+            // the wording may differ, the chain doesn't
+            chain.query = user_query.to_string();
+            return Ok(chain);
+        }
+        tracing::info!(service = %effect.service, events = events.len(), "Causal cache miss");
+
+        let chain = self.causal_analyzer.analyze_events(user_query, effect, &events).await?;
+        if let Ok(json) = serde_json::to_string(&chain) {
+            self.causal_cache.put(&key, self.client.model(), json);
+        }
+        Ok(chain)
+    }
+
     async fn handle_causal_query(
         &self,
         user_query: &str,
@@ -218,14 +433,14 @@ impl RagEngine {
         // Try causal analysis, but fall back to normal search if it fails (e.g., rate limit)
         // Note: Don't pass service filter - logs are already semantically filtered, and 
         // for follow-up queries the analyzed.service may come from conversation context
-        match self.causal_analyzer
-            .analyze(user_query, logs.clone(), None)
-            .await
-        {
+        match self.cached_causal(user_query, &logs, None).await {
             Ok(chain) => Ok(RagResponse {
                 answer: chain.summary.clone(),
                 query_analysis: self.build_query_analysis(analyzed),
                 sources_count: logs.len(),
+                // the analyzer scores events pairwise, it never sends the whole set at once
+                context_logs: logs.len(),
+                dropped_logs: 0,
                 provider: provider_name,
                 causal_chain: Some(chain),
             }),
@@ -244,14 +459,29 @@ impl RagEngine {
         analyzed: &AnalyzedQuery,
     ) -> Result<RagResponse, RagError> {
         let context = self.build_context(&logs);
-        let prompt = self.build_prompt(user_query, &context);
-        let answer = self.client.generate(&prompt).await?;
+        if context.dropped > 0 {
+            tracing::debug!(included = context.included, dropped = context.dropped, "Context trimmed to token budget");
+        }
+        let prompt = self.build_prompt(user_query, &context.text);
+        let answer = match self.cache.get(&prompt, self.client.model()) {
+            Some(cached) => {
+                tracing::debug!("LLM cache hit");
+                cached
+            }
+            None => {
+                let answer = self.client.generate(&prompt).await?;
+                self.cache.put(&prompt, self.client.model(), answer.clone());
+                answer
+            }
+        };
         let provider_name = format!("{} • {}", self.client.provider(), self.client.model());
 
         Ok(RagResponse {
             answer,
             query_analysis: self.build_query_analysis(analyzed),
             sources_count: logs.len(),
+            context_logs: context.included,
+            dropped_logs: context.dropped,
             provider: provider_name,
             causal_chain: None,
         })
@@ -270,15 +500,49 @@ impl RagEngine {
 
     // get analyzed query (for API to use in search)
     pub fn analyze_query(&self, query: &str) -> AnalyzedQuery {
-        self.analyzer.analyze(query)
+        self.analyzer.read().unwrap().analyze(query)
+    }
+
+    /// Service names queries can be scoped to, besides the built-in ones
+    pub fn set_services(&self, names: Vec<String>) {
+        // built before taking the write lock: searches aren't held up by the compile, and
+        // a list that fails to compile never touches the shared analyzer
+        let current = self.analyzer.read().unwrap().services().to_vec();
+        if let Some((names, pattern)) = QueryAnalyzer::prepare_services(names, &current) {
+            self.analyzer.write().unwrap().replace_services(names, pattern);
+        }
     }
 
-    fn build_context(&self, logs: &[String]) -> String {
-        let max_logs = self.config.max_context_logs.min(logs.len());
-        logs[..max_logs].join("\n")
+    /// Join the best-ranked logs (callers pass them in rank order) until the token budget is used up.
+    /// No single line may take more than a quarter of the budget, so one huge stack trace
+    /// can't crowd out everything else.
+    fn build_context(&self, logs: &[String]) -> ContextWindow {
+        let budget = self.config.max_context_tokens;
+        let line_cap = (budget / 4).max(1);
+        let mut lines = Vec::new();
+        let mut used = 0;
+
+        for log in logs.iter().take(self.config.max_context_logs) {
+            let line = truncate_to_tokens(log, line_cap);
+            let cost = estimate_tokens(&line) + 1; // + newline
+            if used + cost > budget {
+                break;
+            }
+            used += cost;
+            lines.push(line);
+        }
+
+        ContextWindow {
+            included: lines.len(),
+            dropped: logs.len() - lines.len(),
+            text: lines.join("\n"),
+        }
     }
 
     fn build_prompt(&self, query: &str, context: &str) -> String {
+        if let Some(template) = &self.config.prompt_template {
+            return render_template(template, context, query);
+        }
         format!(
             r#"You are LogAI, an expert SRE assistant. Analyze logs and answer questions directly.
 
@@ -297,12 +561,162 @@ RULES:
 - For "how to fix" questions: give actionable commands
 - Quote specific log lines as evidence when relevant
 - If you see the same error repeated, just mention the count, don't list all
+- A log with an "occurrences" field (or "[repeated N times]") stands for that many similar logs
 - Vary your response structure based on what the user actually asked"#,
             context, query
         )
     }
 
+    /// Explain a single log line, no retrieval (see `LogExplainer`)
+    pub async fn explain(&self, log: &str, context: &[String]) -> Result<Explanation, RagError> {
+        Ok(self.explainer.explain(log, context).await?)
+    }
+
     pub async fn classify(&self, prompt: &str) -> Result<String, RagError> {
         Ok(self.client.generate(prompt).await?)
     }
 }
+
+/// The effect and every candidate log it was analyzed against, by service, timestamp, level
+/// and message
+fn causal_cache_key(effect: &LogEvent, events: &[LogEvent]) -> String {
+    let line = |e: &LogEvent| format!("{}|{}|{}|{}", e.service, e.timestamp.to_rfc3339(), e.level, e.message);
+    std::iter::once(line(effect))
+        .chain(events.iter().map(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_client::MockLlmClient;
+
+    #[test]
+    fn test_huge_logs_trimmed_to_budget() {
+        let config = RagConfig {
+            max_context_logs: 50,
+            max_context_tokens: 1000,
+            ..RagConfig::default()
+        };
+        let engine = RagEngine::with_client(config, Arc::new(MockLlmClient::new())).unwrap();
+        // 40 logs of ~2500 tokens each, ranked best first
+        let logs: Vec<String> = (0..40)
+            .map(|i| format!("ERROR rank {} {}", i, "stack frame ".repeat(800)))
+            .collect();
+
+        let context = engine.build_context(&logs);
+        assert!(estimate_tokens(&context.text) <= 1000);
+        assert!(context.included > 0);
+        assert_eq!(context.included + context.dropped, 40);
+        // the best-ranked logs survive, each cut short with an ellipsis
+        assert!(context.text.starts_with("ERROR rank 0 "));
+        assert!(context.text.lines().all(|l| l.ends_with('…')));
+        assert!(!context.text.contains("ERROR rank 39 "));
+
+        // small logs are untouched
+        let small = engine.build_context(&["WARN disk 91%".to_string()]);
+        assert_eq!(small, ContextWindow { text: "WARN disk 91%".to_string(), included: 1, dropped: 0 });
+    }
+
+    #[test]
+    fn test_custom_prompt_template() {
+        let config = RagConfig {
+            prompt_template: Some("Q: {question}\n---\n{logs}\n---\nAnswer briefly.".to_string()),
+            ..RagConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        let engine = RagEngine::with_client(config, Arc::new(MockLlmClient::new())).unwrap();
+
+        let prompt = engine.build_prompt("why did {logs} fail?", "ERROR payment timeout");
+        assert_eq!(prompt, "Q: why did {logs} fail?\n---\nERROR payment timeout\n---\nAnswer briefly.");
+
+        let missing = RagConfig { prompt_template: Some("Logs: {logs}".to_string()), ..RagConfig::default() };
+        assert_eq!(missing.validate(), Err("Prompt template is missing {question}".to_string()));
+        let err = RagEngine::with_client(missing, Arc::new(MockLlmClient::new())).err();
+        assert_eq!(err.as_deref(), Some("Invalid RAG config: Prompt template is missing {question}"));
+    }
+
+    #[tokio::test]
+    async fn test_identical_prompts_hit_cache() {
+        let client = Arc::new(MockLlmClient::new());
+        let engine = RagEngine::with_client(RagConfig::default(), client.clone()).unwrap();
+        let logs = vec!["ERROR payment timeout".to_string()];
+
+        let first = engine.query("show me payment errors", logs.clone()).await.unwrap();
+        let second = engine.query("show me payment errors", logs).await.unwrap();
+
+        assert_eq!(first.answer, second.answer);
+        assert_eq!(client.call_count(), 1);
+
+        let stats = engine.cache_stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_causal_routing_end_to_end() {
+        let client = Arc::new(MockLlmClient::with_responses([
+            // effect "OOMKilled" vs candidates (newest first)
+            r#"{"score": 90, "explanation": "memory pressure led to the kill"}"#,
+            r#"{"score": 20, "explanation": "unrelated"}"#,
+            // "Memory at 95%" vs the deploy
+            r#"{"score": 80, "explanation": "new release leaks memory"}"#,
+            "The deploy leaked memory until the pod was OOMKilled.",
+            "- Roll back v2",
+        ]));
+        let engine = RagEngine::with_client(RagConfig::default(), client.clone()).unwrap();
+
+        let logs = vec![
+            r#"{"timestamp":"2026-02-10T02:59:00Z","level":"INFO","service":"payment","message":"Deploy v2 started"}"#.to_string(),
+            r#"{"timestamp":"2026-02-10T03:00:00Z","level":"WARN","service":"payment","message":"Memory at 95%"}"#.to_string(),
+            r#"{"timestamp":"2026-02-10T03:00:05Z","level":"ERROR","service":"payment","message":"OOMKilled"}"#.to_string(),
+        ];
+
+        let response = engine.query("why did payment crash", logs).await.unwrap();
+        let chain = response.causal_chain.expect("causal intent should produce a chain");
+
+        assert_eq!(response.query_analysis.intent, "Causal");
+        assert_eq!(response.answer, "The deploy leaked memory until the pod was OOMKilled.");
+        assert_eq!(chain.effect.message, "OOMKilled");
+        assert_eq!(chain.chain.len(), 2);
+        assert_eq!(chain.chain[0].cause.message, "Memory at 95%");
+        assert_eq!(chain.chain[0].confidence, 0.9);
+        assert_eq!(chain.root_cause.unwrap().message, "Deploy v2 started");
+        assert_eq!(chain.recommendation.as_deref(), Some("- Roll back v2"));
+
+        let prompts = client.prompts();
+        assert_eq!(prompts.len(), 5);
+        assert!(prompts[0].contains("OOMKilled") && prompts[0].contains("Memory at 95%"));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_causal_analysis_is_cached() {
+        let client = Arc::new(MockLlmClient::new().with_default(r#"{"score": 90, "explanation": "memory pressure"}"#));
+        let engine = RagEngine::with_client(RagConfig::default(), client.clone()).unwrap();
+        let line = |ts: &str, level: &str, message: &str| {
+            format!(r#"{{"timestamp":"{}","level":"{}","service":"payment","message":"{}"}}"#, ts, level, message)
+        };
+        let logs = vec![
+            line("2026-02-10T03:00:00Z", "WARN", "Memory at 95%"),
+            line("2026-02-10T03:00:05Z", "ERROR", "OOMKilled"),
+        ];
+
+        let first = engine.analyze_causal("why did payment crash", logs.clone(), None).await.unwrap();
+        let calls = client.call_count();
+        assert!(calls > 0);
+
+        // same effect, same logs: nothing scored again, only the question is new
+        let second = engine.analyze_causal("what caused the payment crash?", logs.clone(), None).await.unwrap();
+        assert_eq!(client.call_count(), calls);
+        assert_eq!(second.query, "what caused the payment crash?");
+        assert_eq!(second.chain.len(), first.chain.len());
+        assert_eq!(second.summary, first.summary);
+
+        // one more candidate log is a different analysis
+        let mut more = logs;
+        more.insert(0, line("2026-02-10T02:59:00Z", "INFO", "Deploy v2 started"));
+        engine.analyze_causal("why did payment crash", more, None).await.unwrap();
+        assert!(client.call_count() > calls);
+    }
+}
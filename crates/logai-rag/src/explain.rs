@@ -0,0 +1,163 @@
+// Log Explainer - "what does this line mean?" for a single log, straight to the LLM
+// (no retrieval). Answers are cached by the log itself, so the same line clicked again
+// (from search, the tail view, or by someone else) doesn't cost another call.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::llm_cache::LlmCache;
+use crate::llm_client::{LlmClient, LlmError};
+
+// surrounding lines beyond this are dropped, the log itself is what's being explained
+const MAX_CONTEXT_LINES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub explanation: String,
+    pub next_steps: Vec<String>,
+    /// Answered from the cache, without an LLM call
+    pub cached: bool,
+}
+
+pub struct LogExplainer {
+    client: Arc<dyn LlmClient>,
+    cache: LlmCache,
+}
+
+impl LogExplainer {
+    pub fn new(client: Arc<dyn LlmClient>, cache: LlmCache) -> Self {
+        Self { client, cache }
+    }
+
+    /// Explain `log`; `context` (the lines logged around it) only informs the first answer,
+    /// later ones for the same log come from the cache
+    pub async fn explain(&self, log: &str, context: &[String]) -> Result<Explanation, LlmError> {
+        let log = log.trim();
+        if let Some(cached) = self.cache.get(log, self.client.model()) {
+            tracing::debug!("Explanation cache hit");
+            return Ok(Explanation {
+                cached: true,
+                ..parse_explanation(&cached)
+            });
+        }
+
+        let answer = self.client.generate(&explain_prompt(log, context)).await?;
+        self.cache.put(log, self.client.model(), answer.clone());
+        Ok(parse_explanation(&answer))
+    }
+}
+
+pub fn explain_prompt(log: &str, context: &[String]) -> String {
+    let context = if context.is_empty() {
+        String::new()
+    } else {
+        let lines: Vec<&str> = context.iter().take(MAX_CONTEXT_LINES).map(|l| l.trim()).collect();
+        format!("\nLOGS AROUND IT (context only):\n```\n{}\n```\n", lines.join("\n"))
+    };
+    format!(
+        r#"You are LogAI, an expert SRE assistant. An engineer clicked on one log line and wants to know what it means.
+
+LOG:
+```
+{}
+```
+{}
+Answer in exactly this format:
+EXPLANATION: <2-4 sentences: what the log means and its most likely causes>
+NEXT STEPS:
+- <a concrete check or command>
+- <at most 3 more>
+
+Be specific to this log; don't explain logging in general."#,
+        log, context
+    )
+}
+
+/// Split an answer into the explanation and its next steps. An answer that ignored the
+/// format is all explanation.
+pub fn parse_explanation(answer: &str) -> Explanation {
+    let answer = answer.trim();
+    // ASCII-only so byte offsets stay valid in `answer` ("ﬁ" upper-cases to the shorter "FI")
+    let upper = answer.to_ascii_uppercase();
+    let (explanation, steps) = match upper.find("NEXT STEPS:") {
+        Some(at) => (&answer[..at], &answer[at + "NEXT STEPS:".len()..]),
+        None => (answer, ""),
+    };
+
+    let explanation = explanation.trim();
+    let explanation = match explanation.get(.."EXPLANATION:".len()) {
+        Some(label) if label.eq_ignore_ascii_case("EXPLANATION:") => &explanation[label.len()..],
+        _ => explanation,
+    };
+    let next_steps = steps
+        .lines()
+        .map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*', '•']);
+            // "1." / "2)" numbering
+            let unnumbered = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            let line = match unnumbered.strip_prefix(['.', ')']) {
+                Some(rest) if unnumbered.len() < line.len() => rest,
+                _ => line,
+            };
+            line.trim().to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Explanation {
+        explanation: explanation.trim().to_string(),
+        next_steps,
+        cached: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_client::MockLlmClient;
+    use std::time::Duration;
+
+    const LOG: &str = "2026-02-10T03:00:00Z ERROR payment: connection pool exhausted (max=20)";
+
+    #[tokio::test]
+    async fn test_log_text_in_prompt() {
+        let mock = Arc::new(MockLlmClient::with_responses([
+            "EXPLANATION: Every database connection was in use.\nNEXT STEPS:\n- Check pool usage\n2. Look for slow queries",
+        ]));
+        let explainer = LogExplainer::new(mock.clone(), LlmCache::new(8, Duration::from_secs(60)));
+        let context = vec!["2026-02-10T02:59:58Z WARN payment: slow query took 4.2s".to_string()];
+
+        let explained = explainer.explain(LOG, &context).await.unwrap();
+        assert_eq!(explained.explanation, "Every database connection was in use.");
+        assert_eq!(explained.next_steps, vec!["Check pool usage", "Look for slow queries"]);
+        assert!(!explained.cached);
+
+        let prompts = mock.prompts();
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].contains(LOG));
+        assert!(prompts[0].contains("slow query took 4.2s"));
+
+        // same log again: cached, no second call even with other context
+        let again = explainer.explain(LOG, &[]).await.unwrap();
+        assert!(again.cached);
+        assert_eq!(again.next_steps.len(), 2);
+        assert_eq!(mock.call_count(), 1);
+    }
+
+    #[test]
+    fn test_unformatted_answer() {
+        let parsed = parse_explanation("  The pool ran out of connections.  ");
+        assert_eq!(parsed.explanation, "The pool ran out of connections.");
+        assert!(parsed.next_steps.is_empty());
+
+        assert!(!explain_prompt(LOG, &[]).contains("LOGS AROUND IT"));
+    }
+
+    #[test]
+    fn test_non_ascii_before_next_steps() {
+        // "ﬁ" upper-cases to "FI", a byte shorter, which used to shift the split
+        let parsed = parse_explanation("Explanation: The ﬁle handle was closed twice.\nnext steps:\n- Restart it");
+        assert_eq!(parsed.explanation, "The ﬁle handle was closed twice.");
+        assert_eq!(parsed.next_steps, vec!["Restart it"]);
+    }
+}
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::llm_client::{LlmClient, LlmError};
+use crate::retry::RetryPolicy;
 
 #[derive(Error, Debug)]
 pub enum GroqError {
@@ -26,6 +27,8 @@ pub struct GroqClient {
     client: Client,
     api_key: String,
     model: String,
+    base_url: String,
+    retry: RetryPolicy,
 }
 
 #[derive(Serialize)]
@@ -65,17 +68,34 @@ impl GroqClient {
             client: Client::new(),
             api_key: api_key.into(),
             model: model.into(),
+            base_url: Self::BASE_URL.to_string(),
+            retry: RetryPolicy::default(),
         }
     }
     
-    /// Create from env GROQ_API_KEY
+    /// Create from env GROQ_API_KEY (retry timeout from LOGAI_LLM_TIMEOUT_SECS)
     pub fn from_env(model: impl Into<String>) -> Result<Self, GroqError> {
         let api_key = std::env::var("GROQ_API_KEY").map_err(|_| GroqError::MissingApiKey)?;
-        Ok(Self::new(api_key, model))
+        Ok(Self::new(api_key, model).with_retry(RetryPolicy::from_env()))
     }
 
-    /// Generate text from prompt (returns GroqError for internal use)
-    pub async fn generate(&self, prompt: &str) -> Result<String, GroqError> {
+    /// Point at another OpenAI-compatible chat completions endpoint
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Generate text from prompt, retrying rate limits and server errors
+    pub async fn generate(&self, prompt: &str) -> Result<String, LlmError> {
+        self.retry.run(|| self.generate_once(prompt)).await
+    }
+
+    async fn generate_once(&self, prompt: &str) -> Result<String, LlmError> {
         let request = ChatRequest {
             model: &self.model,
             messages: vec![
@@ -93,24 +113,27 @@ impl GroqClient {
         };
         let response = self
             .client
-            .post(Self::BASE_URL)
+            .post(&self.base_url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
-            .await?;
+            .await
+            .map_err(|e| LlmError::RequestFailed(e.to_string()))?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(GroqError::ApiError(error_text));
+            return Err(LlmError::from_response(response).await);
         }
-        let result: ChatResponse = response.json().await?;
+        let result: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| LlmError::ApiError(format!("Failed to parse response: {}", e)))?;
         result
             .choices
             .into_iter()
             .next()
             .map(|c| c.message.content)
-            .ok_or_else(|| GroqError::ApiError("No response".to_string()))
+            .ok_or_else(|| LlmError::ApiError("No response".to_string()))
     }
     
     /// Get model name
@@ -122,10 +145,7 @@ impl GroqClient {
 #[async_trait]
 impl LlmClient for GroqClient {
     async fn generate(&self, prompt: &str) -> Result<String, LlmError> {
-        // Call the inherent method and convert error
-        GroqClient::generate(self, prompt)
-            .await
-            .map_err(|e| LlmError::ApiError(e.to_string()))
+        GroqClient::generate(self, prompt).await
     }
 
     fn model(&self) -> &str {
@@ -146,4 +166,86 @@ mod tests {
         let client = GroqClient::new("test-key", "llama-3.3-70b-versatile");
         assert_eq!(client.model(), "llama-3.3-70b-versatile");
     }
+
+    // Minimal HTTP server: answers each connection with the next canned response
+    async fn mock_server(responses: Vec<String>) -> (String, tokio::task::JoinHandle<usize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/chat/completions", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut served = 0;
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                // read the whole request (headers, then Content-Length bytes of body)
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length || n == 0 {
+                            break;
+                        }
+                    }
+                }
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+                served += 1;
+            }
+            served
+        });
+        (url, server)
+    }
+
+    fn http_response(status: &str, extra_headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            extra_headers,
+            body.len(),
+            body
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_then_success() {
+        let (url, server) = mock_server(vec![
+            http_response(
+                "429 Too Many Requests",
+                "Retry-After: 0\r\n",
+                r#"{"error":{"message":"Rate limit reached","code":"rate_limit_exceeded"}}"#,
+            ),
+            http_response("200 OK", "", r#"{"choices":[{"message":{"role":"assistant","content":"Disk full on db-1"}}]}"#),
+        ])
+        .await;
+
+        let client = GroqClient::new("test-key", "llama-3.3-70b-versatile").with_base_url(url);
+        let answer = client.generate("why did the db crash?").await.unwrap();
+
+        assert_eq!(answer, "Disk full on db-1");
+        assert_eq!(server.await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_auth_error_fails_fast() {
+        let (url, server) = mock_server(vec![http_response(
+            "401 Unauthorized",
+            "",
+            r#"{"error":{"message":"Invalid API Key"}}"#,
+        )])
+        .await;
+
+        let client = GroqClient::new("bad-key", "llama-3.3-70b-versatile").with_base_url(url);
+        let err = client.generate("hello").await.unwrap_err();
+
+        assert!(matches!(err, LlmError::Status { status: 401, .. }));
+        assert!(!err.is_retryable());
+        assert_eq!(server.await.unwrap(), 1);
+    }
 }
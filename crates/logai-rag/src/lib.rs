@@ -4,14 +4,40 @@ pub mod query_analyzer;
 pub mod engine;
 pub mod reranker;
 pub mod llm_client;
+pub mod llm_cache;
 pub mod groq_client;
 pub mod ollama_client;
 pub mod causal;
+pub mod retry;
+pub mod trace;
+pub mod explain;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_client;
 
 pub use query_analyzer::{AnalyzedQuery, QueryAnalyzer, QueryIntent};
-pub use engine::{RagEngine, RagConfig, RagResponse, QueryAnalysis};
-pub use reranker::{Reranker, RankedLog};
+pub use engine::{estimate_tokens, ContextWindow, RagEngine, RagConfig, RagResponse, QueryAnalysis};
+pub use reranker::{highlight_ranges, MessageNormalizer, Reranker, RankedLog, RerankConfig};
 pub use llm_client::{LlmClient, LlmError, LlmProvider};
+pub use llm_cache::{CacheStats, LlmCache};
 pub use groq_client::GroqClient;
 pub use ollama_client::OllamaClient;
-pub use causal::{CausalChainAnalyzer, CausalChain, CausalLink, LogEvent, CausalError};
+pub use retry::RetryPolicy;
+pub use causal::{CausalChainAnalyzer, CausalChain, CausalConfig, CausalLink, LogEvent, CausalError};
+pub use trace::TraceResponse;
+pub use explain::{Explanation, LogExplainer};
+#[cfg(any(test, feature = "test-utils"))]
+pub use mock_client::MockLlmClient;
+
+/// Typical operator questions, shared by the criterion bench (benches/rag.rs) and `logai bench`
+pub const SAMPLE_QUERIES: &[&str] = &[
+    "show me errors in nginx last 2 hours",
+    "why did the payment service crash at 3am yesterday",
+    "what caused the database timeout this week",
+    "summarize all authentication failures today",
+    "trace request abc-123-xyz through the system",
+    "find connection refused errors in redis",
+    "list all warnings from api-gateway past 30 minutes",
+    "what happened before the OOM kill at 2:30am",
+    "show me kafka consumer lag alerts",
+    "why are users getting 502 errors on checkout",
+];
@@ -0,0 +1,160 @@
+// LLM response cache - avoids paying for the same prompt twice
+
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Hit/miss counters for observability
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+struct CacheEntry {
+    response: String,
+    inserted_at: Instant,
+    last_used: u64, // logical clock for LRU eviction
+}
+
+/// In-memory LRU cache keyed on a hash of (prompt, model)
+pub struct LlmCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<CacheInner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    map: HashMap<u64, CacheEntry>,
+    clock: u64,
+}
+
+impl LlmCache {
+    /// capacity of 0 disables caching
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::new(CacheInner::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn key(prompt: &str, model: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (prompt, model).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn get(&self, prompt: &str, model: &str) -> Option<String> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let key = Self::key(prompt, model);
+        let mut inner = self.entries.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+
+        let expired = match inner.map.get_mut(&key) {
+            Some(entry) if entry.inserted_at.elapsed() <= self.ttl => {
+                entry.last_used = clock;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Some(entry.response.clone());
+            }
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            inner.map.remove(&key);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    pub fn put(&self, prompt: &str, model: &str, response: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = Self::key(prompt, model);
+        let mut inner = self.entries.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+
+        // evict least recently used entry when full
+        if !inner.map.contains_key(&key) && inner.map.len() >= self.capacity {
+            let oldest = inner
+                .map
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| *k);
+            if let Some(k) = oldest {
+                inner.map.remove(&k);
+            }
+        }
+
+        inner.map.insert(key, CacheEntry {
+            response,
+            inserted_at: Instant::now(),
+            last_used: clock,
+        });
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().map.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_and_miss() {
+        let cache = LlmCache::new(4, Duration::from_secs(60));
+        assert!(cache.get("prompt", "model").is_none());
+        cache.put("prompt", "model", "answer".to_string());
+        assert_eq!(cache.get("prompt", "model"), Some("answer".to_string()));
+
+        // same prompt on another model is a different entry
+        assert!(cache.get("prompt", "other-model").is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = LlmCache::new(2, Duration::from_secs(60));
+        cache.put("a", "m", "1".to_string());
+        cache.put("b", "m", "2".to_string());
+        cache.get("a", "m"); // "b" is now least recently used
+        cache.put("c", "m", "3".to_string());
+
+        assert!(cache.get("a", "m").is_some());
+        assert!(cache.get("b", "m").is_none());
+        assert!(cache.get("c", "m").is_some());
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = LlmCache::new(2, Duration::ZERO);
+        cache.put("a", "m", "1".to_string());
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(cache.get("a", "m").is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
@@ -1,6 +1,7 @@
 // LLM Client Abstraction - Supports multiple LLM providers
 
 use async_trait::async_trait;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,6 +14,42 @@ pub enum LlmError {
 
     #[error("Missing configuration: {0}")]
     MissingConfig(String),
+
+    /// Non-2xx answer from the provider; `retry_after` comes from the Retry-After header
+    #[error("HTTP {status}: {body}")]
+    Status {
+        status: u16,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+
+    #[error("LLM request timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+impl LlmError {
+    /// Rate limits, server errors and dropped connections are worth another try;
+    /// bad keys, bad requests and unparseable answers are not
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LlmError::Status { status, .. } => *status == 408 || *status == 429 || *status >= 500,
+            LlmError::RequestFailed(_) | LlmError::Timeout(_) => true,
+            LlmError::ApiError(_) | LlmError::MissingConfig(_) => false,
+        }
+    }
+
+    /// Build a `Status` error from a failed response, reading Retry-After (seconds) and the body
+    pub async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.unwrap_or_default();
+        LlmError::Status { status, body, retry_after }
+    }
 }
 
 /// Common trait for all LLM clients
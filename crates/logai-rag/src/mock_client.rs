@@ -0,0 +1,110 @@
+// Mock LLM client - scripted responses for deterministic tests (no API key needed)
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::llm_client::{LlmClient, LlmError};
+
+/// LLM client that replays queued responses and records every prompt it receives
+pub struct MockLlmClient {
+    responses: Mutex<VecDeque<Result<String, String>>>,
+    default_response: String,
+    prompts: Mutex<Vec<String>>,
+}
+
+impl MockLlmClient {
+    pub fn new() -> Self {
+        Self {
+            responses: Mutex::new(VecDeque::new()),
+            default_response: "mock response".to_string(),
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create a mock that answers with these responses in order
+    pub fn with_responses<I, S>(responses: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mock = Self::new();
+        for r in responses {
+            mock.push_response(r);
+        }
+        mock
+    }
+
+    /// Response returned once the queue is empty
+    pub fn with_default(mut self, response: impl Into<String>) -> Self {
+        self.default_response = response.into();
+        self
+    }
+
+    pub fn push_response(&self, response: impl Into<String>) {
+        self.responses.lock().unwrap().push_back(Ok(response.into()));
+    }
+
+    /// Queue an error (e.g. to simulate a rate limit)
+    pub fn push_error(&self, error: impl Into<String>) {
+        self.responses.lock().unwrap().push_back(Err(error.into()));
+    }
+
+    /// All prompts received so far, in call order
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.prompts.lock().unwrap().len()
+    }
+}
+
+impl Default for MockLlmClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LlmClient for MockLlmClient {
+    async fn generate(&self, prompt: &str) -> Result<String, LlmError> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        match self.responses.lock().unwrap().pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(error)) => Err(LlmError::ApiError(error)),
+            None => Ok(self.default_response.clone()),
+        }
+    }
+
+    fn model(&self) -> &str {
+        "mock-model"
+    }
+
+    fn provider(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scripted_responses() {
+        let client = MockLlmClient::with_responses(["first", "second"]).with_default("fallback");
+
+        assert_eq!(client.generate("a").await.unwrap(), "first");
+        assert_eq!(client.generate("b").await.unwrap(), "second");
+        assert_eq!(client.generate("c").await.unwrap(), "fallback");
+        assert_eq!(client.prompts(), vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_queued_error() {
+        let client = MockLlmClient::new();
+        client.push_error("rate_limit");
+        assert!(client.generate("a").await.is_err());
+        assert_eq!(client.call_count(), 1);
+    }
+}
@@ -5,12 +5,14 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::llm_client::{LlmClient, LlmError};
+use crate::retry::RetryPolicy;
 
 #[derive(Debug, Clone)]
 pub struct OllamaClient {
     client: Client,
     base_url: String,
     model: String,
+    retry: RetryPolicy,
 }
 
 #[derive(Serialize)]
@@ -38,25 +40,29 @@ impl OllamaClient {
             client: Client::new(),
             base_url: base_url.into(),
             model: model.into(),
+            retry: RetryPolicy::default(),
         }
     }
 
     /// Create from environment variables
     /// - OLLAMA_URL: Base URL (default: http://localhost:11434)
     /// - OLLAMA_MODEL: Model name (default: llama3.2:3b)
+    /// - LOGAI_LLM_TIMEOUT_SECS: see `RetryPolicy::from_env`
     pub fn from_env() -> Result<Self, LlmError> {
         let base_url = std::env::var("OLLAMA_URL")
             .unwrap_or_else(|_| "http://localhost:11434".to_string());
         let model = std::env::var("OLLAMA_MODEL")
             .unwrap_or_else(|_| "llama3.2:3b".to_string());
         
-        Ok(Self::new(base_url, model))
+        Ok(Self::new(base_url, model).with_retry(RetryPolicy::from_env()))
     }
-}
 
-#[async_trait]
-impl LlmClient for OllamaClient {
-    async fn generate(&self, prompt: &str) -> Result<String, LlmError> {
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    async fn generate_once(&self, prompt: &str) -> Result<String, LlmError> {
         let url = format!("{}/api/generate", self.base_url);
         
         let request = GenerateRequest {
@@ -78,8 +84,7 @@ impl LlmClient for OllamaClient {
             .map_err(|e| LlmError::RequestFailed(e.to_string()))?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(LlmError::ApiError(error_text));
+            return Err(LlmError::from_response(response).await);
         }
 
         let result: GenerateResponse = response
@@ -89,6 +94,14 @@ impl LlmClient for OllamaClient {
         
         Ok(result.response)
     }
+}
+
+#[async_trait]
+impl LlmClient for OllamaClient {
+    async fn generate(&self, prompt: &str) -> Result<String, LlmError> {
+        // a model still loading answers 503, worth waiting for
+        self.retry.run(|| self.generate_once(prompt)).await
+    }
 
     fn model(&self) -> &str {
         &self.model
@@ -1,7 +1,8 @@
 // Query Analyzer - extracts time, service, level, and intent from natural language queries
 
-use chrono::{DateTime, Duration, Utc};
-use regex::Regex;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -27,11 +28,27 @@ pub struct AnalyzedQuery {
     pub service: Option<String>,
     pub level: Option<String>,
     pub intent: QueryIntent,
+    pub trace_id: Option<String>, // id following "trace" / "request id" / "trace-id"
 }
 
+// recognised even before any logs arrive
+const BUILTIN_SERVICES: &[&str] = &[
+    "nginx", "apache", "mysql", "postgres", "redis", "kafka", "docker", "kubernetes", "k8s",
+    "api", "auth", "gateway", "payment", "order", "user", "checkout",
+];
+// extra names past this are dropped: they all end up in one alternation
+const MAX_EXTRA_SERVICES: usize = 2000;
+// compiled size the service pattern may reach before the new list is rejected
+const SERVICE_PATTERN_SIZE_LIMIT: usize = 2 * 1024 * 1024;
+
 pub struct QueryAnalyzer {
     time_patterns: Vec<(Regex, i64, &'static str)>,
     service_pattern: Regex,
+    // names added with set_services, as logged (sorted)
+    services: Vec<String>,
+    trace_id_pattern: Regex,
+    // "today"/"yesterday"/"this week" start at midnight here, not in UTC
+    timezone: Tz,
 }
 
 impl QueryAnalyzer {
@@ -43,11 +60,90 @@ impl QueryAnalyzer {
             (Regex::new(r"past\s+(\d+)\s*h(?:our)?s?").unwrap(), 3600, "seconds"),
             (Regex::new(r"past\s+(\d+)\s*m(?:in(?:ute)?)?s?").unwrap(), 60, "seconds"),
         ];
-        let service_pattern = Regex::new(
-            r"\b(nginx|apache|mysql|postgres|redis|kafka|docker|kubernetes|k8s|api|auth|gateway|payment|order|user|checkout)\b",
+        let service_pattern = Self::service_pattern(&[]).expect("built-in service pattern");
+        // id token right after a trace keyword: dashed ids ("abc-123-xyz", UUIDs) or hex ("7f3a9c")
+        let trace_id_pattern = Regex::new(
+            r"(?i)\b(?:trace[-_ ]?id|request[-_ ]?id|trace)\b(?:\s+(?:request|for|of|id))*[\s:=#]*([0-9a-z]+(?:-[0-9a-z]+)+|[0-9a-f]{6,})\b",
         ).unwrap();
 
-        Self { time_patterns, service_pattern }
+        Self { time_patterns, service_pattern, services: Vec::new(), trace_id_pattern, timezone: Tz::UTC }
+    }
+
+    /// Analyzer whose calendar words follow the operators' local time
+    pub fn with_timezone(timezone: Tz) -> Self {
+        Self { timezone, ..Self::new() }
+    }
+
+    /// Analyzer that also recognises `names` (e.g. the services logs were ingested under)
+    /// on top of the built-in list
+    pub fn with_services(names: Vec<String>) -> Self {
+        let mut analyzer = Self::new();
+        analyzer.set_services(names);
+        analyzer
+    }
+
+    /// Replace the extra service names; the regex is only rebuilt when they changed, and
+    /// a list too large to compile leaves the previous one in place
+    pub fn set_services(&mut self, names: Vec<String>) {
+        if let Some((names, pattern)) = Self::prepare_services(names, &self.services) {
+            self.replace_services(names, pattern);
+        }
+    }
+
+    /// Extra service names currently recognised, as logged (sorted)
+    pub fn services(&self) -> &[String] {
+        &self.services
+    }
+
+    /// The cleaned-up names and their pattern, or None when they equal `current` or the
+    /// pattern can't be built. Needs no analyzer, so a shared one is only locked for the swap
+    pub(crate) fn prepare_services(names: Vec<String>, current: &[String]) -> Option<(Vec<String>, Regex)> {
+        let mut names: Vec<String> = names
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort();
+        names.dedup();
+        if names.len() > MAX_EXTRA_SERVICES {
+            tracing::warn!(
+                services = names.len(),
+                kept = MAX_EXTRA_SERVICES,
+                "Too many service names for the query analyzer, ignoring the rest"
+            );
+            names.truncate(MAX_EXTRA_SERVICES);
+        }
+        if names == current {
+            return None;
+        }
+        match Self::service_pattern(&names) {
+            Ok(pattern) => Some((names, pattern)),
+            Err(e) => {
+                tracing::warn!(error = %e, services = names.len(), "Service pattern not rebuilt, keeping the previous one");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn replace_services(&mut self, names: Vec<String>, pattern: Regex) {
+        self.service_pattern = pattern;
+        self.services = names;
+    }
+
+    // Longest names first: the regex takes the first alternative that matches, so
+    // "billing-worker" has to be tried before "billing"
+    fn service_pattern(extra: &[String]) -> Result<Regex, regex::Error> {
+        let mut names: Vec<String> = BUILTIN_SERVICES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(extra.iter().map(|name| name.to_lowercase()))
+            .collect();
+        names.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        names.dedup();
+        let alternation: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+        RegexBuilder::new(&format!(r"\b({})\b", alternation.join("|")))
+            .size_limit(SERVICE_PATTERN_SIZE_LIMIT)
+            .build()
     }
 
     pub fn analyze(&self, query: &str) -> AnalyzedQuery {
@@ -59,6 +155,7 @@ impl QueryAnalyzer {
         let level = self.extract_level(&query_lower);
         let search_query = self.clean_query(&query_lower);
         let intent = self.detect_intent(&query_lower);
+        let trace_id = self.extract_trace_id(query);
 
         AnalyzedQuery {
             original: query.to_string(),
@@ -68,6 +165,7 @@ impl QueryAnalyzer {
             service,
             level,
             intent,
+            trace_id,
         }
     }
 
@@ -98,17 +196,22 @@ impl QueryAnalyzer {
     }
 
     fn extract_time_range(&self, query: &str, now: DateTime<Utc>) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        // calendar words are local-midnight boundaries, converted back to UTC for the filters
+        let today = now.with_timezone(&self.timezone).date_naive();
         if query.contains("yesterday") {
-            return (Some(now - Duration::hours(24)), None);
+            let start = today.pred_opt().unwrap_or(today);
+            return (Some(self.local_midnight(start)), Some(self.local_midnight(today)));
         }
         if query.contains("today") {
-            return (Some(now - Duration::hours(12)), None);
+            return (Some(self.local_midnight(today)), None);
         }
         if query.contains("this week") {
-            return (Some(now - Duration::days(7)), None);
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            return (Some(self.local_midnight(monday)), None);
         }
         if query.contains("this month") {
-            return (Some(now - Duration::days(30)), None);
+            let first = today.with_day(1).unwrap_or(today);
+            return (Some(self.local_midnight(first)), None);
         }
         if query.contains("last hour") || query.contains("past hour") || query.contains("the hour") {
             return (Some(now - Duration::hours(1)), None);
@@ -136,8 +239,35 @@ impl QueryAnalyzer {
         (None, None)
     }
 
+    // a few zones skip midnight on DST days, so take the first hour that exists
+    fn local_midnight(&self, date: NaiveDate) -> DateTime<Utc> {
+        (0..3)
+            .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+            .find_map(|local| self.timezone.from_local_datetime(&local).earliest())
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+    }
+
+    // the query is lowercased; a registered name comes back as logged, since it's used as a filter
     fn extract_service(&self, query: &str) -> Option<String> {
-        self.service_pattern.find(query).map(|m| m.as_str().to_string())
+        let found = self.service_pattern.find(query)?.as_str();
+        let name = self.services.iter().find(|name| name.eq_ignore_ascii_case(found));
+        Some(name.map_or_else(|| found.to_string(), |name| name.clone()))
+    }
+
+    // Keep the original casing, trace ids are stored as-is
+    fn extract_trace_id(&self, query: &str) -> Option<String> {
+        self.trace_id_pattern
+            .captures_iter(query)
+            .filter_map(|caps| caps.get(1))
+            .map(|m| m.as_str())
+            .find(|id| Self::looks_like_id(id))
+            .map(|id| id.to_string())
+    }
+
+    // at least 6 chars and a digit, otherwise words like "facade" or "re-run" would match
+    fn looks_like_id(token: &str) -> bool {
+        token.len() >= 6 && token.chars().any(|c| c.is_ascii_digit())
     }
 
     fn extract_level(&self, query: &str) -> Option<String> {
@@ -172,6 +302,19 @@ impl QueryAnalyzer {
             cleaned = re.replace_all(&cleaned, " ").to_string();
         }
 
+        // trace ids are opaque, they only add noise to the embedding
+        cleaned = self
+            .trace_id_pattern
+            .replace_all(&cleaned, |caps: &regex::Captures| {
+                let id = &caps[1];
+                if Self::looks_like_id(id) {
+                    caps[0].replace(id, " ")
+                } else {
+                    caps[0].to_string()
+                }
+            })
+            .to_string();
+
         let filler_patterns = [
             r"^show\s+me\s+", r"^give\s+me\s+", r"^what\s+are\s+(?:the\s+)?",
             r"^what\s+is\s+(?:the\s+)?", r"^can\s+you\s+show\s+", r"^please\s+show\s+",
@@ -206,6 +349,45 @@ mod tests {
         assert_eq!(result.service, Some("nginx".to_string()));
     }
 
+    #[test]
+    fn test_custom_services() {
+        let analyzer = QueryAnalyzer::with_services(vec![
+            "billing-worker".to_string(),
+            "billing".to_string(),
+            "Payment-Reconciler".to_string(),
+            " ".to_string(),
+        ]);
+
+        let result = analyzer.analyze("errors in billing-worker last hour");
+        assert_eq!(result.service, Some("billing-worker".to_string()));
+        assert_eq!(analyzer.analyze("is billing slow").service, Some("billing".to_string()));
+
+        // longest name wins over the built-in "payment", with the logged casing
+        let result = analyzer.analyze("why did payment-reconciler crash");
+        assert_eq!(result.service, Some("Payment-Reconciler".to_string()));
+        assert_eq!(analyzer.analyze("payment timeouts").service, Some("payment".to_string()));
+
+        // not part of a longer word
+        assert_eq!(analyzer.analyze("rebilling errors").service, None);
+        assert_eq!(QueryAnalyzer::new().analyze("errors in billing-worker").service, None);
+    }
+
+    #[test]
+    fn test_oversized_service_list_keeps_previous() {
+        let mut analyzer = QueryAnalyzer::with_services(vec!["billing".to_string()]);
+
+        // far past the size limit once compiled
+        let huge: Vec<String> = (0..50).map(|i| format!("{}{}", "x".repeat(50_000), i)).collect();
+        analyzer.set_services(huge);
+        assert_eq!(analyzer.services(), ["billing".to_string()]);
+        assert_eq!(analyzer.analyze("is billing slow").service, Some("billing".to_string()));
+
+        let many: Vec<String> = (0..MAX_EXTRA_SERVICES + 10).map(|i| format!("svc-{:05}", i)).collect();
+        analyzer.set_services(many);
+        assert_eq!(analyzer.services().len(), MAX_EXTRA_SERVICES);
+        assert_eq!(analyzer.analyze("errors in svc-00042").service, Some("svc-00042".to_string()));
+    }
+
     #[test]
     fn test_intent_detection() {
         let analyzer = QueryAnalyzer::new();
@@ -224,10 +406,95 @@ mod tests {
         assert_eq!(result.intent, QueryIntent::Summary);
     }
 
+    #[test]
+    fn test_trace_id_extraction() {
+        let analyzer = QueryAnalyzer::new();
+
+        let result = analyzer.analyze("trace request abc123");
+        assert_eq!(result.intent, QueryIntent::Trace);
+        assert_eq!(result.trace_id, Some("abc123".to_string()));
+
+        let result = analyzer.analyze("Trace 550E8400-e29b-41d4-a716-446655440000 please");
+        assert_eq!(result.trace_id, Some("550E8400-e29b-41d4-a716-446655440000".to_string()));
+
+        // "facade" is hex but not an id
+        let result = analyzer.analyze("trace the facade service");
+        assert_eq!(result.intent, QueryIntent::Trace);
+        assert_eq!(result.trace_id, None);
+
+        // ids are only pulled out after a trace keyword
+        let result = analyzer.analyze("show me errors from abc123");
+        assert_eq!(result.trace_id, None);
+    }
+
+    #[test]
+    fn test_trace_id_uuid() {
+        let analyzer = QueryAnalyzer::new();
+        let result = analyzer.analyze("show me trace-id 7f3a9c0e-1b2d-4e5f-8a9b-0c1d2e3f4a5b");
+        assert_eq!(result.trace_id, Some("7f3a9c0e-1b2d-4e5f-8a9b-0c1d2e3f4a5b".to_string()));
+        assert!(!result.search_query.contains("7f3a9c0e"));
+    }
+
+    #[test]
+    fn test_trace_id_short_hex() {
+        let analyzer = QueryAnalyzer::new();
+
+        let result = analyzer.analyze("request id: 7f3a9c failed");
+        assert_eq!(result.trace_id, Some("7f3a9c".to_string()));
+        assert_eq!(result.search_query, "request id: failed");
+
+        let result = analyzer.analyze("trace request abc-123-xyz");
+        assert_eq!(result.trace_id, Some("abc-123-xyz".to_string()));
+        assert_eq!(result.search_query, "trace request");
+    }
+
+    #[test]
+    fn test_no_trace_id() {
+        let analyzer = QueryAnalyzer::new();
+        let result = analyzer.analyze("trace slow checkout requests");
+        assert_eq!(result.intent, QueryIntent::Trace);
+        assert_eq!(result.trace_id, None);
+        assert_eq!(result.search_query, "trace slow checkout requests");
+    }
+
     #[test]
     fn test_clean_query() {
         let analyzer = QueryAnalyzer::new();
         let result = analyzer.analyze("show me errors last 1 hour");
         assert_eq!(result.search_query, "errors");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_today_in_new_york() {
+        // 03:30 UTC is still the previous evening in New York (UTC-5)
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 3, 30, 0).unwrap();
+        let utc = QueryAnalyzer::new();
+        let new_york = QueryAnalyzer::with_timezone(chrono_tz::America::New_York);
+
+        let (from, to) = utc.extract_time_range("errors today", now);
+        assert_eq!(from, Some(Utc.with_ymd_and_hms(2026, 2, 10, 0, 0, 0).unwrap()));
+        assert_eq!(to, None);
+
+        let (from, to) = new_york.extract_time_range("errors today", now);
+        assert_eq!(from, Some(Utc.with_ymd_and_hms(2026, 2, 9, 5, 0, 0).unwrap()));
+        assert_eq!(to, None);
+
+        let (from, to) = new_york.extract_time_range("errors yesterday", now);
+        assert_eq!(from, Some(Utc.with_ymd_and_hms(2026, 2, 8, 5, 0, 0).unwrap()));
+        assert_eq!(to, Some(Utc.with_ymd_and_hms(2026, 2, 9, 5, 0, 0).unwrap()));
+
+        // Feb 9 2026 is a Monday
+        let (from, _) = new_york.extract_time_range("errors this week", now);
+        assert_eq!(from, Some(Utc.with_ymd_and_hms(2026, 2, 9, 5, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_today_after_dst_switch() {
+        // New York is on EDT (UTC-4) from Mar 8 2026
+        let now = Utc.with_ymd_and_hms(2026, 3, 9, 12, 0, 0).unwrap();
+        let new_york = QueryAnalyzer::with_timezone(chrono_tz::America::New_York);
+
+        let (from, _) = new_york.extract_time_range("what broke today", now);
+        assert_eq!(from, Some(Utc.with_ymd_and_hms(2026, 3, 9, 4, 0, 0).unwrap()));
+    }
+}
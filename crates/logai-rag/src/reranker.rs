@@ -3,19 +3,175 @@
 // combines semantic score with keyword overlap foor better ranking
 // Reranks loogs based on query relevance
 
-pub struct Reranker;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Reranker weights configuration
+#[derive(Debug, Clone)]
+pub struct RerankConfig {
+    pub semantic_weight: f32,
+    pub keyword_weight: f32,
+    /// Extra weight given to newer logs when the query asks for "recent"/"latest" (0 = off)
+    pub recency_boost: f32,
+    /// Collapse logs that only differ in numbers, UUIDs or IPs into one representative
+    pub dedup_similar: bool,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            semantic_weight: 0.7,
+            keyword_weight: 0.3,
+            recency_boost: 0.0,
+            dedup_similar: false,
+        }
+    }
+}
+
+impl RerankConfig {
+    /// Create config from environment variables
+    ///
+    /// Environment variables:
+    /// - LOGAI_RERANK_SEMANTIC_WEIGHT: Weight of the vector similarity score (default: 0.7)
+    /// - LOGAI_RERANK_KEYWORD_WEIGHT: Weight of the keyword overlap score (default: 0.3)
+    /// - LOGAI_RERANK_RECENCY_BOOST: Weight of the recency score for "recent"/"latest" queries (default: 0.0)
+    /// - LOGAI_RERANK_DEDUP_SIMILAR: Collapse near-identical logs into one with a count (default: false)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: f32| {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            semantic_weight: read("LOGAI_RERANK_SEMANTIC_WEIGHT", defaults.semantic_weight),
+            keyword_weight: read("LOGAI_RERANK_KEYWORD_WEIGHT", defaults.keyword_weight),
+            recency_boost: read("LOGAI_RERANK_RECENCY_BOOST", defaults.recency_boost),
+            dedup_similar: std::env::var("LOGAI_RERANK_DEDUP_SIMILAR")
+                .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+                .unwrap_or(defaults.dedup_similar),
+        }
+    }
+}
+
+pub struct Reranker {
+    config: RerankConfig,
+    normalizer: MessageNormalizer,
+}
 
 #[derive(Debug, Clone)]
 pub struct RankedLog{
     pub message: String,
     pub semantic_score: f32,
     pub keyword_score: f32,
+    pub recency_score: f32,
     pub final_score: f32,
+    /// How many logs this one stands for (> 1 when `dedup_similar` collapsed look-alikes into it)
+    pub occurrences: usize,
+}
+
+impl RankedLog {
+    /// The log as it should go into the LLM context: JSON lines get an `occurrences` field
+    /// when similar logs were collapsed into this one, other lines a "[repeated N times]" suffix
+    pub fn context_line(&self) -> String {
+        if self.occurrences <= 1 {
+            return self.message.clone();
+        }
+        match serde_json::from_str::<serde_json::Value>(&self.message) {
+            Ok(serde_json::Value::Object(mut log)) => {
+                log.insert("occurrences".to_string(), self.occurrences.into());
+                serde_json::Value::Object(log).to_string()
+            }
+            _ => format!("{} [repeated {} times]", self.message, self.occurrences),
+        }
+    }
+}
+
+/// Reduces a log to its template by masking the parts that vary between occurrences:
+/// "user_1234 login failed from 10.0.0.7" -> "user_<NUM> login failed from <IP>"
+pub struct MessageNormalizer {
+    path: Regex,
+    uuid: Regex,
+    ip: Regex,
+    number: Regex,
+}
+
+impl MessageNormalizer {
+    // The patterns are public so SQL can mask the same way (ClickHouse's RE2 accepts them
+    // as they are); `template` applies them in the order UUID, IP, NUM, PATH.
+
+    /// absolute or relative unix paths, after whitespace, a quote, '=' or ':' ("file=/tmp/x");
+    /// group 1 is the character before the path, which stays
+    pub const PATH_PATTERN: &'static str = r#"(^|[\s"'=:(\[])(?:~|\.{1,2})?/[^\s"'()\[\],;]+"#;
+    pub const UUID_PATTERN: &'static str = r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b";
+    pub const IP_PATTERN: &'static str = r"\b\d{1,3}(?:\.\d{1,3}){3}(?::\d{1,5})?\b";
+    pub const NUMBER_PATTERN: &'static str = r"\d+(?:\.\d+)?";
+
+    pub fn new() -> Self {
+        Self {
+            path: Regex::new(Self::PATH_PATTERN).unwrap(),
+            uuid: Regex::new(Self::UUID_PATTERN).unwrap(),
+            ip: Regex::new(Self::IP_PATTERN).unwrap(),
+            number: Regex::new(Self::NUMBER_PATTERN).unwrap(),
+        }
+    }
+
+    /// For the API's JSON log lines only level, service and message count (the timestamp
+    /// always differs); anything else is normalized as a whole
+    pub fn normalize(&self, log: &str) -> String {
+        let text = match serde_json::from_str::<serde_json::Value>(log) {
+            Ok(parsed) if parsed.get("message").is_some() => {
+                let field = |key: &str| parsed.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+                format!("{} {} {}", field("level"), field("service"), field("message"))
+            }
+            _ => log.to_string(),
+        };
+
+        let text = self.uuid.replace_all(&text, "<UUID>");
+        let text = self.ip.replace_all(&text, "<IP>");
+        self.number.replace_all(&text, "<NUM>").into_owned()
+    }
+
+    /// `normalize` with file paths masked too, for grouping messages into patterns:
+    /// "open /var/lib/app/run-42.pid failed" -> "open <PATH> failed". Kept apart from
+    /// `normalize`, where the path is often what tells two errors apart.
+    pub fn template(&self, log: &str) -> String {
+        self.path.replace_all(&self.normalize(log), "${1}<PATH>").into_owned()
+    }
+}
+
+impl Default for MessageNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Reranker {
     pub fn new() -> Self {
-        Self
+        Self::with_config(RerankConfig::default())
+    }
+
+    /// Create a reranker with custom semantic/keyword weights
+    pub fn with_weights(semantic: f32, keyword: f32) -> Self {
+        Self::with_config(RerankConfig {
+            semantic_weight: semantic,
+            keyword_weight: keyword,
+            ..RerankConfig::default()
+        })
+    }
+
+    pub fn with_config(config: RerankConfig) -> Self {
+        Self {
+            config,
+            normalizer: MessageNormalizer::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RerankConfig {
+        &self.config
     }
 
     // Rerank logs by combining semantic score with keyword overlap most imp
@@ -29,29 +185,67 @@ impl Reranker {
         let query_words: Vec<&str> = query_lower
             .split_whitespace()
             .collect();
+
+        // recency only kicks in when the user actually asks for new stuff
+        let wants_recent = self.config.recency_boost > 0.0
+            && (query_lower.contains("recent") || query_lower.contains("latest"));
+        let recency_scores = if wants_recent {
+            Self::compute_recency_scores(&logs)
+        } else {
+            vec![0.0; logs.len()]
+        };
+
         let mut ranked: Vec<RankedLog> = logs
         .into_iter()
-        .map(|(message, semantic_score)| {
+        .zip(recency_scores)
+        .map(|((message, semantic_score), recency_score)| {
             let keyword_score = self.compute_keyword_score(&query_words, &message);
 
-            // weightd combination 70% semantic + 30% keyword
-            let final_score = (semantic_score * 0.7) + (keyword_score * 0.3);
+            // weighted combination (default 70% semantic + 30% keyword)
+            let final_score = (semantic_score * self.config.semantic_weight)
+                + (keyword_score * self.config.keyword_weight)
+                + (recency_score * self.config.recency_boost);
 
             RankedLog{
                 message,
                 semantic_score,
                 keyword_score,
+                recency_score,
                 final_score,
+                occurrences: 1,
             }
         })
         .collect();
     // sort by final score descending
     ranked.sort_by(|a, b| b.final_score.partial_cmp(&a.final_score).unwrap());
 
+    if self.config.dedup_similar {
+        ranked = self.collapse_similar(ranked);
+    }
+
     // return top_k
     ranked.into_iter().take(top_k).collect()
     }
 
+    /// Keep the best-scored log of every template and count the rest into it; expects `ranked`
+    /// sorted best first and keeps that order
+    fn collapse_similar(&self, ranked: Vec<RankedLog>) -> Vec<RankedLog> {
+        let mut kept: Vec<RankedLog> = Vec::new();
+        let mut by_template: HashMap<String, usize> = HashMap::new();
+
+        for log in ranked {
+            let template = self.normalizer.normalize(&log.message);
+            match by_template.get(&template) {
+                Some(&i) => kept[i].occurrences += log.occurrences,
+                None => {
+                    by_template.insert(template, kept.len());
+                    kept.push(log);
+                }
+            }
+        }
+        kept
+    }
+
     fn compute_keyword_score(&self, query_words: &[&str], log: &str) -> f32 {
         let log_lower = log.to_lowercase();
 
@@ -76,6 +270,75 @@ impl Reranker {
             (weighted_matches / (query_words.len() as f32 * 2.5)).min(1.0)
         }
     }
+
+    // Normalize timestamps to 0-1 (oldest = 0, newest = 1), logs without a timestamp get 0
+    fn compute_recency_scores(logs: &[(String, f32)]) -> Vec<f32> {
+        let timestamps: Vec<Option<i64>> = logs
+            .iter()
+            .map(|(message, _)| extract_timestamp(message).map(|t| t.timestamp_millis()))
+            .collect();
+
+        let min = timestamps.iter().flatten().min().copied();
+        let max = timestamps.iter().flatten().max().copied();
+
+        match (min, max) {
+            (Some(min), Some(max)) if max > min => timestamps
+                .iter()
+                .map(|t| t.map_or(0.0, |t| (t - min) as f32 / (max - min) as f32))
+                .collect(),
+            // all logs share the same timestamp, nothing to boost
+            _ => vec![0.0; logs.len()],
+        }
+    }
+}
+
+/// Byte ranges of `text` holding the query's words, the same words the keyword score counts,
+/// sorted and merged. A word's whole-word occurrences are preferred; only a word that never
+/// stands alone is marked inside longer ones. ASCII case is ignored (so offsets stay valid).
+pub fn highlight_ranges(query: &str, text: &str) -> Vec<(usize, usize)> {
+    let text_lower = text.to_ascii_lowercase();
+    let query_lower = query.to_ascii_lowercase();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for word in query_lower.split_whitespace() {
+        // "timeout?" in a question still marks "timeout"
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if word.is_empty() {
+            continue;
+        }
+        let found: Vec<(usize, usize)> = text_lower
+            .match_indices(word)
+            .map(|(start, _)| (start, start + word.len()))
+            .collect();
+        let is_boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+        let whole: Vec<(usize, usize)> = found
+            .iter()
+            .copied()
+            .filter(|&(start, end)| {
+                is_boundary(text_lower[..start].chars().next_back()) && is_boundary(text_lower[end..].chars().next())
+            })
+            .collect();
+        ranges.extend(if whole.is_empty() { found } else { whole });
+    }
+
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+// Logs passed to the reranker are JSON strings built by the API
+fn extract_timestamp(log: &str) -> Option<DateTime<Utc>> {
+    let parsed: serde_json::Value = serde_json::from_str(log).ok()?;
+    let ts = parsed.get("timestamp")?.as_str()?;
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
 }
 
 impl Default for Reranker {
@@ -89,10 +352,34 @@ impl Default for Reranker {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_highlight_ranges() {
+        let message = "Payment timeout: upstream TIMEOUT after 30s (timeouts=3)";
+        let ranges = highlight_ranges("why timeout? payment", message);
+        let marked: Vec<&str> = ranges.iter().map(|&(start, end)| &message[start..end]).collect();
+        // whole words only while there are any; "timeouts" isn't marked
+        assert_eq!(marked, ["Payment", "timeout", "TIMEOUT"]);
+        assert_eq!(ranges[0], (0, 7));
+
+        // only inside longer words: marked there
+        let ranges = highlight_ranges("conn", "ConnectionRefused while connecting");
+        assert_eq!(ranges, vec![(0, 4), (24, 28)]);
+
+        // overlapping words merge into one range; nothing matched, nothing marked
+        assert_eq!(highlight_ranges("db-01 01", "host db-01 down"), vec![(5, 10)]);
+        assert!(highlight_ranges("redis", "disk full").is_empty());
+        assert!(highlight_ranges("  ?! ", "disk full").is_empty());
+
+        // offsets stay on char boundaries around multi-byte text
+        let message = "Zürich → timeout";
+        let ranges = highlight_ranges("timeout", message);
+        assert_eq!(&message[ranges[0].0..ranges[0].1], "timeout");
+    }
+
     #[test]
     fn test_reranking() {
         let reranker = Reranker::new();
-        
+
         let logs = vec![
             ("GET /health 200 OK".to_string(), 0.8),
             ("ERROR: Payment failed timeout".to_string(), 0.6),
@@ -100,9 +387,115 @@ mod tests {
         ];
 
         let result = reranker.rerank("payment error", logs, 2);
-        
+
         // "Payment failed" should be first despite lower semantic score
         assert!(result[0].message.contains("Payment"));
         assert_eq!(result.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_pure_keyword_weight() {
+        let reranker = Reranker::with_weights(0.0, 1.0);
+
+        let logs = vec![
+            ("Disk usage at 80%".to_string(), 0.95),
+            ("Redis connection refused".to_string(), 0.1),
+            ("Cache warmed up".to_string(), 0.9),
+        ];
+
+        let result = reranker.rerank("redis refused", logs, 3);
+
+        // semantic score is ignored, exact term match wins
+        assert_eq!(result[0].message, "Redis connection refused");
+        assert_eq!(result[0].final_score, result[0].keyword_score);
+    }
+
+    #[test]
+    fn test_recency_boost() {
+        let reranker = Reranker::with_config(RerankConfig {
+            recency_boost: 1.0,
+            ..RerankConfig::default()
+        });
+
+        let old = r#"{"timestamp":"2026-02-10T01:00:00Z","message":"db timeout"}"#.to_string();
+        let new = r#"{"timestamp":"2026-02-10T03:00:00Z","message":"db timeout"}"#.to_string();
+        let logs = vec![(old.clone(), 0.8), (new.clone(), 0.8)];
+
+        let result = reranker.rerank("latest db timeout", logs.clone(), 2);
+        assert_eq!(result[0].message, new);
+        assert_eq!(result[0].recency_score, 1.0);
+
+        // no boost unless the query asks for recent logs
+        let result = reranker.rerank("db timeout", logs, 2);
+        assert_eq!(result[0].recency_score, 0.0);
+        assert_eq!(result[1].recency_score, 0.0);
+    }
+
+    #[test]
+    fn test_normalize_masks_variable_parts() {
+        let normalizer = MessageNormalizer::new();
+
+        assert_eq!(
+            normalizer.normalize("user_1234 login failed from 10.0.0.7:5432 after 1.5s"),
+            "user_<NUM> login failed from <IP> after <NUM>s"
+        );
+        assert_eq!(
+            normalizer.normalize("order 6f1c2b9e-0d4a-4e51-9a0e-2c8d1e5b7a90 not found"),
+            "order <UUID> not found"
+        );
+        assert_eq!(
+            normalizer.template("open /var/lib/app/run-42.pid failed (file=./cache/7.tmp)"),
+            "open <PATH> failed (file=<PATH>)"
+        );
+
+        // JSON lines: the timestamp doesn't matter, level and service do
+        let line = |ts: &str, level: &str, user: u32| {
+            format!(r#"{{"timestamp":"{}","level":"{}","service":"auth","message":"user_{} login failed"}}"#, ts, level, user)
+        };
+        assert_eq!(
+            normalizer.normalize(&line("2026-02-10T03:00:00Z", "Error", 1234)),
+            normalizer.normalize(&line("2026-02-10T03:05:00Z", "Error", 5678))
+        );
+        assert_ne!(
+            normalizer.normalize(&line("2026-02-10T03:00:00Z", "Error", 1234)),
+            normalizer.normalize(&line("2026-02-10T03:00:00Z", "Warn", 1234))
+        );
+    }
+
+    #[test]
+    fn test_dedup_similar_collapses_with_count() {
+        let reranker = Reranker::with_config(RerankConfig {
+            dedup_similar: true,
+            ..RerankConfig::default()
+        });
+
+        let mut logs: Vec<(String, f32)> = (0..47)
+            .map(|i| (format!("user_{} login failed", 1000 + i), 0.5 + i as f32 / 1000.0))
+            .collect();
+        logs.push(("redis connection refused".to_string(), 0.4));
+        logs.push(("disk usage at 91%".to_string(), 0.3));
+
+        let result = reranker.rerank("login failed", logs, 3);
+        let messages: Vec<&str> = result.iter().map(|r| r.message.as_str()).collect();
+        // the best-scored look-alike stands in for all 47, making room for the other logs
+        assert_eq!(messages, ["user_1046 login failed", "redis connection refused", "disk usage at 91%"]);
+        assert_eq!(result[0].occurrences, 47);
+        assert_eq!(result[1].occurrences, 1);
+
+        assert_eq!(result[0].context_line(), "user_1046 login failed [repeated 47 times]");
+        assert_eq!(result[1].context_line(), "redis connection refused");
+        let json = RankedLog {
+            message: r#"{"level":"Error","message":"user_1046 login failed"}"#.to_string(),
+            occurrences: 3,
+            ..result[0].clone()
+        };
+        assert_eq!(json.context_line(), r#"{"level":"Error","message":"user_1046 login failed","occurrences":3}"#);
+
+        // off by default
+        let look_alikes = vec![
+            ("user_1 login failed".to_string(), 0.5),
+            ("user_2 login failed".to_string(), 0.5),
+        ];
+        assert_eq!(Reranker::new().rerank("login failed", look_alikes, 3).len(), 2);
+    }
+}
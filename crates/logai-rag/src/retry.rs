@@ -0,0 +1,134 @@
+// Retry policy shared by the HTTP LLM clients: a 429 or 503 from the provider
+// shouldn't fail a whole ask/chat request
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::llm_client::LlmError;
+
+/// Environment variables:
+/// - LOGAI_LLM_TIMEOUT_SECS: Budget for one generate call, retries and backoff included (default: 60)
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            timeout: std::env::var("LOGAI_LLM_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            ..defaults
+        }
+    }
+
+    /// Wait before retry number `retry` (1-based): the server's Retry-After if it sent one,
+    /// otherwise base * 2^(retry-1); never more than max_delay
+    pub fn delay(&self, retry: u32, error: &LlmError) -> Duration {
+        let backoff = match error {
+            LlmError::Status { retry_after: Some(wait), .. } => *wait,
+            _ => self.base_delay.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1))),
+        };
+        backoff.min(self.max_delay)
+    }
+
+    /// Run `attempt` until it succeeds, fails with a non-retryable error or runs out of attempts,
+    /// all within `timeout`
+    pub async fn run<F, Fut>(&self, mut attempt: F) -> Result<String, LlmError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<String, LlmError>>,
+    {
+        let attempts = async {
+            let mut tries = 1;
+            loop {
+                match attempt().await {
+                    Ok(text) => return Ok(text),
+                    Err(e) if e.is_retryable() && tries < self.max_attempts => {
+                        let delay = self.delay(tries, &e);
+                        tracing::warn!(
+                            attempt = tries,
+                            max_attempts = self.max_attempts,
+                            delay_ms = delay.as_millis() as u64,
+                            "LLM request failed, retrying: {}",
+                            e
+                        );
+                        tokio::time::sleep(delay).await;
+                        tries += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        tokio::time::timeout(self.timeout, attempts)
+            .await
+            .unwrap_or(Err(LlmError::Timeout(self.timeout)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(code: u16, retry_after: Option<u64>) -> LlmError {
+        LlmError::Status {
+            status: code,
+            body: String::new(),
+            retry_after: retry_after.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(status(429, None).is_retryable());
+        assert!(status(503, None).is_retryable());
+        assert!(LlmError::Timeout(Duration::from_secs(1)).is_retryable());
+        assert!(!status(401, None).is_retryable());
+        assert!(!status(400, None).is_retryable());
+    }
+
+    #[test]
+    fn test_delay_honors_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, &status(503, None)), Duration::from_millis(500));
+        assert_eq!(policy.delay(2, &status(503, None)), Duration::from_secs(1));
+        assert_eq!(policy.delay(1, &status(429, Some(3))), Duration::from_secs(3));
+        // a huge Retry-After is capped rather than stalling the request
+        assert_eq!(policy.delay(1, &status(429, Some(3600))), policy.max_delay);
+    }
+
+    #[tokio::test]
+    async fn test_auth_errors_are_not_retried() {
+        let policy = RetryPolicy::default();
+        let mut calls = 0;
+        let result = policy
+            .run(|| {
+                calls += 1;
+                async { Err(status(401, None)) }
+            })
+            .await;
+
+        assert_eq!(calls, 1);
+        assert!(matches!(result, Err(LlmError::Status { status: 401, .. })));
+    }
+}
@@ -0,0 +1,94 @@
+// Trace View - reconstructs a distributed request from all logs sharing a trace_id
+
+use serde::{Deserialize, Serialize};
+
+use crate::causal::LogEvent;
+
+/// All logs for one trace, in the order they happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceResponse {
+    pub trace_id: String,
+    pub spans: Vec<LogEvent>,
+    pub services_involved: Vec<String>, // in order of first appearance
+    pub duration_ms: i64,               // first to last log
+}
+
+impl TraceResponse {
+    pub fn from_events(trace_id: impl Into<String>, mut spans: Vec<LogEvent>) -> Self {
+        spans.sort_by_key(|e| e.timestamp);
+
+        let mut services_involved: Vec<String> = Vec::new();
+        for span in &spans {
+            if !services_involved.contains(&span.service) {
+                services_involved.push(span.service.clone());
+            }
+        }
+
+        let duration_ms = match (spans.first(), spans.last()) {
+            (Some(first), Some(last)) => (last.timestamp - first.timestamp).num_milliseconds(),
+            _ => 0,
+        };
+
+        Self {
+            trace_id: trace_id.into(),
+            spans,
+            services_involved,
+            duration_ms,
+        }
+    }
+
+    /// One-paragraph description used as the answer for trace questions
+    pub fn summary(&self) -> String {
+        if self.spans.is_empty() {
+            return format!("No logs found for trace {}.", self.trace_id);
+        }
+        let errors = self
+            .spans
+            .iter()
+            .filter(|s| matches!(s.level.to_uppercase().as_str(), "ERROR" | "FATAL"))
+            .count();
+        format!(
+            "Trace {} spans {} logs across {} service(s) ({}) over {} ms, with {} error(s).",
+            self.trace_id,
+            self.spans.len(),
+            self.services_involved.len(),
+            self.services_involved.join(" → "),
+            self.duration_ms,
+            errors
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn event(secs: u32, service: &str, level: &str) -> LogEvent {
+        LogEvent {
+            timestamp: Utc.with_ymd_and_hms(2026, 2, 10, 3, 0, secs).unwrap(),
+            level: level.to_string(),
+            service: service.to_string(),
+            message: format!("{} did something", service),
+        }
+    }
+
+    #[test]
+    fn test_trace_orders_spans() {
+        let trace = TraceResponse::from_events(
+            "abc123",
+            vec![
+                event(5, "payment", "ERROR"),
+                event(0, "gateway", "INFO"),
+                event(2, "order", "INFO"),
+                event(3, "gateway", "INFO"),
+            ],
+        );
+
+        assert_eq!(trace.spans[0].service, "gateway");
+        assert_eq!(trace.spans[3].service, "payment");
+        assert_eq!(trace.services_involved, vec!["gateway", "order", "payment"]);
+        assert_eq!(trace.duration_ms, 5000);
+        assert!(trace.summary().contains("1 error"));
+    }
+}
@@ -0,0 +1,75 @@
+// Optional chunk mode: embed a summary per burst of logs (see logai_core::chunking)
+// instead of one point per log. ClickHouse still gets every log individually.
+
+use logai_core::chunking::ChunkConfig;
+use logai_core::LogChunk;
+use qdrant_client::qdrant::{PointStruct, UpsertPointsBuilder};
+use qdrant_client::{Payload, Qdrant};
+use serde_json::json;
+use std::time::Duration;
+use tracing::info;
+
+
+// how often quiet services get their open chunk flushed
+pub const FLUSH_EVERY: Duration = Duration::from_secs(5);
+
+/// Environment variables:
+/// - WORKER_CHUNK_LOGS: embed chunks instead of single logs (default false)
+/// - WORKER_CHUNK_WINDOW_SECS: max time span of one chunk (default 60)
+/// - WORKER_CHUNK_MAX_LOGS: max logs per chunk (default 50)
+///
+/// None when chunking is off
+pub fn config_from_env() -> Option<ChunkConfig> {
+    let enabled = std::env::var("WORKER_CHUNK_LOGS")
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+
+    let defaults = ChunkConfig::default();
+    let env_positive = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|n| *n > 0)
+    };
+    Some(ChunkConfig {
+        window: env_positive("WORKER_CHUNK_WINDOW_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.window),
+        max_logs: env_positive("WORKER_CHUNK_MAX_LOGS")
+            .map(|n| n as usize)
+            .unwrap_or(defaults.max_logs),
+    })
+}
+
+/// Store a chunk's embedding; the payload mirrors a single log's so search needs no changes
+/// (the summary is the message, the most severe level is the level)
+pub async fn store_chunk(
+    qdrant: &Qdrant,
+    collection: &str,
+    chunk: &LogChunk,
+    vector: Vec<f32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let payload: Payload = json!({
+        "log_id": chunk.log_ids.first().map(|id| id.to_string()).unwrap_or_default(),
+        "chunk_id": chunk.id.to_string(),
+        "log_ids": chunk.log_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>(),
+        "log_count": chunk.log_count,
+        "service": chunk.service,
+        "level": format!("{:?}", chunk.max_level),
+        "message": chunk.summary,
+        "timestamp": chunk.start_time.to_rfc3339(),
+        "timestamp_unix": chunk.start_time.timestamp(),
+        "end_timestamp": chunk.end_time.to_rfc3339(),
+    })
+    .try_into()
+    .unwrap();
+
+    let point = PointStruct::new(chunk.id.to_string(), vector, payload);
+    qdrant.upsert_points(UpsertPointsBuilder::new(collection, vec![point]).wait(true)).await?;
+
+    info!(chunk = %chunk.id, logs = chunk.log_count, service = %chunk.service, "Chunk embedded & stored in Qdrant");
+    Ok(())
+}
@@ -0,0 +1,130 @@
+// How the log_embeddings collection(s) are created. Both settings only apply at creation
+// time: Qdrant can't change the metric of an existing collection, so a mismatch is an error.
+// Payload indexes are checked on every start, missing ones get added.
+
+use qdrant_client::qdrant::{Distance, FieldType};
+use std::collections::HashSet;
+
+/// Environment variables:
+/// - QDRANT_DISTANCE: cosine, dot or euclid (default cosine)
+/// - QDRANT_ON_DISK_PAYLOAD: keep payloads on disk instead of in RAM (default false)
+#[derive(Debug, Clone)]
+pub struct CollectionConfig {
+    pub distance: Distance,
+    pub on_disk_payload: bool,
+}
+
+impl CollectionConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let distance = match std::env::var("QDRANT_DISTANCE") {
+            Ok(name) if !name.trim().is_empty() => parse_distance(&name).ok_or_else(|| {
+                format!("Unknown QDRANT_DISTANCE '{}' (supported: cosine, dot, euclid)", name)
+            })?,
+            _ => Distance::Cosine,
+        };
+        let on_disk_payload = std::env::var("QDRANT_ON_DISK_PAYLOAD")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or(false);
+
+        Ok(Self {
+            distance,
+            on_disk_payload,
+        })
+    }
+}
+
+impl Default for CollectionConfig {
+    fn default() -> Self {
+        Self {
+            distance: Distance::Cosine,
+            on_disk_payload: false,
+        }
+    }
+}
+
+/// Case-insensitive
+pub fn parse_distance(name: &str) -> Option<Distance> {
+    match name.trim().to_lowercase().as_str() {
+        "cosine" => Some(Distance::Cosine),
+        "dot" => Some(Distance::Dot),
+        "euclid" => Some(Distance::Euclid),
+        _ => None,
+    }
+}
+
+/// Error when an existing collection was created with another metric; `None` if they match
+/// or the stored value isn't one we know (then we can't tell, and keep going)
+pub fn distance_mismatch(collection: &str, existing: i32, requested: Distance) -> Option<String> {
+    let existing = Distance::try_from(existing).ok().filter(|d| *d != Distance::UnknownDistance)?;
+    (existing != requested).then(|| {
+        format!(
+            "Qdrant collection '{}' uses {} distance, but QDRANT_DISTANCE asks for {}. \
+             Set QDRANT_DISTANCE back or delete the collection to re-index.",
+            collection,
+            existing.as_str_name().to_lowercase(),
+            requested.as_str_name().to_lowercase()
+        )
+    })
+}
+
+/// Payload indexes for `payload_fields` plus the always-filtered fields, minus those
+/// `existing` (the collection's payload schema) already has
+pub fn missing_payload_indexes(payload_fields: &[String], existing: &HashSet<String>) -> Vec<(String, FieldType)> {
+    let mut indexes = vec![
+        ("service".to_string(), FieldType::Keyword),
+        ("level".to_string(), FieldType::Keyword),
+        ("timestamp_unix".to_string(), FieldType::Integer),
+    ];
+    indexes.extend(payload_fields.iter().map(|f| (format!("fields.{}", f), FieldType::Keyword)));
+    indexes.retain(|(field, _)| !existing.contains(field));
+    indexes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_names() {
+        assert_eq!(parse_distance("cosine"), Some(Distance::Cosine));
+        assert_eq!(parse_distance(" Dot "), Some(Distance::Dot));
+        assert_eq!(parse_distance("EUCLID"), Some(Distance::Euclid));
+        assert_eq!(parse_distance("manhattan"), None);
+        assert_eq!(parse_distance(""), None);
+    }
+
+    #[test]
+    fn test_distance_mismatch() {
+        assert!(distance_mismatch("log_embeddings", Distance::Cosine as i32, Distance::Cosine).is_none());
+        assert!(distance_mismatch("log_embeddings", Distance::UnknownDistance as i32, Distance::Dot).is_none());
+
+        let err = distance_mismatch("log_embeddings_checkout", Distance::Cosine as i32, Distance::Dot).unwrap();
+        assert!(err.starts_with("Qdrant collection 'log_embeddings_checkout' uses"));
+        assert!(err.contains("uses cosine distance"));
+        assert!(err.contains("asks for dot"));
+    }
+
+    #[test]
+    fn test_payload_indexes_for_fresh_collection() {
+        let fields = vec!["user_id".to_string()];
+        let fresh = missing_payload_indexes(&fields, &HashSet::new());
+        assert_eq!(
+            fresh,
+            vec![
+                ("service".to_string(), FieldType::Keyword),
+                ("level".to_string(), FieldType::Keyword),
+                ("timestamp_unix".to_string(), FieldType::Integer),
+                ("fields.user_id".to_string(), FieldType::Keyword),
+            ]
+        );
+
+        // restart: nothing left to create; a newly allowlisted field is the only addition
+        let existing: HashSet<String> = fresh.into_iter().map(|(field, _)| field).collect();
+        assert!(missing_payload_indexes(&fields, &existing).is_empty());
+        let more = vec!["user_id".to_string(), "endpoint".to_string()];
+        assert_eq!(
+            missing_payload_indexes(&more, &existing),
+            vec![("fields.endpoint".to_string(), FieldType::Keyword)]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+// Dead-letter queue - logs that still can't be stored after retries are parked on `logs.dlq`.
+// A JetStream work-queue stream captures the subject, so they survive until
+// `logai-worker --replay-dlq` stores them and acks them away.
+
+use async_nats::jetstream::{self, consumer, stream};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DLQ_SUBJECT: &str = "logs.dlq";
+const DLQ_STREAM: &str = "LOGS_DLQ";
+const REPLAY_CONSUMER: &str = "dlq-replay";
+const RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Which write failed; replay only redoes that one so ClickHouse doesn't get duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Clickhouse,
+    Qdrant,
+}
+
+/// Message published on `logs.dlq`: the original payload plus why it ended up here
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub stage: Stage,
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: u64, // unix seconds
+    pub payload: serde_json::Value, // the logs.ingest message as received
+}
+
+impl DeadLetter {
+    pub fn new(stage: Stage, error: String, attempts: u32, payload: &[u8]) -> Self {
+        let failed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            stage,
+            error,
+            attempts,
+            failed_at,
+            payload: serde_json::from_slice(payload)
+                .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(payload).into_owned())),
+        }
+    }
+}
+
+/// Make sure `logs.dlq` is persisted (plain NATS would drop it with nobody subscribed)
+pub async fn ensure_stream(nats: &async_nats::Client) -> Result<stream::Stream, Box<dyn std::error::Error>> {
+    let js = jetstream::new(nats.clone());
+    let stream = js
+        .get_or_create_stream(stream::Config {
+            name: DLQ_STREAM.to_string(),
+            subjects: vec![DLQ_SUBJECT.to_string()],
+            retention: stream::RetentionPolicy::WorkQueue,
+            max_age: RETENTION,
+            ..Default::default()
+        })
+        .await?;
+    Ok(stream)
+}
+
+pub async fn publish(nats: &async_nats::Client, letter: &DeadLetter) -> Result<(), Box<dyn std::error::Error>> {
+    let body = serde_json::to_vec(letter)?;
+    nats.publish(DLQ_SUBJECT, body.into()).await?;
+    Ok(())
+}
+
+/// Durable pull consumer used by `--replay-dlq`
+pub async fn replay_consumer(
+    stream: &stream::Stream,
+) -> Result<consumer::Consumer<consumer::pull::Config>, Box<dyn std::error::Error>> {
+    let consumer = stream
+        .get_or_create_consumer(
+            REPLAY_CONSUMER,
+            consumer::pull::Config {
+                durable_name: Some(REPLAY_CONSUMER.to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    Ok(consumer)
+}
@@ -0,0 +1,98 @@
+// Numeric log fields, copied into the `numeric_fields Map(String, Float64)` column at insert
+// time so latencies, sizes and counts aggregate without parsing the `fields` JSON per row.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The worker's log INSERT; `numeric_fields` is bound as the (keys, values) pair below
+pub const INSERT_LOG_SQL: &str = "INSERT INTO logs (id, timestamp, level, service, message, raw, trace_id, span_id, \
+                                  error_category, fields, numeric_fields, ingested_at)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, mapFromArrays(?, ?), ?)";
+
+/// Top-level numbers of `fields` as (keys, values), sorted by key, ready for ClickHouse's
+/// `mapFromArrays`. Numeric strings count too: text parsers capture `"502"` or `"0.042"` as
+/// strings. Booleans, nested objects and arrays are left out.
+pub fn numeric_fields(fields: &HashMap<String, Value>) -> (Vec<String>, Vec<f64>) {
+    let mut numeric: Vec<(&String, f64)> = fields
+        .iter()
+        .filter_map(|(key, value)| {
+            let number = match value {
+                Value::Number(n) => n.as_f64(),
+                Value::String(s) => s.trim().parse::<f64>().ok(),
+                _ => None,
+            }?;
+            number.is_finite().then_some((key, number))
+        })
+        .collect();
+    numeric.sort_by(|a, b| a.0.cmp(b.0));
+    numeric.into_iter().map(|(key, number)| (key.clone(), number)).unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_numeric_fields_of_json() {
+        let fields: HashMap<String, Value> = serde_json::from_value(json!({
+            "latency_ms": 234,
+            "request_time": "0.042",
+            "status": "502",
+            "endpoint": "/api/checkout",
+            "cached": true,
+            "upstream": {"latency_ms": 12},
+            "ratio": "inf",
+        }))
+        .unwrap();
+
+        // `fields` keeps the number a number
+        let stored = serde_json::to_string(&fields).unwrap();
+        assert!(stored.contains(r#""latency_ms":234"#), "{}", stored);
+
+        let (keys, values) = numeric_fields(&fields);
+        assert_eq!(keys, vec!["latency_ms", "request_time", "status"]);
+        assert_eq!(values, vec![234.0, 0.042, 502.0]);
+
+        assert_eq!(numeric_fields(&HashMap::new()), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_insert_binds_numeric_fields() {
+        let inside = |open: &str| {
+            let start = INSERT_LOG_SQL.find(open).unwrap() + open.len();
+            let end = start + INSERT_LOG_SQL[start..].rfind(')').unwrap();
+            INSERT_LOG_SQL[start..end].to_string()
+        };
+        let columns: Vec<String> = inside("logs (")
+            .split(')')
+            .next()
+            .unwrap()
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .collect();
+        // split VALUES at the commas outside mapFromArrays(...)
+        let mut values = vec![String::new()];
+        let mut depth = 0;
+        for c in inside("VALUES (").chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    values.push(String::new());
+                    continue;
+                }
+                _ => {}
+            }
+            values.last_mut().unwrap().push(c);
+        }
+        let values: Vec<&str> = values.iter().map(|v| v.trim()).collect();
+
+        assert_eq!(columns.len(), values.len());
+        let at = columns.iter().position(|c| c == "numeric_fields").unwrap();
+        assert_eq!(values[at], "mapFromArrays(?, ?)");
+        assert!(values.iter().enumerate().all(|(i, v)| i == at || *v == "?"));
+        // insert_log binds one value per column, two for the map
+        assert_eq!(INSERT_LOG_SQL.matches('?').count(), columns.len() + 1);
+    }
+}
@@ -1,124 +1,503 @@
+mod chunks;
+mod collection;
+mod dlq;
+mod fields;
+mod retention;
+mod retry;
+
 use clickhouse::Client;
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use futures::StreamExt;
-use logai_core::LogEntry;
-use tracing::{info, error};
+use logai_core::chunking::Chunker;
+use logai_core::embedding::{verify_embedding_dimensions, CollectionLayout, EmbeddingModelKind, COLLECTION_NAME};
+use logai_core::nats::NatsConfig;
+use logai_core::{LogChunk, LogEntry, REQUEST_ID_HEADER};
+use tracing::{info, info_span, error, warn, Instrument};
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use qdrant_client::qdrant::{
-    CreateCollectionBuilder, Distance, PointStruct, UpsertPointsBuilder, VectorParamsBuilder,
+    vectors_config, CreateCollectionBuilder, CreateFieldIndexCollectionBuilder,
+    PointStruct, UpsertPointsBuilder, VectorParamsBuilder,
 };
 use qdrant_client::{Payload, Qdrant};
 
-const COLLECTION_NAME: &str = "log_embeddings";
-const VECTOR_SIZE: u64 = 384; // all mini LML6V2 output 384 dimensions
+use collection::CollectionConfig;
+use dlq::{DeadLetter, Stage};
+use retry::{with_retries, RetryPolicy};
 
 #[tokio::main]
-
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt::init();
+    logai_core::logging::init_tracing();
+    run().instrument(info_span!("worker", service = "logai-worker")).await
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // `logai-worker --replay-dlq` drains logs.dlq and exits instead of consuming logs.ingest
+    let replay = std::env::args().any(|arg| arg == "--replay-dlq");
 
     let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "localhost:4222".to_string());
     let clickhouse_url = std::env::var("CLICKHOUSE_URL").unwrap_or_else(|_| "http://localhost:8123".to_string());
     let qdrant_url = std::env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".to_string());
+    let embedding_model = EmbeddingModelKind::from_env()?;
+    info!(
+        model = embedding_model.name(),
+        dimension = embedding_model.dimension(),
+        "Embedding model selected"
+    );
+    let retry = RetryPolicy::from_env();
+    info!(
+        max_attempts = retry.max_attempts,
+        backoff = ?retry.schedule(),
+        "Write retry policy"
+    );
+    let payload_fields = payload_fields_from_env();
+    info!(fields = ?payload_fields, "Log fields copied into the Qdrant payload");
+    let collection_config = CollectionConfig::from_env()?;
+    info!(
+        distance = collection_config.distance.as_str_name(),
+        on_disk_payload = collection_config.on_disk_payload,
+        "Qdrant collection settings"
+    );
+    let layout = CollectionLayout::from_env();
+    if layout == CollectionLayout::PerService {
+        info!("One Qdrant collection per service ({}_{{service}}), created on first use", COLLECTION_NAME);
+    }
+    let ttl_days = retention::ttl_days_from_env()?;
+    match ttl_days {
+        Some(days) => info!(days, "Logs expire from ClickHouse and Qdrant"),
+        // a TTL set by an earlier run stays on the table (see retention::ttl_days_from_env)
+        None => info!("Log retention off, logs are kept forever unless the logs table has its own TTL"),
+    }
+    let chunk_config = chunks::config_from_env();
+    if let Some(ref config) = chunk_config {
+        info!(window = ?config.window, max_logs = config.max_logs, "Embedding chunks instead of single logs");
+    }
 
     //connect to NATS
     info!("Connecting to NATS at {}...", nats_url);
     let nats = async_nats::connect(&nats_url).await?;
     info!("Connected to NATS!");
 
+    // JetStream keeps dead letters around until someone replays them
+    let dlq_stream = match dlq::ensure_stream(&nats).await {
+        Ok(stream) => Some(stream),
+        Err(e) if !replay => {
+            warn!("DLQ stream unavailable (is JetStream enabled?), failed logs won't be kept: {}", e);
+            None
+        }
+        Err(e) => return Err(e),
+    };
+
     // connect to clickhouese
     info!("Connecting to ClickHouse at {}...", clickhouse_url);
     let clickhouse = Client::default()
         .with_url(&clickhouse_url)
         .with_database("logai");
-    create_logs_table(&clickhouse).await?;
+    create_logs_table(&clickhouse, ttl_days).await?;
     info!("Clickhouse ready!");
 
     // Conncect to qdrant
     info!("Connecting to Qdrant at {}...", qdrant_url);
     let qdrant = Qdrant::from_url(&qdrant_url).build()?;
-    setup_qdrant_collection(&qdrant).await?;
+    let mut ready_collections = HashSet::new();
+    if layout == CollectionLayout::Single {
+        setup_qdrant_collection(&qdrant, COLLECTION_NAME, embedding_model, &collection_config, &payload_fields)
+            .await?;
+        ready_collections.insert(COLLECTION_NAME.to_string());
+    }
     info!("Qdrant ready!");
+    if let Some(days) = ttl_days.filter(|_| !replay) {
+        tokio::spawn(retention::run_qdrant_cleanup(Qdrant::from_url(&qdrant_url).build()?, days));
+    }
 
     // Load embedding model (running locally)
-    info!("Loading embedding model (First time downloads 30mb)..");
-    let mut  model = TextEmbedding::try_new(InitOptions::new(EmbeddingModel::AllMiniLML6V2).with_show_download_progress(true),)?;
+    info!("Loading embedding model {} (first run downloads it)..", embedding_model.name());
+    let model = TextEmbedding::try_new(InitOptions::new(fastembed_model(embedding_model)).with_show_download_progress(true),)?;
     info!("Embedding model loaded!");
 
-    //Subscribe to logs.ingest
-    info!("Subscribing to logs.ingest...");
-    let mut subscriber = nats.subscribe("logs.ingest").await?;
+    let mut sinks = Sinks {
+        clickhouse,
+        qdrant,
+        model,
+        retry,
+        payload_fields,
+        layout,
+        embedding_model,
+        collection_config,
+        ready_collections,
+    };
+
+    if let Some(stream) = dlq_stream.as_ref().filter(|_| replay) {
+        return replay_dlq(stream, &mut sinks).await;
+    }
+
+    // a queue group, so each log goes to one of however many workers are running; chunk
+    // windows are per worker, so more workers means smaller chunks (see NatsConfig)
+    let nats_config = NatsConfig::from_env();
+    info!(
+        subject = %nats_config.ingest_subject,
+        queue_group = %nats_config.queue_group,
+        "Subscribing to ingest subject..."
+    );
+    let mut subscriber = nats
+        .queue_subscribe(nats_config.ingest_subject, nats_config.queue_group)
+        .await?;
     info!("Worker ready! Waiting for logs...");
 
+    // in chunk mode Qdrant gets chunks, so each log only goes through ClickHouse here
+    let mut chunker = chunk_config.map(Chunker::new);
+    let stages: &[Stage] = if chunker.is_some() {
+        &[Stage::Clickhouse]
+    } else {
+        &[Stage::Clickhouse, Stage::Qdrant]
+    };
+    let mut flush_tick = tokio::time::interval(chunks::FLUSH_EVERY);
+
     //process messages
-    while let Some(message) = subscriber.next().await {
-        match serde_json::from_slice::<LogEntry>(&message.payload) {
-            Ok(entry) => {
-                info!(
-                    id = %entry.id,
-                    level = ?entry.level,
-                    service = %entry.service,
-                    "Received Log"
-                );
-                // Store in ClickHouse (exisitng)
-                if let Err(e) = insert_log(&clickhouse, &entry).await {
-                    error!("ClickHouse insert failed: {}", e);
-                } 
-
-                // Generate mebdding & store in Qdrant 
-                if let Err(e) = embed_and_store(&mut model, &qdrant, &entry).await {
-                    error!("Qdrant Store failed: {}", e);
+    loop {
+        let message = tokio::select! {
+            message = subscriber.next() => match message {
+                Some(message) => message,
+                None => break,
+            },
+            _ = flush_tick.tick(), if chunker.is_some() => {
+                let idle = chunker.as_mut().map(|c| c.flush_idle(std::time::Instant::now())).unwrap_or_default();
+                for chunk in idle {
+                    sinks.store_chunk(&chunk).await;
                 }
+                continue;
             }
-            Err(e) => {
-                error!("Failed to parse messgae: {}", e);
+        };
+
+        // logs published by the API carry its request id; the span puts it on every line below
+        let span = info_span!("log", request_id = tracing::field::Empty);
+        if let Some(id) = message.headers.as_ref().and_then(|h| h.get(REQUEST_ID_HEADER)) {
+            span.record("request_id", id.as_str());
+        }
+
+        async {
+            match serde_json::from_slice::<LogEntry>(&message.payload) {
+                Ok(entry) => {
+                    info!(
+                        id = %entry.id,
+                        level = ?entry.level,
+                        service = %entry.service,
+                        "Received Log"
+                    );
+                    // Store in ClickHouse, then embed & store in Qdrant; each failure is dead-lettered on its own
+                    for &stage in stages {
+                        let Err(e) = sinks.write(stage, &entry).await else { continue };
+                        error!(id = %entry.id, ?stage, "Giving up, sending to {}: {}", dlq::DLQ_SUBJECT, e);
+                        if dlq_stream.is_none() {
+                            continue;
+                        }
+
+                        let letter = DeadLetter::new(stage, e, sinks.retry.max_attempts, &message.payload);
+                        if let Err(e) = dlq::publish(&nats, &letter).await {
+                            error!(id = %entry.id, "DLQ publish failed, log is lost: {}", e);
+                        }
+                    }
+
+                    if let Some(closed) = chunker.as_mut().and_then(|c| c.push(entry)) {
+                        sinks.store_chunk(&closed).await;
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to parse messgae: {}", e);
+                }
             }
         }
+        .instrument(span)
+        .await;
+    }
+
+    if let Some(mut chunker) = chunker {
+        for chunk in chunker.flush() {
+            sinks.store_chunk(&chunk).await;
+        }
     }
     Ok(())
 
 }
 
+/// Where every log gets written, shared by the live loop and the DLQ replay
+struct Sinks {
+    clickhouse: Client,
+    qdrant: Qdrant,
+    model: TextEmbedding,
+    retry: RetryPolicy,
+    payload_fields: Vec<String>,
+    layout: CollectionLayout,
+    embedding_model: EmbeddingModelKind,
+    collection_config: CollectionConfig,
+    // collections known to exist with the right dimension and indexes
+    ready_collections: HashSet<String>,
+}
+
+impl Sinks {
+    /// One stage with retries; the error is the last failure
+    async fn write(&mut self, stage: Stage, entry: &LogEntry) -> Result<(), String> {
+        match stage {
+            Stage::Clickhouse => with_retries(&self.retry, "ClickHouse insert", || {
+                insert_log(&self.clickhouse, entry)
+            })
+            .await
+            .map_err(|e| e.to_string()),
+            Stage::Qdrant => {
+                // embedding runs locally, only the upsert is worth retrying
+                let vector = embed_log(&mut self.model, entry).map_err(|e| e.to_string())?;
+                let collection = self.collection(&entry.service).await?;
+                with_retries(&self.retry, "Qdrant upsert", || {
+                    store_embedding(&self.qdrant, &collection, entry, vector.clone(), &self.payload_fields)
+                })
+                .await
+                .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// Embed and store a chunk with retries. A chunk that still fails isn't dead-lettered:
+    /// its logs are already in ClickHouse, they just won't show up in semantic search.
+    async fn store_chunk(&mut self, chunk: &LogChunk) {
+        let vector = embed_text(&mut self.model, &chunk.summary).map_err(|e| e.to_string());
+        let result = match (vector, self.collection(&chunk.service).await) {
+            (Ok(vector), Ok(collection)) => with_retries(&self.retry, "Qdrant chunk upsert", || {
+                chunks::store_chunk(&self.qdrant, &collection, chunk, vector.clone())
+            })
+            .await
+            .map_err(|e| e.to_string()),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+        if let Err(e) = result {
+            error!(chunk = %chunk.id, logs = chunk.log_count, service = %chunk.service, "Chunk not embedded: {}", e);
+        }
+    }
+
+    /// The collection `service`'s embeddings go in, set up the first time it's used
+    async fn collection(&mut self, service: &str) -> Result<String, String> {
+        let name = self.layout.collection_for(service);
+        if !self.ready_collections.contains(&name) {
+            setup_qdrant_collection(&self.qdrant, &name, self.embedding_model, &self.collection_config, &self.payload_fields)
+                .await
+                .map_err(|e| e.to_string())?;
+            self.ready_collections.insert(name.clone());
+        }
+        Ok(name)
+    }
+}
+
+/// Re-run the failed stage for everything parked on logs.dlq, then exit.
+/// Stops at the first log that still fails (the store is likely still down) and leaves it queued.
+async fn replay_dlq(
+    stream: &async_nats::jetstream::stream::Stream,
+    sinks: &mut Sinks,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let consumer = dlq::replay_consumer(stream).await?;
+    let mut replayed = 0;
+
+    loop {
+        let mut batch = consumer.fetch().max_messages(100).messages().await?;
+        let mut fetched = 0;
+
+        while let Some(message) = batch.next().await {
+            let message = message?;
+            fetched += 1;
+
+            let parsed = serde_json::from_slice::<DeadLetter>(&message.payload).and_then(|letter| {
+                serde_json::from_value::<LogEntry>(letter.payload).map(|entry| (letter.stage, entry))
+            });
+            let (stage, entry) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    warn!("Dropping malformed DLQ message: {}", e);
+                    message.ack().await.map_err(|e| e.to_string())?;
+                    continue;
+                }
+            };
+
+            if let Err(e) = sinks.write(stage, &entry).await {
+                error!(id = %entry.id, ?stage, replayed, "Replay failed, leaving the rest queued: {}", e);
+                return Err(e.into());
+            }
+            message.ack().await.map_err(|e| e.to_string())?;
+            replayed += 1;
+        }
+
+        if fetched == 0 {
+            break;
+        }
+    }
+
+    info!(replayed, "DLQ replay finished");
+    Ok(())
+}
+
 /// Setuping the qdrant collection like creating a table
+/// The collection dimension must match the embedding model: an empty collection with the
+/// wrong size is recreated, a populated one is an error (re-embedding is a manual decision)
 
-async fn setup_qdrant_collection(qdrant: &Qdrant) -> Result<(), Box<dyn std::error::Error>> {
+async fn setup_qdrant_collection(
+    qdrant: &Qdrant,
+    name: &str,
+    model: EmbeddingModelKind,
+    config: &CollectionConfig,
+    payload_fields: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dimension = model.dimension();
     // check if collection already exists or not
     let collection = qdrant.list_collections().await?;
     let exists = collection
     .collections
     .iter()
-    .any(|c| c.name == COLLECTION_NAME);
+    .any(|c| c.name == name);
 
-    if !exists {
-        info!("Creating Qdrant collection: {}", COLLECTION_NAME);
-        qdrant
-        .create_collection(
-            CreateCollectionBuilder::new(COLLECTION_NAME)
-                        .vectors_config(VectorParamsBuilder::new(VECTOR_SIZE, Distance::Cosine))
-        )
-        .await?;
+    if exists {
+        let info = qdrant.collection_info(name).await?.result;
+        let points = info.as_ref().and_then(|i| i.points_count).unwrap_or(0);
+        let existing = info
+            .and_then(|i| i.config)
+            .and_then(|c| c.params)
+            .and_then(|p| p.vectors_config)
+            .and_then(|v| v.config);
+
+        match existing {
+            Some(vectors_config::Config::Params(params))
+                if params.size == dimension
+                    && collection::distance_mismatch(name, params.distance, config.distance).is_none() =>
+            {
+                info!("Qdrant collection {} already exists ({} dims)", name, dimension);
+                return create_payload_indexes(qdrant, name, payload_fields).await;
+            }
+            Some(vectors_config::Config::Params(params)) if points == 0 => {
+                info!(
+                    "Recreating empty Qdrant collection: {} dims -> {} dims, {} distance",
+                    params.size,
+                    dimension,
+                    config.distance.as_str_name()
+                );
+                qdrant.delete_collection(name).await?;
+            }
+            Some(vectors_config::Config::Params(params)) if params.size == dimension => {
+                let mismatch = collection::distance_mismatch(name, params.distance, config.distance)
+                    .expect("same dimension, so the distance must differ");
+                return Err(mismatch.into());
+            }
+            Some(vectors_config::Config::Params(params)) => {
+                let mismatch = verify_embedding_dimensions(name, params.size, model)
+                    .expect_err("the arms above cover an equal size");
+                return Err(format!("{} ({} vectors stored)", mismatch, points).into());
+            }
+            _ => {
+                warn!("Could not read the Qdrant collection dimension, assuming {}", dimension);
+                return Ok(());
+            }
+        }
+    }
+
+    info!(
+        "Creating Qdrant collection: {} ({} dims, {} distance)",
+        name,
+        dimension,
+        config.distance.as_str_name()
+    );
+    qdrant
+    .create_collection(
+        CreateCollectionBuilder::new(name)
+                    .vectors_config(VectorParamsBuilder::new(dimension, config.distance))
+                    .on_disk_payload(config.on_disk_payload)
+    )
+    .await?;
     info!("Collection Created");
-    } else {
-        info!("Qdrant collection already exists");
+    create_payload_indexes(qdrant, name, payload_fields).await
+}
+
+/// Indexes behind filtered search: `service` and `level` matches, `timestamp_unix` ranges
+/// (and ordering for the level fast path), and the `filters=field:value` search param
+/// (`fields.<name>`). Ones the collection already has are left alone.
+async fn create_payload_indexes(
+    qdrant: &Qdrant,
+    name: &str,
+    payload_fields: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let existing: HashSet<String> = qdrant
+        .collection_info(name)
+        .await?
+        .result
+        .map(|info| info.payload_schema.into_keys().collect())
+        .unwrap_or_default();
+
+    for (field, field_type) in collection::missing_payload_indexes(payload_fields, &existing) {
+        info!(collection = name, field = %field, "Creating payload index");
+        qdrant
+            .create_field_index(CreateFieldIndexCollectionBuilder::new(name, field, field_type))
+            .await?;
     }
     Ok(())
 }
 
-/// Generate embedding for a log and store in Qdrant
+/// Environment variables:
+/// - WORKER_PAYLOAD_FIELDS: log fields searchable via `filters=` (default: user_id,endpoint,status_code,error_code)
+fn payload_fields_from_env() -> Vec<String> {
+    let raw = std::env::var("WORKER_PAYLOAD_FIELDS")
+        .unwrap_or_else(|_| "user_id,endpoint,status_code,error_code".to_string());
+    raw.split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Allowlisted scalar fields, all as strings so `status_code:500` matches whether the
+// parser produced 500 or "500"; objects, arrays and nulls are skipped
+fn scalar_fields(
+    fields: &HashMap<String, serde_json::Value>,
+    allowlist: &[String],
+) -> serde_json::Map<String, serde_json::Value> {
+    allowlist
+        .iter()
+        .filter_map(|name| {
+            let value = match fields.get(name)? {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            Some((name.clone(), serde_json::Value::String(value)))
+        })
+        .collect()
+}
 
-async fn embed_and_store(
+fn fastembed_model(kind: EmbeddingModelKind) -> EmbeddingModel {
+    match kind {
+        EmbeddingModelKind::AllMiniLmL6V2 => EmbeddingModel::AllMiniLML6V2,
+        EmbeddingModelKind::AllMiniLmL12V2 => EmbeddingModel::AllMiniLML12V2,
+        EmbeddingModelKind::BgeSmallEn => EmbeddingModel::BGESmallENV15,
+        EmbeddingModelKind::BgeBaseEn => EmbeddingModel::BGEBaseENV15,
+        EmbeddingModelKind::BgeLargeEn => EmbeddingModel::BGELargeENV15,
+        EmbeddingModelKind::NomicEmbedText => EmbeddingModel::NomicEmbedTextV15,
+    }
+}
+
+/// Generate embedding for a log
+
+fn embed_log(
     model: &mut TextEmbedding,
-    qdrant: &Qdrant,
     entry: &LogEntry,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
     // Create text to embed: combine service + Level + message
     let text_to_embed = format!(
         "service:{} level:{:?} {}",
         entry.service, entry.level, entry.message
     );
+    embed_text(model, &text_to_embed)
+}
 
-    // Generate embedding (text -> 384D vector)
-    let documents: Vec<String> = vec![text_to_embed.clone()];
+fn embed_text(
+    model: &mut TextEmbedding,
+    text: &str,
+) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    // Generate embedding (text -> vector, size depends on EMBEDDING_MODEL)
+    let documents: Vec<String> = vec![text.to_string()];
     let embeddings = model.embed(documents, None)?;
     let vector = embeddings.into_iter().next().ok_or("No embeddings generated")?;
 
@@ -126,9 +505,20 @@ async fn embed_and_store(
         return Err("Embedding returned empty vector".into());
     }
     info!("Generated embedding with {} dimensions", vector.len());
+    Ok(vector)
+}
 
+/// Store a log's embedding in Qdrant
+async fn store_embedding(
+    qdrant: &Qdrant,
+    collection: &str,
+    entry: &LogEntry,
+    vector: Vec<f32>,
+    payload_fields: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
     // Create point with metadata (payload)
     let payload: Payload = json!({
+        "fields": scalar_fields(&entry.fields, payload_fields),
         "log_id": entry.id.to_string(),
         "service": entry.service,
         "level": format!("{:?}", entry.level),
@@ -142,40 +532,33 @@ async fn embed_and_store(
     let point = PointStruct::new(entry.id.to_string(), vector, payload,);
 
     //Upsert (insert or update) into the Qdrant
-    qdrant.upsert_points(UpsertPointsBuilder::new(COLLECTION_NAME, vec![point]).wait(true)).await?;
+    qdrant.upsert_points(UpsertPointsBuilder::new(collection, vec![point]).wait(true)).await?;
 
     info!(id = %entry.id, "Embedded & stored in Qdrant");
     Ok(())
 }
 
-async fn create_logs_table(client: &Client) -> Result<(), clickhouse::error::Error> {
-    client.query(r#"
-        CREATE TABLE IF NOT EXISTS logs (
-            id UUID,
-            timestamp DateTime64(3),
-            level String,
-            service String,
-            message String,
-            raw String,
-            trace_id Nullable(String),
-            span_id Nullable(String),
-            error_category Nullable(String),
-            fields String,
-            ingested_at DateTime64(3)
-        ) ENGINE = MergeTree()
-        ORDER BY (service, timestamp)
-        PARTITION BY toYYYYMM(timestamp)
-    "#).execute().await?;
+async fn create_logs_table(client: &Client, ttl_days: Option<u32>) -> Result<(), clickhouse::error::Error> {
+    client.query(&retention::create_logs_table_sql(ttl_days)).execute().await?;
+    // CREATE IF NOT EXISTS leaves an existing table alone: add columns from later versions
+    // and set its TTL explicitly
+    client.query(retention::ADD_NUMERIC_FIELDS_SQL).execute().await?;
+    if let Some(days) = ttl_days {
+        // only when it changed: the ALTER rewrites every partition to apply it
+        let engine: String = client.query(retention::LOGS_ENGINE_SQL).fetch_one().await?;
+        if !retention::has_ttl(&engine, days) {
+            info!(days, "Applying the log TTL to the existing logs table");
+            client.query(&retention::modify_ttl_sql(days)).execute().await?;
+        }
+    }
 
     info!("Logs table ready");
     Ok(())
 }
 
 async fn insert_log(client: &Client, entry: &LogEntry) -> Result<(), clickhouse::error::Error> {
-    client.query(r#"
-    INSERT INTO logs (id, timestamp, level, service, message, raw, trace_id, span_id, error_category, fields, ingested_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-    "#)
+    let (numeric_keys, numeric_values) = fields::numeric_fields(&entry.fields);
+    client.query(fields::INSERT_LOG_SQL)
     .bind(entry.id)
     .bind(entry.timestamp.timestamp_millis())
     .bind(format!("{:?}", entry.level))
@@ -186,6 +569,8 @@ async fn insert_log(client: &Client, entry: &LogEntry) -> Result<(), clickhouse:
     .bind(&entry.span_id)
     .bind(entry.error_category.map(|e| format!("{:?}", e)))
     .bind(serde_json::to_string(&entry.fields).unwrap_or_else(|_| "{}".to_string()))
+    .bind(numeric_keys)
+    .bind(numeric_values)
     .bind(entry.ingested_at.timestamp_millis())
     .execute()
     .await?;
@@ -0,0 +1,151 @@
+// Log retention: a TTL on the ClickHouse logs table, and a periodic Qdrant delete with the
+// same cutoff so search never returns logs that ClickHouse has already dropped.
+
+use logai_core::embedding::is_log_collection;
+use qdrant_client::qdrant::{Condition, DeletePointsBuilder, Filter, Range};
+use qdrant_client::Qdrant;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// How often expired points are deleted from Qdrant
+pub const CLEANUP_EVERY: Duration = Duration::from_secs(3600);
+
+/// Environment variables:
+/// - CLICKHOUSE_LOG_TTL_DAYS: drop logs older than this many days from ClickHouse and
+///   Qdrant (default: keep forever). Unsetting it later does not drop a TTL an earlier run
+///   put on the table; remove that with `ALTER TABLE logs REMOVE TTL`
+pub fn ttl_days_from_env() -> Result<Option<u32>, String> {
+    match std::env::var("CLICKHOUSE_LOG_TTL_DAYS") {
+        Ok(value) if !value.trim().is_empty() => match value.trim().parse::<u32>() {
+            Ok(days) if days > 0 => Ok(Some(days)),
+            _ => Err(format!("Invalid CLICKHOUSE_LOG_TTL_DAYS '{}': expected a number of days above 0", value)),
+        },
+        _ => Ok(None),
+    }
+}
+
+// TTL wants a Date or DateTime, older servers reject DateTime64 columns there
+fn ttl_clause(days: u32) -> String {
+    format!("TTL toDateTime(timestamp) + INTERVAL {} DAY", days)
+}
+
+/// The logs table, expiring rows after `ttl_days` when set
+pub fn create_logs_table_sql(ttl_days: Option<u32>) -> String {
+    let ttl = ttl_days.map(|days| format!("\n        {}", ttl_clause(days))).unwrap_or_default();
+    format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS logs (
+            id UUID,
+            timestamp DateTime64(3),
+            level String,
+            service String,
+            message String,
+            raw String,
+            trace_id Nullable(String),
+            span_id Nullable(String),
+            error_category Nullable(String),
+            fields String,
+            numeric_fields Map(String, Float64),
+            ingested_at DateTime64(3)
+        ) ENGINE = MergeTree()
+        ORDER BY (service, timestamp)
+        PARTITION BY toYYYYMM(timestamp){}
+    "#,
+        ttl
+    )
+}
+
+/// Adds `numeric_fields` to a table created before it existed; older rows get an empty map
+pub const ADD_NUMERIC_FIELDS_SQL: &str =
+    "ALTER TABLE logs ADD COLUMN IF NOT EXISTS numeric_fields Map(String, Float64) AFTER fields";
+
+/// The logs table's engine definition, TTL included (`engine_full` in system.tables)
+pub const LOGS_ENGINE_SQL: &str =
+    "SELECT engine_full FROM system.tables WHERE database = currentDatabase() AND name = 'logs'";
+
+/// Whether `engine_full` already carries the TTL for `days`. ClickHouse stores the interval
+/// as `toIntervalDay(N)`; the clause as written is accepted too
+pub fn has_ttl(engine_full: &str, days: u32) -> bool {
+    engine_full.contains(&format!("TTL toDateTime(timestamp) + toIntervalDay({})", days))
+        || engine_full.contains(&ttl_clause(days))
+}
+
+/// Applies the TTL to a table created before retention was configured (or with another value).
+/// Only issued when `has_ttl` says the table differs: the ALTER materializes the TTL, rewriting
+/// old partitions (merges alone never revisit them) so ClickHouse and Qdrant expire together
+pub fn modify_ttl_sql(days: u32) -> String {
+    format!("ALTER TABLE logs MODIFY {}", ttl_clause(days))
+}
+
+/// Points logged before this unix time are expired
+pub fn cutoff(days: u32, now: i64) -> i64 {
+    now - i64::from(days) * 86_400
+}
+
+/// Delete expired points every `CLEANUP_EVERY` (the first run is immediate)
+pub async fn run_qdrant_cleanup(qdrant: Qdrant, days: u32) {
+    let mut ticker = tokio::time::interval(CLEANUP_EVERY);
+    loop {
+        ticker.tick().await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+        let before = cutoff(days, now);
+        // chunks carry their start time, so one straddling the cutoff goes up to a window early
+        let expired = Filter::must([Condition::range(
+            "timestamp_unix",
+            Range {
+                lt: Some(before as f64),
+                ..Default::default()
+            },
+        )]);
+        // per-service collections appear as services do, so list them every run
+        let collections = match qdrant.list_collections().await {
+            Ok(list) => list.collections.into_iter().map(|c| c.name).filter(|name| is_log_collection(name)),
+            Err(e) => {
+                warn!("Qdrant retention cleanup failed: {}", e);
+                continue;
+            }
+        };
+        for collection in collections {
+            match qdrant
+                .delete_points(DeletePointsBuilder::new(&collection).points(expired.clone()).wait(true))
+                .await
+            {
+                Ok(_) => info!(days, before, collection = %collection, "Deleted expired points from Qdrant"),
+                Err(e) => warn!(collection = %collection, "Qdrant retention cleanup failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logs_table_ddl() {
+        let forever = create_logs_table_sql(None);
+        assert!(forever.contains("ENGINE = MergeTree()"));
+        assert!(forever.contains("numeric_fields Map(String, Float64),"));
+        assert!(!forever.contains("TTL"));
+        assert!(forever.trim_end().ends_with("PARTITION BY toYYYYMM(timestamp)"));
+
+        let retained = create_logs_table_sql(Some(30));
+        assert!(retained
+            .trim_end()
+            .ends_with("PARTITION BY toYYYYMM(timestamp)\n        TTL toDateTime(timestamp) + INTERVAL 30 DAY"));
+
+        assert_eq!(modify_ttl_sql(7), "ALTER TABLE logs MODIFY TTL toDateTime(timestamp) + INTERVAL 7 DAY");
+        assert_eq!(cutoff(2, 1770692400), 1770692400 - 2 * 86_400);
+    }
+
+    #[test]
+    fn test_has_ttl() {
+        let engine = "MergeTree PARTITION BY toYYYYMM(timestamp) ORDER BY (service, timestamp) \
+                      TTL toDateTime(timestamp) + toIntervalDay(30) SETTINGS index_granularity = 8192";
+        assert!(has_ttl(engine, 30));
+        assert!(!has_ttl(engine, 3));
+        assert!(!has_ttl(engine, 7));
+        assert!(!has_ttl("MergeTree PARTITION BY toYYYYMM(timestamp) ORDER BY (service, timestamp)", 30));
+        assert!(has_ttl(&create_logs_table_sql(Some(7)), 7));
+    }
+}
@@ -0,0 +1,135 @@
+// Bounded retries with exponential backoff, so a short ClickHouse/Qdrant outage doesn't lose logs
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// Environment variables:
+/// - WORKER_MAX_ATTEMPTS: tries per write, including the first (default 5)
+/// - WORKER_RETRY_BASE_MS: delay before the first retry, doubled each time (default 200)
+/// - WORKER_RETRY_MAX_MS: upper bound for a single delay (default 10000)
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env_u64 = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+
+        Self {
+            max_attempts: std::env::var("WORKER_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_attempts),
+            base_delay: env_u64("WORKER_RETRY_BASE_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            max_delay: env_u64("WORKER_RETRY_MAX_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.max_delay),
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based): base * 2^(retry-1), capped at max_delay
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Every delay the policy will sleep through before giving up
+    pub fn schedule(&self) -> Vec<Duration> {
+        (1..self.max_attempts).map(|retry| self.delay(retry)).collect()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Run `op` until it succeeds or the policy runs out of attempts, returning the last error
+pub async fn with_retries<T, E, F, Fut>(policy: &RetryPolicy, what: &str, mut op: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts => {
+                let delay = policy.delay(attempt);
+                warn!(
+                    attempt,
+                    max_attempts = policy.max_attempts,
+                    delay_ms = delay.as_millis() as u64,
+                    "{} failed, retrying: {}",
+                    what,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_schedule() {
+        let policy = RetryPolicy {
+            max_attempts: 7,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        };
+
+        let schedule: Vec<u128> = policy.schedule().iter().map(|d| d.as_millis()).collect();
+        assert_eq!(schedule, vec![200, 400, 800, 1600, 3200, 5000]);
+    }
+
+    #[test]
+    fn test_single_attempt_never_sleeps() {
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+        assert!(policy.schedule().is_empty());
+        // huge retry numbers saturate instead of overflowing
+        assert_eq!(policy.delay(64), policy.max_delay);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_with_last_error() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        };
+        let mut calls = 0;
+        let result: Result<(), String> = with_retries(&policy, "test", || {
+            calls += 1;
+            let attempt = calls;
+            async move { Err(format!("failure {}", attempt)) }
+        })
+        .await;
+
+        assert_eq!(calls, 3);
+        assert_eq!(result.unwrap_err(), "failure 3");
+    }
+}
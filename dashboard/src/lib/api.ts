@@ -32,6 +32,14 @@ export interface CausalChain {
   recommendation: string | null
 }
 
+export interface SourceLog {
+  log_id: string
+  timestamp: string
+  service: string
+  level: string
+  message: string
+}
+
 export interface ChatResponse {
   answer: string
   sources_count: number
@@ -40,6 +48,7 @@ export interface ChatResponse {
   context_logs: number
   conversation_turn: number
   source_logs: string[]
+  sources: SourceLog[]
   causal_chain?: CausalChain
 }
 
@@ -81,17 +90,19 @@ export async function fetchLogs(params?: {
   search?: string
   limit?: number
   offset?: number
+  before?: string
 }): Promise<Log[]> {
   const searchParams = new URLSearchParams()
   if (params?.limit) searchParams.set("limit", params.limit.toString())
   if (params?.service) searchParams.set("service", params.service)
   if (params?.level) searchParams.set("level", params.level)
+  if (params?.before) searchParams.set("before", params.before)
 
   // Use the new /logs/recent endpoint for chronological ordering
   const res = await fetch(`${API_BASE}/logs/recent?${searchParams}`)
   if (!res.ok) throw new Error("Failed to fetch logs")
   const data = await res.json()
-  return data.map(
+  return data.logs.map(
     (item: {
       log_id: string
       service: string
@@ -123,6 +134,7 @@ export async function searchLogs(params: {
 
   const res = await fetch(`${API_BASE}/search?${searchParams}`)
   if (!res.ok) throw new Error("Failed to search logs")
+  // a bare array of hits, best first
   const data = await res.json()
   return data.map(
     (item: {
@@ -171,8 +183,14 @@ export async function sendChat(query: string): Promise<ChatResponse> {
   return res.json()
 }
 
+export interface ServicesResponse {
+  services: string[]
+  last_refreshed: string | null
+}
+
 export async function fetchServices(): Promise<string[]> {
   const res = await fetch(`${API_BASE}/services`)
   if (!res.ok) throw new Error("Failed to fetch services")
-  return res.json()
+  const data: ServicesResponse = await res.json()
+  return data.services
 }